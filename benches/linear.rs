@@ -26,12 +26,12 @@ pub fn benchmark(c: &mut Criterion) {
     for length in [200, 300, 400, 600, 800, 1200, 1600, 2400, 3200, 4800, 6400].iter() {
         let text = lorem_ipsum(*length);
 
-        #[cfg(feature = "smawk")]
+        #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
         {
             #[cfg(feature = "unicode-linebreak")]
             {
                 let options = textwrap::Options::new(LINE_LENGTH)
-                    .wrap_algorithm(textwrap::wrap_algorithms::OptimalFit)
+                    .wrap_algorithm(textwrap::wrap_algorithms::OptimalFit::new())
                     .word_separator(textwrap::UnicodeBreakProperties);
                 group.bench_with_input(
                     BenchmarkId::new("fill_optimal_fit_unicode", length),
@@ -43,7 +43,7 @@ pub fn benchmark(c: &mut Criterion) {
             }
 
             let options = textwrap::Options::new(LINE_LENGTH)
-                .wrap_algorithm(textwrap::wrap_algorithms::OptimalFit)
+                .wrap_algorithm(textwrap::wrap_algorithms::OptimalFit::new())
                 .word_separator(textwrap::AsciiSpace);
             group.bench_with_input(
                 BenchmarkId::new("fill_optimal_fit_ascii", length),
@@ -52,6 +52,17 @@ pub fn benchmark(c: &mut Criterion) {
                     b.iter(|| textwrap::fill(text, &options));
                 },
             );
+
+            // A single word which must be broken apart should not
+            // make the optimal-fit algorithm quadratic.
+            let long_word_text = format!("{} {}", "x".repeat(2 * LINE_LENGTH), text);
+            group.bench_with_input(
+                BenchmarkId::new("fill_optimal_fit_long_word", length),
+                &long_word_text,
+                |b, text| {
+                    b.iter(|| textwrap::fill(text, &options));
+                },
+            );
         }
 
         let options = textwrap::Options::new(LINE_LENGTH)
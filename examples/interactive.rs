@@ -108,7 +108,7 @@ mod unix_only {
         )?;
         left_row += 1;
 
-        #[cfg(feature = "smawk")]
+        #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
         {
             write!(
                 stdout,
@@ -235,8 +235,8 @@ mod unix_only {
     pub fn main() -> Result<(), io::Error> {
         let mut wrap_algorithms: Vec<Box<dyn wrap_algorithms::WrapAlgorithm>> =
             vec![Box::new(wrap_algorithms::FirstFit)];
-        #[cfg(feature = "smawk")]
-        wrap_algorithms.push(Box::new(wrap_algorithms::OptimalFit));
+        #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+        wrap_algorithms.push(Box::new(wrap_algorithms::OptimalFit::new()));
 
         let mut splitters: Vec<Box<dyn WordSplitter>> =
             vec![Box::new(HyphenSplitter), Box::new(NoHyphenation)];
@@ -299,7 +299,7 @@ mod unix_only {
                 Key::Left => options.width = options.width.saturating_sub(1),
                 Key::Right => options.width = options.width.saturating_add(1),
                 Key::Ctrl('b') => options.break_words = !options.break_words,
-                #[cfg(feature = "smawk")]
+                #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
                 Key::Ctrl('o') => {
                     std::mem::swap(&mut options.wrap_algorithm, &mut wrap_algorithms[0]);
                     wrap_algorithms.rotate_left(1);
@@ -60,22 +60,20 @@ impl<'a> CanvasWord<'a> {
     }
 }
 
-const PRECISION: usize = 10;
-
 impl core::Fragment for CanvasWord<'_> {
     #[inline]
-    fn width(&self) -> usize {
-        (self.width * PRECISION as f64) as usize
+    fn width(&self) -> f64 {
+        self.width
     }
 
     #[inline]
-    fn whitespace_width(&self) -> usize {
-        (self.whitespace_width * PRECISION as f64) as usize
+    fn whitespace_width(&self) -> f64 {
+        self.whitespace_width
     }
 
     #[inline]
-    fn penalty_width(&self) -> usize {
-        (self.penalty_width * PRECISION as f64) as usize
+    fn penalty_width(&self) -> f64 {
+        self.penalty_width
     }
 }
 
@@ -167,7 +165,7 @@ pub fn draw_wrapped_text(
             .map(|word| CanvasWord::from(ctx, word))
             .collect::<Vec<_>>();
 
-        let line_lengths = [width * PRECISION];
+        let line_lengths = [width as f64];
         let wrapped_words = wrap_algorithms::wrap_first_fit(&canvas_words, &line_lengths);
 
         for words_in_line in wrapped_words {
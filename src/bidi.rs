@@ -0,0 +1,647 @@
+//! Functions for wrapping bidirectional text.
+//!
+//! Text which mixes right-to-left scripts, such as Arabic and Hebrew,
+//! with left-to-right scripts is stored in _logical_ order: the order
+//! in which it is read. Lines are broken in logical order, and each
+//! wrapped line is then reordered into _visual_ order, from left to
+//! right, using the Unicode Bidirectional Algorithm from [UAX #9].
+//! The lines can then be shown in terminals which do not reorder text
+//! themselves.
+//!
+//! The _paragraph direction_ decides the order of the runs of
+//! left-to-right and right-to-left text on a line, and the side of
+//! the line where it starts. It is found from the first letter of the
+//! paragraph, or it can be given explicitly.
+//!
+//! The implicit part of the algorithm is implemented: numbers and
+//! neutral characters such as spaces and punctuation are resolved,
+//! the lines are reordered, and brackets are mirrored. The
+//! bidirectional types of the characters are approximated by script
+//! ranges. Explicit directional formatting characters, such as U+202B
+//! (Right-to-Left Embedding) and U+2067 (Right-to-Left Isolate), are
+//! treated like other neutral characters.
+//!
+//! Only available when the `bidi` Cargo feature is enabled.
+//!
+//! [UAX #9]: https://www.unicode.org/reports/tr9/
+
+use std::borrow::Cow;
+
+use crate::core::{ansi_escape_sequences, is_grapheme_extend, ZERO_WIDTH_JOINER};
+use crate::{wrap_algorithms, wrap_with_breaks, Alignment, LastLine, Options};
+use crate::{WordSeparator, WordSplitter};
+
+/// The direction of a paragraph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Lines start on the left, like in English.
+    LeftToRight,
+    /// Lines start on the right, like in Arabic and Hebrew.
+    RightToLeft,
+}
+
+/// The bidirectional character types from UAX #9.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    /// Strong left-to-right (L).
+    Left,
+    /// Strong right-to-left (R).
+    Right,
+    /// Arabic letter (AL).
+    ArabicLetter,
+    /// European number (EN).
+    EuropeanNumber,
+    /// European number separator (ES).
+    EuropeanSeparator,
+    /// European number terminator (ET).
+    EuropeanTerminator,
+    /// Arabic number (AN).
+    ArabicNumber,
+    /// Common number separator (CS).
+    CommonSeparator,
+    /// Nonspacing mark (NSM).
+    NonspacingMark,
+    /// Boundary neutral (BN), also used for ANSI escape sequences.
+    BoundaryNeutral,
+    /// Paragraph separator (B).
+    ParagraphSeparator,
+    /// Segment separator (S).
+    SegmentSeparator,
+    /// Whitespace (WS).
+    Whitespace,
+    /// Other neutral (ON).
+    OtherNeutral,
+}
+
+impl Class {
+    fn is_neutral(self) -> bool {
+        matches!(
+            self,
+            Class::ParagraphSeparator
+                | Class::SegmentSeparator
+                | Class::Whitespace
+                | Class::OtherNeutral
+        )
+    }
+
+    /// The strong direction of a resolved class. Numbers count as
+    /// right-to-left for resolving neutrals.
+    fn strong(self) -> Option<Class> {
+        match self {
+            Class::Left => Some(Class::Left),
+            Class::Right | Class::EuropeanNumber | Class::ArabicNumber => Some(Class::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Approximate the bidirectional character type of `ch`.
+fn class_of(ch: char) -> Class {
+    match ch {
+        '0'..='9' | '\u{b2}' | '\u{b3}' | '\u{b9}' | '\u{6f0}'..='\u{6f9}' => Class::EuropeanNumber,
+        '+' | '-' | '\u{207a}' | '\u{207b}' | '\u{2212}' => Class::EuropeanSeparator,
+        '#'
+        | '$'
+        | '%'
+        | '\u{a2}'..='\u{a5}'
+        | '\u{b0}'
+        | '\u{b1}'
+        | '\u{2030}'..='\u{2034}'
+        | '\u{20a0}'..='\u{20cf}' => Class::EuropeanTerminator,
+        ',' | '.' | '/' | ':' | '\u{a0}' | '\u{60c}' | '\u{202f}' | '\u{2044}' => {
+            Class::CommonSeparator
+        }
+        '\u{600}'..='\u{605}' | '\u{660}'..='\u{669}' | '\u{66b}' | '\u{66c}' | '\u{6dd}' => {
+            Class::ArabicNumber
+        }
+        '\n' | '\r' | '\u{1c}'..='\u{1e}' | '\u{85}' | '\u{2029}' => Class::ParagraphSeparator,
+        '\t' | '\u{b}' | '\u{1f}' => Class::SegmentSeparator,
+        ' ' | '\u{c}' | '\u{2000}'..='\u{200a}' | '\u{2028}' | '\u{205f}' | '\u{3000}' => {
+            Class::Whitespace
+        }
+        '\u{200e}' => Class::Left,
+        '\u{200f}' => Class::Right,
+        '\u{61c}' => Class::ArabicLetter,
+        '\u{200b}'..='\u{200d}' | '\u{2060}'..='\u{2064}' | '\u{feff}' => Class::BoundaryNeutral,
+        _ if ch.is_control() => Class::BoundaryNeutral,
+        _ if is_grapheme_extend(ch) => Class::NonspacingMark,
+        '\u{606}' | '\u{607}' | '\u{609}' | '\u{60a}' | '\u{60e}' | '\u{60f}' | '\u{6de}'
+        | '\u{6e9}' | '\u{fd3e}' | '\u{fd3f}' => Class::OtherNeutral,
+        '\u{590}'..='\u{5ff}'
+        | '\u{7c0}'..='\u{85f}'
+        | '\u{fb1d}'..='\u{fb4f}'
+        | '\u{10800}'..='\u{10fff}'
+        | '\u{1e800}'..='\u{1edff}'
+        | '\u{1ef00}'..='\u{1efff}' => Class::Right,
+        '\u{600}'..='\u{7bf}'
+        | '\u{860}'..='\u{8ff}'
+        | '\u{fb50}'..='\u{fdff}'
+        | '\u{fe70}'..='\u{fefe}'
+        | '\u{1ee00}'..='\u{1eeff}' => Class::ArabicLetter,
+        '!'..='/' | ':'..='@' | '['..='`' | '{'..='~' => Class::OtherNeutral,
+        '\u{a1}'..='\u{bf}' | '\u{d7}' | '\u{f7}' => Class::OtherNeutral,
+        '\u{2010}'..='\u{205e}' | '\u{2190}'..='\u{2bff}' | '\u{3001}'..='\u{3003}' => {
+            Class::OtherNeutral
+        }
+        '\u{fe50}'..='\u{fe6f}' | '\u{ff01}'..='\u{ff0f}' => Class::OtherNeutral,
+        _ => Class::Left,
+    }
+}
+
+/// Find the direction of a paragraph from its first strong
+/// character. Returns `None` if `text` has no letters from
+/// left-to-right or right-to-left scripts.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::bidi::{paragraph_direction, Direction};
+///
+/// assert_eq!(paragraph_direction("Hello, עולם!"), Some(Direction::LeftToRight));
+/// assert_eq!(paragraph_direction("123 שלום world"), Some(Direction::RightToLeft));
+/// assert_eq!(paragraph_direction("123 ..."), None);
+/// ```
+pub fn paragraph_direction(text: &str) -> Option<Direction> {
+    let escape_sequences = ansi_escape_sequences(text);
+    text.char_indices()
+        .filter(|(idx, _)| !escape_sequences.iter().any(|range| range.contains(idx)))
+        .find_map(|(_, ch)| match class_of(ch) {
+            Class::Left => Some(Direction::LeftToRight),
+            Class::Right | Class::ArabicLetter => Some(Direction::RightToLeft),
+            _ => None,
+        })
+}
+
+/// A character together with the combining marks which follow it,
+/// or an ANSI escape sequence. Units are kept intact when a line is
+/// reordered.
+#[derive(Debug)]
+struct Unit {
+    start: usize,
+    end: usize,
+    class: Class,
+}
+
+fn units(line: &str) -> Vec<Unit> {
+    let escape_sequences = ansi_escape_sequences(line);
+    let mut units: Vec<Unit> = Vec::new();
+    let mut prev = None;
+    for (idx, ch) in line.char_indices() {
+        let end = idx + ch.len_utf8();
+        if let Some(range) = escape_sequences.iter().find(|range| range.contains(&idx)) {
+            match units.last_mut() {
+                Some(unit) if unit.start == range.start => unit.end = end,
+                _ => units.push(Unit {
+                    start: idx,
+                    end,
+                    class: Class::BoundaryNeutral,
+                }),
+            }
+            prev = None;
+            continue;
+        }
+        let joined = is_grapheme_extend(ch) || prev == Some(ZERO_WIDTH_JOINER);
+        match units.last_mut() {
+            Some(unit) if joined && prev.is_some() => unit.end = end,
+            _ => units.push(Unit {
+                start: idx,
+                end,
+                class: class_of(ch),
+            }),
+        }
+        prev = Some(ch);
+    }
+    units
+}
+
+/// Resolve the embedding levels of the `units` of `line`. This
+/// applies rules W1–W7, N0–N2, I1–I2 and L1 of UAX #9.
+fn resolve_levels(line: &str, units: &[Unit], direction: Direction) -> Vec<u8> {
+    let (base, embedding) = match direction {
+        Direction::LeftToRight => (0, Class::Left),
+        Direction::RightToLeft => (1, Class::Right),
+    };
+
+    // Boundary neutrals take no part in the resolution.
+    let indices = (0..units.len())
+        .filter(|&i| units[i].class != Class::BoundaryNeutral)
+        .collect::<Vec<_>>();
+    let mut types = indices.iter().map(|&i| units[i].class).collect::<Vec<_>>();
+    let n = types.len();
+
+    // W1: nonspacing marks take the type of the previous character.
+    for i in 0..n {
+        if types[i] == Class::NonspacingMark {
+            types[i] = if i == 0 { embedding } else { types[i - 1] };
+        }
+    }
+    // W2 and W3: European numbers after Arabic letters are Arabic
+    // numbers, and Arabic letters are right-to-left.
+    let mut last_strong = embedding;
+    for class in types.iter_mut() {
+        match *class {
+            Class::Left | Class::Right => last_strong = *class,
+            Class::ArabicLetter => {
+                last_strong = Class::ArabicLetter;
+                *class = Class::Right;
+            }
+            Class::EuropeanNumber if last_strong == Class::ArabicLetter => {
+                *class = Class::ArabicNumber;
+            }
+            _ => {}
+        }
+    }
+    // W4: a single separator between two numbers of the same type.
+    for i in 1..n.saturating_sub(1) {
+        let (before, after) = (types[i - 1], types[i + 1]);
+        types[i] = match types[i] {
+            Class::EuropeanSeparator | Class::CommonSeparator
+                if before == Class::EuropeanNumber && after == Class::EuropeanNumber =>
+            {
+                Class::EuropeanNumber
+            }
+            Class::CommonSeparator
+                if before == Class::ArabicNumber && after == Class::ArabicNumber =>
+            {
+                Class::ArabicNumber
+            }
+            class => class,
+        };
+    }
+    // W5: terminators next to European numbers.
+    let mut i = 0;
+    while i < n {
+        if types[i] != Class::EuropeanTerminator {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < n && types[i] == Class::EuropeanTerminator {
+            i += 1;
+        }
+        let before = start > 0 && types[start - 1] == Class::EuropeanNumber;
+        let after = i < n && types[i] == Class::EuropeanNumber;
+        if before || after {
+            types[start..i]
+                .iter_mut()
+                .for_each(|class| *class = Class::EuropeanNumber);
+        }
+    }
+    // W6 and W7: remaining separators and terminators are neutral,
+    // and European numbers after left-to-right text are
+    // left-to-right.
+    let mut last_strong = embedding;
+    for class in types.iter_mut() {
+        match *class {
+            Class::EuropeanSeparator | Class::EuropeanTerminator | Class::CommonSeparator => {
+                *class = Class::OtherNeutral;
+            }
+            Class::Left | Class::Right => last_strong = *class,
+            Class::EuropeanNumber if last_strong == Class::Left => *class = Class::Left,
+            _ => {}
+        }
+    }
+    // N0: paired brackets get the direction of the text inside
+    // them, if that agrees with the paragraph direction or with the
+    // text before them.
+    for (open, close) in bracket_pairs(line, units, &indices, &types) {
+        let inside = types[open + 1..close]
+            .iter()
+            .filter_map(|class| class.strong())
+            .collect::<Vec<_>>();
+        if inside.is_empty() {
+            continue;
+        }
+        let resolved = if inside.contains(&embedding) {
+            embedding
+        } else {
+            let before = types[..open].iter().rev().find_map(|class| class.strong());
+            if before.unwrap_or(embedding) == embedding {
+                embedding
+            } else {
+                inside[0]
+            }
+        };
+        types[open] = resolved;
+        types[close] = resolved;
+    }
+    // N1 and N2: neutrals between text of the same direction get
+    // that direction, other neutrals get the paragraph direction.
+    let mut i = 0;
+    while i < n {
+        if !types[i].is_neutral() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < n && types[i].is_neutral() {
+            i += 1;
+        }
+        let before = match start {
+            0 => embedding,
+            _ => types[start - 1].strong().unwrap_or(embedding),
+        };
+        let after = match i {
+            _ if i == n => embedding,
+            _ => types[i].strong().unwrap_or(embedding),
+        };
+        let resolved = if before == after { before } else { embedding };
+        types[start..i]
+            .iter_mut()
+            .for_each(|class| *class = resolved);
+    }
+
+    // I1 and I2: the levels of the resolved types. Boundary neutrals
+    // get the level of the previous character.
+    let mut levels = vec![base; units.len()];
+    for (&i, class) in indices.iter().zip(&types) {
+        levels[i] = match (base, class) {
+            (0, Class::Right) => 1,
+            (0, Class::ArabicNumber) | (0, Class::EuropeanNumber) => 2,
+            (1, Class::Left) | (1, Class::ArabicNumber) | (1, Class::EuropeanNumber) => 2,
+            _ => base,
+        };
+    }
+    for i in 1..units.len() {
+        if units[i].class == Class::BoundaryNeutral {
+            levels[i] = levels[i - 1];
+        }
+    }
+
+    // L1: separators, and the whitespace before them and at the end
+    // of the line, get the paragraph level.
+    let mut trailing = true;
+    for (unit, level) in units.iter().zip(levels.iter_mut()).rev() {
+        match unit.class {
+            Class::ParagraphSeparator | Class::SegmentSeparator => {
+                trailing = true;
+                *level = base;
+            }
+            Class::Whitespace | Class::BoundaryNeutral if trailing => *level = base,
+            _ => trailing = false,
+        }
+    }
+    levels
+}
+
+/// Find the pairs of matching brackets among the units with the
+/// given `indices` and resolved `types`. The pairs are returned as
+/// positions in `types`, ordered by the opening bracket.
+fn bracket_pairs(
+    line: &str,
+    units: &[Unit],
+    indices: &[usize],
+    types: &[Class],
+) -> Vec<(usize, usize)> {
+    const BRACKETS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('{', '}')];
+    let mut stack: Vec<(char, usize)> = Vec::new();
+    let mut pairs = Vec::new();
+    for (pos, &i) in indices.iter().enumerate() {
+        if types[pos] != Class::OtherNeutral {
+            continue;
+        }
+        let text = &line[units[i].start..units[i].end];
+        if let Some(&(_, close)) = BRACKETS.iter().find(|(open, _)| text.starts_with(*open)) {
+            stack.push((close, pos));
+        } else if let Some(depth) = stack
+            .iter()
+            .rposition(|&(close, _)| text.starts_with(close))
+        {
+            pairs.push((stack[depth].1, pos));
+            stack.truncate(depth);
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// The mirrored form of a bracket or other paired character.
+fn mirror(ch: char) -> Option<char> {
+    const PAIRS: &[(char, char)] = &[
+        ('(', ')'),
+        ('<', '>'),
+        ('[', ']'),
+        ('{', '}'),
+        ('«', '»'),
+        ('‹', '›'),
+        ('⁅', '⁆'),
+        ('≤', '≥'),
+        ('⟨', '⟩'),
+        ('「', '」'),
+    ];
+    PAIRS.iter().find_map(|&(open, close)| match ch {
+        _ if ch == open => Some(close),
+        _ if ch == close => Some(open),
+        _ => None,
+    })
+}
+
+/// Reorder a single `line` of text from logical order into visual
+/// order. The characters of right-to-left runs are reversed, and
+/// brackets in them are mirrored. Combining marks stay after the
+/// characters they belong to, and ANSI escape sequences are kept
+/// intact.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::bidi::{reorder, Direction};
+///
+/// assert_eq!(reorder("abc אבג (דה)", Direction::LeftToRight), "abc (הד) גבא");
+/// assert_eq!(reorder("abc אבג (דה)", Direction::RightToLeft), "(הד) גבא abc");
+/// assert_eq!(reorder("אבג 123", Direction::RightToLeft), "123 גבא");
+/// ```
+pub fn reorder(line: &str, direction: Direction) -> Cow<'_, str> {
+    let units = units(line);
+    let levels = resolve_levels(line, &units, direction);
+    let max_level = levels.iter().copied().max().unwrap_or(0);
+    if max_level == 0 {
+        return Cow::Borrowed(line);
+    }
+
+    // L2: reverse every run at or above each level, from the highest
+    // level down to the lowest odd level.
+    let mut order = (0..units.len()).collect::<Vec<_>>();
+    let lowest_odd = levels.iter().copied().min().unwrap_or(0) | 1;
+    for level in (lowest_odd..=max_level).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+
+    // L4: mirror brackets in right-to-left runs.
+    let mut result = String::with_capacity(line.len());
+    for i in order {
+        let text = &line[units[i].start..units[i].end];
+        let mut chars = text.chars();
+        match (chars.next().and_then(mirror), chars.next()) {
+            (Some(mirrored), None) if levels[i] % 2 == 1 => result.push(mirrored),
+            _ => result.push_str(text),
+        }
+    }
+    if result == line {
+        return Cow::Borrowed(line);
+    }
+    Cow::Owned(result)
+}
+
+/// Wrap bidirectional text into lines in visual order.
+///
+/// Every line of `text` is a paragraph. The paragraph is wrapped in
+/// logical order, and each wrapped line is then reordered with
+/// [`reorder`]. The paragraph direction is `direction`, or it is found
+/// with [`paragraph_direction`] if `direction` is `None`. Paragraphs
+/// without letters are left-to-right.
+///
+/// The [`Options::alignment`] is relative to the paragraph direction:
+/// [`Alignment::Left`] aligns the lines at the start of the
+/// paragraph, which is on the right for right-to-left paragraphs, and
+/// [`Alignment::Right`] aligns them at the end. The same holds for
+/// indentation, which is reordered with the rest of the line.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::bidi::{self, Direction};
+///
+/// let text = "שלום עולם, hello";
+/// assert_eq!(bidi::wrap(text, 12, None), vec!["  ,םלוע םולש", "       hello"]);
+/// assert_eq!(bidi::wrap(text, 12, Some(Direction::LeftToRight)),
+///            vec!["םלוע םולש,", "hello"]);
+/// ```
+///
+/// [`Options::alignment`]: crate::Options::alignment
+pub fn wrap<'a, A, R, S, Opt>(
+    text: &str,
+    width_or_options: Opt,
+    direction: Option<Direction>,
+) -> Vec<Cow<'_, str>>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let mut options = width_or_options.into();
+    let alignment = options.alignment;
+    // The lines are aligned after they have been reordered.
+    options.alignment = match alignment {
+        Alignment::Justify(_) => Alignment::Justify(LastLine::Left),
+        _ => Alignment::Left,
+    };
+
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let direction = direction
+            .or_else(|| paragraph_direction(paragraph))
+            .unwrap_or(Direction::LeftToRight);
+        let wrapped = wrap_with_breaks(paragraph, &options, &[]);
+        let last = wrapped.len().saturating_sub(1);
+        for (line_number, line) in wrapped.into_iter().enumerate() {
+            let visual = match reorder(&line, direction) {
+                Cow::Owned(visual) => Some(visual),
+                Cow::Borrowed(_) => None,
+            };
+            let line = visual.map_or(line, Cow::Owned);
+            let line_alignment = match alignment {
+                Alignment::Justify(_) if line_number < last => Alignment::Left,
+                Alignment::Justify(LastLine::Left) => Alignment::Left,
+                Alignment::Justify(LastLine::Center) => Alignment::Center,
+                Alignment::Justify(LastLine::Right) => Alignment::Right,
+                alignment => alignment,
+            };
+            let padding = options
+                .width_of(line_number)
+                .saturating_sub(options.text_width(&line));
+            let left_padding = match (line_alignment, direction) {
+                (Alignment::Center, _) => padding / 2,
+                (Alignment::Left, Direction::RightToLeft) => padding,
+                (Alignment::Right, Direction::LeftToRight) => padding,
+                _ => 0,
+            };
+            if left_padding == 0 {
+                lines.push(line);
+            } else {
+                lines.push(Cow::Owned(" ".repeat(left_padding) + &line));
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reorder_left_to_right() {
+        assert_eq!(reorder("foo bar", Direction::LeftToRight), "foo bar");
+        assert!(matches!(
+            reorder("foo bar", Direction::LeftToRight),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            reorder("foo אב גד bar", Direction::LeftToRight),
+            "foo דג בא bar"
+        );
+        assert_eq!(reorder("אב 12 גד", Direction::LeftToRight), "דג 12 בא");
+    }
+
+    #[test]
+    fn reorder_right_to_left() {
+        assert_eq!(reorder("אב foo bar", Direction::RightToLeft), "foo bar בא");
+        assert_eq!(reorder("foo bar", Direction::RightToLeft), "foo bar");
+        assert_eq!(reorder("אב 1.5%", Direction::RightToLeft), "1.5% בא");
+        assert_eq!(reorder("אב  ", Direction::RightToLeft), "  בא");
+    }
+
+    #[test]
+    fn reorder_arabic_numbers() {
+        // European digits after Arabic letters are Arabic numbers.
+        assert_eq!(reorder("سلام 12", Direction::RightToLeft), "12 مالس");
+        assert_eq!(reorder("abc ١٢", Direction::LeftToRight), "abc ١٢");
+    }
+
+    #[test]
+    fn reorder_keeps_marks_and_escapes() {
+        assert_eq!(reorder("בְּ ג", Direction::LeftToRight), "ג בְּ");
+        assert_eq!(
+            reorder("\x1b[1mאב\x1b[0m", Direction::LeftToRight),
+            "\x1b[1mבא\x1b[0m"
+        );
+        assert_eq!(
+            paragraph_direction("\x1b[1mאב"),
+            Some(Direction::RightToLeft)
+        );
+    }
+
+    #[test]
+    fn wrap_alignment() {
+        let text = "אב גד הו";
+        assert_eq!(wrap(text, 6, None), vec![" דג בא", "    וה"]);
+        let options = Options::new(6).alignment(Alignment::Right);
+        assert_eq!(wrap(text, &options, None), vec!["דג בא", "וה"]);
+        let options = Options::new(6).alignment(Alignment::Center);
+        assert_eq!(wrap(text, &options, None), vec!["דג בא", "  וה"]);
+        assert_eq!(wrap("foo bar", 5, None), vec!["foo", "bar"]);
+    }
+
+    #[test]
+    fn wrap_paragraph_directions() {
+        let text = "foo אב\nאב foo";
+        assert_eq!(wrap(text, 8, None), vec!["foo בא", "  foo בא"]);
+        assert_eq!(
+            wrap(text, 8, Some(Direction::LeftToRight)),
+            vec!["foo בא", "בא foo"]
+        );
+    }
+}
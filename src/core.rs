@@ -33,7 +33,7 @@
 //! the functionality here is not sufficient or if you have ideas for
 //! improving it. We would love to hear from you!
 
-use crate::{Options, WordSplitter};
+use crate::{BreakMarker, CharWidth, Options, WordSplitter};
 
 /// The CSI or “Control Sequence Introducer” introduces an ANSI escape
 /// sequence. This is typically used for colored text and will be
@@ -41,28 +41,164 @@ use crate::{Options, WordSplitter};
 const CSI: (char, char) = ('\x1b', '[');
 /// The final bytes of an ANSI escape sequence must be in this range.
 const ANSI_FINAL_BYTE: std::ops::RangeInclusive<char> = '\x40'..='\x7e';
+/// The OSC or “Operating System Command” introduces an escape
+/// sequence which sets the terminal title, makes a hyperlink, and
+/// similar. It will also be ignored when computing the text width.
+const OSC: (char, char) = ('\x1b', ']');
+/// An OSC sequence ends with BEL or with ST, the “String Terminator”
+/// `ESC \`.
+const BEL: char = '\x07';
+
+/// Reset all colors and other styles set by SGR (“Select Graphic
+/// Rendition”) escape sequences.
+const SGR_RESET: &str = "\x1b[0m";
 
 /// Skip ANSI escape sequences. The `ch` is the current `char`, the
 /// `chars` provide the following characters. The `chars` will be
 /// modified if `ch` is the start of an ANSI escape sequence.
 #[inline]
 pub(crate) fn skip_ansi_escape_sequence<I: Iterator<Item = char>>(ch: char, chars: &mut I) -> bool {
-    if ch == CSI.0 && chars.next() == Some(CSI.1) {
-        // We have found the start of an ANSI escape code, typically
-        // used for colored terminal text. We skip until we find a
-        // "final byte" in the range 0x40–0x7E.
-        for ch in chars {
-            if ANSI_FINAL_BYTE.contains(&ch) {
-                return true;
+    if ch != CSI.0 {
+        return false;
+    }
+    match chars.next() {
+        Some(next) if next == CSI.1 => {
+            // We have found the start of an ANSI escape code, typically
+            // used for colored terminal text. We skip until we find a
+            // "final byte" in the range 0x40–0x7E.
+            for ch in chars {
+                if ANSI_FINAL_BYTE.contains(&ch) {
+                    return true;
+                }
+            }
+        }
+        Some(next) if next == OSC.1 => {
+            // An OSC sequence can contain any text, such as the URL of
+            // a hyperlink. We skip until we find the terminator.
+            while let Some(ch) = chars.next() {
+                if ch == BEL {
+                    return true;
+                }
+                if ch == OSC.0 {
+                    chars.next();
+                    return true;
+                }
             }
         }
+        _ => {}
     }
     false
 }
 
+/// Find the byte ranges of the ANSI escape sequences in `text`.
+pub(crate) fn ansi_escape_sequences(text: &str) -> Vec<std::ops::Range<usize>> {
+    let mut sequences = Vec::new();
+    let mut char_indices = text.char_indices();
+    while let Some((start, ch)) = char_indices.next() {
+        if skip_ansi_escape_sequence(ch, &mut char_indices.by_ref().map(|(_, ch)| ch)) {
+            let end = char_indices
+                .clone()
+                .next()
+                .map_or(text.len(), |(idx, _)| idx);
+            sequences.push(start..end);
+        }
+    }
+    sequences
+}
+
+/// Is the byte offset `idx` inside one of the escape `sequences`
+/// found by [`ansi_escape_sequences`]?
+pub(crate) fn inside_escape_sequence(sequences: &[std::ops::Range<usize>], idx: usize) -> bool {
+    sequences
+        .iter()
+        .any(|sequence| sequence.start < idx && idx < sequence.end)
+}
+
+/// The styles and the hyperlink which are active at some point in a
+/// text with ANSI escape sequences.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ActiveStyles {
+    /// The SGR sequences seen since the styles were last reset.
+    sgr: String,
+    /// The OSC 8 sequence which opened the current hyperlink.
+    hyperlink: Option<String>,
+}
+
+impl ActiveStyles {
+    /// Update the styles with the escape sequences in `text`.
+    pub(crate) fn update(&mut self, text: &str) {
+        for sequence in ansi_escape_sequences(text) {
+            let sequence = &text[sequence];
+            if sequence.starts_with("\x1b]8;") {
+                // A hyperlink is written as `ESC ] 8 ; params ; URI ST`
+                // and it is closed by an empty URI.
+                let end = sequence.len() - if sequence.ends_with(BEL) { 1 } else { 2 };
+                let uri = sequence[..end].splitn(3, ';').nth(2).unwrap_or("");
+                self.hyperlink = if uri.is_empty() {
+                    None
+                } else {
+                    Some(sequence.to_string())
+                };
+                continue;
+            }
+            if !sequence.starts_with("\x1b[") || !sequence.ends_with('m') {
+                continue;
+            }
+            let params = &sequence[2..sequence.len() - 1];
+            // An empty parameter means 0, which resets all styles.
+            if params.is_empty() || params == "0" {
+                self.sgr.clear();
+                continue;
+            }
+            if params.starts_with("0;") || params.starts_with(';') {
+                self.sgr.clear();
+            }
+            self.sgr.push_str(sequence);
+        }
+    }
+
+    /// Are no styles active?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sgr.is_empty() && self.hyperlink.is_none()
+    }
+
+    /// The escape sequences which reopen the styles.
+    pub(crate) fn reopen(&self) -> String {
+        let mut result = self.sgr.clone();
+        if let Some(hyperlink) = &self.hyperlink {
+            result.push_str(hyperlink);
+        }
+        result
+    }
+
+    /// Push the escape sequences which close the styles to `result`.
+    pub(crate) fn close(&self, result: &mut String) {
+        if let Some(hyperlink) = &self.hyperlink {
+            // The hyperlink is closed with the same terminator as it
+            // was opened with.
+            result.push_str("\x1b]8;;");
+            result.push_str(if hyperlink.ends_with(BEL) {
+                "\x07"
+            } else {
+                "\x1b\\"
+            });
+        }
+        if !self.sgr.is_empty() {
+            result.push_str(SGR_RESET);
+        }
+    }
+}
+
+/// Soft hyphen, also knows as a “shy hyphen”. Shows up as ‘-’ if a
+/// line is broken at this point, and is otherwise invisible.
+pub(crate) const SOFT_HYPHEN: char = '\u{00ad}';
+
 #[cfg(feature = "unicode-width")]
 #[inline]
-fn ch_width(ch: char) -> usize {
+pub(crate) fn ch_width(ch: char) -> usize {
+    if ch == SOFT_HYPHEN {
+        return 0;
+    }
     unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0)
 }
 
@@ -73,14 +209,68 @@ const DOUBLE_WIDTH_CUTOFF: char = '\u{1100}';
 
 #[cfg(not(feature = "unicode-width"))]
 #[inline]
-fn ch_width(ch: char) -> usize {
-    if ch < DOUBLE_WIDTH_CUTOFF {
+pub(crate) fn ch_width(ch: char) -> usize {
+    if ch == SOFT_HYPHEN || ch.is_control() {
+        0
+    } else if ch < DOUBLE_WIDTH_CUTOFF {
         1
+    } else if ch == ZERO_WIDTH_SPACE || ch == WORD_JOINER {
+        0
     } else {
         2
     }
 }
 
+/// U+200B (Zero Width Space) marks an invisible break opportunity.
+#[cfg(not(feature = "unicode-width"))]
+const ZERO_WIDTH_SPACE: char = '\u{200b}';
+
+/// U+2060 (Word Joiner) suppresses a break between its neighbors.
+const WORD_JOINER: char = '\u{2060}';
+
+/// U+200D (Zero Width Joiner) joins emoji into a single grapheme.
+pub(crate) const ZERO_WIDTH_JOINER: char = '\u{200d}';
+
+/// Does `ch` extend the grapheme cluster of the character before it?
+///
+/// This covers combining marks, variation selectors, emoji modifiers,
+/// and emoji tags. It is an approximation of the extended grapheme
+/// clusters of [Unicode Standard Annex #29][uax29] which needs no
+/// Unicode tables.
+///
+/// [uax29]: https://www.unicode.org/reports/tr29/
+pub(crate) fn is_grapheme_extend(ch: char) -> bool {
+    matches!(ch,
+        '\u{0300}'..='\u{036f}'      // Combining Diacritical Marks
+        | '\u{0483}'..='\u{0489}'    // Cyrillic combining marks
+        | '\u{0591}'..='\u{05bd}'    // Hebrew points
+        | '\u{064b}'..='\u{065f}'    // Arabic harakat
+        | '\u{0e31}' | '\u{0e34}'..='\u{0e3a}' | '\u{0e47}'..='\u{0e4e}' // Thai
+        | '\u{1ab0}'..='\u{1aff}'    // Combining Diacritical Marks Extended
+        | '\u{1dc0}'..='\u{1dff}'    // Combining Diacritical Marks Supplement
+        | '\u{200c}' | ZERO_WIDTH_JOINER
+        | '\u{20d0}'..='\u{20ff}'    // Combining Marks for Symbols
+        | '\u{fe00}'..='\u{fe0f}'    // Variation Selectors
+        | '\u{fe20}'..='\u{fe2f}'    // Combining Half Marks
+        | '\u{1f3fb}'..='\u{1f3ff}'  // Emoji skin tone modifiers
+        | '\u{e0020}'..='\u{e007f}'  // Tags
+        | '\u{e0100}'..='\u{e01ef}'  // Variation Selectors Supplement
+    )
+}
+
+/// Is `ch` a regional indicator? Two of them form a flag.
+pub(crate) fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&ch)
+}
+
+/// Punctuation which may hang past the end of a line.
+fn is_hanging_punctuation(ch: char) -> bool {
+    matches!(
+        ch,
+        '.' | ',' | ':' | ';' | '-' | '\u{2010}' | '\'' | '"' | '\u{2019}' | '\u{201d}' | '\u{bb}'
+    )
+}
+
 /// Compute the display width of `text` while skipping over ANSI
 /// escape sequences.
 ///
@@ -91,12 +281,32 @@ fn ch_width(ch: char) -> usize {
 ///
 /// assert_eq!(display_width("Café Plain"), 10);
 /// assert_eq!(display_width("\u{1b}[31mCafé Rouge\u{1b}[0m"), 10);
+/// assert_eq!(display_width("\u{1b}]0;Window title\u{7}Café"), 4);
+/// ```
+///
+/// Only the text of a terminal hyperlink is counted:
+///
+/// ```
+/// use textwrap::core::display_width;
+///
+/// let link = "\u{1b}]8;;https://example.org\u{1b}\\Example\u{1b}]8;;\u{1b}\\";
+/// assert_eq!(display_width(link), 7);
+/// ```
+///
+/// A U+00AD (Soft Hyphen) is invisible unless a line is broken at it,
+/// so it has no width:
+///
+/// ```
+/// use textwrap::core::display_width;
+///
+/// assert_eq!(display_width("hy\u{ad}phen"), 6);
 /// ```
 ///
 /// **Note:** When the `unicode-width` Cargo feature is disabled, the
 /// width of a `char` is determined by a crude approximation which
-/// simply counts chars below U+1100 as 1 column wide, and all other
-/// characters as 2 columns wide. With the feature enabled, function
+/// simply counts chars below U+1100 as 1 column wide, control
+/// characters, U+200B (Zero Width Space), and U+2060 (Word Joiner) as
+/// 0 columns wide, and all other characters as 2 columns wide. With the feature enabled, function
 /// will correctly deal with [combining characters] in their
 /// decomposed form (see [Unicode equivalence]).
 ///
@@ -169,6 +379,9 @@ fn ch_width(ch: char) -> usize {
 /// Gnome Terminal version 3.38.1, shows “❤️” as a big red heart, but
 /// shows "👨‍🦰" as “👨🦰”.
 ///
+/// Use [`textwrap::display_width`](crate::display_width) to measure
+/// text with the same settings as [`Options`](crate::Options).
+///
 /// [combining characters]: https://en.wikipedia.org/wiki/Combining_character
 /// [Unicode equivalence]: https://en.wikipedia.org/wiki/Unicode_equivalence
 /// [CJK characters]: https://en.wikipedia.org/wiki/CJK_characters
@@ -185,6 +398,33 @@ pub fn display_width(text: &str) -> usize {
     width
 }
 
+/// How good a place the end of a [`Fragment`] is for a line break.
+///
+/// The priorities are ordered: a line only ends at a break with a
+/// lower priority if there is no break with a higher priority within
+/// the line. This means that lower-priority breaks are used as a last
+/// resort when higher-priority breaks cannot produce a line which
+/// fits. Both wrapping algorithms in
+/// [`wrap_algorithms`](crate::wrap_algorithms) follow this rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BreakPriority {
+    /// A break which can be made anywhere, such as between two
+    /// characters of a word which is too wide to fit on a line. This
+    /// is used for the pieces made by [`Word::break_apart`].
+    Anywhere = 0,
+    /// A break at a soft hyphen, which is only used if a word cannot
+    /// be wrapped at normal break opportunities.
+    SoftHyphen = 1,
+    /// A break after punctuation without whitespace, such as an em
+    /// dash or a closing parenthesis. This is used by
+    /// [`PunctuationBreaks`](crate::PunctuationBreaks).
+    Punctuation = 2,
+    /// A normal break opportunity, such as the whitespace between two
+    /// words or a split point found by a
+    /// [`WordSplitter`](crate::WordSplitter). This is the default.
+    Normal = 3,
+}
+
 /// A (text) fragment denotes the unit which we wrap into lines.
 ///
 /// Fragments represent an abstract _word_ plus the _whitespace_
@@ -195,17 +435,261 @@ pub fn display_width(text: &str) -> usize {
 /// For wrapping purposes, the precise content of the word, the
 /// whitespace, and the penalty is irrelevant. All we need to know is
 /// the displayed width of each part, which this trait provides.
+///
+/// The widths are floating point numbers. This allows you to wrap
+/// text measured with a proportional font, where the widths are
+/// typically given in pixels or points. For text in a terminal, the
+/// widths are simply the number of columns used.
 pub trait Fragment: std::fmt::Debug {
     /// Displayed width of word represented by this fragment.
-    fn width(&self) -> usize;
+    fn width(&self) -> f64;
 
     /// Displayed width of the whitespace that must follow the word
     /// when the word is not at the end of a line.
-    fn whitespace_width(&self) -> usize;
+    fn whitespace_width(&self) -> f64;
 
     /// Displayed width of the penalty that must be inserted if the
     /// word falls at the end of a line.
-    fn penalty_width(&self) -> usize;
+    fn penalty_width(&self) -> f64;
+
+    /// Does a line have to end after this fragment?
+    ///
+    /// This corresponds to a penalty of −∞ in the Knuth–Plass
+    /// algorithm: both wrapping algorithms will always end the line
+    /// after a fragment which returns `true` here. This lets you
+    /// embed hard line breaks in a paragraph without splitting it
+    /// first. A forced break after the final fragment has no effect.
+    ///
+    /// The default implementation returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::Fragment;
+    /// use textwrap::wrap_algorithms::wrap_first_fit;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Item(f64, bool);
+    ///
+    /// impl Fragment for Item {
+    ///     fn width(&self) -> f64 { self.0 }
+    ///     fn whitespace_width(&self) -> f64 { 1.0 }
+    ///     fn penalty_width(&self) -> f64 { 0.0 }
+    ///     fn forced_break(&self) -> bool { self.1 }
+    /// }
+    ///
+    /// let items = [Item(1.0, false), Item(1.0, true), Item(1.0, false), Item(1.0, false)];
+    /// assert_eq!(wrap_first_fit(&items, &[80.0]),
+    ///            vec![&items[..2], &items[2..]]);
+    ///
+    /// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    /// assert_eq!(textwrap::wrap_algorithms::wrap_optimal_fit(&items, &[80.0], &Default::default()),
+    ///            vec![&items[..2], &items[2..]]);
+    /// ```
+    fn forced_break(&self) -> bool {
+        false
+    }
+
+    /// Must this fragment be kept on the same line as the next
+    /// fragment?
+    ///
+    /// This corresponds to a penalty of +∞ in the Knuth–Plass
+    /// algorithm: the wrapping algorithms will never end a line after
+    /// a fragment which returns `true` here. A run of such fragments,
+    /// together with the fragment following them, forms a group which
+    /// is never split across lines. Use this for things like `"10 kg"`
+    /// or `"Mr. Smith"`. If a group is too wide to fit on a line by
+    /// itself, it will overflow the line.
+    ///
+    /// A [`Fragment::forced_break`] takes precedence over this.
+    ///
+    /// The default implementation returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::Fragment;
+    /// use textwrap::wrap_algorithms::wrap_first_fit;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Item(f64, bool);
+    ///
+    /// impl Fragment for Item {
+    ///     fn width(&self) -> f64 { self.0 }
+    ///     fn whitespace_width(&self) -> f64 { 1.0 }
+    ///     fn penalty_width(&self) -> f64 { 0.0 }
+    ///     fn keep_with_next(&self) -> bool { self.1 }
+    /// }
+    ///
+    /// // Without the group, the second line would start with items[2].
+    /// let items = [Item(3.0, false), Item(2.0, true), Item(2.0, false), Item(3.0, false)];
+    /// assert_eq!(wrap_first_fit(&items, &[7.0]),
+    ///            vec![&items[..1], &items[1..3], &items[3..]]);
+    ///
+    /// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    /// assert_eq!(textwrap::wrap_algorithms::wrap_optimal_fit(&items, &[7.0], &Default::default()),
+    ///            vec![&items[..1], &items[1..3], &items[3..]]);
+    /// ```
+    fn keep_with_next(&self) -> bool {
+        false
+    }
+
+    /// How much the whitespace following the word can be stretched
+    /// beyond [`Fragment::whitespace_width`] when the line is too
+    /// short.
+    ///
+    /// Together with [`Fragment::whitespace_shrink`], this describes
+    /// the whitespace as Knuth–Plass style _glue_. When a line has
+    /// stretchable or shrinkable whitespace, the
+    /// [`wrap_optimal_fit`](crate::wrap_algorithms::wrap_optimal_fit)
+    /// algorithm measures the quality of a line by how much the
+    /// whitespace must be adjusted relative to how much it can be
+    /// adjusted. This is the foundation for high-quality full
+    /// justification.
+    ///
+    /// The default implementation returns `0.0`, meaning that the
+    /// whitespace is rigid.
+    fn whitespace_stretch(&self) -> f64 {
+        0.0
+    }
+
+    /// How much the whitespace following the word can be shrunk below
+    /// [`Fragment::whitespace_width`] when the line is too long. See
+    /// [`Fragment::whitespace_stretch`] for details.
+    ///
+    /// The default implementation returns `0.0`, meaning that the
+    /// whitespace is rigid.
+    fn whitespace_shrink(&self) -> f64 {
+        0.0
+    }
+
+    /// The priority of a line break after this fragment.
+    ///
+    /// A line only ends after a fragment with a low priority if no
+    /// fragment earlier in the line has a higher priority. Use
+    /// this to offer break opportunities which should only be used
+    /// when the line cannot be wrapped otherwise. See
+    /// [`BreakPriority`] for details.
+    ///
+    /// The default implementation returns [`BreakPriority::Normal`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::{BreakPriority, Fragment};
+    /// use textwrap::wrap_algorithms::wrap_first_fit;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Item(&'static str, BreakPriority);
+    ///
+    /// impl Fragment for Item {
+    ///     fn width(&self) -> f64 { self.0.len() as f64 }
+    ///     fn whitespace_width(&self) -> f64 {
+    ///         if self.1 == BreakPriority::Normal { 1.0 } else { 0.0 }
+    ///     }
+    ///     fn penalty_width(&self) -> f64 { 0.0 }
+    ///     fn break_priority(&self) -> BreakPriority { self.1 }
+    /// }
+    ///
+    /// // The word "bar" can be broken anywhere, but only if we must.
+    /// let items = [
+    ///     Item("foo", BreakPriority::Normal),
+    ///     Item("b", BreakPriority::Anywhere),
+    ///     Item("a", BreakPriority::Anywhere),
+    ///     Item("r", BreakPriority::Normal),
+    /// ];
+    /// assert_eq!(wrap_first_fit(&items, &[5.0]),
+    ///            vec![&items[..1], &items[1..]]);
+    /// assert_eq!(wrap_first_fit(&items, &[2.0]),
+    ///            vec![&items[..1], &items[1..3], &items[3..]]);
+    ///
+    /// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    /// assert_eq!(textwrap::wrap_algorithms::wrap_optimal_fit(&items, &[5.0], &Default::default()),
+    ///            vec![&items[..1], &items[1..]]);
+    /// ```
+    fn break_priority(&self) -> BreakPriority {
+        BreakPriority::Normal
+    }
+
+    /// Did the text have a line break after this fragment before it
+    /// was re-wrapped?
+    ///
+    /// When text which has already been wrapped is wrapped again,
+    /// such as when a document under version control is reformatted,
+    /// keeping the existing line breaks gives the smallest change.
+    /// The [`wrap_optimal_fit`](crate::wrap_algorithms::wrap_optimal_fit)
+    /// algorithm adds a penalty for every line which does not end at
+    /// an existing line break. This is only done if some fragment
+    /// returns `true` here.
+    ///
+    /// The default implementation returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::Fragment;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Item(f64, bool);
+    ///
+    /// impl Fragment for Item {
+    ///     fn width(&self) -> f64 { self.0 }
+    ///     fn whitespace_width(&self) -> f64 { 1.0 }
+    ///     fn penalty_width(&self) -> f64 { 0.0 }
+    ///     fn existing_break(&self) -> bool { self.1 }
+    /// }
+    ///
+    /// // The line used to end after items[1]. The gaps are smaller
+    /// // when the line ends after items[2], but we keep the old break.
+    /// let items = [Item(3.0, false), Item(3.0, true), Item(3.0, false), Item(3.0, false)];
+    /// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    /// assert_eq!(textwrap::wrap_algorithms::wrap_optimal_fit(&items, &[12.0], &Default::default()),
+    ///            vec![&items[..2], &items[2..]]);
+    /// ```
+    fn existing_break(&self) -> bool {
+        false
+    }
+
+    /// Displayed width at the end of the fragment, including the
+    /// penalty, which may hang past the end of a line.
+    ///
+    /// When the fragment falls at the end of a line, this much of it
+    /// is not counted toward the line width. This allows for _hanging
+    /// punctuation_, also known as optical margin alignment, where
+    /// trailing periods, commas, and hyphens stick out into the
+    /// margin. The hanging width should not exceed the width of the
+    /// fragment plus the width of its penalty.
+    ///
+    /// The default implementation returns `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::core::Fragment;
+    /// use textwrap::wrap_algorithms::wrap_first_fit;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Item(f64, f64);
+    ///
+    /// impl Fragment for Item {
+    ///     fn width(&self) -> f64 { self.0 }
+    ///     fn whitespace_width(&self) -> f64 { 1.0 }
+    ///     fn penalty_width(&self) -> f64 { 0.0 }
+    ///     fn hanging_width(&self) -> f64 { self.1 }
+    /// }
+    ///
+    /// // The final period of items[1] hangs past the line width.
+    /// let items = [Item(3.0, 0.0), Item(4.0, 1.0), Item(3.0, 0.0)];
+    /// assert_eq!(wrap_first_fit(&items, &[7.0]),
+    ///            vec![&items[..2], &items[2..]]);
+    ///
+    /// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    /// assert_eq!(textwrap::wrap_algorithms::wrap_optimal_fit(&items, &[7.0], &Default::default()),
+    ///            vec![&items[..2], &items[2..]]);
+    /// ```
+    fn hanging_width(&self) -> f64 {
+        0.0
+    }
 }
 
 /// A piece of wrappable text, including any trailing whitespace.
@@ -220,8 +704,26 @@ pub struct Word<'a> {
     pub whitespace: &'a str,
     /// Penalty string to insert if the word falls at the end of a line.
     pub penalty: &'a str,
+    /// Priority of a line break after the word.
+    pub break_priority: BreakPriority,
+    /// Did the text have a line break after the word before it was
+    /// re-wrapped? See [`Fragment::existing_break`].
+    pub existing_break: bool,
     // Cached width in columns.
     width: usize,
+    // Is the penalty left out of the line width? See
+    // `BreakMarker::counts_toward_width`.
+    hanging_penalty: bool,
+    // Width of trailing punctuation which may hang past the end of a
+    // line. See `Options::hanging_punctuation`.
+    hanging_width: usize,
+    // Is this a piece made by `Word::break_apart` which ends inside
+    // the original word?
+    broken_apart: bool,
+    // Widths of the whitespace and the penalty measured by
+    // `Word::measure`, if any.
+    whitespace_width: Option<usize>,
+    penalty_width: Option<usize>,
 }
 
 impl std::ops::Deref for Word<'_> {
@@ -241,29 +743,104 @@ impl<'a> Word<'a> {
         let trimmed = word.trim_end_matches(' ');
         Word {
             word: trimmed,
-            width: display_width(&trimmed),
+            width: display_width(trimmed),
             whitespace: &word[trimmed.len()..],
             penalty: "",
+            break_priority: BreakPriority::Normal,
+            existing_break: false,
+            hanging_penalty: false,
+            hanging_width: 0,
+            broken_apart: false,
+            whitespace_width: None,
+            penalty_width: None,
         }
     }
 
+    /// Change the penalty to a `marker` from `break_marker`.
+    pub(crate) fn set_penalty(&mut self, marker: &'a str, break_marker: &BreakMarker<'_>) {
+        self.penalty = marker;
+        self.hanging_penalty = !break_marker.counts_toward_width;
+    }
+
+    /// Measure the word, its whitespace, and its penalty with
+    /// `char_width`. See [`Options::char_width`].
+    pub(crate) fn measure(&mut self, char_width: &dyn CharWidth) {
+        self.width = char_width.str_width(self.word);
+        self.whitespace_width = Some(char_width.str_width(self.whitespace));
+        self.penalty_width = Some(char_width.str_width(self.penalty));
+    }
+
+    /// Is this a piece made by [`Word::break_apart`] which ends inside
+    /// the original word?
+    pub(crate) fn is_broken_apart(&self) -> bool {
+        self.broken_apart
+    }
+
+    /// Let trailing punctuation hang past the end of a line. This is
+    /// the last character of the penalty, or of the word if there is
+    /// no penalty.
+    pub(crate) fn hang_punctuation(&mut self, char_width: &dyn CharWidth) {
+        let end = if self.penalty.is_empty() {
+            self.word
+        } else if self.hanging_penalty {
+            ""
+        } else {
+            self.penalty
+        };
+        let mut chars = end.chars();
+        let mut last = None;
+        while let Some(ch) = chars.next() {
+            if !skip_ansi_escape_sequence(ch, &mut chars) {
+                last = Some(ch);
+            }
+        }
+        self.hanging_width = match last {
+            Some(ch) if is_hanging_punctuation(ch) => char_width.char_width(ch),
+            _ => 0,
+        };
+    }
+
     /// Break this word into smaller words with a width of at most
-    /// `line_width`. The whitespace and penalty from this `Word` is
-    /// added to the last piece.
+    /// `line_width`. The whitespace, penalty, break priority, and
+    /// existing break from this `Word` is added to the last piece.
+    /// The other pieces can be broken [`BreakPriority::Anywhere`].
+    ///
+    /// The word is never broken next to a U+2060 (Word Joiner), so a
+    /// piece can be wider than `line_width` if it contains one. The
+    /// word is also never broken inside a grapheme cluster: combining
+    /// marks, variation selectors, emoji modifiers, and characters
+    /// after a U+200D (Zero Width Joiner) stay with the character
+    /// before them, and the two regional indicators of a flag are
+    /// kept together.
     ///
     /// # Examples
     ///
     /// ```
-    /// use textwrap::core::Word;
-    /// assert_eq!(
-    ///     Word::from("Hello!  ").break_apart(3).collect::<Vec<_>>(),
-    ///     vec![Word::from("Hel"), Word::from("lo!  ")]
-    /// );
+    /// use textwrap::core::{BreakPriority, Word};
+    /// let pieces = Word::from("Hello!  ").break_apart(3).collect::<Vec<_>>();
+    /// assert_eq!(pieces[0].word, "Hel");
+    /// assert_eq!(pieces[0].break_priority, BreakPriority::Anywhere);
+    /// assert_eq!(pieces[1], Word::from("lo!  "));
+    ///
+    /// let pieces = Word::from("Hel\u{2060}lo").break_apart(3).collect::<Vec<_>>();
+    /// assert_eq!(pieces[0].word, "Hel\u{2060}l");
+    /// assert_eq!(pieces[1].word, "o");
+    ///
+    /// let pieces = Word::from("e\u{301}e\u{301}").break_apart(1).collect::<Vec<_>>();
+    /// assert_eq!(pieces[0].word, "e\u{301}");
+    /// assert_eq!(pieces[1].word, "e\u{301}");
+    ///
+    /// let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+    /// let pieces = Word::from(family).break_apart(2).collect::<Vec<_>>();
+    /// assert_eq!(pieces[0].word, family);
     /// ```
     pub fn break_apart<'b>(&'b self, line_width: usize) -> impl Iterator<Item = Word<'a>> + 'b {
         let mut char_indices = self.word.char_indices();
         let mut offset = 0;
         let mut width = 0;
+        let mut prev = None;
+        // Number of regional indicators in a row before `ch`.
+        let mut regional_indicators = 0;
 
         std::iter::from_fn(move || {
             while let Some((idx, ch)) = char_indices.next() {
@@ -271,12 +848,32 @@ impl<'a> Word<'a> {
                     continue;
                 }
 
-                if width > 0 && width + ch_width(ch) > line_width {
+                // A word joiner glues its neighbors together, and a
+                // grapheme cluster is never broken.
+                let joined = ch == WORD_JOINER
+                    || prev == Some(WORD_JOINER)
+                    || prev == Some(ZERO_WIDTH_JOINER)
+                    || is_grapheme_extend(ch)
+                    || is_regional_indicator(ch) && regional_indicators % 2 == 1;
+                regional_indicators = if is_regional_indicator(ch) {
+                    regional_indicators + 1
+                } else {
+                    0
+                };
+                prev = Some(ch);
+                if width > 0 && width + ch_width(ch) > line_width && !joined {
                     let word = Word {
                         word: &self.word[offset..idx],
                         width: width,
                         whitespace: "",
                         penalty: "",
+                        break_priority: BreakPriority::Anywhere,
+                        existing_break: false,
+                        hanging_penalty: false,
+                        hanging_width: 0,
+                        broken_apart: true,
+                        whitespace_width: None,
+                        penalty_width: None,
                     };
                     offset = idx;
                     width = ch_width(ch);
@@ -292,6 +889,13 @@ impl<'a> Word<'a> {
                     width: width,
                     whitespace: self.whitespace,
                     penalty: self.penalty,
+                    break_priority: self.break_priority,
+                    existing_break: self.existing_break,
+                    hanging_penalty: self.hanging_penalty,
+                    hanging_width: self.hanging_width,
+                    broken_apart: self.broken_apart,
+                    whitespace_width: None,
+                    penalty_width: None,
                 };
                 offset = self.word.len();
                 return Some(word);
@@ -304,23 +908,57 @@ impl<'a> Word<'a> {
 
 impl Fragment for Word<'_> {
     #[inline]
-    fn width(&self) -> usize {
-        self.width
+    fn width(&self) -> f64 {
+        self.width as f64
     }
 
     // We assume the whitespace consist of ' ' only. This allows us to
     // compute the display width in constant time.
     #[inline]
-    fn whitespace_width(&self) -> usize {
-        self.whitespace.len()
+    fn whitespace_width(&self) -> f64 {
+        match self.whitespace_width {
+            Some(width) => width as f64,
+            None => self.whitespace.len() as f64,
+        }
     }
 
-    // We assume the penalty is `""` or `"-"`. This allows us to
-    // compute the display width in constant time.
+    // The penalty is usually `""` or `"-"`, for which the display
+    // width is the length.
     #[inline]
-    fn penalty_width(&self) -> usize {
-        self.penalty.len()
+    fn penalty_width(&self) -> f64 {
+        if self.hanging_penalty {
+            0.0
+        } else if let Some(width) = self.penalty_width {
+            width as f64
+        } else if self.penalty.is_ascii() {
+            self.penalty.len() as f64
+        } else {
+            display_width(self.penalty) as f64
+        }
+    }
+
+    #[inline]
+    fn break_priority(&self) -> BreakPriority {
+        self.break_priority
     }
+
+    #[inline]
+    fn existing_break(&self) -> bool {
+        self.existing_break
+    }
+
+    #[inline]
+    fn hanging_width(&self) -> f64 {
+        self.hanging_width as f64
+    }
+}
+
+/// The kinds of split points used by [`split_words`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SplitKind {
+    SoftHyphen,
+    Normal,
+    Fallback,
 }
 
 /// Split words into smaller words according to the split points given
@@ -350,6 +988,24 @@ impl Fragment for Word<'_> {
 ///     vec![Word::from("foo-bar")]
 /// );
 /// ```
+///
+/// Words are also split after soft hyphens (U+00AD). These pieces
+/// have [`BreakPriority::SoftHyphen`] and a `"-"` penalty:
+///
+/// ```
+/// use textwrap::core::{split_words, BreakPriority, Word};
+/// use textwrap::Options;
+///
+/// let options = Options::new(80);
+/// let words = split_words(vec![Word::from("soft\u{ad}ware")], &options).collect::<Vec<_>>();
+/// assert_eq!(words[0].word, "soft\u{ad}");
+/// assert_eq!(words[0].penalty, "-");
+/// assert_eq!(words[0].break_priority, BreakPriority::SoftHyphen);
+/// assert_eq!(words[1], Word::from("ware"));
+/// ```
+///
+/// The pieces made at [`WordSplitter::fallback_points`] also have
+/// [`BreakPriority::SoftHyphen`].
 pub fn split_words<'a, I, A, R, S>(
     words: I,
     options: &'a Options<'a, A, R, S>,
@@ -358,17 +1014,62 @@ where
     I: IntoIterator<Item = Word<'a>>,
     S: WordSplitter,
 {
+    let break_marker = &options.break_marker;
     words.into_iter().flat_map(move |word| {
         let mut prev = 0;
-        let mut split_points = options.splitter.split_points(&word).into_iter();
+        // Split points are ordered by index and then by kind, so that
+        // a soft hyphen wins over a normal split point at the same
+        // index, which in turn wins over a fallback point.
+        let mut split_points = options
+            .splitter
+            .split_points(&word)
+            .into_iter()
+            .map(|idx| (idx, SplitKind::Normal))
+            .collect::<Vec<_>>();
+        let fallback_points = options.splitter.fallback_points(&word);
+        if !fallback_points.is_empty() || word.word.contains(SOFT_HYPHEN) {
+            // Words can always be split after a soft hyphen or at a
+            // fallback point, but only if there is no better split
+            // point.
+            split_points.extend(
+                fallback_points
+                    .into_iter()
+                    .map(|idx| (idx, SplitKind::Fallback)),
+            );
+            split_points.extend(
+                word.word
+                    .match_indices(SOFT_HYPHEN)
+                    .map(|(idx, shy)| (idx + shy.len(), SplitKind::SoftHyphen))
+                    .filter(|&(idx, _)| idx < word.word.len()),
+            );
+            split_points.sort_unstable();
+            split_points.dedup_by_key(|&mut (idx, _)| idx);
+        }
+        if word.word.contains(CSI.0) {
+            // ANSI escape sequences are never split.
+            let sequences = ansi_escape_sequences(&word);
+            split_points.retain(|&(idx, _)| !inside_escape_sequence(&sequences, idx));
+        }
+        let mut split_points = split_points.into_iter();
         std::iter::from_fn(move || {
-            if let Some(idx) = split_points.next() {
-                let need_hyphen = !word[..idx].ends_with('-');
+            if let Some((idx, kind)) = split_points.next() {
+                let need_hyphen = kind == SplitKind::SoftHyphen
+                    || options.splitter.inserts_hyphen() && !word[..idx].ends_with('-');
                 let w = Word {
                     word: &word.word[prev..idx],
                     width: display_width(&word[prev..idx]),
                     whitespace: "",
-                    penalty: if need_hyphen { "-" } else { "" },
+                    penalty: if need_hyphen { break_marker.hyphen } else { "" },
+                    break_priority: match kind {
+                        SplitKind::Normal => BreakPriority::Normal,
+                        _ => BreakPriority::SoftHyphen,
+                    },
+                    existing_break: false,
+                    hanging_penalty: need_hyphen && !break_marker.counts_toward_width,
+                    hanging_width: 0,
+                    broken_apart: false,
+                    whitespace_width: None,
+                    penalty_width: None,
                 };
                 prev = idx;
                 return Some(w);
@@ -380,6 +1081,13 @@ where
                     width: display_width(&word[prev..]),
                     whitespace: word.whitespace,
                     penalty: word.penalty,
+                    break_priority: word.break_priority,
+                    existing_break: word.existing_break,
+                    hanging_penalty: word.hanging_penalty,
+                    hanging_width: word.hanging_width,
+                    broken_apart: word.broken_apart,
+                    whitespace_width: None,
+                    penalty_width: None,
                 };
                 prev = word.word.len() + 1;
                 return Some(w);
@@ -394,14 +1102,16 @@ where
 ///
 /// This simply calls [`Word::break_apart`] on words that are too
 /// wide. This means that no extra `'-'` is inserted, the word is
-/// simply broken into smaller pieces.
+/// simply broken into smaller pieces. The pieces have
+/// [`BreakPriority::Anywhere`], so the wrapping algorithms prefer
+/// other breaks over breaks between them.
 pub fn break_words<'a, I>(words: I, line_width: usize) -> Vec<Word<'a>>
 where
     I: IntoIterator<Item = Word<'a>>,
 {
     let mut shortened_words = Vec::new();
     for word in words {
-        if word.width() > line_width {
+        if word.width() > line_width as f64 {
             shortened_words.extend(word.break_apart(line_width));
         } else {
             shortened_words.push(word);
@@ -433,6 +1143,48 @@ mod tests {
         assert_eq!(chars.next(), Some('H'));
     }
 
+    #[test]
+    fn skip_osc_escape_sequence() {
+        for text in &[
+            "\u{1b}]0;My title\u{7}Hello",
+            "\u{1b}]0;My title\u{1b}\\Hello",
+        ] {
+            let mut chars = text.chars();
+            let ch = chars.next().unwrap();
+            assert!(skip_ansi_escape_sequence(ch, &mut chars));
+            assert_eq!(chars.next(), Some('H'));
+        }
+    }
+
+    #[test]
+    fn active_styles_resets() {
+        let mut active = ActiveStyles::default();
+        active.update("\u{1b}[1mfoo\u{1b}[31m");
+        assert_eq!(active.reopen(), "\u{1b}[1m\u{1b}[31m");
+        active.update("\u{1b}[0;32mbar\u{1b}]0;My title\u{7}");
+        assert_eq!(active.reopen(), "\u{1b}[0;32m");
+        active.update("baz\u{1b}[m");
+        assert!(active.is_empty());
+    }
+
+    #[test]
+    fn active_styles_hyperlinks() {
+        let mut active = ActiveStyles::default();
+        let link = "\u{1b}]8;id=1;https://example.org\u{1b}\\";
+        active.update(&format!("\u{1b}[1m{}foo", link));
+        assert_eq!(active.reopen(), format!("\u{1b}[1m{}", link));
+        let mut closed = String::new();
+        active.close(&mut closed);
+        assert_eq!(closed, "\u{1b}]8;;\u{1b}\\\u{1b}[0m");
+
+        active.update("\u{1b}[0mbar\u{1b}]8;;\u{7}");
+        assert!(active.is_empty());
+        active.update("\u{1b}]8;;https://example.org\u{7}");
+        let mut closed = String::new();
+        active.close(&mut closed);
+        assert_eq!(closed, "\u{1b}]8;;\u{7}");
+    }
+
     #[test]
     fn emojis_have_correct_width() {
         use unic_emoji_char::is_emoji;
@@ -506,6 +1258,27 @@ mod tests {
         assert_eq!(display_width("😂😭🥺🤣✨😍🙏🥰😊🔥"), 20);
     }
 
+    #[test]
+    fn break_apart_keeps_graphemes() {
+        let pieces = |word| {
+            Word::from(word)
+                .break_apart(1)
+                .map(|word| word.word)
+                .collect::<Vec<_>>()
+        };
+        // Two flags made from regional indicators.
+        assert_eq!(
+            pieces("\u{1f1e9}\u{1f1f0}\u{1f1f8}\u{1f1ea}"),
+            vec!["\u{1f1e9}\u{1f1f0}", "\u{1f1f8}\u{1f1ea}"]
+        );
+        // Thumbs up with skin tone and a red heart.
+        assert_eq!(
+            pieces("\u{1f44d}\u{1f3fd}\u{2764}\u{fe0f}"),
+            vec!["\u{1f44d}\u{1f3fd}", "\u{2764}\u{fe0f}"]
+        );
+        assert_eq!(pieces("a\u{308}b"), vec!["a\u{308}", "b"]);
+    }
+
     #[test]
     fn split_words_no_words() {
         assert_iter_eq!(split_words(vec![], &Options::new(80)), vec![]);
@@ -527,6 +1300,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn split_words_escape_sequences() {
+        let link = "\u{1b}]8;;https://example.org/foo-bar\u{7}";
+        let text = format!("{}foo-bar", link);
+        assert_iter_eq!(
+            split_words(vec![Word::from(&text)], &Options::new(80)),
+            vec![Word::from(&text[..link.len() + 4]), Word::from("bar")]
+        );
+    }
+
     #[test]
     fn split_words_short_line() {
         // Note that `split_words` does not take the line width into
@@ -555,13 +1338,27 @@ mod tests {
                     word: "foo",
                     width: 3,
                     whitespace: "",
-                    penalty: "-"
+                    penalty: "-",
+                    break_priority: BreakPriority::Normal,
+                    existing_break: false,
+                    hanging_penalty: false,
+                    hanging_width: 0,
+                    broken_apart: false,
+                    whitespace_width: None,
+                    penalty_width: None,
                 },
                 Word {
                     word: "bar",
                     width: 3,
                     whitespace: "",
-                    penalty: ""
+                    penalty: "",
+                    break_priority: BreakPriority::Normal,
+                    existing_break: false,
+                    hanging_penalty: false,
+                    hanging_width: 0,
+                    broken_apart: false,
+                    whitespace_width: None,
+                    penalty_width: None,
                 }
             ]
         );
@@ -573,13 +1370,27 @@ mod tests {
                     word: "fo-",
                     width: 3,
                     whitespace: "",
-                    penalty: ""
+                    penalty: "",
+                    break_priority: BreakPriority::Normal,
+                    existing_break: false,
+                    hanging_penalty: false,
+                    hanging_width: 0,
+                    broken_apart: false,
+                    whitespace_width: None,
+                    penalty_width: None,
                 },
                 Word {
                     word: "bar",
                     width: 3,
                     whitespace: "",
-                    penalty: ""
+                    penalty: "",
+                    break_priority: BreakPriority::Normal,
+                    existing_break: false,
+                    hanging_penalty: false,
+                    hanging_width: 0,
+                    broken_apart: false,
+                    whitespace_width: None,
+                    penalty_width: None,
                 }
             ]
         );
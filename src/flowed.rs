@@ -0,0 +1,230 @@
+//! Functions for generating and parsing `format=flowed` text.
+//!
+//! The `format=flowed` format from [RFC 3676] is used in email to
+//! send text which the receiving client can rewrap to the width of
+//! its window. The text is wrapped as usual, but every line which
+//! continues on the next line ends with a space. Such a line is
+//! called _flowed_ and the line break after it is a _soft_ line
+//! break. Other line breaks are _hard_ line breaks which the
+//! receiving client keeps.
+//!
+//! Quoted text is marked with one `'>'` per quote level at the start
+//! of each line. Lines which could be mistaken for quotes, or which
+//! start with a space or with `"From "`, are _space-stuffed_: a
+//! single space is added in front of them, which the receiving
+//! client removes again.
+//!
+//! Use [`fill`] to generate `format=flowed` text and [`unfill`] to
+//! parse it.
+//!
+//! [RFC 3676]: https://tools.ietf.org/html/rfc3676
+
+use crate::{
+    wrap_algorithms, wrap_with_breaks, NoHyphenation, Options, WordSeparator, WordSplitter,
+};
+
+/// The signature separator. It ends with a space, but it is never
+/// flowed.
+const SIGNATURE_SEPARATOR: &str = "-- ";
+
+/// Fill `text` as `format=flowed` text.
+///
+/// Every line of `text` is a paragraph, which is wrapped into lines
+/// of at most `width` columns. All lines of a paragraph except the
+/// last end with a space. Lines starting with `'>'` are quoted: the
+/// quote marks are repeated on every wrapped line, followed by a
+/// space. Empty lines and the signature separator (`"-- "`) are kept
+/// as they are.
+///
+/// Words are never split, since a line break inside a word cannot be
+/// represented in `format=flowed` text. Any indentation in the
+/// options is ignored for the same reason.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::flowed;
+///
+/// let text = "Memory safety without garbage collection.\n\
+///             > Concurrency without data races.";
+/// assert_eq!(flowed::fill(text, 20),
+///            "Memory safety \n\
+///             without garbage \n\
+///             collection.\n\
+///             > Concurrency \n\
+///             > without data \n\
+///             > races.");
+/// ```
+pub fn fill<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let mut options = width_or_options
+        .into()
+        .break_words(false)
+        .splitter(NoHyphenation)
+        .initial_indent("")
+        .subsequent_indent("");
+    let line_ending = options.line_ending.as_str(text);
+    let width = options.width;
+
+    let mut result = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            result.push_str(line_ending);
+        }
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (depth, content) = split_quote(line);
+        if content == SIGNATURE_SEPARATOR {
+            result.push_str(line);
+            continue;
+        }
+
+        // Leave room for the quote marks, space-stuffing of quoted
+        // lines, and the trailing space of flowed lines.
+        options.width = width.saturating_sub(depth + usize::from(depth > 0) + 1);
+        let wrapped = wrap_with_breaks(content.trim_end_matches(' '), &options, &[]);
+        for (j, wrapped_line) in wrapped.iter().enumerate() {
+            if j > 0 {
+                result.push_str(line_ending);
+            }
+            result.push_str(&line[..depth]);
+            if !wrapped_line.is_empty() && needs_stuffing(depth, wrapped_line) {
+                result.push(' ');
+            }
+            result.push_str(wrapped_line);
+            if j + 1 < wrapped.len() {
+                result.push(' ');
+            }
+        }
+    }
+    result
+}
+
+/// Unwrap `format=flowed` text.
+///
+/// Flowed lines are joined with the following lines of the same
+/// quote depth, so that every paragraph ends up on a single line.
+/// Space-stuffing is removed. Quoted paragraphs start with one `'>'`
+/// per quote level, followed by a space. This is the inverse of
+/// [`fill`].
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::flowed;
+///
+/// let text = "Memory safety \r\n\
+///             without garbage \r\n\
+///             collection.\r\n\
+///             >> Concurrency \r\n\
+///             >> without data races.\r\n\
+///             \x20From here on, everything is fine.";
+/// assert_eq!(flowed::unfill(text),
+///            "Memory safety without garbage collection.\n\
+///             >> Concurrency without data races.\n\
+///             From here on, everything is fine.");
+/// ```
+pub fn unfill(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    // The quote depth of a flowed line which continues on the next
+    // line.
+    let mut flowed_depth = None;
+    for (i, line) in text.split('\n').enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (depth, content) = split_quote(line);
+        if flowed_depth != Some(depth) {
+            if flowed_depth.is_some() {
+                // A flowed line followed by a line with another
+                // quote depth is treated as a fixed line.
+                trim_end_spaces(&mut result);
+            }
+            if i > 0 {
+                result.push('\n');
+            }
+            result.push_str(&line[..depth]);
+            if depth > 0 && !content.is_empty() {
+                result.push(' ');
+            }
+        }
+        result.push_str(content);
+        flowed_depth = if content.ends_with(' ') && content != SIGNATURE_SEPARATOR {
+            Some(depth)
+        } else {
+            None
+        };
+    }
+    if flowed_depth.is_some() {
+        trim_end_spaces(&mut result);
+    }
+    result
+}
+
+/// Split `line` into its quote depth and its content. Space-stuffing
+/// is removed from the content.
+fn split_quote(line: &str) -> (usize, &str) {
+    let content = line.trim_start_matches('>');
+    let depth = line.len() - content.len();
+    (depth, content.strip_prefix(' ').unwrap_or(content))
+}
+
+/// Must a line with the given quote depth and content be
+/// space-stuffed?
+fn needs_stuffing(depth: usize, content: &str) -> bool {
+    depth > 0
+        || content.starts_with(' ')
+        || content.starts_with('>')
+        || content.starts_with("From ")
+}
+
+/// Remove trailing spaces from `text`.
+fn trim_end_spaces(text: &mut String) {
+    let len = text.trim_end_matches(' ').len();
+    text.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_unfill_roundtrip() {
+        let text = "From the start, this is a paragraph.\n\
+                    \n\
+                    >> It was > quoted, really.\n\
+                    > Some more.\n\
+                    -- \n\
+                    Signature";
+        let flowed = fill(
+            text,
+            Options::new(12).wrap_algorithm(wrap_algorithms::FirstFit),
+        );
+        assert_eq!(
+            flowed,
+            " From the \n\
+             start, this \n\
+             is a \n\
+             paragraph.\n\
+             \n\
+             >> It was > \n\
+             >> quoted, \n\
+             >> really.\n\
+             > Some \n\
+             > more.\n\
+             -- \n\
+             Signature"
+        );
+        assert_eq!(unfill(&flowed), text);
+    }
+
+    #[test]
+    fn unfill_quote_depth_change() {
+        // The flowed line is followed by a line with another quote
+        // depth, so the line break is kept.
+        assert_eq!(unfill("> foo \n>> bar"), "> foo\n>> bar");
+        assert_eq!(unfill(">\n> "), ">\n>");
+    }
+}
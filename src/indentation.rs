@@ -49,29 +49,133 @@
 ///
 /// assert_eq!(indent(" \t  Foo   ", "->"), "-> \t  Foo   ");
 /// ```
+///
+/// No-break spaces, such as U+00A0, are not whitespace here: a line
+/// with only no-break spaces is not empty.
 pub fn indent(s: &str, prefix: &str) -> String {
-    // We know we'll need more than s.len() bytes for the output, but
-    // without counting '\n' characters (which is somewhat slow), we
-    // don't know exactly how much. However, we can preemptively do
-    // the first doubling of the output size.
-    let mut result = String::with_capacity(2 * s.len());
-    let trimmed_prefix = prefix.trim_end();
-    for (idx, line) in s.split_terminator('\n').enumerate() {
-        if idx > 0 {
-            result.push('\n');
+    Indent::new(|_, _| prefix).indent(s)
+}
+
+/// Configurable indentation of lines.
+///
+/// By default, this works like [`indent`], except that the prefix is
+/// computed for every line by a closure. The closure receives the
+/// line number, starting from zero, and the line itself. Blank lines
+/// can get the full prefix, and the trailing newline can be dropped.
+///
+/// # Examples
+///
+/// Emit nested blocks from a code generator:
+///
+/// ```
+/// use textwrap::Indent;
+///
+/// let code = "fn main() {\nprintln!(\"Hello!\");\n\n}\n";
+/// let indent = Indent::new(|_, line: &str| {
+///     if line.starts_with("fn") || line == "}" { "    " } else { "        " }
+/// });
+/// assert_eq!(indent.indent(code),
+///            "    fn main() {\n        println!(\"Hello!\");\n\n    }\n");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Indent<F> {
+    /// Computes the prefix of a line from its line number and its
+    /// content.
+    pub prefix: F,
+    /// Add the full prefix to blank lines. See the
+    /// [`Indent::indent_blank_lines`] method.
+    pub indent_blank_lines: bool,
+    /// Keep the trailing newline of the text. See the
+    /// [`Indent::keep_trailing_newline`] method.
+    pub keep_trailing_newline: bool,
+}
+
+impl<F, P> Indent<F>
+where
+    F: Fn(usize, &str) -> P,
+    P: AsRef<str>,
+{
+    /// Indent lines with the prefix computed by `prefix`. Blank lines
+    /// get the prefix without trailing whitespace, and the trailing
+    /// newline is kept, like with [`indent`].
+    pub fn new(prefix: F) -> Self {
+        Indent {
+            prefix,
+            indent_blank_lines: false,
+            keep_trailing_newline: true,
         }
-        if line.trim().is_empty() {
-            result.push_str(trimmed_prefix);
-        } else {
-            result.push_str(prefix);
+    }
+
+    /// Change [`self.indent_blank_lines`]. By default, trailing
+    /// whitespace is stripped from the prefix of lines which are
+    /// empty or only whitespace. With this setting, they get the full
+    /// prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::Indent;
+    ///
+    /// let indent = Indent::new(|_, _| "# ");
+    /// assert_eq!(indent.indent("foo\n\nbar"), "# foo\n#\n# bar");
+    /// let indent = indent.indent_blank_lines(true);
+    /// assert_eq!(indent.indent("foo\n\nbar"), "# foo\n# \n# bar");
+    /// ```
+    ///
+    /// [`self.indent_blank_lines`]: #structfield.indent_blank_lines
+    pub fn indent_blank_lines(self, setting: bool) -> Self {
+        Indent {
+            indent_blank_lines: setting,
+            ..self
         }
-        result.push_str(line);
     }
-    if s.ends_with('\n') {
-        // split_terminator will have eaten the final '\n'.
-        result.push('\n');
+
+    /// Change [`self.keep_trailing_newline`]. When set to `false`, a
+    /// trailing newline is removed from the indented text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::Indent;
+    ///
+    /// let indent = Indent::new(|_, _| "  ").keep_trailing_newline(false);
+    /// assert_eq!(indent.indent("foo\nbar\n"), "  foo\n  bar");
+    /// ```
+    ///
+    /// [`self.keep_trailing_newline`]: #structfield.keep_trailing_newline
+    pub fn keep_trailing_newline(self, setting: bool) -> Self {
+        Indent {
+            keep_trailing_newline: setting,
+            ..self
+        }
+    }
+
+    /// Indent each line of `s`.
+    pub fn indent(&self, s: &str) -> String {
+        // We know we'll need more than s.len() bytes for the output, but
+        // without counting '\n' characters (which is somewhat slow), we
+        // don't know exactly how much. However, we can preemptively do
+        // the first doubling of the output size.
+        let mut result = String::with_capacity(2 * s.len());
+        for (idx, line) in s.split_terminator('\n').enumerate() {
+            if idx > 0 {
+                result.push('\n');
+            }
+            let prefix = (self.prefix)(idx, line);
+            let prefix = prefix.as_ref();
+            if !self.indent_blank_lines && line.chars().all(is_breaking_whitespace) {
+                result.push_str(prefix.trim_end_matches(is_breaking_whitespace));
+            } else {
+                result.push_str(prefix);
+            }
+            result.push_str(line);
+        }
+        if s.ends_with('\n') && self.keep_trailing_newline {
+            // split_terminator will have eaten the final '\n'.
+            result.push('\n');
+        }
+        result
     }
-    result
 }
 
 /// Removes common leading whitespace from each line.
@@ -92,7 +196,120 @@ pub fn indent(s: &str, prefix: &str) -> String {
 /// 3rd line
 /// ");
 /// ```
+///
+/// No-break spaces, such as U+00A0, are never removed since they glue
+/// the text together.
 pub fn dedent(s: &str) -> String {
+    remove_prefix(s, common_prefix(s))
+}
+
+/// Configurable removal of leading whitespace.
+///
+/// By default, this works like [`dedent`]. Tabs in the indentation
+/// can be expanded to spaces first, which makes lines indented with
+/// tabs and lines indented with spaces line up, and the amount of
+/// whitespace removed can be limited to a number of columns. The
+/// removed prefix is returned together with the text, so that the
+/// text can be indented again later with [`indent`].
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{indent, Dedent};
+///
+/// let text = "\tfoo\n      bar\n";
+/// let (dedented, prefix) = Dedent::new().expand_tabs(4).columns(2).dedent(text);
+/// assert_eq!(dedented, "  foo\n    bar\n");
+/// assert_eq!(prefix, "  ");
+/// assert_eq!(indent(&dedented, &prefix), "    foo\n      bar\n");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dedent {
+    /// Expand tabs in the indentation to this many columns, if set.
+    /// See the [`Dedent::expand_tabs`] method.
+    pub tab_width: Option<usize>,
+    /// Remove at most this many columns, if set. See the
+    /// [`Dedent::columns`] method.
+    pub columns: Option<usize>,
+}
+
+impl Dedent {
+    /// Remove all common leading whitespace, like [`dedent`].
+    pub const fn new() -> Self {
+        Dedent {
+            tab_width: None,
+            columns: None,
+        }
+    }
+
+    /// Change [`self.tab_width`]. Tabs in the indentation of every
+    /// line are replaced by spaces up to the next multiple of
+    /// `tab_width` columns before the indentation is removed. Tabs
+    /// after the indentation are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{dedent, Dedent};
+    ///
+    /// let text = "\tfoo\n        bar";
+    /// assert_eq!(dedent(text), text);
+    /// assert_eq!(Dedent::new().expand_tabs(8).dedent(text).0, "foo\nbar");
+    /// ```
+    ///
+    /// [`self.tab_width`]: #structfield.tab_width
+    pub const fn expand_tabs(self, tab_width: usize) -> Self {
+        Dedent {
+            tab_width: Some(tab_width),
+            ..self
+        }
+    }
+
+    /// Change [`self.columns`]. At most `columns` whitespace
+    /// characters are removed from every line, even when the lines
+    /// share more indentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::Dedent;
+    ///
+    /// let (text, prefix) = Dedent::new().columns(2).dedent("    foo\n      bar");
+    /// assert_eq!(text, "  foo\n    bar");
+    /// assert_eq!(prefix, "  ");
+    /// ```
+    ///
+    /// [`self.columns`]: #structfield.columns
+    pub const fn columns(self, columns: usize) -> Self {
+        Dedent {
+            columns: Some(columns),
+            ..self
+        }
+    }
+
+    /// Remove leading whitespace from each line of `s`. The dedented
+    /// text is returned together with the prefix which was removed
+    /// from every line.
+    pub fn dedent(&self, s: &str) -> (String, String) {
+        let expanded;
+        let s = match self.tab_width {
+            Some(tab_width) => {
+                expanded = expand_indentation(s, tab_width);
+                &expanded
+            }
+            None => s,
+        };
+        let mut prefix = common_prefix(s);
+        if let Some((idx, _)) = self.columns.and_then(|n| prefix.char_indices().nth(n)) {
+            prefix = &prefix[..idx];
+        }
+        (remove_prefix(s, prefix), prefix.to_owned())
+    }
+}
+
+/// Find the longest whitespace prefix shared by all lines of `s`
+/// which are not empty or only whitespace.
+fn common_prefix(s: &str) -> &str {
     let mut prefix = "";
     let mut lines = s.lines();
 
@@ -100,7 +317,7 @@ pub fn dedent(s: &str) -> String {
     for line in &mut lines {
         let mut whitespace_idx = line.len();
         for (idx, ch) in line.char_indices() {
-            if !ch.is_whitespace() {
+            if !is_breaking_whitespace(ch) {
                 whitespace_idx = idx;
                 break;
             }
@@ -131,13 +348,89 @@ pub fn dedent(s: &str) -> String {
         }
     }
 
-    // We now go over the lines a second time to build the result.
-    let mut result = String::new();
+    prefix
+}
+
+/// Remove `prefix` from each line of `s`. Lines with only whitespace
+/// become empty.
+fn remove_prefix(s: &str, prefix: &str) -> String {
+    map_lines(s, |line| {
+        if line.starts_with(prefix) && !line.chars().all(is_breaking_whitespace) {
+            &line[prefix.len()..]
+        } else {
+            ""
+        }
+    })
+}
+
+/// Add `prefix` in front of each line of `s`.
+///
+/// This is like [`indent`], but meant for prefixes such as `"> "`,
+/// `"# "`, or `";; "` used for quotes and comments. Empty lines get
+/// the prefix without trailing whitespace. Lines with only whitespace
+/// get the full prefix, so that [`strip_prefix_lines`] restores the
+/// original text exactly.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::add_prefix_lines;
+///
+/// assert_eq!(add_prefix_lines("foo\n\nbar\n", ";; "), ";; foo\n;;\n;; bar\n");
+/// ```
+pub fn add_prefix_lines(s: &str, prefix: &str) -> String {
+    let trimmed_prefix = prefix.trim_end_matches(is_breaking_whitespace);
+    let mut result = String::with_capacity(s.len() + prefix.len());
     for line in s.lines() {
-        if line.starts_with(&prefix) && line.chars().any(|c| !c.is_whitespace()) {
-            let (_, tail) = line.split_at(prefix.len());
-            result.push_str(tail);
+        if line.is_empty() {
+            result.push_str(trimmed_prefix);
+        } else {
+            result.push_str(prefix);
         }
+        result.push_str(line);
+        result.push('\n');
+    }
+    if !s.ends_with('\n') {
+        result.pop();
+    }
+    result
+}
+
+/// Remove `prefix` from the start of each line.
+///
+/// This is the inverse of [`add_prefix_lines`]. Like [`dedent`] it
+/// removes a prefix common to the lines, but the prefix is given and
+/// it does not need to be whitespace. Lines consisting of the prefix
+/// without trailing whitespace become empty. Lines which do not start
+/// with the prefix are kept as they are.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::strip_prefix_lines;
+///
+/// let quote = "> Memory safety\n>\n> without garbage collection.\n";
+/// assert_eq!(strip_prefix_lines(quote, "> "),
+///            "Memory safety\n\nwithout garbage collection.\n");
+/// ```
+pub fn strip_prefix_lines(s: &str, prefix: &str) -> String {
+    let trimmed_prefix = prefix.trim_end_matches(is_breaking_whitespace);
+    map_lines(s, |line| match line.strip_prefix(prefix) {
+        Some(rest) => rest,
+        None if line == trimmed_prefix => "",
+        None => line,
+    })
+}
+
+/// Replace each line of `s` by `f(line)`. A trailing newline is kept
+/// if there is one.
+fn map_lines<'a, F>(s: &'a str, mut f: F) -> String
+where
+    F: FnMut(&'a str) -> &'a str,
+{
+    let mut result = String::with_capacity(s.len());
+    for line in s.lines() {
+        result.push_str(f(line));
         result.push('\n');
     }
 
@@ -149,6 +442,35 @@ pub fn dedent(s: &str) -> String {
     result
 }
 
+/// Replace the tabs in the indentation of each line of `s` by
+/// spaces up to the next multiple of `tab_width` columns.
+fn expand_indentation(s: &str, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut result = String::with_capacity(s.len());
+    for line in s.split_inclusive('\n') {
+        let rest = line.trim_start_matches(|ch| ch != '\n' && is_breaking_whitespace(ch));
+        let mut column = 0;
+        for ch in line[..line.len() - rest.len()].chars() {
+            if ch == '\t' {
+                let spaces = tab_width - column % tab_width;
+                result.push_str(&" ".repeat(spaces));
+                column += spaces;
+            } else {
+                result.push(ch);
+                column += 1;
+            }
+        }
+        result.push_str(rest);
+    }
+    result
+}
+
+/// Whitespace which can be removed or stripped. No-break spaces are
+/// part of the text they glue together.
+fn is_breaking_whitespace(ch: char) -> bool {
+    ch.is_whitespace() && !matches!(ch, '\u{a0}' | '\u{2007}' | '\u{202f}')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,4 +666,71 @@ mod tests {
         ].join("\n");
         assert_eq!(dedent(&x), y);
     }
+
+    #[test]
+    fn dedent_no_break_space() {
+        let x = "  \u{a0}10 kg\n    \u{202f}!";
+        assert_eq!(dedent(x), "\u{a0}10 kg\n  \u{202f}!");
+    }
+
+    #[test]
+    fn dedent_expand_tabs() {
+        let expand = Dedent::new().expand_tabs(4);
+        assert_eq!(
+            expand.dedent("\tfoo\n  \tbar\n\t\n"),
+            (String::from("foo\nbar\n\n"), String::from("    "))
+        );
+        assert_eq!(expand.dedent("  \t foo\tbar").0, "foo\tbar");
+        assert_eq!(Dedent::new().expand_tabs(0).dedent("\t foo").0, "foo");
+    }
+
+    #[test]
+    fn dedent_columns() {
+        let partial = Dedent::new().columns(3);
+        assert_eq!(
+            partial.dedent("  foo\n    bar"),
+            (String::from("foo\n  bar"), String::from("  "))
+        );
+        assert_eq!(
+            partial.dedent("\t\t\t\tfoo"),
+            (String::from("\tfoo"), String::from("\t\t\t"))
+        );
+        assert_eq!(Dedent::new().columns(0).dedent("  foo").0, "  foo");
+        assert_eq!(
+            Dedent::new().dedent("  foo\n   bar"),
+            (dedent("  foo\n   bar"), String::from("  "))
+        );
+    }
+
+    #[test]
+    fn prefix_lines_roundtrip() {
+        for text in ["", "\n", "foo", "foo\n\n  \nbar", "\nfoo\n"].iter() {
+            let quoted = add_prefix_lines(text, "> ");
+            assert_eq!(strip_prefix_lines(&quoted, "> "), *text);
+        }
+        assert_eq!(add_prefix_lines("\n", "> "), ">\n");
+        assert_eq!(add_prefix_lines("", "> "), "");
+    }
+
+    #[test]
+    fn strip_prefix_lines_mismatch() {
+        assert_eq!(
+            strip_prefix_lines("# foo\n#bar\n#\nbaz", "# "),
+            "foo\n#bar\n\nbaz"
+        );
+    }
+
+    #[test]
+    fn indent_line_numbers() {
+        let indent = Indent::new(|idx, _| format!("{}: ", idx + 1));
+        assert_eq!(indent.indent("foo\n\nbar\n"), "1: foo\n2:\n3: bar\n");
+        let indent = indent.indent_blank_lines(true).keep_trailing_newline(false);
+        assert_eq!(indent.indent("foo\n \nbar\n"), "1: foo\n2:  \n3: bar");
+        assert_eq!(indent.indent(""), "");
+    }
+
+    #[test]
+    fn indent_no_break_space() {
+        assert_eq!(indent("foo\n\u{a0}\n", "# "), "# foo\n# \u{a0}\n");
+    }
 }
@@ -0,0 +1,140 @@
+//! Justification of Arabic text with tatweels, see
+//! [`Justification::Kashida`](crate::Justification::Kashida).
+//!
+//! Only available when the `kashida` Cargo feature is enabled.
+
+use crate::core::is_grapheme_extend;
+
+/// How an Arabic letter joins its neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    /// The letter joins both the previous and the next letter.
+    Dual,
+    /// The letter only joins the previous letter.
+    Right,
+}
+
+/// The joining type of `ch`, if it is an Arabic letter which joins
+/// the previous letter.
+fn arabic_joining(ch: char) -> Option<Joining> {
+    match ch {
+        '\u{626}'
+        | '\u{628}'
+        | '\u{62a}'..='\u{62e}'
+        | '\u{633}'..='\u{647}'
+        | '\u{649}'
+        | '\u{64a}'
+        | '\u{66e}'
+        | '\u{66f}'
+        | '\u{678}'..='\u{687}'
+        | '\u{69a}'..='\u{6bf}'
+        | '\u{6c1}'
+        | '\u{6c2}'
+        | '\u{6cc}'
+        | '\u{6ce}'
+        | '\u{6d0}'
+        | '\u{6d1}'
+        | '\u{6fa}'..='\u{6fc}'
+        | '\u{6ff}' => Some(Joining::Dual),
+        '\u{622}'..='\u{625}'
+        | '\u{627}'
+        | '\u{629}'
+        | '\u{62f}'..='\u{632}'
+        | '\u{648}'
+        | '\u{671}'..='\u{673}'
+        | '\u{675}'..='\u{677}'
+        | '\u{688}'..='\u{699}'
+        | '\u{6c0}'
+        | '\u{6c3}'..='\u{6cb}'
+        | '\u{6cd}'
+        | '\u{6cf}'
+        | '\u{6d2}'
+        | '\u{6d3}'
+        | '\u{6d5}'
+        | '\u{6ee}'
+        | '\u{6ef}' => Some(Joining::Right),
+        _ => None,
+    }
+}
+
+/// Find the places in `chars` where a tatweel can be inserted, that
+/// is, before letters which join the previous letter. The last place
+/// in every word comes first, followed by the other places in order.
+fn kashida_points(chars: &[char]) -> Vec<usize> {
+    const LAM: char = '\u{644}';
+    let is_alef = |ch| matches!(ch, '\u{622}' | '\u{623}' | '\u{625}' | '\u{627}');
+
+    let mut words: Vec<Vec<usize>> = Vec::new();
+    let mut prev: Option<char> = None;
+    for (i, &ch) in chars.iter().enumerate() {
+        // Marks stay with their letter, but U+200C (Zero Width
+        // Non-Joiner) prevents the letters from joining.
+        if prev.is_some() && ch != '\u{200c}' && is_grapheme_extend(ch) {
+            continue;
+        }
+        if arabic_joining(ch).is_none() {
+            prev = None;
+            continue;
+        }
+        match prev {
+            None => words.push(Vec::new()),
+            Some(prev) if arabic_joining(prev) == Some(Joining::Dual) => {
+                if !(prev == LAM && is_alef(ch)) {
+                    if let Some(points) = words.last_mut() {
+                        points.push(i);
+                    }
+                }
+            }
+            Some(_) => {}
+        }
+        prev = Some(ch);
+    }
+
+    let mut points = words
+        .iter()
+        .filter_map(|points| points.last().copied())
+        .collect::<Vec<_>>();
+    for word in &words {
+        if let Some((_, rest)) = word.split_last() {
+            points.extend(rest);
+        }
+    }
+    points
+}
+
+/// Append `chars` to `result` and distribute `padding` tatweels over
+/// the places where Arabic letters join. Returns `false` and leaves
+/// `result` untouched if there are no such places.
+pub(crate) fn push_kashida(result: &mut String, chars: &[char], padding: usize) -> bool {
+    let points = kashida_points(chars);
+    if points.is_empty() {
+        return false;
+    }
+    let mut tatweels = vec![0; chars.len()];
+    for &point in points.iter().cycle().take(padding) {
+        tatweels[point] += 1;
+    }
+    for (&ch, &count) in chars.iter().zip(&tatweels) {
+        result.extend(std::iter::repeat_n('\u{640}', count));
+        result.push(ch);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kashida_points_in_words() {
+        // The last join point of each word comes first.
+        assert_eq!(
+            kashida_points(&"بيت بيت".chars().collect::<Vec<_>>()),
+            vec![2, 6, 1, 5]
+        );
+        // Marks stay with their letters.
+        assert_eq!(kashida_points(&"بِت".chars().collect::<Vec<_>>()), vec![2]);
+        // Lam followed by alef is a ligature.
+        assert!(kashida_points(&"لا".chars().collect::<Vec<_>>()).is_empty());
+    }
+}
@@ -8,10 +8,8 @@
 //! terminal. A quick example:
 //!
 //! ```no_run
-//! fn main() {
-//!     let text = "textwrap: a small library for wrapping text.";
-//!     println!("{}", textwrap::fill(text, 18));
-//! }
+//! let text = "textwrap: a small library for wrapping text.";
+//! println!("{}", textwrap::fill(text, 18));
 //! ```
 //!
 //! When you run this program, it will display the following output:
@@ -115,6 +113,9 @@
 //! assert_eq!(textwrap::dedent(before), after);
 //! ```
 //!
+//! The `textwrap-macros` crate has `dedent!` and `fill!` macros which
+//! do this at compile time for string literals.
+//!
 //! # Cargo Features
 //!
 //! The textwrap library can be slimmed down as needed via a number of
@@ -170,12 +171,46 @@
 //! * `hyphenation`: enables language-sensitive hyphenation via the
 //!   [hyphenation] crate. See the [`WordSplitter`] trait for details.
 //!
+//! * `tex-patterns`: enables hyphenation with TeX hyphenation
+//!   patterns loaded at runtime, without depending on the
+//!   [hyphenation] crate. See the [`TexPatterns`] word splitter for
+//!   details.
+//!
+//! * `dictionary-segmentation`: enables finding words in Thai, Lao,
+//!   and Khmer text, which is written without spaces between words,
+//!   using a dictionary. See the [`DictionarySegmenter`] word
+//!   separator for details.
+//!
+//! * `bidi`: enables wrapping of text which mixes right-to-left and
+//!   left-to-right scripts, with the wrapped lines reordered for
+//!   display. See the [`bidi`] module for details.
+//!
+//! * `kashida`: enables justification of Arabic text by elongating
+//!   the words with tatweels. See [`Justification::Kashida`] for
+//!   details.
+//!
+//! * `regex`: enables finding words with a regular expression via
+//!   the [regex] crate. See the [`RegexSeparator`] word separator for
+//!   details.
+//!
+//! * `rayon`: enables parallel wrapping of the lines in a large text
+//!   via the [rayon] crate. See the [`wrap_par`] and [`fill_par`]
+//!   functions for details.
+//!
+//! * `optimal-fit-dp`: enables the optimal-fit algorithm without the
+//!   [smawk] crate. A simpler algorithm is used, which is fast for
+//!   typical paragraphs, but slower than SMAWK for very wide lines.
+//!   See [`wrap_algorithms::wrap_optimal_fit`] for details. This
+//!   feature has no effect when the `smawk` feature is enabled.
+//!
 //! [unicode-linebreak]: https://docs.rs/unicode-linebreak/
 //! [unicode-width]: https://docs.rs/unicode-width/
 //! [smawk]: https://docs.rs/smawk/
 //! [textwrap-macros]: https://docs.rs/textwrap-macros/
 //! [terminal_size]: https://docs.rs/terminal_size/
 //! [hyphenation]: https://docs.rs/hyphenation/
+//! [rayon]: https://docs.rs/rayon/
+//! [regex]: https://docs.rs/regex/
 
 #![doc(html_root_url = "https://docs.rs/textwrap/0.13.4")]
 #![forbid(unsafe_code)] // See https://github.com/mgeisler/textwrap/issues/210
@@ -185,32 +220,71 @@
 
 use std::borrow::Cow;
 
+use crate::core::Fragment;
+
 mod indentation;
 pub use crate::indentation::dedent;
 pub use crate::indentation::indent;
+pub use crate::indentation::{add_prefix_lines, strip_prefix_lines};
+pub use crate::indentation::{Dedent, Indent};
 
 mod splitting;
-pub use crate::splitting::{HyphenSplitter, NoHyphenation, WordSplitter};
+#[cfg(feature = "tex-patterns")]
+pub use crate::splitting::TexPatterns;
+pub use crate::splitting::{
+    Fallback, FnSplitter, HyphenSplitter, HyphenationExceptions, IdentifierSplitter, MinFragments,
+    NoHyphenation, WordSplitter,
+};
 
 pub mod wrap_algorithms;
 
 mod word_separator;
+#[cfg(feature = "dictionary-segmentation")]
+pub use word_separator::DictionarySegmenter;
+#[cfg(feature = "regex")]
+pub use word_separator::RegexSeparator;
 #[cfg(feature = "unicode-linebreak")]
 pub use word_separator::UnicodeBreakProperties;
-pub use word_separator::{AsciiSpace, WordSeparator};
+pub use word_separator::{
+    AndThen, AsciiSpace, FilterBreaks, Kinsoku, PunctuationBreaks, UrlBreaks, WordSeparator,
+};
 
 pub mod core;
 
+mod width;
+use crate::width::Measure;
+pub use crate::width::{
+    display_width, AmbiguousWidth, AsciiWidth, CharWidth, FontMetrics, GraphemeWidth, UnicodeWidth,
+    WcWidth, WidthOptions,
+};
+
+#[cfg(feature = "bidi")]
+pub mod bidi;
+
+#[cfg(feature = "kashida")]
+mod kashida;
+
+pub mod flowed;
+
+pub mod markdown;
+
+mod wrapper;
+pub use crate::wrapper::Wrapper;
+
+mod wrapped_text;
+use crate::wrapped_text::LineSpan;
+pub use crate::wrapped_text::{LineBreak, Position, WrappedLine, WrappedText};
+
 // These private macros lets us hide the actual WrapAlgorithm and
 // WordSeperator used in the function signatures below.
-#[cfg(feature = "smawk")]
+#[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
 macro_rules! DefaultWrapAlgorithm {
     () => {
         wrap_algorithms::OptimalFit
     };
 }
 
-#[cfg(not(feature = "smawk"))]
+#[cfg(not(any(feature = "smawk", feature = "optimal-fit-dp")))]
 macro_rules! DefaultWrapAlgorithm {
     () => {
         wrap_algorithms::FirstFit
@@ -232,6 +306,28 @@ macro_rules! DefaultWordSeparator {
 }
 
 /// Holds settings for wrapping and filling text.
+///
+/// The indentation and the placeholder can be borrowed or owned
+/// strings. With owned strings, an `Options<'static>` can be kept in
+/// long-lived application state and sent to other threads:
+///
+/// ```
+/// use textwrap::wrap_algorithms::FirstFit;
+/// use textwrap::{fill, AsciiSpace, HyphenSplitter, Options};
+///
+/// fn quote_options(depth: usize) -> Options<'static, FirstFit, AsciiSpace, HyphenSplitter> {
+///     let quote = "> ".repeat(depth);
+///     Options::new(20)
+///         .wrap_algorithm(FirstFit)
+///         .word_separator(AsciiSpace)
+///         .initial_indent(quote.clone())
+///         .subsequent_indent(quote)
+/// }
+///
+/// let options = quote_options(2);
+/// let handle = std::thread::spawn(move || fill("Memory safety without garbage collection.", &options));
+/// assert_eq!(handle.join().unwrap(), "> > Memory safety\n> > without garbage\n> > collection.");
+/// ```
 #[derive(Debug, Clone)]
 pub struct Options<
     'a,
@@ -243,10 +339,10 @@ pub struct Options<
     pub width: usize,
     /// Indentation used for the first line of output. See the
     /// [`Options::initial_indent`] method.
-    pub initial_indent: &'a str,
+    pub initial_indent: Cow<'a, str>,
     /// Indentation used for subsequent lines of output. See the
     /// [`Options::subsequent_indent`] method.
-    pub subsequent_indent: &'a str,
+    pub subsequent_indent: Cow<'a, str>,
     /// Allow long words to be broken if they cannot fit on a line.
     /// When set to `false`, some lines may be longer than
     /// `self.width`. See the [`Options::break_words`] method.
@@ -262,18 +358,607 @@ pub struct Options<
     /// language-aware machine hyphenation. Please see the
     /// [`WordSplitter`] trait for details.
     pub splitter: S,
+    /// Prefer the existing line breaks when refilling text. See the
+    /// [`Options::preserve_breaks`] method.
+    pub preserve_breaks: bool,
+    /// Join lines which end a sentence with two spaces when refilling
+    /// text. See the [`Options::sentence_end_double_space`] method.
+    pub sentence_end_double_space: bool,
+    /// The text inserted where a word is split across two lines. See
+    /// the [`Options::break_marker`] method.
+    pub break_marker: BreakMarker<'a>,
+    /// How the wrapped lines are aligned. See the
+    /// [`Options::alignment`] method.
+    pub alignment: Alignment,
+    /// Where extra space is added when lines are justified. See the
+    /// [`Options::justification`] method.
+    pub justification: Justification,
+    /// Allow trailing punctuation to hang past the line width. See
+    /// the [`Options::hanging_punctuation`] method.
+    pub hanging_punctuation: bool,
+    /// The maximum number of wrapped lines, if any. See the
+    /// [`Options::max_lines`] method.
+    pub max_lines: Option<usize>,
+    /// The text added to the last line when lines are removed because
+    /// of [`Options::max_lines`]. See the [`Options::placeholder`]
+    /// method.
+    pub placeholder: Cow<'a, str>,
+    /// What happens to lines which are too long. See the
+    /// [`Options::overflow`] method.
+    pub overflow: Overflow,
+    /// The line ending used by [`fill`]. See the
+    /// [`Options::line_ending`] method.
+    pub line_ending: LineEnding,
+    /// Break lines at Unicode line and paragraph separators. See the
+    /// [`Options::unicode_line_breaks`] method.
+    pub unicode_line_breaks: bool,
+    /// How the whitespace between words is handled. See the
+    /// [`Options::whitespace`] method.
+    pub whitespace: Whitespace,
+    /// Which whitespace is removed from the ends of the lines. See
+    /// the [`Options::trim`] method.
+    pub trim: Trim,
+    /// Keep email quote prefixes in front of every line. See the
+    /// [`Options::detect_quotes`] method.
+    pub detect_quotes: bool,
+    /// Start every sentence on a new line. See the
+    /// [`Options::semantic_line_breaks`] method.
+    pub semantic_line_breaks: bool,
+    /// Indent the wrapped lines of list items so that they line up
+    /// with the text after the list marker. See the
+    /// [`Options::hanging_list_indent`] method.
+    pub hanging_list_indent: bool,
+    /// Indentation computed for every line, which replaces the
+    /// initial and subsequent indentation. See the
+    /// [`Options::line_indent`] method.
+    pub line_indent: Option<LineIndent>,
+    /// Keep the indentation of every paragraph on all its wrapped
+    /// lines. See the [`Options::preserve_indentation`] method.
+    pub preserve_indentation: bool,
+    /// Derive the subsequent indentation from the first line of every
+    /// paragraph. See the [`Options::auto_subsequent_indent`] method.
+    pub auto_subsequent_indent: bool,
+    /// How [`refill`] splits text into paragraphs. See the
+    /// [`Options::paragraphs`] method.
+    pub paragraphs: Paragraphs,
+    /// Which lines are kept as they are instead of being wrapped. See
+    /// the [`Options::preformatted`] method.
+    pub preformatted: Preformatted,
+    /// Join words which were hyphenated at the end of a line when
+    /// unfilling text. See the [`Options::join_hyphens`] method.
+    pub join_hyphens: bool,
+    /// Keep paragraphs which already fit when refilling text. See
+    /// the [`Options::keep_fitting`] method.
+    pub keep_fitting: bool,
+    /// The width of every line, if it is computed for each line. See
+    /// the [`Options::line_widths`] method.
+    pub line_widths: Option<LineWidth>,
+    /// Hook called with every wrapped line, if any. See the
+    /// [`Options::line_hook`] method.
+    pub line_hook: Option<LineHook>,
+    /// Close ANSI styles at the end of every line and reopen them on
+    /// the next line. See the [`Options::reopen_styles`] method.
+    pub reopen_styles: bool,
+    /// How the displayed width of text is measured, if not with
+    /// [`core::display_width`]. See the [`Options::char_width`]
+    /// method.
+    pub char_width: Option<std::sync::Arc<dyn CharWidth>>,
+    /// How East Asian ambiguous characters are measured. See the
+    /// [`Options::ambiguous_width`] method.
+    pub ambiguous_width: AmbiguousWidth,
+    /// How grapheme clusters, such as emoji sequences, are measured.
+    /// See the [`Options::grapheme_width`] method.
+    pub grapheme_width: GraphemeWidth,
+    /// How control characters in the text are handled. See the
+    /// [`Options::control_chars`] method.
+    pub control_chars: ControlChars,
+}
+
+/// The text inserted where a word is split across two lines.
+///
+/// Words are split when they are hyphenated by the
+/// [`Options::splitter`], when they contain a soft hyphen, and when
+/// they are broken apart because they are too long for a line (see
+/// [`Options::break_words`]). Please see [`Options::break_marker`]
+/// for an example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakMarker<'a> {
+    /// Inserted when a word is hyphenated or split at a soft hyphen.
+    /// This is not used if the first part of the word already ends
+    /// with `-` or if the [`WordSplitter`] does not insert hyphens.
+    /// Defaults to `"-"`.
+    pub hyphen: &'a str,
+    /// Inserted when a word is broken apart because it is too long
+    /// for a line. Defaults to `""`.
+    pub forced: &'a str,
+    /// Do the markers count toward the line width? If not, a marker
+    /// can stick out into the right margin. Defaults to `true`.
+    pub counts_toward_width: bool,
+}
+
+impl BreakMarker<'_> {
+    /// Create the default break marker: a `"-"` for hyphenated words
+    /// and nothing for words which are broken apart.
+    pub const fn new() -> Self {
+        BreakMarker {
+            hyphen: "-",
+            forced: "",
+            counts_toward_width: true,
+        }
+    }
+}
+
+impl Default for BreakMarker<'_> {
+    fn default() -> Self {
+        BreakMarker::new()
+    }
+}
+
+/// How wrapped lines are aligned within the line width.
+///
+/// Please see [`Options::alignment`] for an example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    /// Lines are aligned to the left. Nothing is added to the lines.
+    /// This is the default.
+    #[default]
+    Left,
+    /// Lines are centered by adding half of the remaining width as
+    /// spaces in front of the line. Lines which are too long are left
+    /// alone.
+    Center,
+    /// Lines are aligned to the right by adding the remaining width
+    /// as spaces in front of the line. Lines which are too long are
+    /// left alone.
+    Right,
+    /// Lines are padded to the full width by distributing extra
+    /// spaces over the gaps between the words. The gaps on the left
+    /// get the extra spaces first. The last line of a paragraph is
+    /// aligned according to the [`LastLine`] policy. Lines without
+    /// gaps between words and lines which are too long are left
+    /// alone.
+    Justify(LastLine),
+}
+
+/// How the last line of a paragraph is aligned when the other lines
+/// are justified. See [`Alignment::Justify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LastLine {
+    /// The last line is aligned to the left, like in most books.
+    Left,
+    /// The last line is centered.
+    Center,
+    /// The last line is aligned to the right.
+    Right,
+    /// The last line is justified like the other lines.
+    Justify,
+}
+
+/// Where extra space is added when lines are justified. See
+/// [`Options::justification`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Justification {
+    /// Extra space is added to the whitespace between words.
+    InterWord,
+    /// Extra space is added between all characters. This is how
+    /// Chinese and Japanese text is justified, since there are no
+    /// spaces between the words.
+    InterCharacter,
+    /// Use [`Justification::InterCharacter`] for lines where most
+    /// characters are double-width, such as lines of CJK characters,
+    /// and [`Justification::InterWord`] for all other lines. This is
+    /// the default.
+    #[default]
+    Auto,
+    /// Arabic words are elongated by inserting U+0640 (Tatweel)
+    /// where two letters join, instead of widening the spaces between
+    /// the words. The last join point of each word is used first, and
+    /// a lam followed by an alef is never split since the two letters
+    /// form a ligature. Lines without join points are justified like
+    /// [`Justification::InterWord`]. A tatweel is assumed to be as
+    /// wide as a space.
+    ///
+    /// Tatweels are only inserted when the `kashida` Cargo feature is
+    /// enabled. Without it, this works like
+    /// [`Justification::InterWord`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[cfg(feature = "kashida")] {
+    /// use textwrap::{wrap, Alignment, Justification, LastLine, Options};
+    ///
+    /// let options = Options::new(13)
+    ///     .alignment(Alignment::Justify(LastLine::Justify))
+    ///     .justification(Justification::Kashida);
+    /// assert_eq!(wrap("سلام عليكم", &options), vec!["سـلام عـليكـم"]);
+    /// }
+    /// ```
+    Kashida,
+}
+
+/// What happens to lines which are wider than the line width. See
+/// [`Options::overflow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Lines are wrapped. This is the default.
+    #[default]
+    Wrap,
+    /// Lines are truncated to the line width instead of being
+    /// wrapped. The [`Options::placeholder`] is added to the end of
+    /// truncated lines.
+    Truncate,
+}
+
+/// The line ending used to join wrapped lines. See
+/// [`Options::line_ending`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// Lines end with `"\n"` (LF). This is the default.
+    #[default]
+    Lf,
+    /// Lines end with `"\r\n"` (CRLF), like on Windows.
+    CrLf,
+    /// All lines end like the first line of the input, even if later
+    /// lines of the input end differently. If the input has a single
+    /// line, `"\n"` is used.
+    FirstLine,
+}
+
+impl LineEnding {
+    /// The line ending to use for lines wrapped from `text`.
+    fn as_str(self, text: &str) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::FirstLine => match text.find('\n') {
+                Some(idx) if text[..idx].ends_with('\r') => "\r\n",
+                _ => "\n",
+            },
+        }
+    }
+}
+
+/// How the whitespace between words is handled. See
+/// [`Options::whitespace`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Whitespace {
+    /// The whitespace between words is kept as it is. This is the
+    /// default.
+    #[default]
+    Preserve,
+    /// Every run of spaces and tabs between words is replaced by a
+    /// single space.
+    Normalize,
+    /// Like [`Whitespace::Normalize`], but line breaks in the input
+    /// are also replaced by a single space. The whole text is then
+    /// wrapped as a single paragraph, like whitespace in HTML.
+    Collapse,
+}
+
+/// Which whitespace is removed from the ends of the wrapped lines.
+/// See [`Options::trim`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Trim {
+    /// Trailing whitespace is removed, leading whitespace in front of
+    /// the first word of a paragraph is kept. This is the default.
+    #[default]
+    Trailing,
+    /// Both leading and trailing whitespace is removed.
+    Both,
+    /// No whitespace is removed. The whitespace after the last word
+    /// of a line is kept, unless the line ends with a hyphen.
+    Neither,
+}
+
+/// How control characters in the text are handled. See
+/// [`Options::control_chars`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlChars {
+    /// Control characters are kept and have no width. This is the
+    /// default.
+    #[default]
+    Keep,
+    /// Control characters are removed.
+    Strip,
+    /// Control characters are replaced by visible symbols from the
+    /// Control Pictures block, such as “␀” for U+0000 (Null). C1
+    /// control characters have no such symbols and are replaced by
+    /// “�” (U+FFFD).
+    Replace,
+}
+
+/// How text is split into paragraphs by [`refill`].
+///
+/// Please see [`Options::paragraphs`] for an example.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Paragraphs {
+    /// The whole text is a single paragraph. This is the default.
+    #[default]
+    Whole,
+    /// Paragraphs are separated by blank lines.
+    BlankLines,
+    /// Paragraphs are separated by blank lines, and a new paragraph
+    /// starts where the indentation changes or where a line starts
+    /// with a list marker. The second line of a paragraph can have
+    /// a different indentation than the first line, so that hanging
+    /// and first-line indentation is kept within the paragraph.
+    Indentation,
+    /// Like [`Paragraphs::Indentation`], but the quote and comment
+    /// prefixes recognized by [`unfill`], such as `"> "` and `"// "`,
+    /// are treated as indentation. A change of the quote or comment
+    /// markers always starts a new paragraph, also on the second
+    /// line. Lines with nothing but a prefix separate paragraphs
+    /// like blank lines.
+    Prefix,
+    /// Paragraphs are separated by blank lines, and the function
+    /// decides if a line starts a new paragraph. It is called with
+    /// the previous line and the line.
+    Custom(fn(&str, &str) -> bool),
+}
+
+impl Paragraphs {
+    /// Does `line` separate two paragraphs?
+    fn is_separator(self, line: &str) -> bool {
+        match self {
+            Paragraphs::Whole => false,
+            Paragraphs::Prefix => line.trim_start_matches(PREFIX_CHARS).trim().is_empty(),
+            _ => line.trim().is_empty(),
+        }
+    }
+
+    /// Does `line` start a new paragraph after `prev`, the line
+    /// before it? The `second_line` is set if `prev` is the first line
+    /// of its paragraph.
+    fn starts_paragraph(self, prev: &str, line: &str, second_line: bool) -> bool {
+        fn indent(line: &str) -> &str {
+            &line[..line.len() - line.trim_start().len()]
+        }
+        // The spaces after a prefix are not part of it, so that "> "
+        // and ">" are the same prefix.
+        fn prefix(line: &str) -> &str {
+            let prefix = &line[..line.len() - line.trim_start_matches(PREFIX_CHARS).len()];
+            if prefix.trim().is_empty() {
+                prefix
+            } else {
+                prefix.trim_end()
+            }
+        }
+        // The quote and comment markers of a prefix.
+        fn markers(line: &str) -> String {
+            prefix(line).matches(&['>', '#', '/'][..]).collect()
+        }
+        match self {
+            Paragraphs::Whole | Paragraphs::BlankLines => false,
+            Paragraphs::Indentation | Paragraphs::Prefix if list_marker_len(line).is_some() => true,
+            Paragraphs::Indentation => !second_line && indent(prev) != indent(line),
+            Paragraphs::Prefix => {
+                markers(prev) != markers(line) || !second_line && prefix(prev) != prefix(line)
+            }
+            Paragraphs::Custom(starts_paragraph) => starts_paragraph(prev, line),
+        }
+    }
+}
+
+/// Which lines are preformatted and kept as they are.
+///
+/// Please see [`Options::preformatted`] for an example.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Preformatted {
+    /// All lines are wrapped. This is the default.
+    #[default]
+    Never,
+    /// Lines which look preformatted are kept. These are lines
+    /// indented by four or more spaces or by a tab, as for code
+    /// blocks, and lines which look like a part of a table or a
+    /// diagram: lines starting with `'|'` or `"+-"`, lines with box
+    /// drawing characters, lines with a run of four or more of the
+    /// same punctuation character, such as `"----"`, and lines where
+    /// the text is aligned in columns with three or more spaces.
+    Detect,
+    /// Lines for which the function returns `true` are kept.
+    Custom(fn(&str) -> bool),
+}
+
+impl Preformatted {
+    /// Is `line` preformatted?
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::Preformatted;
+    ///
+    /// assert!(Preformatted::Detect.is_preformatted("    let x = 42;"));
+    /// assert!(Preformatted::Detect.is_preformatted("| Name | Value |"));
+    /// assert!(Preformatted::Detect.is_preformatted("name     value"));
+    /// assert!(!Preformatted::Detect.is_preformatted("Some  text."));
+    /// assert!(!Preformatted::Never.is_preformatted("    let x = 42;"));
+    /// ```
+    pub fn is_preformatted(self, line: &str) -> bool {
+        match self {
+            Preformatted::Never => false,
+            Preformatted::Detect => looks_preformatted(line),
+            Preformatted::Custom(is_preformatted) => is_preformatted(line),
+        }
+    }
+}
+
+/// The heuristic of [`Preformatted::Detect`].
+fn looks_preformatted(line: &str) -> bool {
+    let text = line.trim();
+    if text.is_empty() {
+        return false;
+    }
+    if line.starts_with('\t') || line.starts_with("    ") {
+        return true;
+    }
+    if text.starts_with('|') || text.starts_with("+-") {
+        return true;
+    }
+    if text
+        .chars()
+        .any(|ch| ('\u{2500}'..='\u{257f}').contains(&ch))
+    {
+        return true;
+    }
+    let mut prev = None;
+    let mut run = 0;
+    for ch in text.chars() {
+        run = if Some(ch) == prev { run + 1 } else { 1 };
+        if run >= 4 && ch.is_ascii_punctuation() || run >= 3 && ch == ' ' {
+            return true;
+        }
+        prev = Some(ch);
+    }
+    false
+}
+
+/// Indentation computed for each line by a closure.
+///
+/// The closure is shared between clones of the [`Options`]. Please
+/// see [`Options::line_indent`] for an example.
+#[derive(Clone)]
+pub struct LineIndent {
+    indent: std::sync::Arc<dyn Fn(usize) -> Cow<'static, str> + Send + Sync>,
+}
+
+impl LineIndent {
+    /// Create a new line indentation. The `indent` closure returns
+    /// the indentation of the line with the given line number. The
+    /// first line has line number zero.
+    pub fn new<F>(indent: F) -> Self
+    where
+        F: Fn(usize) -> Cow<'static, str> + Send + Sync + 'static,
+    {
+        LineIndent {
+            indent: std::sync::Arc::new(indent),
+        }
+    }
+
+    /// The indentation of the line with the given line number.
+    pub fn indent(&self, line_number: usize) -> Cow<'static, str> {
+        (self.indent)(line_number)
+    }
+}
+
+impl std::fmt::Debug for LineIndent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LineIndent").finish()
+    }
+}
+
+/// Line width computed for each line by a closure.
+///
+/// The closure is shared between clones of the [`Options`]. Please
+/// see [`Options::line_widths`] for an example.
+#[derive(Clone)]
+pub struct LineWidth {
+    width: std::sync::Arc<dyn Fn(usize) -> usize + Send + Sync>,
+}
+
+impl LineWidth {
+    /// Create a new line width. The `width` closure returns the width
+    /// of the line with the given line number. The first line has
+    /// line number zero.
+    pub fn new<F>(width: F) -> Self
+    where
+        F: Fn(usize) -> usize + Send + Sync + 'static,
+    {
+        LineWidth {
+            width: std::sync::Arc::new(width),
+        }
+    }
+
+    /// The width of the line with the given line number.
+    pub fn width(&self, line_number: usize) -> usize {
+        (self.width)(line_number)
+    }
+}
+
+impl std::fmt::Debug for LineWidth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LineWidth").finish()
+    }
+}
+
+/// Closure called by a [`LineHook`].
+type LineHookFn = dyn Fn(usize, bool, &mut Cow<'_, str>) + Send + Sync;
+
+/// Hook called by a closure with every wrapped line.
+///
+/// The closure is shared between clones of the [`Options`]. Please
+/// see [`Options::line_hook`] for an example.
+#[derive(Clone)]
+pub struct LineHook {
+    hook: std::sync::Arc<LineHookFn>,
+}
+
+impl LineHook {
+    /// Create a new line hook. The `hook` closure is called with the
+    /// line number, whether the line is the last line, and the line
+    /// itself, which it can change. The first line has line number
+    /// zero.
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(usize, bool, &mut Cow<'_, str>) + Send + Sync + 'static,
+    {
+        LineHook {
+            hook: std::sync::Arc::new(hook),
+        }
+    }
+
+    /// Call the hook with the line with the given line number.
+    pub fn call(&self, line_number: usize, last: bool, line: &mut Cow<'_, str>) {
+        (self.hook)(line_number, last, line)
+    }
+}
+
+impl std::fmt::Debug for LineHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LineHook").finish()
+    }
 }
 
 impl<'a, A: Clone, R: Clone, S: Clone> From<&'a Options<'a, A, R, S>> for Options<'a, A, R, S> {
     fn from(options: &'a Options<'a, A, R, S>) -> Self {
         Self {
             width: options.width,
-            initial_indent: options.initial_indent,
-            subsequent_indent: options.subsequent_indent,
+            initial_indent: Cow::Borrowed(&options.initial_indent),
+            subsequent_indent: Cow::Borrowed(&options.subsequent_indent),
             break_words: options.break_words,
             word_separator: options.word_separator.clone(),
             wrap_algorithm: options.wrap_algorithm.clone(),
             splitter: options.splitter.clone(),
+            preserve_breaks: options.preserve_breaks,
+            sentence_end_double_space: options.sentence_end_double_space,
+            break_marker: options.break_marker,
+            alignment: options.alignment,
+            justification: options.justification,
+            hanging_punctuation: options.hanging_punctuation,
+            max_lines: options.max_lines,
+            placeholder: Cow::Borrowed(&options.placeholder),
+            overflow: options.overflow,
+            line_ending: options.line_ending,
+            unicode_line_breaks: options.unicode_line_breaks,
+            whitespace: options.whitespace,
+            trim: options.trim,
+            detect_quotes: options.detect_quotes,
+            semantic_line_breaks: options.semantic_line_breaks,
+            hanging_list_indent: options.hanging_list_indent,
+            line_indent: options.line_indent.clone(),
+            preserve_indentation: options.preserve_indentation,
+            auto_subsequent_indent: options.auto_subsequent_indent,
+            paragraphs: options.paragraphs,
+            preformatted: options.preformatted,
+            join_hyphens: options.join_hyphens,
+            keep_fitting: options.keep_fitting,
+            line_widths: options.line_widths.clone(),
+            line_hook: options.line_hook.clone(),
+            reopen_styles: options.reopen_styles,
+            char_width: options.char_width.clone(),
+            ambiguous_width: options.ambiguous_width,
+            grapheme_width: options.grapheme_width,
+            control_chars: options.control_chars,
         }
     }
 }
@@ -300,18 +985,48 @@ impl<'a> Options<'a, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), HyphenSpl
     /// # let expected =
     /// Options {
     ///     width: width,
-    ///     initial_indent: "",
-    ///     subsequent_indent: "",
+    ///     initial_indent: "".into(),
+    ///     subsequent_indent: "".into(),
     ///     break_words: true,
     ///     #[cfg(feature = "unicode-linebreak")]
     ///     word_separator: UnicodeBreakProperties,
     ///     #[cfg(not(feature = "unicode-linebreak"))]
     ///     word_separator: AsciiSpace,
-    ///     #[cfg(feature = "smawk")]
-    ///     wrap_algorithm: textwrap::wrap_algorithms::OptimalFit,
-    ///     #[cfg(not(feature = "smawk"))]
+    ///     #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    ///     wrap_algorithm: textwrap::wrap_algorithms::OptimalFit::new(),
+    ///     #[cfg(not(any(feature = "smawk", feature = "optimal-fit-dp")))]
     ///     wrap_algorithm: textwrap::wrap_algorithms::FirstFit,
     ///     splitter: HyphenSplitter,
+    ///     preserve_breaks: false,
+    ///     sentence_end_double_space: false,
+    ///     break_marker: textwrap::BreakMarker::new(),
+    ///     alignment: textwrap::Alignment::Left,
+    ///     justification: textwrap::Justification::Auto,
+    ///     hanging_punctuation: false,
+    ///     max_lines: None,
+    ///     placeholder: "…".into(),
+    ///     overflow: textwrap::Overflow::Wrap,
+    ///     line_ending: textwrap::LineEnding::Lf,
+    ///     unicode_line_breaks: false,
+    ///     whitespace: textwrap::Whitespace::Preserve,
+    ///     trim: textwrap::Trim::Trailing,
+    ///     detect_quotes: false,
+    ///     semantic_line_breaks: false,
+    ///     hanging_list_indent: false,
+    ///     line_indent: None,
+    ///     preserve_indentation: false,
+    ///     auto_subsequent_indent: false,
+    ///     paragraphs: textwrap::Paragraphs::Whole,
+    ///     preformatted: textwrap::Preformatted::Never,
+    ///     join_hyphens: false,
+    ///     keep_fitting: false,
+    ///     line_widths: None,
+    ///     line_hook: None,
+    ///     reopen_styles: false,
+    ///     char_width: None,
+    ///     ambiguous_width: textwrap::AmbiguousWidth::Narrow,
+    ///     grapheme_width: textwrap::GraphemeWidth::Characters,
+    ///     control_chars: textwrap::ControlChars::Keep,
     /// }
     /// # ;
     /// # assert_eq!(actual.width, expected.width);
@@ -409,18 +1124,48 @@ impl<'a, S> Options<'a, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), S> {
     /// # let expected =
     /// Options {
     ///     width: width,
-    ///     initial_indent: "",
-    ///     subsequent_indent: "",
+    ///     initial_indent: "".into(),
+    ///     subsequent_indent: "".into(),
     ///     break_words: true,
     ///     #[cfg(feature = "unicode-linebreak")]
     ///     word_separator: UnicodeBreakProperties,
     ///     #[cfg(not(feature = "unicode-linebreak"))]
     ///     word_separator: textwrap::AsciiSpace,
-    ///     #[cfg(feature = "smawk")]
-    ///     wrap_algorithm: textwrap::wrap_algorithms::OptimalFit,
-    ///     #[cfg(not(feature = "smawk"))]
+    ///     #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    ///     wrap_algorithm: textwrap::wrap_algorithms::OptimalFit::new(),
+    ///     #[cfg(not(any(feature = "smawk", feature = "optimal-fit-dp")))]
     ///     wrap_algorithm: textwrap::wrap_algorithms::FirstFit,
     ///     splitter: splitter,
+    ///     preserve_breaks: false,
+    ///     sentence_end_double_space: false,
+    ///     break_marker: textwrap::BreakMarker::new(),
+    ///     alignment: textwrap::Alignment::Left,
+    ///     justification: textwrap::Justification::Auto,
+    ///     hanging_punctuation: false,
+    ///     max_lines: None,
+    ///     placeholder: "…".into(),
+    ///     overflow: textwrap::Overflow::Wrap,
+    ///     line_ending: textwrap::LineEnding::Lf,
+    ///     unicode_line_breaks: false,
+    ///     whitespace: textwrap::Whitespace::Preserve,
+    ///     trim: textwrap::Trim::Trailing,
+    ///     detect_quotes: false,
+    ///     semantic_line_breaks: false,
+    ///     hanging_list_indent: false,
+    ///     line_indent: None,
+    ///     preserve_indentation: false,
+    ///     auto_subsequent_indent: false,
+    ///     paragraphs: textwrap::Paragraphs::Whole,
+    ///     preformatted: textwrap::Preformatted::Never,
+    ///     join_hyphens: false,
+    ///     keep_fitting: false,
+    ///     line_widths: None,
+    ///     line_hook: None,
+    ///     reopen_styles: false,
+    ///     char_width: None,
+    ///     ambiguous_width: textwrap::AmbiguousWidth::Narrow,
+    ///     grapheme_width: textwrap::GraphemeWidth::Characters,
+    ///     control_chars: textwrap::ControlChars::Keep,
     /// }
     /// # ;
     /// # assert_eq!(actual.width, expected.width);
@@ -465,7 +1210,7 @@ impl<'a, S> Options<'a, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), S> {
     /// use textwrap::wrap_algorithms::FirstFit;
     /// # const width: usize = 80;
     ///
-    /// # #[cfg(all(not(feature = "smawk"), not(feature = "unicode-linebreak")))] {
+    /// # #[cfg(all(not(any(feature = "smawk", feature = "optimal-fit-dp")), not(feature = "unicode-linebreak")))] {
     /// const FOO: Options<FirstFit, AsciiSpace, HyphenSplitter> =
     ///     Options::with_splitter(width, HyphenSplitter);
     /// static BAR: Options<FirstFit, AsciiSpace, HyphenSplitter> = FOO;
@@ -474,16 +1219,152 @@ impl<'a, S> Options<'a, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), S> {
     pub const fn with_splitter(width: usize, splitter: S) -> Self {
         Options {
             width,
-            initial_indent: "",
-            subsequent_indent: "",
+            initial_indent: Cow::Borrowed(""),
+            subsequent_indent: Cow::Borrowed(""),
             break_words: true,
             word_separator: DefaultWordSeparator!(),
-            wrap_algorithm: DefaultWrapAlgorithm!(),
+            wrap_algorithm: <DefaultWrapAlgorithm!()>::new(),
             splitter: splitter,
+            preserve_breaks: false,
+            sentence_end_double_space: false,
+            break_marker: BreakMarker::new(),
+            alignment: Alignment::Left,
+            justification: Justification::Auto,
+            hanging_punctuation: false,
+            max_lines: None,
+            placeholder: Cow::Borrowed("…"),
+            overflow: Overflow::Wrap,
+            line_ending: LineEnding::Lf,
+            unicode_line_breaks: false,
+            whitespace: Whitespace::Preserve,
+            trim: Trim::Trailing,
+            detect_quotes: false,
+            semantic_line_breaks: false,
+            hanging_list_indent: false,
+            line_indent: None,
+            preserve_indentation: false,
+            auto_subsequent_indent: false,
+            paragraphs: Paragraphs::Whole,
+            preformatted: Preformatted::Never,
+            join_hyphens: false,
+            keep_fitting: false,
+            line_widths: None,
+            line_hook: None,
+            reopen_styles: false,
+            char_width: None,
+            ambiguous_width: AmbiguousWidth::Narrow,
+            grapheme_width: GraphemeWidth::Characters,
+            control_chars: ControlChars::Keep,
         }
     }
 }
 
+impl<'a> Options<'a, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), NoHyphenation> {
+    /// Creates a new [`Options`] for the body of Git commit messages.
+    ///
+    /// The width is 72 columns, which leaves room for the indentation
+    /// added by `git log`. Words are never broken or hyphenated, so
+    /// that URLs and identifiers stay intact, and indented code and
+    /// tables are kept as they are. Line endings are preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let body = "See https://example.com/a/very/long/url/which/is/never/broken/anywhere for the details.";
+    /// assert_eq!(fill(body, Options::git_commit()),
+    ///            "See https://example.com/a/very/long/url/which/is/never/broken/anywhere\n\
+    ///             for the details.");
+    /// ```
+    pub fn git_commit() -> Self {
+        Options::with_splitter(72, NoHyphenation)
+            .break_words(false)
+            .line_ending(LineEnding::FirstLine)
+            .preformatted(Preformatted::Detect)
+    }
+
+    /// Creates a new [`Options`] for email, to be used with
+    /// [`flowed::fill`].
+    ///
+    /// The width is 66 columns, as recommended by [RFC 3676], and
+    /// lines end with `"\r\n"` as required by email. Words are never
+    /// broken or hyphenated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{flowed, Options};
+    ///
+    /// let text = "Memory safety without garbage collection. \
+    ///             Concurrency without data races. Zero-cost abstractions.";
+    /// assert_eq!(flowed::fill(text, Options::email_flowed()),
+    ///            "Memory safety without garbage collection. Concurrency without \r\n\
+    ///             data races. Zero-cost abstractions.");
+    /// ```
+    ///
+    /// [RFC 3676]: https://tools.ietf.org/html/rfc3676
+    pub fn email_flowed() -> Self {
+        Options::with_splitter(66, NoHyphenation)
+            .break_words(false)
+            .line_ending(LineEnding::CrLf)
+    }
+
+    /// Creates a new [`Options`] for the body of manual pages.
+    ///
+    /// The width is 80 columns and the text is indented by 7 spaces,
+    /// like the paragraphs formatted by `man`. Sentences end with two
+    /// spaces when text is refilled, and words are not hyphenated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "Textwrap is a library for wrapping and indenting text. \
+    ///             It can be used to format the output of command-line programs.";
+    /// assert_eq!(fill(text, Options::man_page()),
+    ///            "       Textwrap is a library for wrapping and indenting text. It can be used to\n\
+    ///             \x20      format the output of command-line programs.");
+    /// ```
+    pub fn man_page() -> Self {
+        Options::with_splitter(80, NoHyphenation)
+            .initial_indent("       ")
+            .subsequent_indent("       ")
+            .sentence_end_double_space(true)
+    }
+
+    /// Creates a new [`Options`] for Markdown documents with the
+    /// given width.
+    ///
+    /// Words are never broken or hyphenated, so that links and code
+    /// spans stay intact. Continuation lines of list items are
+    /// aligned with the item text, and indented code blocks and
+    /// tables are kept as they are. Please see [`markdown::fill`] for
+    /// filling whole documents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "1. Read the documentation at https://docs.rs/textwrap/\n\
+    ///             \n\
+    ///             \x20   let options = Options::markdown(30);";
+    /// assert_eq!(fill(text, Options::markdown(30)),
+    ///            "1. Read the documentation at\n\
+    ///             \x20  https://docs.rs/textwrap/\n\
+    ///             \n\
+    ///             \x20   let options = Options::markdown(30);");
+    /// ```
+    pub fn markdown(width: usize) -> Self {
+        Options::with_splitter(width, NoHyphenation)
+            .break_words(false)
+            .hanging_list_indent(true)
+            .preformatted(Preformatted::Detect)
+    }
+}
+
 impl<'a, A, R, S> Options<'a, A, R, S> {
     /// Change [`self.initial_indent`]. The initial indentation is
     /// used on the very first line of output.
@@ -503,9 +1384,9 @@ impl<'a, A, R, S> Options<'a, A, R, S> {
     /// ```
     ///
     /// [`self.initial_indent`]: #structfield.initial_indent
-    pub fn initial_indent(self, indent: &'a str) -> Self {
+    pub fn initial_indent<T: Into<Cow<'a, str>>>(self, indent: T) -> Self {
         Options {
-            initial_indent: indent,
+            initial_indent: indent.into(),
             ..self
         }
     }
@@ -524,14 +1405,14 @@ impl<'a, A, R, S> Options<'a, A, R, S> {
     /// let options = Options::new(12)
     ///     .initial_indent("* ")
     ///     .subsequent_indent("  ");
-    /// #[cfg(feature = "smawk")]
+    /// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
     /// assert_eq!(wrap("This is a little example.", options),
     ///            vec!["* This is",
     ///                 "  a little",
     ///                 "  example."]);
     ///
     /// // Without the `smawk` feature, the wrapping is a little different:
-    /// #[cfg(not(feature = "smawk"))]
+    /// #[cfg(not(any(feature = "smawk", feature = "optimal-fit-dp")))]
     /// assert_eq!(wrap("This is a little example.", options),
     ///            vec!["* This is a",
     ///                 "  little",
@@ -539,9 +1420,9 @@ impl<'a, A, R, S> Options<'a, A, R, S> {
     /// ```
     ///
     /// [`self.subsequent_indent`]: #structfield.subsequent_indent
-    pub fn subsequent_indent(self, indent: &'a str) -> Self {
+    pub fn subsequent_indent<T: Into<Cow<'a, str>>>(self, indent: T) -> Self {
         Options {
-            subsequent_indent: indent,
+            subsequent_indent: indent.into(),
             ..self
         }
     }
@@ -559,12 +1440,25 @@ impl<'a, A, R, S> Options<'a, A, R, S> {
     /// assert_eq!(wrap("This is a little example.", options),
     ///            vec!["This",
     ///                 "is a",
-    ///                 "litt",
-    ///                 "le",
+    ///                 "lit",
+    ///                 "tle",
     ///                 "exam",
     ///                 "ple."]);
     /// ```
     ///
+    /// A long word is preferably broken after punctuation such as
+    /// `.`, `/`, `_`, or `=`, provided that this leaves a line which
+    /// fits. This keeps the parts of paths, URLs, and identifiers
+    /// together:
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let options = Options::new(12);
+    /// assert_eq!(wrap("/usr/share/doc/textwrap", options),
+    ///            vec!["/usr/share/", "doc/textwrap"]);
+    /// ```
+    ///
     /// [`self.break_words`]: #structfield.break_words
     pub fn break_words(self, setting: bool) -> Self {
         Options {
@@ -573,260 +1467,1603 @@ impl<'a, A, R, S> Options<'a, A, R, S> {
         }
     }
 
-    /// Change [`self.word_separator`].
+    /// Change [`self.preserve_breaks`]. This controls if [`refill`]
+    /// should keep the existing line breaks where possible.
     ///
-    /// See [`WordSeparator`] for details on the choices.
+    /// Reformatting text with a slightly different width normally
+    /// changes the line breaks throughout the paragraph. With this
+    /// setting, the lines preferably end where they ended before,
+    /// which gives the smallest possible diff when the text is kept
+    /// in version control. The setting is only used by [`refill`]
+    /// and it requires the [`wrap_algorithms::OptimalFit`]
+    /// algorithm, see its `new_break_penalty` field.
     ///
-    /// [`self.word_separator`]: #structfield.word_separator
-    pub fn word_separator<T>(self, word_separator: T) -> Options<'a, A, T, S> {
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{refill, Options};
+    ///
+    /// let text = "\
+    /// Memory safety without
+    /// garbage collection.
+    /// ";
+    ///
+    /// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))] {
+    /// assert_eq!(refill(text, Options::new(30)), "\
+    /// Memory safety without garbage
+    /// collection.
+    /// ");
+    ///
+    /// // The text fits as it is, so nothing changes:
+    /// assert_eq!(refill(text, Options::new(30).preserve_breaks(true)), text);
+    /// }
+    /// ```
+    ///
+    /// [`self.preserve_breaks`]: #structfield.preserve_breaks
+    pub fn preserve_breaks(self, setting: bool) -> Self {
         Options {
-            width: self.width,
-            initial_indent: self.initial_indent,
-            subsequent_indent: self.subsequent_indent,
-            break_words: self.break_words,
-            word_separator: word_separator,
-            wrap_algorithm: self.wrap_algorithm,
-            splitter: self.splitter,
+            preserve_breaks: setting,
+            ..self
         }
     }
 
-    /// Change [`self.wrap_algorithm`].
+    /// Change [`self.sentence_end_double_space`]. This controls if
+    /// [`refill`] should join a line which ends a sentence with two
+    /// spaces instead of one.
     ///
-    /// See the [`wrap_algorithms::WrapAlgorithm`] trait for details on
-    /// the choices.
+    /// The whitespace between the words of a line is always kept as
+    /// it is, but the whitespace at the end of a line is lost when
+    /// text is filled. Some typographic conventions use two spaces
+    /// after a sentence, and this setting restores them when the
+    /// lines are joined again. A sentence ends with `'.'`, `'?'`, or
+    /// `'!'`, optionally followed by closing quotes or parentheses.
     ///
-    /// [`self.wrap_algorithm`]: #structfield.wrap_algorithm
-    pub fn wrap_algorithm<B>(self, wrap_algorithm: B) -> Options<'a, B, R, S> {
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{refill, Options};
+    ///
+    /// let text = "\
+    /// Memory safety.  No
+    /// garbage collection.
+    /// Concurrency.
+    /// ";
+    ///
+    /// let options = Options::new(80).sentence_end_double_space(true);
+    /// assert_eq!(refill(text, options),
+    ///            "Memory safety.  No garbage collection.  Concurrency.\n");
+    /// ```
+    ///
+    /// [`self.sentence_end_double_space`]: #structfield.sentence_end_double_space
+    pub fn sentence_end_double_space(self, setting: bool) -> Self {
         Options {
-            width: self.width,
-            initial_indent: self.initial_indent,
-            subsequent_indent: self.subsequent_indent,
-            break_words: self.break_words,
-            word_separator: self.word_separator,
-            wrap_algorithm: wrap_algorithm,
-            splitter: self.splitter,
+            sentence_end_double_space: setting,
+            ..self
         }
     }
 
-    /// Change [`self.splitter`]. The [`WordSplitter`] is used to fit
-    /// part of a word into the current line when wrapping text.
+    /// Change [`self.break_marker`]. This controls the text which is
+    /// inserted at the end of a line when a word is split across two
+    /// lines, and if that text counts toward the line width.
     ///
-    /// This function may return a different type than `Self`. That is
-    /// the case when the given `splitter` is of a different type the
-    /// the currently stored one in the `splitter` field. Take for
-    /// example:
+    /// # Examples
     ///
     /// ```
-    /// use textwrap::{HyphenSplitter, NoHyphenation, Options};
-    /// // The default type returned by `new`:
-    /// let opt: Options<_, _, HyphenSplitter> = Options::new(80);
-    /// // Setting a different splitter changes the type
-    /// let opt: Options<_, _, NoHyphenation> = opt.splitter(NoHyphenation);
+    /// use textwrap::{wrap, BreakMarker, Options};
+    ///
+    /// let options = Options::new(10).break_marker(BreakMarker {
+    ///     hyphen: "\u{2010}",
+    ///     forced: "\u{21aa}",
+    ///     counts_toward_width: false,
+    /// });
+    /// assert_eq!(wrap("Soft\u{ad}ware is: Supercalifragilistic", &options),
+    ///            vec!["Soft\u{2010}", "ware is:", "Supercalif\u{21aa}", "ragilistic"]);
     /// ```
     ///
-    /// [`self.splitter`]: #structfield.splitter
-    pub fn splitter<T>(self, splitter: T) -> Options<'a, A, R, T> {
+    /// [`self.break_marker`]: #structfield.break_marker
+    pub fn break_marker(self, break_marker: BreakMarker<'a>) -> Self {
         Options {
-            width: self.width,
-            initial_indent: self.initial_indent,
-            subsequent_indent: self.subsequent_indent,
-            break_words: self.break_words,
-            word_separator: self.word_separator,
-            wrap_algorithm: self.wrap_algorithm,
-            splitter: splitter,
+            break_marker,
+            ..self
         }
     }
-}
 
-/// Return the current terminal width.
-///
-/// If the terminal width cannot be determined (typically because the
-/// standard output is not connected to a terminal), a default width
-/// of 80 characters will be used.
-///
-/// # Examples
-///
-/// Create an [`Options`] for wrapping at the current terminal width
-/// with a two column margin to the left and the right:
-///
-/// ```no_run
-/// use textwrap::{termwidth, NoHyphenation, Options};
-///
-/// let width = termwidth() - 4; // Two columns on each side.
-/// let options = Options::new(width)
-///     .splitter(NoHyphenation)
-///     .initial_indent("  ")
-///     .subsequent_indent("  ");
-/// ```
-///
-/// **Note:** Only available when the `terminal_size` Cargo feature is
-/// enabled.
-#[cfg(feature = "terminal_size")]
-pub fn termwidth() -> usize {
-    terminal_size::terminal_size().map_or(80, |(terminal_size::Width(w), _)| w.into())
-}
+    /// Change [`self.alignment`]. This controls how the wrapped lines
+    /// are aligned within the line width. The indentation is added in
+    /// front of the aligned lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, wrap, Alignment, LastLine, Options};
+    ///
+    /// let text = "Textwrap: an efficient and powerful library";
+    /// let options = Options::new(20).alignment(Alignment::Center);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["    Textwrap: an",
+    ///                 "   efficient and",
+    ///                 "  powerful library"]);
+    ///
+    /// let options = Options::new(20).alignment(Alignment::Right);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["        Textwrap: an",
+    ///                 "       efficient and",
+    ///                 "    powerful library"]);
+    ///
+    /// let text = "Memory safety without garbage collection.";
+    /// let options = Options::new(16).alignment(Alignment::Justify(LastLine::Left));
+    /// assert_eq!(fill(text, options),
+    ///            "Memory    safety\n\
+    ///             without  garbage\n\
+    ///             collection.");
+    /// ```
+    ///
+    /// [`self.alignment`]: #structfield.alignment
+    pub fn alignment(self, alignment: Alignment) -> Self {
+        Options { alignment, ..self }
+    }
 
-/// Fill a line of text at a given width.
-///
-/// The result is a [`String`], complete with newlines between each
-/// line. Use the [`wrap`] function if you need access to the
-/// individual lines.
-///
-/// The easiest way to use this function is to pass an integer for
-/// `width_or_options`:
-///
-/// ```
-/// use textwrap::fill;
-///
-/// assert_eq!(
-///     fill("Memory safety without garbage collection.", 15),
-///     "Memory safety\nwithout garbage\ncollection."
-/// );
-/// ```
-///
-/// If you need to customize the wrapping, you can pass an [`Options`]
-/// instead of an `usize`:
-///
-/// ```
-/// use textwrap::{fill, Options};
-///
-/// let options = Options::new(15)
-///     .initial_indent("- ")
-///     .subsequent_indent("  ");
-/// assert_eq!(
-///     fill("Memory safety without garbage collection.", &options),
-///     "- Memory safety\n  without\n  garbage\n  collection."
-/// );
-/// ```
-pub fn fill<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> String
-where
-    A: wrap_algorithms::WrapAlgorithm,
-    R: WordSeparator,
-    S: WordSplitter,
-    Opt: Into<Options<'a, A, R, S>>,
-{
-    // This will avoid reallocation in simple cases (no
-    // indentation, no hyphenation).
-    let mut result = String::with_capacity(text.len());
+    /// Change [`self.justification`]. This controls where extra space
+    /// is added when lines are justified with
+    /// [`Alignment::Justify`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Alignment, Justification, LastLine, Options};
+    ///
+    /// let options = Options::new(21)
+    ///     .alignment(Alignment::Justify(LastLine::Justify))
+    ///     .justification(Justification::InterCharacter);
+    /// assert_eq!(wrap("Rust is fun", &options), vec!["R u s t   i s   f u n"]);
+    ///
+    /// // Lines of mostly CJK characters have no gaps between words,
+    /// // so the Auto strategy adds space between the characters.
+    /// let options = Options::new(11)
+    ///     .alignment(Alignment::Justify(LastLine::Justify))
+    ///     .justification(Justification::Auto);
+    /// assert_eq!(wrap("你好世界", &options), vec!["你 好 世 界"]);
+    /// ```
+    ///
+    /// [`self.justification`]: #structfield.justification
+    pub fn justification(self, justification: Justification) -> Self {
+        Options {
+            justification,
+            ..self
+        }
+    }
 
-    for (i, line) in wrap(text, width_or_options).iter().enumerate() {
-        if i > 0 {
-            result.push('\n');
+    /// Change [`self.hanging_punctuation`]. When enabled, a period,
+    /// comma, colon, semicolon, hyphen, or closing quote at the end
+    /// of a line is not counted toward the line width. The
+    /// punctuation then hangs one column past the width, which makes
+    /// the right edge of the text look straighter. This is also known
+    /// as optical margin alignment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Memory safety, without garbage collection.";
+    /// assert_eq!(wrap(text, 13),
+    ///            vec!["Memory", "safety,", "without", "garbage", "collection."]);
+    ///
+    /// let options = Options::new(13).hanging_punctuation(true);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Memory safety,", "without", "garbage", "collection."]);
+    /// ```
+    ///
+    /// [`self.hanging_punctuation`]: #structfield.hanging_punctuation
+    pub fn hanging_punctuation(self, hanging_punctuation: bool) -> Self {
+        Options {
+            hanging_punctuation,
+            ..self
         }
-        result.push_str(&line);
     }
 
-    result
-}
+    /// Change [`self.max_lines`]. The wrapped text is truncated to at
+    /// most this many lines. If lines are removed, the
+    /// [`self.placeholder`] is added to the last line. Words are
+    /// removed from the end of the last line until the placeholder
+    /// fits within the line width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Memory safety without garbage collection.";
+    /// let options = Options::new(15).max_lines(2);
+    /// assert_eq!(wrap(text, &options), vec!["Memory safety", "without…"]);
+    ///
+    /// // Nothing is added if the text fits.
+    /// let options = Options::new(15).max_lines(3);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Memory safety", "without garbage", "collection."]);
+    /// ```
+    ///
+    /// [`self.max_lines`]: #structfield.max_lines
+    /// [`self.placeholder`]: #structfield.placeholder
+    pub fn max_lines(self, max_lines: usize) -> Self {
+        Options {
+            max_lines: Some(max_lines),
+            ..self
+        }
+    }
 
-/// Unpack a paragraph of already-wrapped text.
-///
-/// This function attempts to recover the original text from a single
-/// paragraph of text produced by the [`fill`] function. This means
-/// that it turns
-///
-/// ```text
-/// textwrap: a small
-/// library for
-/// wrapping text.
-/// ```
-///
-/// back into
-///
-/// ```text
-/// textwrap: a small library for wrapping text.
-/// ```
-///
-/// In addition, it will recognize a common prefix among the lines.
-/// The prefix of the first line is returned in
-/// [`Options::initial_indent`] and the prefix (if any) of the the
-/// other lines is returned in [`Options::subsequent_indent`].
-///
-/// In addition to `' '`, the prefixes can consist of characters used
-/// for unordered lists (`'-'`, `'+'`, and `'*'`) and block quotes
-/// (`'>'`) in Markdown as well as characters often used for inline
-/// comments (`'#'` and `'/'`).
-///
-/// The text must come from a single wrapped paragraph. This means
-/// that there can be no `"\n\n"` within the text.
-///
-/// # Examples
-///
-/// ```
-/// use textwrap::unfill;
-///
-/// let (text, options) = unfill("\
-/// * This is an
-///   example of
-///   a list item.
-/// ");
-///
-/// assert_eq!(text, "This is an example of a list item.\n");
-/// assert_eq!(options.initial_indent, "* ");
-/// assert_eq!(options.subsequent_indent, "  ");
-/// ```
-pub fn unfill(
-    text: &str,
-) -> (
-    String,
-    Options<'_, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), HyphenSplitter>,
-) {
-    let trimmed = text.trim_end_matches('\n');
-    let prefix_chars: &[_] = &[' ', '-', '+', '*', '>', '#', '/'];
+    /// Change [`self.placeholder`]. This is the text added to the last
+    /// line when the wrapped text is truncated because of
+    /// [`Options::max_lines`]. The default is `"…"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Memory safety without garbage collection.";
+    /// let options = Options::new(15).max_lines(2).placeholder(" [...]");
+    /// assert_eq!(wrap(text, &options), vec!["Memory safety", "without [...]"]);
+    /// ```
+    ///
+    /// [`self.placeholder`]: #structfield.placeholder
+    pub fn placeholder<T: Into<Cow<'a, str>>>(self, placeholder: T) -> Self {
+        Options {
+            placeholder: placeholder.into(),
+            ..self
+        }
+    }
 
-    let mut options = Options::new(0);
-    for (idx, line) in trimmed.split('\n').enumerate() {
-        options.width = std::cmp::max(options.width, core::display_width(line));
-        let without_prefix = line.trim_start_matches(prefix_chars);
-        let prefix = &line[..line.len() - without_prefix.len()];
+    /// Change [`self.overflow`]. This controls what happens to lines
+    /// which are wider than the line width. With
+    /// [`Overflow::Truncate`], every line of the text is cut at the
+    /// line width instead of being wrapped, and the
+    /// [`self.placeholder`] is added to the end of the lines which
+    /// were cut. Use an empty placeholder to cut the lines without an
+    /// ellipsis. This is useful for showing logs in panes of a fixed
+    /// width.
+    ///
+    /// The lines are measured like when wrapping: the indentation is
+    /// part of the line width, and lines are never cut inside a
+    /// grapheme cluster.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options, Overflow};
+    ///
+    /// let text = "INFO Memory safety without garbage collection.\n\
+    ///             WARN Data race detected.";
+    /// let options = Options::new(20).overflow(Overflow::Truncate);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["INFO Memory safety…", "WARN Data race dete…"]);
+    ///
+    /// let options = options.placeholder("").subsequent_indent("  ");
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["INFO Memory safety w", "  WARN Data race det"]);
+    /// ```
+    ///
+    /// [`self.overflow`]: #structfield.overflow
+    /// [`self.placeholder`]: #structfield.placeholder
+    pub fn overflow(self, overflow: Overflow) -> Self {
+        Options { overflow, ..self }
+    }
 
-        if idx == 0 {
-            options.initial_indent = prefix;
-        } else if idx == 1 {
-            options.subsequent_indent = prefix;
-        } else if idx > 1 {
-            for ((idx, x), y) in prefix.char_indices().zip(options.subsequent_indent.chars()) {
-                if x != y {
-                    options.subsequent_indent = &prefix[..idx];
-                    break;
-                }
-            }
-            if prefix.len() < options.subsequent_indent.len() {
-                options.subsequent_indent = prefix;
-            }
+    /// Change [`self.line_ending`]. This is the line ending used to
+    /// join the lines in [`fill`] and [`refill`]. A `"\r\n"` line
+    /// ending in the input is always treated as a line break, so
+    /// [`LineEnding::FirstLine`] can be used to keep the line endings
+    /// of input which uses the same line ending throughout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, LineEnding, Options};
+    ///
+    /// let text = "Memory safety without garbage collection.";
+    /// let options = Options::new(15).line_ending(LineEnding::CrLf);
+    /// assert_eq!(fill(text, &options), "Memory safety\r\nwithout garbage\r\ncollection.");
+    ///
+    /// let text = "Memory safety without garbage collection.\r\n\
+    ///             Concurrency without data races.";
+    /// let options = Options::new(20).line_ending(LineEnding::FirstLine);
+    /// assert_eq!(fill(text, &options),
+    ///            "Memory safety\r\nwithout garbage\r\ncollection.\r\n\
+    ///             Concurrency without\r\ndata races.");
+    /// ```
+    ///
+    /// [`self.line_ending`]: #structfield.line_ending
+    pub fn line_ending(self, line_ending: LineEnding) -> Self {
+        Options {
+            line_ending,
+            ..self
         }
     }
 
-    let mut unfilled = String::with_capacity(text.len());
-    for (idx, line) in trimmed.split('\n').enumerate() {
-        if idx == 0 {
-            unfilled.push_str(&line[options.initial_indent.len()..]);
-        } else {
-            unfilled.push(' ');
-            unfilled.push_str(&line[options.subsequent_indent.len()..]);
+    /// Change [`self.unicode_line_breaks`]. When enabled, the text
+    /// is broken into lines at U+2028 (Line Separator), U+2029
+    /// (Paragraph Separator), U+0085 (Next Line), and U+000C (Form
+    /// Feed), in addition to `'\n'`. The separators are removed from
+    /// the wrapped lines. Since every line is wrapped as a paragraph
+    /// of its own, line and paragraph separators work the same. When
+    /// disabled, which is the default, these characters are treated
+    /// like other characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "Memory safety\u{2028}without garbage collection.";
+    /// assert_eq!(wrap(text, 20),
+    ///            vec!["Memory", "safety\u{2028}without", "garbage collection."]);
+    ///
+    /// let options = Options::new(20).unicode_line_breaks(true);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["Memory safety", "without garbage", "collection."]);
+    /// ```
+    ///
+    /// [`self.unicode_line_breaks`]: #structfield.unicode_line_breaks
+    pub fn unicode_line_breaks(self, unicode_line_breaks: bool) -> Self {
+        Options {
+            unicode_line_breaks,
+            ..self
         }
     }
 
-    unfilled.push_str(&text[trimmed.len()..]);
-    (unfilled, options)
-}
+    /// Change [`self.whitespace`]. This controls how the whitespace
+    /// between words is handled. By default, it is kept as it is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options, Whitespace};
+    ///
+    /// let text = "Memory  safety\twithout\ngarbage   collection.";
+    /// assert_eq!(wrap(text, 40), vec!["Memory  safety\twithout", "garbage   collection."]);
+    ///
+    /// let options = Options::new(40).whitespace(Whitespace::Normalize);
+    /// assert_eq!(wrap(text, &options), vec!["Memory safety without", "garbage collection."]);
+    ///
+    /// let options = Options::new(45).whitespace(Whitespace::Collapse);
+    /// assert_eq!(wrap(text, &options), vec!["Memory safety without garbage collection."]);
+    /// ```
+    ///
+    /// [`self.whitespace`]: #structfield.whitespace
+    pub fn whitespace(self, whitespace: Whitespace) -> Self {
+        Options { whitespace, ..self }
+    }
 
-/// Refill a paragraph of wrapped text with a new width.
-///
-/// This function will first use the [`unfill`] function to remove
-/// newlines from the text. Afterwards the text is filled again using
-/// the [`fill`] function.
-///
-/// The `new_width_or_options` argument specify the new width and can
-/// specify other options as well — except for
-/// [`Options::initial_indent`] and [`Options::subsequent_indent`],
-/// which are deduced from `filled_text`.
-///
-/// # Examples
-///
-/// ```
-/// use textwrap::refill;
-///
+    /// Change [`self.trim`]. This controls which whitespace is removed
+    /// from the ends of the wrapped lines. By default, only trailing
+    /// whitespace is removed. Whitespace which is kept at the end of a
+    /// line is not counted toward the line width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options, Trim};
+    ///
+    /// let text = "  Memory safety  ";
+    /// assert_eq!(wrap(text, 10), vec!["  Memory", "safety"]);
+    ///
+    /// let options = Options::new(10).trim(Trim::Both);
+    /// assert_eq!(wrap(text, &options), vec!["Memory", "safety"]);
+    ///
+    /// let options = Options::new(10).trim(Trim::Neither);
+    /// assert_eq!(wrap(text, &options), vec!["  Memory ", "safety  "]);
+    /// ```
+    ///
+    /// [`self.trim`]: #structfield.trim
+    pub fn trim(self, trim: Trim) -> Self {
+        Options { trim, ..self }
+    }
+
+    /// Change [`self.detect_quotes`]. When enabled, email quote
+    /// prefixes such as `"> "` or `"> > "` are detected at the start
+    /// of every line. The prefix is removed before the line is
+    /// wrapped and it is added again in front of every wrapped line,
+    /// before the indentation. With [`refill`], every run of lines
+    /// with the same quote depth is refilled as a paragraph of its
+    /// own, so replies with nested quotes can be rewrapped at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, refill, Options};
+    ///
+    /// let options = Options::new(20).detect_quotes(true);
+    /// assert_eq!(fill("> > Memory safety without garbage collection.", &options),
+    ///            "> > Memory safety\n\
+    ///             > > without garbage\n\
+    ///             > > collection.");
+    ///
+    /// let text = "> > Memory safety\n\
+    ///             > > without garbage\n\
+    ///             > > collection.\n\
+    ///             > Concurrency without\n\
+    ///             > data races.";
+    /// let options = Options::new(40).detect_quotes(true);
+    /// assert_eq!(refill(text, &options),
+    ///            "> > Memory safety without garbage\n\
+    ///             > > collection.\n\
+    ///             > Concurrency without data races.");
+    /// ```
+    ///
+    /// [`self.detect_quotes`]: #structfield.detect_quotes
+    pub fn detect_quotes(self, detect_quotes: bool) -> Self {
+        Options {
+            detect_quotes,
+            ..self
+        }
+    }
+
+    /// Change [`self.semantic_line_breaks`]. When enabled, the text
+    /// is broken after every sentence, so that each sentence starts
+    /// on a new line. This is known as _semantic line breaks_ or
+    /// _semantic linefeeds_, and it makes diffs of prose easier to
+    /// read. A sentence ends with a word ending in `'.'`, `'?'`, or
+    /// `'!'`, possibly followed by closing quotes or parentheses,
+    /// which is not followed by a word starting with a lowercase
+    /// letter. Abbreviations such as "e.g." are thus usually kept
+    /// in the sentence.
+    ///
+    /// Sentences longer than the width are still wrapped. Use a width
+    /// of `usize::MAX` to only break lines after sentences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "Memory safety without garbage collection. \
+    ///             Concurrency without data races, e.g. in threads.";
+    /// let options = Options::new(usize::MAX).semantic_line_breaks(true);
+    /// assert_eq!(fill(text, &options),
+    ///            "Memory safety without garbage collection.\n\
+    ///             Concurrency without data races, e.g. in threads.");
+    ///
+    /// let options = Options::new(30).semantic_line_breaks(true);
+    /// assert_eq!(fill(text, &options),
+    ///            "Memory safety without garbage\n\
+    ///             collection.\n\
+    ///             Concurrency without data\n\
+    ///             races, e.g. in threads.");
+    /// ```
+    ///
+    /// [`self.semantic_line_breaks`]: #structfield.semantic_line_breaks
+    pub fn semantic_line_breaks(self, semantic_line_breaks: bool) -> Self {
+        Options {
+            semantic_line_breaks,
+            ..self
+        }
+    }
+
+    /// Change [`self.hanging_list_indent`]. When enabled, lines
+    /// starting with a list marker get a hanging indentation: the
+    /// wrapped lines are indented with spaces so that they line up
+    /// with the text after the marker instead of with the marker
+    /// itself. The indentation replaces
+    /// [`Options::subsequent_indent`] for such lines.
+    ///
+    /// A list marker is one of `'-'`, `'*'`, `'+'`, and `'•'`, or a
+    /// number or a single letter followed by `'.'` or `')'`. The
+    /// marker can be indented and it must be followed by a space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "1. Memory safety without garbage collection.\n\
+    ///             a) Concurrency without data races.";
+    /// let options = Options::new(20).hanging_list_indent(true);
+    /// assert_eq!(fill(text, &options),
+    ///            "1. Memory safety\n   without garbage\n   collection.\n\
+    ///             a) Concurrency\n   without data\n   races.");
+    /// ```
+    ///
+    /// [`self.hanging_list_indent`]: #structfield.hanging_list_indent
+    pub fn hanging_list_indent(self, hanging_list_indent: bool) -> Self {
+        Options {
+            hanging_list_indent,
+            ..self
+        }
+    }
+
+    /// Change [`self.line_indent`]. The closure is called with the
+    /// number of each output line, starting from zero, and it
+    /// returns the indentation of the line. This replaces
+    /// [`Options::initial_indent`], [`Options::subsequent_indent`],
+    /// and [`Options::hanging_list_indent`], and it can be used for
+    /// line numbers, alternating gutters, or indentation which grows
+    /// from line to line.
+    ///
+    /// The indentation can be a static string or a string formatted
+    /// for the line. The closure may be called for more lines than
+    /// the text is eventually wrapped into.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let line_numbers = |n: usize| format!("{:>2} | ", n + 1).into();
+    /// let options = Options::new(25).line_indent(line_numbers);
+    /// assert_eq!(fill("Memory safety without garbage collection.", &options),
+    ///            " 1 | Memory safety\n 2 | without garbage\n 3 | collection.");
+    ///
+    /// let gutters = |n: usize| if n % 2 == 0 { "| " } else { "  " }.into();
+    /// let options = Options::new(16).line_indent(gutters);
+    /// assert_eq!(fill("Concurrency without data races.", &options),
+    ///            "| Concurrency\n  without data\n| races.");
+    /// ```
+    ///
+    /// [`self.line_indent`]: #structfield.line_indent
+    pub fn line_indent<F>(self, line_indent: F) -> Self
+    where
+        F: Fn(usize) -> Cow<'static, str> + Send + Sync + 'static,
+    {
+        Options {
+            line_indent: Some(LineIndent::new(line_indent)),
+            ..self
+        }
+    }
+
+    /// Change [`self.preserve_indentation`]. When enabled, the
+    /// leading whitespace of every line of the input text is used as
+    /// the indentation of all the lines it is wrapped into. The
+    /// whitespace is added after the initial or subsequent
+    /// indentation. This way, indented blocks stay indented when the
+    /// text is filled again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "Memory safety without garbage collection.\n\
+    ///             \x20   Concurrency without data races.";
+    /// let options = Options::new(20).preserve_indentation(true);
+    /// assert_eq!(fill(text, &options),
+    ///            "Memory safety\n\
+    ///             without garbage\n\
+    ///             collection.\n\
+    ///             \x20   Concurrency\n\
+    ///             \x20   without data\n\
+    ///             \x20   races.");
+    /// ```
+    ///
+    /// [`self.preserve_indentation`]: #structfield.preserve_indentation
+    pub fn preserve_indentation(self, preserve_indentation: bool) -> Self {
+        Options {
+            preserve_indentation,
+            ..self
+        }
+    }
+
+    /// Change [`self.auto_subsequent_indent`]. When enabled, the
+    /// wrapped lines of every line of the input text are indented
+    /// with spaces so that they line up with the text on its first
+    /// line. The first line starts with its indentation, followed by
+    /// an optional label: the first word followed by at least two
+    /// spaces, as in `"--verbose   Print more output"`. This gives
+    /// nicely aligned definition lists and command line help.
+    ///
+    /// The indentation replaces [`Options::subsequent_indent`]. The
+    /// hanging indentation of [`Options::hanging_list_indent`] is
+    /// used instead if both are enabled and the line starts with a
+    /// list marker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// let text = "  --color   Color the output, depending on the terminal.\n\
+    ///             \x20 --quiet   Do not print anything.";
+    /// let options = Options::new(32).auto_subsequent_indent(true);
+    /// assert_eq!(fill(text, &options),
+    ///            "  --color   Color the output,\n\
+    ///             \x20           depending on the\n\
+    ///             \x20           terminal.\n\
+    ///             \x20 --quiet   Do not print\n\
+    ///             \x20           anything.");
+    /// ```
+    ///
+    /// [`self.auto_subsequent_indent`]: #structfield.auto_subsequent_indent
+    pub fn auto_subsequent_indent(self, auto_subsequent_indent: bool) -> Self {
+        Options {
+            auto_subsequent_indent,
+            ..self
+        }
+    }
+
+    /// Change [`self.paragraphs`]. This controls how [`refill`]
+    /// splits the text into paragraphs, which are refilled one by
+    /// one. Each paragraph gets its own indentation. Blank lines
+    /// between paragraphs are kept, but trailing whitespace is
+    /// removed from them.
+    ///
+    /// With [`Paragraphs::BlankLines`], the list items below would be
+    /// refilled as a single paragraph. [`Paragraphs::Indentation`]
+    /// starts a new paragraph for every list item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{refill, Options, Paragraphs};
+    ///
+    /// let text = "Memory safety\n\
+    ///             without garbage collection.\n\
+    ///             \n\
+    ///             - Concurrency without\n\
+    ///             \x20 data races.\n\
+    ///             - Zero-cost abstractions.\n";
+    ///
+    /// let options = Options::new(50).paragraphs(Paragraphs::Indentation);
+    /// assert_eq!(refill(text, &options),
+    ///            "Memory safety without garbage collection.\n\
+    ///             \n\
+    ///             - Concurrency without data races.\n\
+    ///             - Zero-cost abstractions.\n");
+    /// ```
+    ///
+    /// [`self.paragraphs`]: #structfield.paragraphs
+    pub fn paragraphs(self, paragraphs: Paragraphs) -> Self {
+        Options { paragraphs, ..self }
+    }
+
+    /// Change [`self.preformatted`]. Preformatted lines are not
+    /// wrapped: they are kept as they are, after the initial or
+    /// subsequent indentation. Use this to fill documents with
+    /// tables, diagrams, or code without mangling them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options, Preformatted};
+    ///
+    /// let text = "The results of the benchmark are:\n\
+    ///             +-------+------+\n\
+    ///             | wrap  | 1 ms |\n\
+    ///             +-------+------+";
+    /// let options = Options::new(20).preformatted(Preformatted::Detect);
+    /// assert_eq!(fill(text, &options),
+    ///            "The results of the\n\
+    ///             benchmark are:\n\
+    ///             +-------+------+\n\
+    ///             | wrap  | 1 ms |\n\
+    ///             +-------+------+");
+    /// ```
+    ///
+    /// [`self.preformatted`]: #structfield.preformatted
+    pub fn preformatted(self, preformatted: Preformatted) -> Self {
+        Options {
+            preformatted,
+            ..self
+        }
+    }
+
+    /// Change [`self.join_hyphens`]. This controls if [`refill`]
+    /// should join a line which ends with a hyphen with the next line
+    /// when the next line starts with a lowercase letter. The hyphen
+    /// is removed, so that a word hyphenated by [`fill`] is restored.
+    ///
+    /// Words which contain a hyphen of their own, such as
+    /// `"zero-cost"`, lose it when they happen to be split at the
+    /// hyphen. This is why the setting is off by default. Use
+    /// [`unfill_hyphenated`] to unfill text in the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{refill, Options};
+    ///
+    /// let text = "Memory safety with-\nout garbage collec-\ntion.\n";
+    /// assert_eq!(refill(text, 50),
+    ///            "Memory safety with- out garbage collec- tion.\n");
+    /// assert_eq!(refill(text, Options::new(50).join_hyphens(true)),
+    ///            "Memory safety without garbage collection.\n");
+    /// ```
+    ///
+    /// [`self.join_hyphens`]: #structfield.join_hyphens
+    pub fn join_hyphens(self, setting: bool) -> Self {
+        Options {
+            join_hyphens: setting,
+            ..self
+        }
+    }
+
+    /// Change [`self.keep_fitting`]. This controls if [`refill`]
+    /// should leave paragraphs alone when all their lines already fit
+    /// within the new width. Only the paragraphs with lines which are
+    /// too long are refilled. This gives the smallest possible diff
+    /// when the text is kept in version control.
+    ///
+    /// The prefix and the width of the lines are detected separately
+    /// for each paragraph, see [`Options::paragraphs`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{refill, Options, Paragraphs};
+    ///
+    /// let text = "\
+    /// > Memory safety
+    /// > without garbage
+    /// > collection.
+    ///
+    /// - Concurrency without data races and zero-cost
+    ///   abstractions.
+    /// ";
+    /// let options = Options::new(40)
+    ///     .paragraphs(Paragraphs::BlankLines)
+    ///     .keep_fitting(true);
+    /// assert_eq!(refill(text, &options), "\
+    /// > Memory safety
+    /// > without garbage
+    /// > collection.
+    ///
+    /// - Concurrency without data races and
+    ///   zero-cost abstractions.
+    /// ");
+    /// ```
+    ///
+    /// [`self.keep_fitting`]: #structfield.keep_fitting
+    pub fn keep_fitting(self, setting: bool) -> Self {
+        Options {
+            keep_fitting: setting,
+            ..self
+        }
+    }
+
+    /// Change [`self.line_widths`]. The `line_widths` closure returns
+    /// the width of every output line, given its line number. This
+    /// replaces [`Options::width`] for the line, and like it, the
+    /// width includes the indentation. The first line has line number
+    /// zero. This makes shaped text, such as text flowing around an
+    /// image or a drop cap, possible with [`wrap`] and [`fill`].
+    ///
+    /// Please see [`wrap_algorithms::LineWidths`] for line widths
+    /// used directly with the wrapping algorithms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// // The first two lines make room for something on the left.
+    /// let options = Options::new(20)
+    ///     .line_indent(|n| if n < 2 { "      " } else { "" }.into())
+    ///     .line_widths(|n| if n < 2 { 20 } else { 14 });
+    /// assert_eq!(wrap("Memory safety without garbage collection.", &options),
+    ///            vec!["      Memory safety",
+    ///                 "      without",
+    ///                 "garbage",
+    ///                 "collection."]);
+    /// ```
+    ///
+    /// [`self.line_widths`]: #structfield.line_widths
+    pub fn line_widths<F>(self, line_widths: F) -> Self
+    where
+        F: Fn(usize) -> usize + Send + Sync + 'static,
+    {
+        Options {
+            line_widths: Some(LineWidth::new(line_widths)),
+            ..self
+        }
+    }
+
+    /// Change [`self.line_hook`]. The `line_hook` closure is called
+    /// with every wrapped line before it is returned. It receives the
+    /// line number, whether the line is the last line, and the line,
+    /// which it can change in place. The first line has line number
+    /// zero.
+    ///
+    /// The hook runs after the lines have been indented, aligned,
+    /// and truncated, so anything it adds is not counted in the line
+    /// width. This avoids a second pass over the wrapped lines when
+    /// they are rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{fill, Options};
+    ///
+    /// // Add a shell-style continuation marker to all but the last line.
+    /// let options = Options::new(15).line_hook(|_, last, line| {
+    ///     if !last {
+    ///         line.to_mut().push_str(" \\");
+    ///     }
+    /// });
+    /// assert_eq!(fill("Memory safety without garbage collection.", &options),
+    ///            "Memory safety \\\nwithout garbage \\\ncollection.");
+    ///
+    /// // Uppercase the first line.
+    /// let options = Options::new(15).line_hook(|n, _, line| {
+    ///     if n == 0 {
+    ///         *line = line.to_uppercase().into();
+    ///     }
+    /// });
+    /// assert_eq!(fill("Memory safety without garbage collection.", &options),
+    ///            "MEMORY SAFETY\nwithout garbage\ncollection.");
+    /// ```
+    ///
+    /// [`self.line_hook`]: #structfield.line_hook
+    pub fn line_hook<F>(self, line_hook: F) -> Self
+    where
+        F: Fn(usize, bool, &mut Cow<'_, str>) + Send + Sync + 'static,
+    {
+        Options {
+            line_hook: Some(LineHook::new(line_hook)),
+            ..self
+        }
+    }
+
+    /// Change [`self.reopen_styles`]. When set, colors and other
+    /// styles set by ANSI escape sequences, as well as terminal
+    /// hyperlinks, are closed at the end of every line and reopened
+    /// after the indentation of the next line. This lets you wrap colored text, such as the output of
+    /// a command, and show the lines separately, for example in
+    /// columns or next to a border.
+    ///
+    /// Escape sequences are always measured as zero columns wide and
+    /// they are never broken, this option only affects the styles.
+    /// The default value is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, Options};
+    ///
+    /// let text = "\x1b[31mMemory safety without garbage\x1b[0m collection.";
+    /// let options = Options::new(17).subsequent_indent("  ").reopen_styles(true);
+    /// assert_eq!(wrap(text, &options),
+    ///            vec!["\x1b[31mMemory safety\x1b[0m",
+    ///                 "  \x1b[31mwithout garbage\x1b[0m",
+    ///                 "  collection."]);
+    /// ```
+    ///
+    /// [`self.reopen_styles`]: #structfield.reopen_styles
+    pub fn reopen_styles(self, setting: bool) -> Self {
+        Options {
+            reopen_styles: setting,
+            ..self
+        }
+    }
+
+    /// Change [`self.char_width`]. This changes how the displayed
+    /// width of words, indentation, and placeholders is measured, see
+    /// [`CharWidth`] for the choices. By default, text is measured
+    /// with [`core::display_width`].
+    ///
+    /// The [`self.width`] and the widths given by
+    /// [`Options::line_widths`] are in the unit of `char_width`. This
+    /// lets you wrap text for a proportional font by measuring it in
+    /// pixels, see [`FontMetrics`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, AsciiWidth, Options};
+    ///
+    /// let text = "你好 你好 你好";
+    /// assert_eq!(wrap(text, 6), vec!["你好", "你好", "你好"]);
+    /// let options = Options::new(6).char_width(AsciiWidth);
+    /// assert_eq!(wrap(text, &options), vec!["你好 你好", "你好"]);
+    /// ```
+    ///
+    /// [`self.char_width`]: #structfield.char_width
+    /// [`self.width`]: #structfield.width
+    pub fn char_width<W: CharWidth + 'static>(self, char_width: W) -> Self {
+        Options {
+            char_width: Some(std::sync::Arc::new(char_width)),
+            ..self
+        }
+    }
+
+    /// Change [`self.ambiguous_width`]. Characters such as “°”, “±”,
+    /// “×”, Greek and Cyrillic letters, and box drawing characters
+    /// have an ambiguous width in East Asian typography. Terminals
+    /// configured for Chinese, Japanese, or Korean text usually show
+    /// them two columns wide, other terminals one column wide.
+    ///
+    /// The setting is used wherever text is measured: for words,
+    /// indentation, and truncation. Only ambiguous characters which
+    /// are one column wide according to the [`Options::char_width`]
+    /// are widened, so combining marks stay zero columns wide. The
+    /// default value is [`AmbiguousWidth::Narrow`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, AmbiguousWidth, Options};
+    ///
+    /// let text = "α β γ";
+    /// assert_eq!(wrap(text, 5), vec!["α β γ"]);
+    /// let options = Options::new(5).ambiguous_width(AmbiguousWidth::Wide);
+    /// assert_eq!(wrap(text, &options), vec!["α β", "γ"]);
+    /// ```
+    ///
+    /// [`self.ambiguous_width`]: #structfield.ambiguous_width
+    pub fn ambiguous_width(self, ambiguous_width: AmbiguousWidth) -> Self {
+        Options {
+            ambiguous_width,
+            ..self
+        }
+    }
+
+    /// Change [`self.grapheme_width`]. By default, the width of text
+    /// is the sum of the widths of its characters. This overestimates
+    /// the width of emojis made from several characters, such as
+    /// “👨‍🦰” (man with red hair), “🇩🇰” (a flag), or “👍🏽” (thumbs
+    /// up with a skin tone). Terminals and browsers which combine
+    /// such emojis show them as a single character.
+    ///
+    /// With [`GraphemeWidth::Clusters`], every grapheme cluster is
+    /// measured once. The other settings also choose how emojis with
+    /// variation selectors are measured, see [`GraphemeWidth`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, AsciiSpace, GraphemeWidth, Options};
+    ///
+    /// let text = "I \u{2764}\u{fe0f} \u{1f468}\u{200d}\u{1f9b0}";
+    /// let options = Options::new(7).word_separator(AsciiSpace);
+    /// assert_eq!(wrap(text, &options).len(), 2);
+    /// let options = options.grapheme_width(GraphemeWidth::Clusters);
+    /// assert_eq!(wrap(text, &options), vec![text]);
+    /// ```
+    ///
+    /// [`self.grapheme_width`]: #structfield.grapheme_width
+    pub fn grapheme_width(self, grapheme_width: GraphemeWidth) -> Self {
+        Options {
+            grapheme_width,
+            ..self
+        }
+    }
+
+    /// Change [`self.control_chars`]. Control characters, such as
+    /// U+0000 (Null) or U+0008 (Backspace), are sometimes found in
+    /// text from files and programs. They have no width, and a
+    /// terminal may act on them instead of showing them. Use
+    /// [`ControlChars::Strip`] to remove them, or
+    /// [`ControlChars::Replace`] to show them as symbols.
+    ///
+    /// Tabs are not affected by this setting, see
+    /// [`Options::whitespace`]. ANSI escape sequences are also kept.
+    /// The default value is [`ControlChars::Keep`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, ControlChars, Options};
+    ///
+    /// let text = "foo\x00bar\x08baz";
+    /// assert_eq!(wrap(text, 10), vec![text]);
+    /// let options = Options::new(10).control_chars(ControlChars::Strip);
+    /// assert_eq!(wrap(text, &options), vec!["foobarbaz"]);
+    /// let options = Options::new(13).control_chars(ControlChars::Replace);
+    /// assert_eq!(wrap(text, &options), vec!["foo␀bar␈baz"]);
+    /// ```
+    ///
+    /// [`self.control_chars`]: #structfield.control_chars
+    pub fn control_chars(self, control_chars: ControlChars) -> Self {
+        Options {
+            control_chars,
+            ..self
+        }
+    }
+
+    /// How text is measured with these options.
+    fn measure(&self) -> Measure<'_> {
+        Measure::new(
+            self.char_width.as_deref(),
+            self.ambiguous_width,
+            self.grapheme_width,
+        )
+    }
+
+    /// The displayed width of `text`, measured like the words.
+    fn text_width(&self, text: &str) -> usize {
+        self.measure().str_width(text)
+    }
+
+    /// The width of output line `line_number`. This is the width
+    /// unless [`Options::line_widths`] is set.
+    fn width_of(&self, line_number: usize) -> usize {
+        match &self.line_widths {
+            Some(line_widths) => line_widths.width(line_number),
+            None => self.width,
+        }
+    }
+
+    /// The indentation of output line `line_number`. This is `indent`
+    /// unless [`Options::line_indent`] is set.
+    fn indent_of<'b>(&self, line_number: usize, indent: &'b str) -> Cow<'b, str> {
+        match &self.line_indent {
+            Some(line_indent) => line_indent.indent(line_number),
+            None => Cow::from(indent),
+        }
+    }
+
+    /// Check that the options leave room for text on every line.
+    /// This is used by [`try_wrap`] and [`try_fill`].
+    fn validate(&self) -> Result<(), WrapError> {
+        let indents = [&self.initial_indent, &self.subsequent_indent];
+        for (line_number, indent) in indents.iter().enumerate() {
+            let width = self.width_of(line_number);
+            if width == 0 {
+                return Err(WrapError::ZeroWidth);
+            }
+            let indent_width = self.text_width(&self.indent_of(line_number, indent));
+            if indent_width >= width {
+                return Err(WrapError::IndentTooWide {
+                    indent_width,
+                    width,
+                });
+            }
+        }
+
+        let truncates = self.overflow == Overflow::Truncate || self.max_lines.is_some();
+        let placeholder_width = self.text_width(&self.placeholder);
+        if truncates && placeholder_width > self.width {
+            return Err(WrapError::PlaceholderTooWide {
+                placeholder_width,
+                width: self.width,
+            });
+        }
+        Ok(())
+    }
+
+    /// Change [`self.word_separator`].
+    ///
+    /// See [`WordSeparator`] for details on the choices.
+    ///
+    /// [`self.word_separator`]: #structfield.word_separator
+    pub fn word_separator<T>(self, word_separator: T) -> Options<'a, A, T, S> {
+        Options {
+            width: self.width,
+            initial_indent: self.initial_indent,
+            subsequent_indent: self.subsequent_indent,
+            break_words: self.break_words,
+            word_separator: word_separator,
+            wrap_algorithm: self.wrap_algorithm,
+            splitter: self.splitter,
+            preserve_breaks: self.preserve_breaks,
+            sentence_end_double_space: self.sentence_end_double_space,
+            break_marker: self.break_marker,
+            alignment: self.alignment,
+            justification: self.justification,
+            hanging_punctuation: self.hanging_punctuation,
+            max_lines: self.max_lines,
+            placeholder: self.placeholder,
+            overflow: self.overflow,
+            line_ending: self.line_ending,
+            unicode_line_breaks: self.unicode_line_breaks,
+            whitespace: self.whitespace,
+            trim: self.trim,
+            detect_quotes: self.detect_quotes,
+            semantic_line_breaks: self.semantic_line_breaks,
+            hanging_list_indent: self.hanging_list_indent,
+            line_indent: self.line_indent,
+            preserve_indentation: self.preserve_indentation,
+            auto_subsequent_indent: self.auto_subsequent_indent,
+            paragraphs: self.paragraphs,
+            preformatted: self.preformatted,
+            join_hyphens: self.join_hyphens,
+            keep_fitting: self.keep_fitting,
+            line_widths: self.line_widths,
+            line_hook: self.line_hook,
+            reopen_styles: self.reopen_styles,
+            char_width: self.char_width,
+            ambiguous_width: self.ambiguous_width,
+            grapheme_width: self.grapheme_width,
+            control_chars: self.control_chars,
+        }
+    }
+
+    /// Change [`self.wrap_algorithm`].
+    ///
+    /// See the [`wrap_algorithms::WrapAlgorithm`] trait for details on
+    /// the choices.
+    ///
+    /// [`self.wrap_algorithm`]: #structfield.wrap_algorithm
+    pub fn wrap_algorithm<B>(self, wrap_algorithm: B) -> Options<'a, B, R, S> {
+        Options {
+            width: self.width,
+            initial_indent: self.initial_indent,
+            subsequent_indent: self.subsequent_indent,
+            break_words: self.break_words,
+            word_separator: self.word_separator,
+            wrap_algorithm: wrap_algorithm,
+            splitter: self.splitter,
+            preserve_breaks: self.preserve_breaks,
+            sentence_end_double_space: self.sentence_end_double_space,
+            break_marker: self.break_marker,
+            alignment: self.alignment,
+            justification: self.justification,
+            hanging_punctuation: self.hanging_punctuation,
+            max_lines: self.max_lines,
+            placeholder: self.placeholder,
+            overflow: self.overflow,
+            line_ending: self.line_ending,
+            unicode_line_breaks: self.unicode_line_breaks,
+            whitespace: self.whitespace,
+            trim: self.trim,
+            detect_quotes: self.detect_quotes,
+            semantic_line_breaks: self.semantic_line_breaks,
+            hanging_list_indent: self.hanging_list_indent,
+            line_indent: self.line_indent,
+            preserve_indentation: self.preserve_indentation,
+            auto_subsequent_indent: self.auto_subsequent_indent,
+            paragraphs: self.paragraphs,
+            preformatted: self.preformatted,
+            join_hyphens: self.join_hyphens,
+            keep_fitting: self.keep_fitting,
+            line_widths: self.line_widths,
+            line_hook: self.line_hook,
+            reopen_styles: self.reopen_styles,
+            char_width: self.char_width,
+            ambiguous_width: self.ambiguous_width,
+            grapheme_width: self.grapheme_width,
+            control_chars: self.control_chars,
+        }
+    }
+
+    /// Change [`self.splitter`]. The [`WordSplitter`] is used to fit
+    /// part of a word into the current line when wrapping text.
+    ///
+    /// This function may return a different type than `Self`. That is
+    /// the case when the given `splitter` is of a different type the
+    /// the currently stored one in the `splitter` field. Take for
+    /// example:
+    ///
+    /// ```
+    /// use textwrap::{HyphenSplitter, NoHyphenation, Options};
+    /// // The default type returned by `new`:
+    /// let opt: Options<_, _, HyphenSplitter> = Options::new(80);
+    /// // Setting a different splitter changes the type
+    /// let opt: Options<_, _, NoHyphenation> = opt.splitter(NoHyphenation);
+    /// ```
+    ///
+    /// [`self.splitter`]: #structfield.splitter
+    pub fn splitter<T>(self, splitter: T) -> Options<'a, A, R, T> {
+        Options {
+            width: self.width,
+            initial_indent: self.initial_indent,
+            subsequent_indent: self.subsequent_indent,
+            break_words: self.break_words,
+            word_separator: self.word_separator,
+            wrap_algorithm: self.wrap_algorithm,
+            splitter: splitter,
+            preserve_breaks: self.preserve_breaks,
+            sentence_end_double_space: self.sentence_end_double_space,
+            break_marker: self.break_marker,
+            alignment: self.alignment,
+            justification: self.justification,
+            hanging_punctuation: self.hanging_punctuation,
+            max_lines: self.max_lines,
+            placeholder: self.placeholder,
+            overflow: self.overflow,
+            line_ending: self.line_ending,
+            unicode_line_breaks: self.unicode_line_breaks,
+            whitespace: self.whitespace,
+            trim: self.trim,
+            detect_quotes: self.detect_quotes,
+            semantic_line_breaks: self.semantic_line_breaks,
+            hanging_list_indent: self.hanging_list_indent,
+            line_indent: self.line_indent,
+            preserve_indentation: self.preserve_indentation,
+            auto_subsequent_indent: self.auto_subsequent_indent,
+            paragraphs: self.paragraphs,
+            preformatted: self.preformatted,
+            join_hyphens: self.join_hyphens,
+            keep_fitting: self.keep_fitting,
+            line_widths: self.line_widths,
+            line_hook: self.line_hook,
+            reopen_styles: self.reopen_styles,
+            char_width: self.char_width,
+            ambiguous_width: self.ambiguous_width,
+            grapheme_width: self.grapheme_width,
+            control_chars: self.control_chars,
+        }
+    }
+}
+
+/// Return the current terminal width.
+///
+/// If the terminal width cannot be determined (typically because the
+/// standard output is not connected to a terminal), a default width
+/// of 80 characters will be used.
+///
+/// # Examples
+///
+/// Create an [`Options`] for wrapping at the current terminal width
+/// with a two column margin to the left and the right:
+///
+/// ```no_run
+/// use textwrap::{termwidth, NoHyphenation, Options};
+///
+/// let width = termwidth() - 4; // Two columns on each side.
+/// let options = Options::new(width)
+///     .splitter(NoHyphenation)
+///     .initial_indent("  ")
+///     .subsequent_indent("  ");
+/// ```
+///
+/// **Note:** Only available when the `terminal_size` Cargo feature is
+/// enabled.
+#[cfg(feature = "terminal_size")]
+pub fn termwidth() -> usize {
+    terminal_size::terminal_size().map_or(80, |(terminal_size::Width(w), _)| w.into())
+}
+
+/// Fill a line of text at a given width.
+///
+/// The result is a [`String`], complete with newlines between each
+/// line. Use the [`wrap`] function if you need access to the
+/// individual lines.
+///
+/// The easiest way to use this function is to pass an integer for
+/// `width_or_options`:
+///
+/// ```
+/// use textwrap::fill;
+///
+/// assert_eq!(
+///     fill("Memory safety without garbage collection.", 15),
+///     "Memory safety\nwithout garbage\ncollection."
+/// );
+/// ```
+///
+/// If you need to customize the wrapping, you can pass an [`Options`]
+/// instead of an `usize`:
+///
+/// ```
+/// use textwrap::{fill, Options};
+///
+/// let options = Options::new(15)
+///     .initial_indent("- ")
+///     .subsequent_indent("  ");
+/// assert_eq!(
+///     fill("Memory safety without garbage collection.", &options),
+///     "- Memory safety\n  without\n  garbage\n  collection."
+/// );
+/// ```
+pub fn fill<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    // This will avoid reallocation in simple cases (no
+    // indentation, no hyphenation).
+    let mut result = String::with_capacity(text.len());
+
+    // Writing to a String cannot fail.
+    let _ = fill_into(text, width_or_options, &mut result);
+
+    result
+}
+
+/// Fill a line of text at a given width, but return an error instead
+/// of overflowing the lines when the options are invalid.
+///
+/// This works like [`fill`], but the options are checked first. See
+/// [`try_wrap`] for the checks done and [`WrapError`] for the
+/// possible errors. This is useful when the width comes from user
+/// input, such as a command line argument.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{try_fill, Options, WrapError};
+///
+/// assert_eq!(try_fill("Memory safety", 10), Ok(String::from("Memory\nsafety")));
+/// assert_eq!(
+///     try_fill("Memory safety", Options::new(4).initial_indent("    ")),
+///     Err(WrapError::IndentTooWide { indent_width: 4, width: 4 })
+/// );
+/// ```
+pub fn try_fill<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> Result<String, WrapError>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let options = width_or_options.into();
+    options.validate()?;
+    Ok(fill(text, options))
+}
+
+/// Fill a line of text at a given width and write it to `writer`.
+///
+/// This works like [`fill`], but the lines are written directly to
+/// `writer` instead of being collected in a new `String`. Use this
+/// to fill large documents into an existing buffer. See
+/// [`fill_into_io`] for writing to a file or a socket.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Write;
+/// use textwrap::fill_into;
+///
+/// let mut page = String::from("Rust:\n");
+/// fill_into("Memory safety without garbage collection.", 15, &mut page).unwrap();
+/// writeln!(page).unwrap();
+/// assert_eq!(page, "Rust:\nMemory safety\nwithout garbage\ncollection.\n");
+/// ```
+pub fn fill_into<'a, A, R, S, Opt, W>(
+    text: &str,
+    width_or_options: Opt,
+    writer: &mut W,
+) -> std::fmt::Result
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+    W: std::fmt::Write + ?Sized,
+{
+    let options = width_or_options.into();
+    let line_ending = options.line_ending.as_str(text);
+    for (i, line) in wrap(text, options).iter().enumerate() {
+        if i > 0 {
+            writer.write_str(line_ending)?;
+        }
+        writer.write_str(line)?;
+    }
+    Ok(())
+}
+
+/// Fill a line of text at a given width and write it to an
+/// [`std::io::Write`] `writer`.
+///
+/// This works like [`fill_into`], but for files, sockets, and other
+/// byte-oriented writers. Wrap the `writer` in a
+/// [`std::io::BufWriter`] if it is unbuffered, since every line is
+/// written with separate calls.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::fill_into_io;
+///
+/// let mut output = Vec::new();
+/// fill_into_io("Memory safety without garbage collection.", 15, &mut output).unwrap();
+/// assert_eq!(output, b"Memory safety\nwithout garbage\ncollection.");
+/// ```
+pub fn fill_into_io<'a, A, R, S, Opt, W>(
+    text: &str,
+    width_or_options: Opt,
+    writer: &mut W,
+) -> std::io::Result<()>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+    W: std::io::Write + ?Sized,
+{
+    let options = width_or_options.into();
+    let line_ending = options.line_ending.as_str(text);
+    for (i, line) in wrap(text, options).iter().enumerate() {
+        if i > 0 {
+            writer.write_all(line_ending.as_bytes())?;
+        }
+        writer.write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Unpack a paragraph of already-wrapped text.
+///
+/// This function attempts to recover the original text from a single
+/// paragraph of text produced by the [`fill`] function. This means
+/// that it turns
+///
+/// ```text
+/// textwrap: a small
+/// library for
+/// wrapping text.
+/// ```
+///
+/// back into
+///
+/// ```text
+/// textwrap: a small library for wrapping text.
+/// ```
+///
+/// In addition, it will recognize a common prefix among the lines.
+/// The prefix of the first line is returned in
+/// [`Options::initial_indent`] and the prefix (if any) of the the
+/// other lines is returned in [`Options::subsequent_indent`].
+///
+/// In addition to `' '`, the prefixes can consist of characters used
+/// for unordered lists (`'-'`, `'+'`, and `'*'`) and block quotes
+/// (`'>'`) in Markdown as well as characters often used for inline
+/// comments (`'#'` and `'/'`).
+///
+/// The text must come from a single wrapped paragraph. This means
+/// that there can be no `"\n\n"` within the text.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::unfill;
+///
+/// let (text, options) = unfill("\
+/// * This is an
+///   example of
+///   a list item.
+/// ");
+///
+/// assert_eq!(text, "This is an example of a list item.\n");
+/// assert_eq!(options.initial_indent, "* ");
+/// assert_eq!(options.subsequent_indent, "  ");
+/// ```
+pub fn unfill(
+    text: &str,
+) -> (
+    String,
+    Options<'_, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), HyphenSplitter>,
+) {
+    let (unfilled, options, _) = unfill_with_breaks(text, false, false);
+    (unfilled, options)
+}
+
+/// Unpack a paragraph and join hyphenated words.
+///
+/// This works like [`unfill`], except that a line which ends with a
+/// hyphen is joined with the next line without a space when the next
+/// line starts with a lowercase letter. The hyphen is removed. This
+/// undoes the hyphenation done by [`fill`], so that the text can be
+/// wrapped again without leaving stray hyphens behind. The returned
+/// options have [`Options::join_hyphens`] set.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{fill, unfill_hyphenated};
+///
+/// let (text, options) = unfill_hyphenated("\
+/// > Memory safety with-
+/// > out garbage collec-
+/// > tion. Non-
+/// > Rust code.
+/// ");
+///
+/// assert_eq!(text, "Memory safety without garbage collection. Non- Rust code.\n");
+/// assert_eq!(options.initial_indent, "> ");
+/// assert!(options.join_hyphens);
+/// ```
+pub fn unfill_hyphenated(
+    text: &str,
+) -> (
+    String,
+    Options<'_, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), HyphenSplitter>,
+) {
+    let (unfilled, options, _) = unfill_with_breaks(text, false, true);
+    (unfilled, options.join_hyphens(true))
+}
+
+/// Characters of the prefixes removed by [`unfill`].
+const PREFIX_CHARS: &[char] = &[' ', '-', '+', '*', '>', '#', '/'];
+
+/// Unpack a paragraph like [`unfill`]. The byte offsets in the
+/// unfilled text where the lines used to start are returned too.
+fn unfill_with_breaks(
+    text: &str,
+    sentence_end_double_space: bool,
+    join_hyphens: bool,
+) -> (
+    String,
+    Options<'_, DefaultWrapAlgorithm!(), DefaultWordSeparator!(), HyphenSplitter>,
+    Vec<usize>,
+) {
+    let trimmed = text.trim_end_matches(&['\r', '\n'][..]);
+    let mut options = Options::new(0);
+    let mut initial_indent = "";
+    let mut subsequent_indent = "";
+    for (idx, line) in trimmed.split('\n').enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        options.width = std::cmp::max(options.width, core::display_width(line));
+        let without_prefix = line.trim_start_matches(PREFIX_CHARS);
+        let prefix = &line[..line.len() - without_prefix.len()];
+
+        if idx == 0 {
+            initial_indent = prefix;
+        } else if idx == 1 {
+            subsequent_indent = prefix;
+        } else if idx > 1 {
+            for ((idx, x), y) in prefix.char_indices().zip(subsequent_indent.chars()) {
+                if x != y {
+                    subsequent_indent = &prefix[..idx];
+                    break;
+                }
+            }
+            if prefix.len() < subsequent_indent.len() {
+                subsequent_indent = prefix;
+            }
+        }
+    }
+    options.initial_indent = Cow::Borrowed(initial_indent);
+    options.subsequent_indent = Cow::Borrowed(subsequent_indent);
+
+    let mut unfilled = String::with_capacity(text.len());
+    let mut breaks = Vec::new();
+    for (idx, line) in trimmed.split('\n').enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if idx == 0 {
+            unfilled.push_str(&line[options.initial_indent.len()..]);
+        } else {
+            let rest = &line[options.subsequent_indent.len()..];
+            if join_hyphens && ends_hyphenated(&unfilled) && starts_lowercase(rest) {
+                unfilled.pop();
+                unfilled.push_str(rest);
+                continue;
+            }
+            unfilled.push(' ');
+            if sentence_end_double_space && ends_sentence(&unfilled[..unfilled.len() - 1]) {
+                unfilled.push(' ');
+            }
+            breaks.push(unfilled.len());
+            unfilled.push_str(rest);
+        }
+    }
+
+    unfilled.push_str(&text[trimmed.len()..]);
+    (unfilled, options, breaks)
+}
+
+/// Does `text` end with a word hyphenated at the end of a line? The
+/// hyphen must follow a letter.
+fn ends_hyphenated(text: &str) -> bool {
+    match text.strip_suffix('-') {
+        Some(word) => word.chars().next_back().is_some_and(char::is_alphabetic),
+        None => false,
+    }
+}
+
+/// Does `text` start with a lowercase letter?
+fn starts_lowercase(text: &str) -> bool {
+    text.chars().next().is_some_and(char::is_lowercase)
+}
+
+/// Does `text` end with the end of a sentence?
+fn ends_sentence(text: &str) -> bool {
+    let closing_chars: &[_] = &['"', '\'', ')', ']', '’', '”'];
+    text.trim_end_matches(closing_chars)
+        .ends_with(&['.', '?', '!'][..])
+}
+
+/// Refill a paragraph of wrapped text with a new width.
+///
+/// This function will first use the [`unfill`] function to remove
+/// newlines from the text. Afterwards the text is filled again using
+/// the [`fill`] function.
+///
+/// The `new_width_or_options` argument specify the new width and can
+/// specify other options as well — except for
+/// [`Options::initial_indent`] and [`Options::subsequent_indent`],
+/// which are deduced from `filled_text`. Use
+/// [`Options::preserve_breaks`] to keep the existing line breaks where
+/// possible, and [`Options::paragraphs`] to refill several paragraphs
+/// at once. The prefix of every paragraph is detected on its own.
+/// Use [`Options::keep_fitting`] to only refill the paragraphs with
+/// lines which are too long.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::refill;
+///
 /// // Some loosely wrapped text. The "> " prefix is recognized automatically.
 /// let text = "\
 /// > Memory
@@ -840,46 +3077,541 @@ pub fn unfill(
 /// > collection.
 /// ");
 ///
-/// assert_eq!(refill(text, 40), "\
-/// > Memory safety without garbage
-/// > collection.
-/// ");
+/// assert_eq!(refill(text, 40), "\
+/// > Memory safety without garbage
+/// > collection.
+/// ");
+///
+/// assert_eq!(refill(text, 60), "\
+/// > Memory safety without garbage collection.
+/// ");
+/// ```
+///
+/// You can also reshape bullet points:
+///
+/// ```
+/// use textwrap::refill;
+///
+/// let text = "\
+/// - This is my
+///   list item.
+/// ";
+///
+/// assert_eq!(refill(text, 20), "\
+/// - This is my list
+///   item.
+/// ");
+/// ```
+pub fn refill<'a, A, R, S, Opt>(filled_text: &str, new_width_or_options: Opt) -> String
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let trimmed = filled_text.trim_end_matches(&['\r', '\n'][..]);
+    let mut new_options = new_width_or_options.into();
+    let line_ending = new_options.line_ending.as_str(filled_text);
+    let mut refilled = if new_options.detect_quotes {
+        // The quote prefixes are treated as indentation of the
+        // quoted paragraphs, which are refilled one by one.
+        new_options.detect_quotes = false;
+        quoted_paragraphs(trimmed)
+            .into_iter()
+            .map(|paragraph| match split_quote_prefix(paragraph) {
+                (quote, "") => quote.trim_end_matches(' ').to_owned(),
+                _ => refill_paragraph(paragraph, &mut new_options, line_ending),
+            })
+            .collect::<Vec<_>>()
+            .join(line_ending)
+    } else if let Paragraphs::Whole = new_options.paragraphs {
+        refill_paragraph(trimmed, &mut new_options, line_ending)
+    } else {
+        let paragraphs = new_options.paragraphs;
+        split_paragraphs(trimmed, paragraphs)
+            .into_iter()
+            .map(|paragraph| {
+                if paragraphs.is_separator(paragraph) {
+                    paragraph.trim_end().to_owned()
+                } else {
+                    refill_paragraph(paragraph, &mut new_options, line_ending)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(line_ending)
+    };
+    refilled.push_str(&filled_text[trimmed.len()..]);
+    refilled
+}
+
+/// Refill a single paragraph without trailing newlines, see
+/// [`refill`]. The indentation of `options` is changed to the
+/// indentation of `text`, unless the
+/// paragraph is kept as it is.
+fn refill_paragraph<'t, A, R, S>(
+    text: &'t str,
+    options: &mut Options<'t, A, R, S>,
+    line_ending: &str,
+) -> String
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    let lines = text
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line));
+    if options.keep_fitting
+        && lines
+            .clone()
+            .all(|line| options.text_width(line) <= options.width)
+    {
+        return lines.collect::<Vec<_>>().join(line_ending);
+    }
+
+    let (text, unfill_options, breaks) = unfill_with_breaks(
+        text,
+        options.sentence_end_double_space,
+        options.join_hyphens,
+    );
+    options.initial_indent = unfill_options.initial_indent;
+    options.subsequent_indent = unfill_options.subsequent_indent;
+    let existing_breaks = if options.preserve_breaks {
+        &breaks[..]
+    } else {
+        &[]
+    };
+    wrap_with_breaks(&text, options, existing_breaks).join(line_ending)
+}
+
+/// Split `line` into its email quote prefix, such as `"> > "`, and
+/// the rest of the line. See [`Options::detect_quotes`].
+fn split_quote_prefix(line: &str) -> (&str, &str) {
+    if !line.starts_with('>') {
+        return ("", line);
+    }
+    let rest = line.trim_start_matches(&['>', ' '][..]);
+    line.split_at(line.len() - rest.len())
+}
+
+/// Split `text` into paragraphs as described by `paragraphs`. Lines
+/// separating paragraphs are returned on their own.
+fn split_paragraphs(text: &str, paragraphs: Paragraphs) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    // The previous line and its number within its paragraph.
+    let mut prev: Option<(&str, usize)> = None;
+    for (offset, line) in split_lines(text, false) {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let line_number = match prev {
+            Some((prev, line_number)) => {
+                if paragraphs.is_separator(prev)
+                    || paragraphs.is_separator(line)
+                    || paragraphs.starts_paragraph(prev, line, line_number == 0)
+                {
+                    result.push(&text[start..offset - 1]);
+                    start = offset;
+                    0
+                } else {
+                    line_number + 1
+                }
+            }
+            None => 0,
+        };
+        prev = Some((line, line_number));
+    }
+    result.push(&text[start..]);
+    result
+}
+
+/// Split `text` into runs of lines with the same quote depth. Lines
+/// with only a quote prefix are returned on their own.
+fn quoted_paragraphs(text: &str) -> Vec<&str> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut prev_key = None;
+    for (offset, line) in split_lines(text, false) {
+        let (quote, rest) = split_quote_prefix(line.strip_suffix('\r').unwrap_or(line));
+        let key = (quote.matches('>').count(), rest.is_empty());
+        if offset > 0 && (prev_key != Some(key) || key.1) {
+            paragraphs.push(&text[start..offset - 1]);
+            start = offset;
+        }
+        prev_key = Some(key);
+    }
+    paragraphs.push(&text[start..]);
+    paragraphs
+}
+
+/// Comment markers recognized by [`reflow_comment`]. Longer markers
+/// come first.
+const COMMENT_MARKERS: [&str; 5] = ["///", "//!", "//", "#", "*"];
+
+/// Reflow a block of source code comments with a new width.
+///
+/// The comment prefix shared by all lines of `comment` is detected
+/// first. The prefix consists of the indentation of the lines, one of
+/// the comment markers `"///"`, `"//!"`, `"//"`, `"#"`, or `"*"`, and
+/// the space after the marker. The prefix is removed, every paragraph
+/// of the remaining text is refilled like with [`refill`] to the
+/// width minus the width of the prefix, and the prefix is added again
+/// in front of every line. Paragraphs are separated by comment lines
+/// without any text, which are kept as they are.
 ///
-/// assert_eq!(refill(text, 60), "\
-/// > Memory safety without garbage collection.
-/// ");
-/// ```
+/// Lines without a shared comment prefix are refilled as plain text.
 ///
-/// You can also reshape bullet points:
+/// # Examples
 ///
 /// ```
-/// use textwrap::refill;
+/// use textwrap::reflow_comment;
 ///
-/// let text = "\
-/// - This is my
-///   list item.
+/// let comment = "\
+/// /// Memory safety
+/// /// without garbage collection.
+/// ///
+/// /// Concurrency without data races.
 /// ";
 ///
-/// assert_eq!(refill(text, 20), "\
-/// - This is my list
-///   item.
+/// assert_eq!(reflow_comment(comment, 30), "\
+/// /// Memory safety without
+/// /// garbage collection.
+/// ///
+/// /// Concurrency without data
+/// /// races.
 /// ");
 /// ```
-pub fn refill<'a, A, R, S, Opt>(filled_text: &str, new_width_or_options: Opt) -> String
+///
+/// Block comments in the C style are handled too:
+///
+/// ```
+/// use textwrap::reflow_comment;
+///
+/// let comment = " * Memory safety\n * without garbage collection.";
+/// assert_eq!(reflow_comment(comment, 80),
+///            " * Memory safety without garbage collection.");
+/// ```
+pub fn reflow_comment<'a, A, R, S, Opt>(comment: &str, new_width_or_options: Opt) -> String
 where
     A: wrap_algorithms::WrapAlgorithm,
     R: WordSeparator,
     S: WordSplitter,
     Opt: Into<Options<'a, A, R, S>>,
 {
-    let trimmed = filled_text.trim_end_matches('\n');
-    let (text, options) = unfill(trimmed);
-    let mut new_options = new_width_or_options.into();
-    new_options.initial_indent = options.initial_indent;
-    new_options.subsequent_indent = options.subsequent_indent;
-    let mut refilled = fill(&text, new_options);
-    refilled.push_str(&filled_text[trimmed.len()..]);
-    refilled
+    let trimmed = comment.trim_end_matches(&['\r', '\n'][..]);
+    let lines = trimmed
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+    let marker = comment_prefix(lines[0])
+        .filter(|marker| lines.iter().all(|line| line.starts_with(marker)))
+        .unwrap_or("");
+    let contents = lines
+        .iter()
+        .map(|line| &line[marker.len()..])
+        .collect::<Vec<_>>();
+    let space = if !marker.is_empty() && contents[0].starts_with(' ') {
+        " "
+    } else {
+        ""
+    };
+
+    // Paragraphs of comment text without the prefix, None for
+    // comment lines without text.
+    let mut paragraphs: Vec<Option<String>> = Vec::new();
+    for content in contents {
+        if content.trim().is_empty() {
+            paragraphs.push(None);
+            continue;
+        }
+        let content = content.strip_prefix(space).unwrap_or(content);
+        match paragraphs.last_mut() {
+            Some(Some(paragraph)) => {
+                paragraph.push('\n');
+                paragraph.push_str(content);
+            }
+            _ => paragraphs.push(Some(content.to_owned())),
+        }
+    }
+
+    let mut options = new_width_or_options.into();
+    let line_ending = options.line_ending.as_str(comment);
+    let prefix = [marker, space].concat();
+    options.width = options.width.saturating_sub(options.text_width(&prefix));
+    let mut reflowed = paragraphs
+        .iter()
+        .map(|paragraph| match paragraph {
+            Some(paragraph) => refill_paragraph(paragraph, &mut options, "\n")
+                .split('\n')
+                .map(|line| [prefix.as_str(), line].concat())
+                .collect::<Vec<_>>()
+                .join(line_ending),
+            None => marker.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(line_ending);
+    reflowed.push_str(&comment[trimmed.len()..]);
+    reflowed
+}
+
+/// Find the comment prefix of `line` without the space after the
+/// comment marker, see [`reflow_comment`].
+fn comment_prefix(line: &str) -> Option<&str> {
+    let rest = line.trim_start();
+    let indent = line.len() - rest.len();
+    COMMENT_MARKERS
+        .iter()
+        .find(|marker| rest.starts_with(*marker))
+        .map(|marker| &line[..indent + marker.len()])
+}
+
+/// Format a Git commit message.
+///
+/// The first line of `message` is the subject line, which is kept as
+/// it is. The paragraphs of the body are filled to 72 columns with
+/// [`markdown::fill`], so bullet lists and fenced code blocks are
+/// handled like in Markdown. Some lines are never wrapped:
+///
+/// - Trailer lines such as `Signed-off-by: Name <email>`, when they
+///   form the last paragraph of the message.
+/// - Indented blocks, i.e., paragraphs starting with an indented
+///   line. They are typically used for code and command output.
+/// - Comment lines starting with `'#'`.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::format_commit_message;
+///
+/// let message = "\
+/// Fix the frobnicator
+///
+/// The frobnicator failed to frobnicate when the input was empty. This was noticed when running:
+///
+///     $ frob --input /dev/null --output-format=json --verbose --all
+///
+/// Signed-off-by: Some Developer With A Very Long Name <developer@example.com>
+/// ";
+///
+/// assert_eq!(format_commit_message(message), "\
+/// Fix the frobnicator
+///
+/// The frobnicator failed to frobnicate when the input was empty. This was
+/// noticed when running:
+///
+///     $ frob --input /dev/null --output-format=json --verbose --all
+///
+/// Signed-off-by: Some Developer With A Very Long Name <developer@example.com>
+/// ");
+/// ```
+pub fn format_commit_message(message: &str) -> String {
+    let trimmed = message.trim_end_matches(&['\r', '\n'][..]);
+    let line_ending = LineEnding::FirstLine.as_str(message);
+    let lines = trimmed
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+
+    // The trailers are the last paragraph, if all its lines are
+    // trailers.
+    let last_paragraph = lines
+        .iter()
+        .rposition(|line| line.trim().is_empty())
+        .map_or(lines.len(), |idx| idx + 1);
+    let trailers_start =
+        if last_paragraph > 1 && lines[last_paragraph..].iter().all(|line| is_trailer(line)) {
+            last_paragraph
+        } else {
+            lines.len()
+        };
+
+    let mut result = vec![lines[0].to_owned()];
+    let mut fillable = Vec::new();
+    let mut indented_block = false;
+    for (idx, line) in lines.iter().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            indented_block = false;
+        } else if lines[idx - 1].trim().is_empty() && line.starts_with(char::is_whitespace) {
+            indented_block = true;
+        }
+        if indented_block || line.starts_with('#') || idx >= trailers_start {
+            if !fillable.is_empty() {
+                result.push(markdown::fill(&fillable.join("\n"), 72));
+                fillable.clear();
+            }
+            result.push((*line).to_owned());
+        } else {
+            fillable.push(*line);
+        }
+    }
+    if !fillable.is_empty() {
+        result.push(markdown::fill(&fillable.join("\n"), 72));
+    }
+
+    let mut formatted = result.join("\n");
+    if line_ending != "\n" {
+        formatted = formatted.replace('\n', line_ending);
+    }
+    formatted.push_str(&message[trimmed.len()..]);
+    formatted
+}
+
+/// Is `line` a Git trailer such as `Signed-off-by: Name <email>`?
+fn is_trailer(line: &str) -> bool {
+    match line.find(": ") {
+        Some(idx) => {
+            let token = &line[..idx];
+            !token.is_empty() && token.chars().all(|ch| ch.is_alphanumeric() || ch == '-')
+        }
+        None => false,
+    }
+}
+
+/// Collapse and truncate `text` to fit on a single line.
+///
+/// All ASCII whitespace in `text` is first collapsed into single
+/// spaces, and leading and trailing whitespace is removed. If the
+/// result fits in `width` columns, it is returned. Otherwise words
+/// are removed from the end until the remaining words and the
+/// `placeholder` fit in `width` columns. This is the same as
+/// wrapping the text with [`Options::max_lines`] set to 1, and it
+/// works like the `shorten` function from Python's `textwrap`
+/// module.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::shorten;
+///
+/// assert_eq!(shorten("Hello  world!", 12, " [...]"), "Hello world!");
+/// assert_eq!(shorten("Hello  world!", 11, " [...]"), "Hello [...]");
+/// assert_eq!(shorten("Memory safety\nwithout garbage collection.", 25, "…"),
+///            "Memory safety without…");
+/// ```
+///
+/// If not even the first word fits together with the placeholder,
+/// only the placeholder is returned, without its leading whitespace:
+///
+/// ```
+/// use textwrap::shorten;
+///
+/// assert_eq!(shorten("Supercalifragilistic", 10, " [...]"), "[...]");
+/// ```
+pub fn shorten(text: &str, width: usize, placeholder: &str) -> String {
+    let text = text
+        .split(|ch: char| ch.is_ascii_whitespace())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let options = Options::new(width)
+        .wrap_algorithm(wrap_algorithms::FirstFit)
+        .max_lines(1)
+        .placeholder(placeholder);
+    wrap(&text, &options)
+        .into_iter()
+        .next()
+        .map(Cow::into_owned)
+        .unwrap_or_default()
+}
+
+/// Truncate `text` by replacing its middle with `placeholder`.
+///
+/// If `text` fits in `width` columns, it is returned unchanged.
+/// Otherwise, the start and the end of `text` are kept and the
+/// characters in between are replaced by the `placeholder` so that
+/// the result is at most `width` columns wide. The start gets the
+/// extra column if the available width is odd. This is useful for
+/// file names, paths, and URLs, where both ends carry information.
+///
+/// The text is measured by its displayed width and it is never cut
+/// in the middle of a grapheme cluster, see
+/// [`core::Word::break_apart`]. If `placeholder` is wider than
+/// `width`, only the placeholder is returned.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::truncate_middle;
+///
+/// assert_eq!(truncate_middle("verylongfilename.txt", 16, "…"), "verylong…ame.txt");
+/// assert_eq!(truncate_middle("short.txt", 16, "…"), "short.txt");
+/// assert_eq!(truncate_middle("😊😊😊😊😊", 6, "…"), "😊…😊");
+/// ```
+pub fn truncate_middle<'a>(text: &'a str, width: usize, placeholder: &str) -> Cow<'a, str> {
+    if core::display_width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+
+    let pieces = grapheme_pieces(text, &UnicodeWidth);
+    let available = width.saturating_sub(core::display_width(placeholder));
+    let (mut head_len, mut head_width) = (0, 0);
+    for &(len, width) in &pieces {
+        if head_width + width > available - available / 2 {
+            break;
+        }
+        head_len += len;
+        head_width += width;
+    }
+    let (mut tail_len, mut tail_width) = (0, 0);
+    for &(len, width) in pieces.iter().rev() {
+        if head_width + tail_width + width > available {
+            break;
+        }
+        tail_len += len;
+        tail_width += width;
+    }
+
+    Cow::Owned(format!(
+        "{}{}{}",
+        &text[..head_len],
+        placeholder,
+        &text[text.len() - tail_len..]
+    ))
+}
+
+/// Truncate the end of `text` so that it fits in `width` columns
+/// together with `placeholder`, which is added if `text` is cut.
+fn truncate_end<'a>(
+    text: &'a str,
+    width: usize,
+    placeholder: &str,
+    char_width: &dyn CharWidth,
+) -> Cow<'a, str> {
+    if char_width.str_width(text) <= width {
+        return Cow::Borrowed(text);
+    }
+
+    let available = width.saturating_sub(char_width.str_width(placeholder));
+    let (mut head_len, mut head_width) = (0, 0);
+    for (len, width) in grapheme_pieces(text, char_width) {
+        if head_width + width > available {
+            break;
+        }
+        head_len += len;
+        head_width += width;
+    }
+    let head = text[..head_len].trim_end_matches(' ');
+    if placeholder.is_empty() {
+        Cow::Borrowed(head)
+    } else {
+        Cow::Owned(format!("{}{}", head, placeholder))
+    }
+}
+
+/// Split `text` into pieces which must not be separated, and return
+/// the length and displayed width of each piece, measured with
+/// `char_width`. The pieces are single characters, except for
+/// grapheme clusters, see [`core::Word::break_apart`].
+fn grapheme_pieces(text: &str, char_width: &dyn CharWidth) -> Vec<(usize, usize)> {
+    // Trailing spaces are not part of the word, so they are added as
+    // separate pieces.
+    let word = core::Word::from(text);
+    let mut pieces = word
+        .break_apart(1)
+        .map(|piece| (piece.len(), char_width.str_width(&piece)))
+        .collect::<Vec<_>>();
+    let space = (1, char_width.char_width(' '));
+    pieces.extend(std::iter::repeat_n(space, word.whitespace.len()));
+    pieces
 }
 
 /// Wrap a line of text at a given width.
@@ -959,12 +3691,12 @@ where
 /// second last line:
 ///
 /// ```
-/// # #[cfg(feature = "smawk")] {
+/// # #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))] {
 /// # use textwrap::{Options, wrap};
 /// # use textwrap::wrap_algorithms::OptimalFit;
 /// #
 /// # let lines = wrap("To be, or not to be: that is the question",
-/// #                  Options::new(10).wrap_algorithm(OptimalFit));
+/// #                  Options::new(10).wrap_algorithm(OptimalFit::new()));
 /// # assert_eq!(lines.join("\n") + "\n", "\
 /// To be,
 /// or not to
@@ -984,74 +3716,1395 @@ where
 /// the input, but the subsequent lines become owned strings:
 ///
 /// ```
-/// use std::borrow::Cow::{Borrowed, Owned};
-/// use textwrap::{wrap, Options};
+/// use std::borrow::Cow::{Borrowed, Owned};
+/// use textwrap::{wrap, Options};
+///
+/// let options = Options::new(15).subsequent_indent("....");
+/// let lines = wrap("Wrapping text all day long.", &options);
+/// let annotated = lines
+///     .iter()
+///     .map(|line| match line {
+///         Borrowed(text) => format!("[Borrowed] {}", text),
+///         Owned(text) => format!("[Owned]    {}", text),
+///     })
+///     .collect::<Vec<_>>();
+/// assert_eq!(
+///     annotated,
+///     &[
+///         "[Borrowed] Wrapping text",
+///         "[Owned]    ....all day",
+///         "[Owned]    ....long.",
+///     ]
+/// );
+/// ```
+///
+/// ## Leading and Trailing Whitespace
+///
+/// As a rule, leading whitespace (indentation) is preserved and
+/// trailing whitespace is discarded.
+///
+/// In more details, when wrapping words into lines, words are found
+/// by splitting the input text on space characters. One or more
+/// spaces (shown here as “␣”) are attached to the end of each word:
+///
+/// ```text
+/// "Foo␣␣␣bar␣baz" -> ["Foo␣␣␣", "bar␣", "baz"]
+/// ```
+///
+/// These words are then put into lines. The interword whitespace is
+/// preserved, unless the lines are wrapped so that the `"Foo␣␣␣"`
+/// word falls at the end of a line:
+///
+/// ```
+/// use textwrap::wrap;
+///
+/// assert_eq!(wrap("Foo   bar baz", 10), vec!["Foo   bar", "baz"]);
+/// assert_eq!(wrap("Foo   bar baz", 8), vec!["Foo", "bar baz"]);
+/// ```
+///
+/// Notice how the trailing whitespace is removed in both case: in the
+/// first example, `"bar␣"` becomes `"bar"` and in the second case
+/// `"Foo␣␣␣"` becomes `"Foo"`.
+///
+/// Leading whitespace is preserved when the following word fits on
+/// the first line. To understand this, consider how words are found
+/// in a text with leading spaces:
+///
+/// ```text
+/// "␣␣foo␣bar" -> ["␣␣", "foo␣", "bar"]
+/// ```
+///
+/// When put into lines, the indentation is preserved if `"foo"` fits
+/// on the first line, otherwise you end up with an empty line:
+///
+/// ```
+/// use textwrap::wrap;
+///
+/// assert_eq!(wrap("  foo bar", 8), vec!["  foo", "bar"]);
+/// assert_eq!(wrap("  foo bar", 4), vec!["", "foo", "bar"]);
+/// ```
+pub fn wrap<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> Vec<Cow<'_, str>>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let options = width_or_options.into();
+    wrap_with_breaks(text, &options, &[])
+}
+
+/// Wrap a line of text at a given width, producing the lines on
+/// demand.
+///
+/// This works like [`wrap`], but returns an iterator over the wrapped
+/// lines. Every line of `text` is wrapped when the iterator reaches
+/// it, so taking the first few lines of a huge document only wraps
+/// the start of the document.
+///
+/// Options which need all lines at once are not lazy: with
+/// [`Options::max_lines`], [`Options::line_indent`],
+/// [`Options::line_widths`], [`Options::line_hook`],
+/// [`Options::reopen_styles`], or [`Whitespace::Collapse`], the whole
+/// text is wrapped by the first call to `next`.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, wrap_iter};
+///
+/// let text = "Memory safety without garbage collection.\n\
+///             Concurrency without data races.";
+/// let first_screen = wrap_iter(text, 15).take(2).collect::<Vec<_>>();
+/// assert_eq!(first_screen, vec!["Memory safety", "without garbage"]);
+/// assert_eq!(wrap_iter(text, 15).collect::<Vec<_>>(), wrap(text, 15));
+/// ```
+pub fn wrap_iter<'a, 't, A, R, S, Opt>(
+    text: &'t str,
+    width_or_options: Opt,
+) -> WrapIter<'a, 't, A, R, S>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    WrapIter {
+        options: width_or_options.into(),
+        text,
+        next_line_start: Some(0),
+        pending: Vec::new().into_iter(),
+    }
+}
+
+/// Iterator over wrapped lines, returned by [`wrap_iter`].
+#[derive(Debug, Clone)]
+pub struct WrapIter<'a, 't, A, R, S> {
+    options: Options<'a, A, R, S>,
+    text: &'t str,
+    // The byte offset of the next line of `text` to wrap, if any.
+    next_line_start: Option<usize>,
+    // Wrapped lines which have not been returned yet.
+    pending: std::vec::IntoIter<Cow<'t, str>>,
+}
+
+impl<'a, 't, A, R, S> Iterator for WrapIter<'a, 't, A, R, S>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    type Item = Cow<'t, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.pending.next() {
+                return Some(line);
+            }
+            let line_start = self.next_line_start?;
+            let options = &self.options;
+            let first_line = line_start == 0;
+            if first_line
+                && (options.max_lines.is_some()
+                    || options.line_indent.is_some()
+                    || options.line_widths.is_some()
+                    || options.line_hook.is_some()
+                    || options.reopen_styles
+                    || options.whitespace == Whitespace::Collapse)
+            {
+                self.next_line_start = None;
+                self.pending = wrap_with_breaks(self.text, options, &[]).into_iter();
+                continue;
+            }
+
+            let rest = &self.text[line_start..];
+            let unicode_line_breaks = options.unicode_line_breaks;
+            let line = match rest
+                .char_indices()
+                .find(|&(_, ch)| is_line_break(ch, unicode_line_breaks))
+            {
+                Some((idx, ch)) => {
+                    self.next_line_start = Some(line_start + idx + ch.len_utf8());
+                    &rest[..idx]
+                }
+                None => {
+                    self.next_line_start = None;
+                    rest
+                }
+            };
+            let mut lines = Vec::new();
+            wrap_line(line, line_start, options, &[], first_line, &mut lines, None);
+            self.pending = lines.into_iter();
+        }
+    }
+}
+
+/// Wrap a line of text at a given width, but return an error instead
+/// of overflowing the lines when the options are invalid.
+///
+/// This works like [`wrap`], but the options are checked first:
+///
+/// * The width of every line must be positive.
+/// * The indentation must be narrower than the line width, so that
+///   there is room for text on every line.
+/// * When lines are truncated, the [`Options::placeholder`] must fit
+///   within the line width.
+///
+/// With [`wrap`], such options result in lines which are wider than
+/// the width. This function is useful when the width or indentation
+/// comes from user input and should be rejected gracefully.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{try_wrap, Options, WrapError};
+///
+/// assert_eq!(try_wrap("Memory safety", 10).unwrap(), vec!["Memory", "safety"]);
+/// assert_eq!(try_wrap("Memory safety", 0), Err(WrapError::ZeroWidth));
+///
+/// let options = Options::new(10).max_lines(1).placeholder(" [continued]");
+/// assert_eq!(
+///     try_wrap("Memory safety", &options),
+///     Err(WrapError::PlaceholderTooWide { placeholder_width: 12, width: 10 })
+/// );
+/// ```
+pub fn try_wrap<'a, A, R, S, Opt>(
+    text: &str,
+    width_or_options: Opt,
+) -> Result<Vec<Cow<'_, str>>, WrapError>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let options = width_or_options.into();
+    options.validate()?;
+    Ok(wrap_with_breaks(text, &options, &[]))
+}
+
+/// Invalid options found by [`try_wrap`] and [`try_fill`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapError {
+    /// The line width is zero.
+    ZeroWidth,
+    /// The indentation is as wide as the line, or wider.
+    IndentTooWide {
+        /// The width of the indentation.
+        indent_width: usize,
+        /// The width of the line.
+        width: usize,
+    },
+    /// The [`Options::placeholder`] added to truncated lines is wider
+    /// than the line.
+    PlaceholderTooWide {
+        /// The width of the placeholder.
+        placeholder_width: usize,
+        /// The width of the line.
+        width: usize,
+    },
+}
+
+impl std::fmt::Display for WrapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            WrapError::ZeroWidth => write!(f, "line width is zero"),
+            WrapError::IndentTooWide {
+                indent_width,
+                width,
+            } => write!(
+                f,
+                "indentation of width {} leaves no room on lines of width {}",
+                indent_width, width
+            ),
+            WrapError::PlaceholderTooWide {
+                placeholder_width,
+                width,
+            } => write!(
+                f,
+                "placeholder of width {} does not fit on lines of width {}",
+                placeholder_width, width
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WrapError {}
+
+/// Wrap a text with many lines in parallel.
+///
+/// This works like [`wrap`], but the lines of `text` are wrapped on
+/// the [rayon] thread pool. Every line is a paragraph which is
+/// wrapped independently of the other lines, so the result is the
+/// same as for [`wrap`]. This is useful for large documents with
+/// many paragraphs.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, wrap_par};
 ///
-/// let options = Options::new(15).subsequent_indent("....");
-/// let lines = wrap("Wrapping text all day long.", &options);
-/// let annotated = lines
-///     .iter()
-///     .map(|line| match line {
-///         Borrowed(text) => format!("[Borrowed] {}", text),
-///         Owned(text) => format!("[Owned]    {}", text),
-///     })
-///     .collect::<Vec<_>>();
-/// assert_eq!(
-///     annotated,
-///     &[
-///         "[Borrowed] Wrapping text",
-///         "[Owned]    ....all day",
-///         "[Owned]    ....long.",
-///     ]
-/// );
+/// let text = "Memory safety without garbage collection.\n\
+///             Concurrency without data races.";
+/// assert_eq!(wrap_par(text, 20), wrap(text, 20));
 /// ```
 ///
-/// ## Leading and Trailing Whitespace
+/// **Note:** Only available when the `rayon` Cargo feature is
+/// enabled.
 ///
-/// As a rule, leading whitespace (indentation) is preserved and
-/// trailing whitespace is discarded.
+/// [rayon]: https://docs.rs/rayon/
+#[cfg(feature = "rayon")]
+pub fn wrap_par<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> Vec<Cow<'_, str>>
+where
+    A: wrap_algorithms::WrapAlgorithm + Sync,
+    R: WordSeparator + Sync,
+    S: WordSplitter + Sync,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    use rayon::prelude::*;
+
+    let options = width_or_options.into();
+    if options.whitespace == Whitespace::Collapse || options.reopen_styles {
+        return wrap_with_breaks(text, &options, &[]);
+    }
+    let lines = split_lines(text, options.unicode_line_breaks)
+        .map(|(_, line)| line)
+        .collect::<Vec<_>>();
+    let wrapped_lines = lines
+        .par_iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let mut wrapped = Vec::new();
+            wrap_single_line(line, 0, &options, &[], idx == 0, &mut wrapped, None);
+            wrapped
+        })
+        .collect::<Vec<_>>();
+    let mut lines = wrapped_lines.into_iter().flatten().collect();
+    truncate_lines(&mut lines, &options, None);
+    lines
+}
+
+/// Fill a text with many lines in parallel.
 ///
-/// In more details, when wrapping words into lines, words are found
-/// by splitting the input text on space characters. One or more
-/// spaces (shown here as “␣”) are attached to the end of each word:
+/// This works like [`fill`], but the lines are wrapped in parallel,
+/// see [`wrap_par`] for details.
 ///
-/// ```text
-/// "Foo␣␣␣bar␣baz" -> ["Foo␣␣␣", "bar␣", "baz"]
-/// ```
+/// # Examples
 ///
-/// These words are then put into lines. The interword whitespace is
-/// preserved, unless the lines are wrapped so that the `"Foo␣␣␣"`
-/// word falls at the end of a line:
+/// ```
+/// use textwrap::{fill, fill_par};
 ///
+/// let text = "Memory safety without garbage collection.\n\
+///             Concurrency without data races.";
+/// assert_eq!(fill_par(text, 20), fill(text, 20));
 /// ```
-/// use textwrap::wrap;
 ///
-/// assert_eq!(wrap("Foo   bar baz", 10), vec!["Foo   bar", "baz"]);
-/// assert_eq!(wrap("Foo   bar baz", 8), vec!["Foo", "bar baz"]);
-/// ```
+/// **Note:** Only available when the `rayon` Cargo feature is
+/// enabled.
+#[cfg(feature = "rayon")]
+pub fn fill_par<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    A: wrap_algorithms::WrapAlgorithm + Sync,
+    R: WordSeparator + Sync,
+    S: WordSplitter + Sync,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let options = width_or_options.into();
+    let line_ending = options.line_ending.as_str(text);
+    let mut result = String::with_capacity(text.len());
+
+    for (i, line) in wrap_par(text, options).iter().enumerate() {
+        if i > 0 {
+            result.push_str(line_ending);
+        }
+        result.push_str(line);
+    }
+
+    result
+}
+
+/// Wrap text like [`wrap`]. The words ending at the given byte
+/// offsets in `text` are marked as [`core::Word::existing_break`].
+fn wrap_with_breaks<'t, A, R, S>(
+    text: &'t str,
+    options: &Options<'_, A, R, S>,
+    existing_breaks: &[usize],
+) -> Vec<Cow<'t, str>>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    wrap_with_spans(text, options, existing_breaks, None)
+}
+
+/// Wrap text like [`wrap_with_breaks`]. The geometry of every line
+/// is pushed onto `spans` if given, see [`WrappedText`].
+fn wrap_with_spans<'t, A, R, S>(
+    text: &'t str,
+    options: &Options<'_, A, R, S>,
+    existing_breaks: &[usize],
+    spans: Option<&mut Vec<LineSpan>>,
+) -> Vec<Cow<'t, str>>
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    if options.whitespace == Whitespace::Collapse {
+        if let Some(collapsed) = collapse_lines(text, options) {
+            let lines = wrap_with_breaks(&collapsed, options, &[]);
+            if let Some(spans) = spans {
+                // The lines come from the collapsed text, so every
+                // line covers all of the text.
+                push_whole_spans(0..text.len(), lines.len(), spans);
+            }
+            return lines
+                .into_iter()
+                .map(|line| Cow::Owned(line.into_owned()))
+                .collect();
+        }
+    }
+
+    // Styles are reopened at the start of the line content, so we
+    // need the spans.
+    let mut style_spans = Vec::new();
+    let mut spans = match spans {
+        None if options.reopen_styles => Some(&mut style_spans),
+        spans => spans,
+    };
+
+    let mut lines = Vec::new();
+    for (line_start, line) in split_lines(text, options.unicode_line_breaks) {
+        wrap_line(
+            line,
+            line_start,
+            options,
+            existing_breaks,
+            true,
+            &mut lines,
+            spans.as_deref_mut(),
+        );
+    }
+    truncate_lines(&mut lines, options, spans.as_deref_mut());
+    if let (true, Some(spans)) = (options.reopen_styles, spans) {
+        reopen_styles(&mut lines, spans);
+    }
+    call_line_hook(&mut lines, options);
+
+    lines
+}
+
+/// Push spans for `count` lines which all come from the `source`
+/// text. The lines are separated by spaces.
+fn push_whole_spans(source: std::ops::Range<usize>, count: usize, spans: &mut Vec<LineSpan>) {
+    for i in 0..count {
+        let line_break = if i + 1 < count {
+            LineBreak::Space
+        } else {
+            LineBreak::Newline
+        };
+        spans.push(LineSpan::new(source.clone(), 0, line_break));
+    }
+}
+
+/// Wrap a single line of input, splitting it into sentences first
+/// if [`Options::semantic_line_breaks`] is set. The first wrapped
+/// line is the first output line if `first_line` is true and `lines`
+/// is empty.
+fn wrap_line<'t, A, R, S>(
+    line: &'t str,
+    line_start: usize,
+    options: &Options<'_, A, R, S>,
+    existing_breaks: &[usize],
+    first_line: bool,
+    lines: &mut Vec<Cow<'t, str>>,
+    mut spans: Option<&mut Vec<LineSpan>>,
+) where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    let sentences = if options.semantic_line_breaks {
+        split_sentences(line)
+    } else {
+        vec![(0, line)]
+    };
+    for (i, &(sentence_start, sentence)) in sentences.iter().enumerate() {
+        wrap_single_line(
+            sentence,
+            line_start + sentence_start,
+            options,
+            existing_breaks,
+            first_line && lines.is_empty(),
+            lines,
+            spans.as_deref_mut(),
+        );
+        if i + 1 < sentences.len() {
+            // The sentences are separated by whitespace.
+            if let Some(span) = spans.as_deref_mut().and_then(|spans| spans.last_mut()) {
+                span.line_break = LineBreak::Space;
+            }
+        }
+    }
+}
+
+/// Close the styles which are active at the end of every line and
+/// reopen them at the start of the content of the next line, see
+/// [`Options::reopen_styles`]. The `spans` are updated to start the
+/// content after the reopened styles.
+fn reopen_styles(lines: &mut [Cow<'_, str>], spans: &mut [LineSpan]) {
+    let mut active = core::ActiveStyles::default();
+    for (line, span) in lines.iter_mut().zip(spans) {
+        let content_start = span.content_start;
+        if content_start >= line.len() {
+            continue;
+        }
+        let reopened = active.reopen();
+        active.update(&line[content_start..]);
+        if reopened.is_empty() && active.is_empty() {
+            continue;
+        }
+
+        let mut result = String::with_capacity(2 * line.len());
+        result.push_str(&line[..content_start]);
+        result.push_str(&reopened);
+        result.push_str(&line[content_start..]);
+        active.close(&mut result);
+        span.content_start += reopened.len();
+        *line = Cow::Owned(result);
+    }
+}
+
+/// Call the [`Options::line_hook`], if any, with every line.
+fn call_line_hook<A, R, S>(lines: &mut [Cow<'_, str>], options: &Options<'_, A, R, S>) {
+    if let Some(line_hook) = &options.line_hook {
+        let last_line = lines.len().saturating_sub(1);
+        for (line_number, line) in lines.iter_mut().enumerate() {
+            line_hook.call(line_number, line_number == last_line, line);
+        }
+    }
+}
+
+/// Split `line` after the sentences, see
+/// [`Options::semantic_line_breaks`]. The byte offset of each
+/// sentence is returned together with the sentence.
+fn split_sentences(line: &str) -> Vec<(usize, &str)> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut idx = 0;
+    // The end of the previous word if it ends a sentence.
+    let mut sentence_end = None;
+    while let Some(pos) = line[idx..].find(|ch: char| !ch.is_whitespace()) {
+        let word_start = idx + pos;
+        let word_end = line[word_start..]
+            .find(char::is_whitespace)
+            .map_or(line.len(), |len| word_start + len);
+        if let Some(end) = sentence_end.take() {
+            if !line[word_start..].starts_with(char::is_lowercase) {
+                sentences.push((start, &line[start..end]));
+                start = word_start;
+            }
+        }
+        if ends_sentence(&line[word_start..word_end]) {
+            sentence_end = Some(word_end);
+        }
+        idx = word_end;
+    }
+    sentences.push((start, &line[start..]));
+    sentences
+}
+
+/// Split `text` into lines and return the byte offset of each line
+/// together with the line. Lines end at `'\n'` and, if
+/// `unicode_line_breaks` is set, at the separators described in
+/// [`Options::unicode_line_breaks`].
+fn split_lines(text: &str, unicode_line_breaks: bool) -> impl Iterator<Item = (usize, &str)> {
+    text.split(move |ch| is_line_break(ch, unicode_line_breaks))
+        .map(move |line| (line.as_ptr() as usize - text.as_ptr() as usize, line))
+}
+
+/// Does `ch` end a line? See [`split_lines`].
+fn is_line_break(ch: char, unicode_line_breaks: bool) -> bool {
+    ch == '\n' || unicode_line_breaks && matches!(ch, '\u{2028}' | '\u{2029}' | '\u{85}' | '\u{c}')
+}
+
+/// Join the lines of `text` with spaces for
+/// [`Whitespace::Collapse`]. Returns `None` if `text` is a single
+/// line.
+fn collapse_lines<A, R, S>(text: &str, options: &Options<'_, A, R, S>) -> Option<String> {
+    let mut lines = split_lines(text, options.unicode_line_breaks)
+        .map(|(_, line)| line.strip_suffix('\r').unwrap_or(line));
+    let mut collapsed = String::from(lines.next()?);
+    let mut lines = lines.peekable();
+    lines.peek()?;
+    for line in lines {
+        collapsed.push(' ');
+        collapsed.push_str(line);
+    }
+    Some(collapsed)
+}
+
+/// Is `ch` a control character which is handled according to
+/// [`Options::control_chars`]? Tabs and the ESC which starts an ANSI
+/// escape sequence are not.
+fn is_control_char(ch: char) -> bool {
+    ch.is_control() && ch != '\t' && ch != '\x1b'
+}
+
+/// Apply [`Options::control_chars`] to `line`. Returns `None` if
+/// nothing changes.
+fn replace_control_chars(line: &str, control_chars: ControlChars) -> Option<String> {
+    if control_chars == ControlChars::Keep || !line.contains(is_control_char) {
+        return None;
+    }
+    let escape_sequences = core::ansi_escape_sequences(line);
+    let mut replaced = String::with_capacity(line.len());
+    for (idx, ch) in line.char_indices() {
+        if !is_control_char(ch) || core::inside_escape_sequence(&escape_sequences, idx) {
+            replaced.push(ch);
+        } else if control_chars == ControlChars::Replace {
+            replaced.push(match ch {
+                '\x00'..='\x1f' => std::char::from_u32(0x2400 + ch as u32).unwrap_or(ch),
+                '\x7f' => '\u{2421}',
+                _ => '\u{fffd}',
+            });
+        }
+    }
+    Some(replaced)
+}
+
+/// Apply [`Options::control_chars`], [`Options::whitespace`], and
+/// [`Options::trim`] to `line`. Returns `None` if nothing changes.
+fn normalize_whitespace<A, R, S>(line: &str, options: &Options<'_, A, R, S>) -> Option<String> {
+    let replaced = replace_control_chars(line, options.control_chars);
+    let original = line;
+    let line = replaced.as_deref().unwrap_or(line);
+    let is_space = |ch: char| ch == ' ' || ch == '\t';
+    let content = line.trim_start_matches(is_space);
+    let indent = match options.trim {
+        Trim::Both => "",
+        _ => &line[..line.len() - content.len()],
+    };
+
+    let mut normalized = String::with_capacity(line.len());
+    normalized.push_str(indent);
+    if options.whitespace == Whitespace::Preserve {
+        normalized.push_str(content);
+    } else {
+        let mut words = content.split(is_space).filter(|word| !word.is_empty());
+        if let Some(word) = words.next() {
+            normalized.push_str(word);
+            for word in words {
+                normalized.push(' ');
+                normalized.push_str(word);
+            }
+            if options.trim == Trim::Neither && content.ends_with(is_space) {
+                normalized.push(' ');
+            }
+        }
+    }
+
+    if normalized == original {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+/// Wrap a single line of text which starts at byte offset
+/// `line_start` and push the wrapped lines onto `lines`. The first
+/// wrapped line uses the initial indentation if `first_line` is set.
+fn wrap_single_line<'t, A, R, S>(
+    line: &'t str,
+    line_start: usize,
+    options: &Options<'_, A, R, S>,
+    existing_breaks: &[usize],
+    first_line: bool,
+    lines: &mut Vec<Cow<'t, str>>,
+    spans: Option<&mut Vec<LineSpan>>,
+) where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let source = line_start..line_start + line.len();
+    if options.preformatted.is_preformatted(line) {
+        let indent = if first_line {
+            &*options.initial_indent
+        } else {
+            &*options.subsequent_indent
+        };
+        let indent = options.indent_of(lines.len(), indent);
+        lines.push(if indent.is_empty() {
+            Cow::from(line)
+        } else {
+            Cow::Owned([&indent, line].concat())
+        });
+        if let Some(spans) = spans {
+            spans.push(LineSpan::new(source, indent.len(), LineBreak::Newline));
+        }
+        return;
+    }
+    if let Some(normalized) = normalize_whitespace(line, options) {
+        // The existing breaks refer to the original line, so they
+        // cannot be used for the normalized line.
+        let mut wrapped = Vec::new();
+        wrap_single_line(&normalized, 0, options, &[], first_line, &mut wrapped, None);
+        if let Some(spans) = spans {
+            push_whole_spans(source, wrapped.len(), spans);
+        }
+        lines.extend(
+            wrapped
+                .into_iter()
+                .map(|line| Cow::Owned(line.into_owned())),
+        );
+        return;
+    }
+    if options.overflow == Overflow::Truncate {
+        return push_truncated_line(line, line_start, options, first_line, lines, spans);
+    }
+
+    let (quote, line) = if options.detect_quotes {
+        split_quote_prefix(line)
+    } else {
+        ("", line)
+    };
+    let (paragraph_indent, line) = if options.preserve_indentation {
+        line.split_at(line.len() - line.trim_start().len())
+    } else {
+        ("", line)
+    };
+    let line_start = line_start + quote.len() + paragraph_indent.len();
+    let first_indent = if first_line {
+        &*options.initial_indent
+    } else {
+        &*options.subsequent_indent
+    };
+    let first_indent = match paragraph_indent {
+        "" => Cow::from(first_indent),
+        _ => Cow::Owned([first_indent, paragraph_indent].concat()),
+    };
+    let hanging_len = match list_marker_len(line) {
+        Some(len) if options.hanging_list_indent => Some(len),
+        _ if options.auto_subsequent_indent => Some(label_len(line)),
+        _ => None,
+    };
+    let subsequent_indent = match hanging_len {
+        Some(len) => {
+            let width = options.text_width(&first_indent) + options.text_width(&line[..len]);
+            Cow::Owned(" ".repeat(width))
+        }
+        None if !paragraph_indent.is_empty() => {
+            Cow::Owned([&options.subsequent_indent, paragraph_indent].concat())
+        }
+        None => Cow::from(&*options.subsequent_indent),
+    };
+    let quote_width = options.text_width(quote);
+    let indent_width = |line_number, indent| {
+        let line_number = lines.len() + line_number;
+        let indent = options.indent_of(line_number, indent);
+        options
+            .width_of(line_number)
+            .saturating_sub(quote_width + options.text_width(&indent))
+    };
+    let initial_width = indent_width(0, &first_indent);
+    let subsequent_width = indent_width(1, &subsequent_indent);
+
+    let mut broken_words = Vec::new();
+    find_line_words(
+        line,
+        options,
+        initial_width,
+        subsequent_width,
+        &mut broken_words,
+    );
+    if !existing_breaks.is_empty() {
+        // The words are contiguous in `line`, so a word ends where
+        // the next word starts.
+        let mut offset = line_start;
+        for word in &mut broken_words {
+            offset += word.len() + word.whitespace.len();
+            word.existing_break = existing_breaks.binary_search(&offset).is_ok();
+        }
+    }
+    let mut line_widths = vec![initial_width, subsequent_width];
+    if options.line_indent.is_some() || options.line_widths.is_some() {
+        // Every line has its own width. There are at most as many
+        // lines as words.
+        line_widths.extend((2..broken_words.len()).map(|i| indent_width(i, "")));
+    }
+    let wrapped_words = options.wrap_algorithm.wrap(&broken_words, &line_widths);
+    let indents = [&*first_indent, &subsequent_indent];
+    push_wrapped_lines(
+        line,
+        line_start,
+        wrapped_words,
+        options,
+        quote,
+        indents,
+        lines,
+        spans,
+    );
+}
+
+/// Find the byte length of the list marker at the start of `line`,
+/// including the indentation before and the spaces after the marker.
+/// See [`Options::hanging_list_indent`].
+fn list_marker_len(line: &str) -> Option<usize> {
+    let rest = line.trim_start();
+    let mut chars = rest.chars();
+    let marker_len = match chars.next()? {
+        ch if matches!(ch, '-' | '*' | '+' | '•') => ch.len_utf8(),
+        ch if ch.is_ascii_alphabetic() && matches!(chars.next(), Some('.') | Some(')')) => 2,
+        _ => {
+            let digits = rest.len()
+                - rest
+                    .trim_start_matches(|ch: char| ch.is_ascii_digit())
+                    .len();
+            match rest[digits..].chars().next() {
+                Some('.') | Some(')') if digits > 0 => digits + 1,
+                _ => return None,
+            }
+        }
+    };
+    let after = &rest[marker_len..];
+    let spaces = after.len() - after.trim_start_matches(' ').len();
+    if spaces == 0 || spaces == after.len() {
+        return None;
+    }
+    Some(line.len() - after.len() + spaces)
+}
+
+/// Find the byte length of the indentation at the start of `line`,
+/// including the label and the spaces after it if there is one. See
+/// [`Options::auto_subsequent_indent`].
+fn label_len(line: &str) -> usize {
+    let rest = line.trim_start();
+    let indent = line.len() - rest.len();
+    let label_end = match rest.find(char::is_whitespace) {
+        Some(end) => end,
+        None => return indent,
+    };
+    let after = &rest[label_end..];
+    let text = after.trim_start_matches(' ');
+    if after.starts_with("  ") && !text.is_empty() {
+        line.len() - text.len()
+    } else {
+        indent
+    }
+}
+
+/// Turn the wrapped words of `line` into lines of text and push them
+/// onto `lines`. The `quote` is put in front of every line. The first
+/// line is indented with `indents[0]`, the other lines with
+/// `indents[1]`. The `line` starts at byte offset `line_start` in the
+/// source text, which is used for the `spans`.
+#[allow(clippy::too_many_arguments)]
+fn push_wrapped_lines<'t, 'w, 'x: 'w, A, R, S, I>(
+    line: &'t str,
+    line_start: usize,
+    wrapped_words: I,
+    options: &Options<'_, A, R, S>,
+    quote: &'t str,
+    indents: [&str; 2],
+    lines: &mut Vec<Cow<'t, str>>,
+    mut spans: Option<&mut Vec<LineSpan>>,
+) where
+    I: IntoIterator<Item = &'w [core::Word<'x>]>,
+{
+    let mut idx = 0;
+    let mut wrapped_words = wrapped_words.into_iter().enumerate().peekable();
+    while let Some((line_number, words)) = wrapped_words.next() {
+        let last_line = wrapped_words.peek().is_none();
+        let last_word = match words.last() {
+            None => {
+                let empty_line = quote.trim_end_matches(' ');
+                lines.push(Cow::from(empty_line));
+                if let Some(spans) = spans.as_deref_mut() {
+                    let start = line_start + idx;
+                    spans.push(LineSpan::new(
+                        start..start,
+                        empty_line.len(),
+                        LineBreak::Newline,
+                    ));
+                }
+                continue;
+            }
+            Some(word) => word,
+        };
+
+        // We assume here that all words are contiguous in `line`.
+        // That is, the sum of their lengths should add up to the
+        // length of `line`.
+        let len = words
+            .iter()
+            .map(|word| word.len() + word.whitespace.len())
+            .sum::<usize>()
+            - last_word.whitespace.len();
+
+        // The result is owned if we have indentation, otherwise we
+        // can simply borrow an empty string.
+        let indent = options.indent_of(lines.len(), indents[usize::from(line_number > 0)]);
+        let mut result = if !quote.is_empty() || !indent.is_empty() {
+            Cow::Owned([quote, &indent].concat())
+        } else {
+            // We can use an empty string here since string
+            // concatenation for `Cow` preserves a borrowed value
+            // when either side is empty.
+            Cow::from("")
+        };
+
+        // Keep the whitespace after the last word if we do not trim
+        // the lines.
+        let text = if options.trim == Trim::Neither && last_word.penalty.is_empty() {
+            &line[idx..idx + len + last_word.whitespace.len()]
+        } else {
+            &line[idx..idx + len]
+        };
+        let alignment = if last_line {
+            last_line_alignment(options.alignment)
+        } else {
+            options.alignment
+        };
+        let padding = match alignment {
+            Alignment::Left => 0,
+            _ => {
+                let words_width = words
+                    .iter()
+                    .map(|word| word.width() + word.whitespace_width())
+                    .sum::<f64>()
+                    - last_word.whitespace_width()
+                    + last_word.penalty_width()
+                    - last_word.hanging_width();
+                options
+                    .width_of(lines.len())
+                    .saturating_sub(options.text_width(&result) + words_width as usize)
+            }
+        };
+        // The padding is made of spaces, which need not be one unit
+        // wide with `Options::char_width`.
+        let padding = padding / std::cmp::max(options.text_width(" "), 1);
+        match alignment {
+            Alignment::Center => push_spaces(result.to_mut(), padding / 2),
+            Alignment::Right => push_spaces(result.to_mut(), padding),
+            _ => {}
+        }
+        let content_start = result.len();
+        let justified = match alignment {
+            Alignment::Justify(_) if padding > 0 => {
+                push_justified(result.to_mut(), words, padding, options.justification)
+            }
+            _ => false,
+        };
+        // Soft hyphens are invisible. If the line ends at a soft
+        // hyphen, the penalty shows a hyphen instead.
+        if !justified && text.contains(core::SOFT_HYPHEN) {
+            result
+                .to_mut()
+                .extend(text.chars().filter(|&ch| ch != core::SOFT_HYPHEN));
+        } else if !justified {
+            result += text;
+        }
+
+        if !last_word.penalty.is_empty() {
+            result.to_mut().push_str(last_word.penalty);
+        }
+
+        lines.push(result);
+
+        if let Some(spans) = spans.as_deref_mut() {
+            let line_break = if last_line {
+                LineBreak::Newline
+            } else if !last_word.whitespace.is_empty() {
+                LineBreak::Space
+            } else if last_word.is_broken_apart() {
+                LineBreak::Forced
+            } else {
+                LineBreak::Hyphenation
+            };
+            let start = line_start + idx;
+            spans.push(LineSpan::new(start..start + len, content_start, line_break));
+        }
+
+        // Advance by the length of `result`, plus the length of
+        // `last_word.whitespace` -- even if we had a penalty, we
+        // need to skip over the whitespace.
+        idx += len + last_word.whitespace.len();
+    }
+}
+
+/// Keep at most [`Options::max_lines`] of the wrapped `lines`. If
+/// lines are removed, the [`Options::placeholder`] is added to the
+/// last line, removing words from it until the placeholder fits. If
+/// no words are left, the placeholder is added to the line before,
+/// or it is put on a line of its own.
+fn truncate_lines<A, R, S>(
+    lines: &mut Vec<Cow<'_, str>>,
+    options: &Options<'_, A, R, S>,
+    mut spans: Option<&mut Vec<LineSpan>>,
+) {
+    let max_lines = match options.max_lines {
+        Some(max_lines) if lines.len() > max_lines => max_lines,
+        _ => return,
+    };
+    lines.truncate(max_lines);
+    if let Some(spans) = spans.as_deref_mut() {
+        spans.truncate(max_lines);
+    }
+    let last_line = match lines.pop() {
+        Some(line) => line,
+        None => return,
+    };
+
+    let placeholder_width = options.text_width(&options.placeholder);
+    let indent = options.indent_of(
+        lines.len(),
+        if lines.is_empty() {
+            &options.initial_indent
+        } else {
+            &options.subsequent_indent
+        },
+    );
+    // The lines are aligned again since the truncated line becomes
+    // the last line.
+    let (indent_len, content) = split_padding(&last_line, &indent, options);
+    let line_indent = &last_line[..indent_len];
+    let mut end = content.len();
+    loop {
+        let text = content[..end].trim_end_matches(' ');
+        if text.is_empty() {
+            break;
+        }
+        if options.text_width(line_indent) + options.text_width(text) + placeholder_width
+            <= options.width_of(lines.len())
+        {
+            let truncated = Cow::Owned([text, &options.placeholder].concat());
+            let (line, content_start) =
+                align_last_line(line_indent, truncated, lines.len(), options);
+            lines.push(line);
+            if let Some(span) = spans.and_then(|spans| spans.last_mut()) {
+                // Words removed from the end are no longer shown.
+                let removed = content.len() - text.len();
+                span.source.end = std::cmp::max(span.source.start, span.source.end - removed);
+                span.content_start = content_start;
+                span.line_break = LineBreak::Newline;
+            }
+            return;
+        }
+        end = text.rfind(' ').unwrap_or(0);
+    }
+
+    // The last line is dropped and the placeholder is put on the line
+    // before it or on a line of its own.
+    let span = spans.as_deref_mut().and_then(|spans| spans.pop());
+    let prev_number = lines.len().saturating_sub(1);
+    let prev_indent = options.indent_of(
+        prev_number,
+        if prev_number == 0 {
+            &options.initial_indent
+        } else {
+            &options.subsequent_indent
+        },
+    );
+    if let Some(prev_line) = lines.last_mut() {
+        let (indent_len, content) = split_padding(prev_line, &prev_indent, options);
+        let line_indent = &prev_line[..indent_len];
+        if options.text_width(line_indent) + options.text_width(content) + placeholder_width
+            <= options.width_of(prev_number)
+        {
+            let content = Cow::Owned([content, &options.placeholder].concat());
+            let (line, content_start) = align_last_line(line_indent, content, prev_number, options);
+            *prev_line = line;
+            if let Some(prev_span) = spans.and_then(|spans| spans.last_mut()) {
+                prev_span.content_start = content_start;
+                prev_span.line_break = LineBreak::Newline;
+            }
+            return;
+        }
+    }
+    let placeholder = Cow::from(options.placeholder.trim_start());
+    let (line, content_start) = align_last_line(&indent, placeholder, lines.len(), options);
+    lines.push(Cow::Owned(line.into_owned()));
+    if let (Some(spans), Some(span)) = (spans, span) {
+        let start = span.source.start;
+        spans.push(LineSpan::new(
+            start..start,
+            content_start,
+            LineBreak::Newline,
+        ));
+    }
+}
+
+/// The alignment used for the last line of a paragraph, see
+/// [`LastLine`].
+fn last_line_alignment(alignment: Alignment) -> Alignment {
+    match alignment {
+        Alignment::Justify(LastLine::Left) => Alignment::Left,
+        Alignment::Justify(LastLine::Center) => Alignment::Center,
+        Alignment::Justify(LastLine::Right) => Alignment::Right,
+        alignment => alignment,
+    }
+}
+
+/// Split a wrapped `line` into the byte length of its `indent` and
+/// its content without the padding added by [`Options::alignment`].
+fn split_padding<'l, A, R, S>(
+    line: &'l str,
+    indent: &str,
+    options: &Options<'_, A, R, S>,
+) -> (usize, &'l str) {
+    let indent_len = if line.starts_with(indent) {
+        indent.len()
+    } else {
+        0
+    };
+    let content = &line[indent_len..];
+    match options.alignment {
+        Alignment::Center
+        | Alignment::Right
+        | Alignment::Justify(LastLine::Center)
+        | Alignment::Justify(LastLine::Right) => (indent_len, content.trim_start_matches(' ')),
+        _ => (indent_len, content),
+    }
+}
+
+/// Put `content` after `indent`, padded as the last line of a
+/// paragraph according to [`Options::alignment`]. Returns the line
+/// and the byte offset of `content` in it.
+fn align_last_line<'l, A, R, S>(
+    indent: &str,
+    content: Cow<'l, str>,
+    line_number: usize,
+    options: &Options<'_, A, R, S>,
+) -> (Cow<'l, str>, usize) {
+    let padding = options
+        .width_of(line_number)
+        .saturating_sub(options.text_width(indent) + options.text_width(&content));
+    // The padding is made of spaces, see `push_wrapped_lines`.
+    let padding = padding / std::cmp::max(options.text_width(" "), 1);
+    let padding = match last_line_alignment(options.alignment) {
+        Alignment::Center => padding / 2,
+        Alignment::Right => padding,
+        _ => 0,
+    };
+    if indent.is_empty() && padding == 0 {
+        return (content, 0);
+    }
+    let mut line = String::with_capacity(indent.len() + padding + content.len());
+    line.push_str(indent);
+    push_spaces(&mut line, padding);
+    let content_start = line.len();
+    line.push_str(&content);
+    (Cow::Owned(line), content_start)
+}
+
+/// Truncate `line` to the line width and push it onto `lines`. This
+/// is used instead of wrapping for [`Overflow::Truncate`]. The line
+/// uses the initial indentation if `first_line` is set.
+fn push_truncated_line<'t, A, R, S>(
+    line: &'t str,
+    line_start: usize,
+    options: &Options<'_, A, R, S>,
+    first_line: bool,
+    lines: &mut Vec<Cow<'t, str>>,
+    spans: Option<&mut Vec<LineSpan>>,
+) {
+    let line = line.trim_end_matches(' ');
+    if line.is_empty() {
+        lines.push(Cow::from(""));
+        if let Some(spans) = spans {
+            spans.push(LineSpan::new(line_start..line_start, 0, LineBreak::Newline));
+        }
+        return;
+    }
+    let indent = options.indent_of(
+        lines.len(),
+        if first_line {
+            &options.initial_indent
+        } else {
+            &options.subsequent_indent
+        },
+    );
+    let width = options
+        .width_of(lines.len())
+        .saturating_sub(options.text_width(&indent));
+    let truncated = truncate_end(line, width, &options.placeholder, &options.measure());
+    let shown = match &truncated {
+        Cow::Borrowed(shown) => shown.len(),
+        Cow::Owned(shown) => shown.len() - options.placeholder.len(),
+    };
+    // Every line is the last line of its paragraph.
+    let (truncated, content_start) = align_last_line(&indent, truncated, lines.len(), options);
+    if let Some(spans) = spans {
+        spans.push(LineSpan::new(
+            line_start..line_start + shown,
+            content_start,
+            LineBreak::Newline,
+        ));
+    }
+    lines.push(truncated);
+}
+
+/// Append `count` spaces to `result`.
+fn push_spaces(result: &mut String, count: usize) {
+    result.extend(std::iter::repeat_n(' ', count));
+}
+
+/// Append the `words` of a line to `result` and distribute `padding`
+/// extra spaces according to `justification`. Returns `false` and
+/// leaves `result` untouched if the line has no place for the spaces.
+fn push_justified(
+    result: &mut String,
+    words: &[core::Word<'_>],
+    padding: usize,
+    justification: Justification,
+) -> bool {
+    // The characters of the line without the trailing whitespace.
+    let chars = || {
+        let last = words.len() - 1;
+        words
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, word)| {
+                let whitespace = if i < last { word.whitespace } else { "" };
+                word.chars().chain(whitespace.chars())
+            })
+            .filter(|&ch| ch != core::SOFT_HYPHEN)
+    };
+    // Can extra space be added before `ch`?
+    let is_gap = |ch: char| core::ch_width(ch) > 0 && !core::is_grapheme_extend(ch);
+
+    let inter_character = match justification {
+        Justification::InterWord => false,
+        Justification::InterCharacter => true,
+        Justification::Kashida => {
+            #[cfg(feature = "kashida")]
+            {
+                if kashida::push_kashida(result, &chars().collect::<Vec<_>>(), padding) {
+                    return true;
+                }
+            }
+            false
+        }
+        Justification::Auto => {
+            let visible = chars().filter(|ch| !ch.is_whitespace());
+            let (wide, total) = visible.fold((0, 0), |(wide, total), ch| {
+                (wide + usize::from(core::ch_width(ch) == 2), total + 1)
+            });
+            2 * wide > total
+        }
+    };
+
+    if inter_character {
+        let gaps = chars().skip(1).filter(|&ch| is_gap(ch)).count();
+        if gaps == 0 {
+            return false;
+        }
+        let mut gap = 0;
+        for (i, ch) in chars().enumerate() {
+            if i > 0 && is_gap(ch) {
+                push_spaces(result, padding / gaps + usize::from(gap < padding % gaps));
+                gap += 1;
+            }
+            result.push(ch);
+        }
+    } else {
+        let gaps = words[..words.len() - 1]
+            .iter()
+            .filter(|word| !word.whitespace.is_empty())
+            .count();
+        if gaps == 0 {
+            return false;
+        }
+        let mut gap = 0;
+        for (i, word) in words.iter().enumerate() {
+            result.extend(word.chars().filter(|&ch| ch != core::SOFT_HYPHEN));
+            if i + 1 < words.len() && !word.whitespace.is_empty() {
+                result.push_str(word.whitespace);
+                push_spaces(result, padding / gaps + usize::from(gap < padding % gaps));
+                gap += 1;
+            }
+        }
+    }
+    true
+}
+
+/// Find the words of a single line of text and prepare them for the
+/// wrapping algorithm.
+fn find_line_words<'a, A, R, S>(
+    line: &'a str,
+    options: &'a Options<'a, A, R, S>,
+    initial_width: usize,
+    subsequent_width: usize,
+    broken_words: &mut Vec<core::Word<'a>>,
+) where
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    broken_words.clear();
+    options.word_separator.find_words_into(line, broken_words);
+
+    // Words which are too wide for a line are broken into pieces one
+    // column wide. The wrapping algorithm only breaks between the
+    // pieces if the word cannot be moved to a line of its own, so the
+    // word is broken according to the width of the line it ends up
+    // on.
+    let narrowest_width = std::cmp::min(initial_width, subsequent_width);
+    let measure = options.measure();
+
+    // The words are split in place: the pieces are pushed after the
+    // words found above, which are then removed. This reuses the
+    // memory of `broken_words`.
+    let count = broken_words.len();
+    for idx in 0..count {
+        let word = broken_words[idx];
+        for mut word in core::split_words(Some(word), options) {
+            if !measure.is_default() {
+                word.measure(&measure);
+            }
+            if options.break_words && word.width() > narrowest_width as f64 {
+                let start = broken_words.len();
+                broken_words.extend(word.break_apart(1));
+                let end = broken_words.len() - 1;
+                for idx in start..end {
+                    // Prefer breaking after punctuation, but not in
+                    // the middle of a run of punctuation.
+                    if ends_with_break_punctuation(&broken_words[idx])
+                        && !starts_with_break_punctuation(&broken_words[idx + 1])
+                    {
+                        broken_words[idx].break_priority = core::BreakPriority::Punctuation;
+                    }
+                    broken_words[idx]
+                        .set_penalty(options.break_marker.forced, &options.break_marker);
+                }
+                if !measure.is_default() {
+                    for word in &mut broken_words[start..] {
+                        word.measure(&measure);
+                    }
+                }
+            } else {
+                broken_words.push(word);
+            }
+        }
+    }
+    broken_words.drain(..count);
+
+    if options.hanging_punctuation {
+        for word in broken_words.iter_mut() {
+            word.hang_punctuation(&measure);
+        }
+    }
+
+    if options.break_words && broken_words.is_empty() && !options.initial_indent.is_empty() {
+        // An empty zero-width word ensures that the indentation is
+        // applied to an empty line.
+        broken_words.push(core::Word::from(""));
+    }
+}
+
+/// Punctuation after which a word which is too long for a line is
+/// preferably broken.
+fn is_break_punctuation(ch: char) -> bool {
+    matches!(
+        ch,
+        '.' | ',' | ':' | ';' | '/' | '\\' | '_' | '=' | '&' | '?' | '-'
+    )
+}
+
+fn ends_with_break_punctuation(word: &str) -> bool {
+    word.chars().next_back().is_some_and(is_break_punctuation)
+}
+
+fn starts_with_break_punctuation(word: &str) -> bool {
+    word.chars().next().is_some_and(is_break_punctuation)
+}
+
+/// Measure the lines of wrapped text without building them.
+///
+/// This returns the display width of each line which [`wrap`] would
+/// return, including any indentation. The number of lines is the
+/// length of the returned vector. No strings are allocated for the
+/// lines, which makes this useful for layout engines which need to
+/// know the size of a text before rendering it.
 ///
-/// Notice how the trailing whitespace is removed in both case: in the
-/// first example, `"bar␣"` becomes `"bar"` and in the second case
-/// `"Foo␣␣␣"` becomes `"Foo"`.
+/// The `width_or_options` argument works like for [`wrap`].
 ///
-/// Leading whitespace is preserved when the following word fits on
-/// the first line. To understand this, consider how words are found
-/// in a text with leading spaces:
+/// # Examples
 ///
-/// ```text
-/// "␣␣foo␣bar" -> ["␣␣", "foo␣", "bar"]
 /// ```
+/// use textwrap::{measure, wrap, Options};
 ///
-/// When put into lines, the indentation is preserved if `"foo"` fits
-/// on the first line, otherwise you end up with an empty line:
-///
-/// ```
-/// use textwrap::wrap;
+/// let text = "Memory safety without garbage collection.";
+/// assert_eq!(measure(text, 15), vec![13, 15, 11]);
+/// assert_eq!(wrap(text, 15),
+///            vec!["Memory safety", "without garbage", "collection."]);
 ///
-/// assert_eq!(wrap("  foo bar", 8), vec!["  foo", "bar"]);
-/// assert_eq!(wrap("  foo bar", 4), vec!["", "foo", "bar"]);
+/// let options = Options::new(15).initial_indent("- ").subsequent_indent("  ");
+/// assert_eq!(measure(text, &options), vec![15, 9, 9, 13]);
 /// ```
-pub fn wrap<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> Vec<Cow<'_, str>>
+pub fn measure<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> Vec<usize>
 where
     A: wrap_algorithms::WrapAlgorithm,
     R: WordSeparator,
@@ -1060,83 +5113,108 @@ where
 {
     let options = width_or_options.into();
 
-    let initial_width = options
-        .width
-        .saturating_sub(core::display_width(options.initial_indent));
-    let subsequent_width = options
-        .width
-        .saturating_sub(core::display_width(options.subsequent_indent));
+    let initial_indent_width = options.text_width(&options.initial_indent);
+    let subsequent_indent_width = options.text_width(&options.subsequent_indent);
+    let initial_width = options.width.saturating_sub(initial_indent_width);
+    let subsequent_width = options.width.saturating_sub(subsequent_indent_width);
 
-    let mut lines = Vec::new();
+    let mut widths = Vec::new();
+    let mut words = Vec::new();
     for line in text.split('\n') {
-        let words = options.word_separator.find_words(line);
-        let split_words = core::split_words(words, &options);
-        let broken_words = if options.break_words {
-            let mut broken_words = core::break_words(split_words, subsequent_width);
-            if !options.initial_indent.is_empty() {
-                // Without this, the first word will always go into
-                // the first line. However, since we break words based
-                // on the _second_ line width, it can be wrong to
-                // unconditionally put the first word onto the first
-                // line. An empty zero-width word fixed this.
-                broken_words.insert(0, core::Word::from(""));
-            }
-            broken_words
-        } else {
-            split_words.collect::<Vec<_>>()
-        };
-
+        find_line_words(line, &options, initial_width, subsequent_width, &mut words);
         let line_widths = [initial_width, subsequent_width];
-        let wrapped_words = options.wrap_algorithm.wrap(&broken_words, &line_widths);
-
-        let mut idx = 0;
-        for words in wrapped_words {
+        for words in options.wrap_algorithm.wrap(&words, &line_widths) {
             let last_word = match words.last() {
                 None => {
-                    lines.push(Cow::from(""));
+                    widths.push(0);
                     continue;
                 }
                 Some(word) => word,
             };
 
-            // We assume here that all words are contiguous in `line`.
-            // That is, the sum of their lengths should add up to the
-            // length of `line`.
-            let len = words
-                .iter()
-                .map(|word| word.len() + word.whitespace.len())
-                .sum::<usize>()
-                - last_word.whitespace.len();
-
-            // The result is owned if we have indentation, otherwise
-            // we can simply borrow an empty string.
-            let mut result = if lines.is_empty() && !options.initial_indent.is_empty() {
-                Cow::Owned(options.initial_indent.to_owned())
-            } else if !lines.is_empty() && !options.subsequent_indent.is_empty() {
-                Cow::Owned(options.subsequent_indent.to_owned())
+            let indent_width = if widths.is_empty() {
+                initial_indent_width
             } else {
-                // We can use an empty string here since string
-                // concatenation for `Cow` preserves a borrowed value
-                // when either side is empty.
-                Cow::from("")
+                subsequent_indent_width
             };
+            let words_width = words
+                .iter()
+                .map(|word| word.width() + word.whitespace_width())
+                .sum::<f64>()
+                - last_word.whitespace_width()
+                + options.text_width(last_word.penalty) as f64;
+            widths.push(indent_width + words_width as usize);
+        }
+    }
 
-            result += &line[idx..idx + len];
-
-            if !last_word.penalty.is_empty() {
-                result.to_mut().push_str(&last_word.penalty);
-            }
+    widths
+}
 
-            lines.push(result);
+/// Find the smallest width at which text wraps into at most
+/// `max_lines` lines.
+///
+/// The width is found with a binary search between zero and the
+/// width given by `width_or_options`, which can be a [`Options`]
+/// value if you want to customize the wrapping. The text is measured
+/// with [`measure`] for each width tried, so the returned width
+/// includes any indentation.
+///
+/// The text fits if it wraps into at most `max_lines` lines and no
+/// line is wider than the width. This matters when
+/// [`Options::break_words`] is `false`, since long words then stick
+/// out into the margin. Returns `None` if the text does not fit even
+/// at the given width.
+///
+/// This is useful for sizing popups and tooltips to fit their
+/// content. Note that the binary search assumes that wrapping text
+/// at a larger width never gives more lines. This is true for the
+/// first-fit algorithm, but the optimal-fit algorithm can in rare
+/// cases use an extra line to avoid short lines. The text is still
+/// guaranteed to fit at the returned width.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{fit_width, Options};
+///
+/// let text = "Press any key to continue";
+/// assert_eq!(fit_width(text, 1, 80), Some(25));
+/// assert_eq!(fit_width(text, 2, 80), Some(13));
+/// assert_eq!(fit_width(text, 1, 20), None);
+///
+/// let options = Options::new(80).initial_indent("> ").subsequent_indent("> ");
+/// assert_eq!(fit_width(text, 2, &options), Some(15));
+/// ```
+pub fn fit_width<'a, A, R, S, Opt>(
+    text: &str,
+    max_lines: usize,
+    width_or_options: Opt,
+) -> Option<usize>
+where
+    A: wrap_algorithms::WrapAlgorithm + Clone,
+    R: WordSeparator + Clone,
+    S: WordSplitter + Clone,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let mut options = width_or_options.into();
+    let fits = |options: &Options<'a, A, R, S>| {
+        let widths = measure(text, options);
+        widths.len() <= max_lines && widths.iter().all(|&width| width <= options.width)
+    };
+    if !fits(&options) {
+        return None;
+    }
 
-            // Advance by the length of `result`, plus the length of
-            // `last_word.whitespace` -- even if we had a penalty, we
-            // need to skip over the whitespace.
-            idx += len + last_word.whitespace.len();
+    let (mut low, mut high) = (0, options.width);
+    while low < high {
+        options.width = low + (high - low) / 2;
+        if fits(&options) {
+            high = options.width;
+        } else {
+            low = options.width + 1;
         }
     }
-
-    lines
+    Some(high)
 }
 
 /// Wrap text into columns with a given total width.
@@ -1182,7 +5260,7 @@ where
 /// This is an example text, which is wrapped into three columns. \
 /// Notice how the final column can be shorter than the others.";
 ///
-/// #[cfg(feature = "smawk")]
+/// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
 /// assert_eq!(wrap_columns(text, 3, 50, "| ", " | ", " |"),
 ///            vec!["| This is       | into three    | column can be  |",
 ///                 "| an example    | columns.      | shorter than   |",
@@ -1190,7 +5268,7 @@ where
 ///                 "| is wrapped    | the final     |                |"]);
 ///
 /// // Without the `smawk` feature, the middle column is a little more uneven:
-/// #[cfg(not(feature = "smawk"))]
+/// #[cfg(not(any(feature = "smawk", feature = "optimal-fit-dp")))]
 /// assert_eq!(wrap_columns(text, 3, 50, "| ", " | ", " |"),
 ///            vec!["| This is an    | three         | column can be  |",
 ///                 "| example text, | columns.      | shorter than   |",
@@ -1216,14 +5294,14 @@ where
 
     let inner_width = options
         .width
-        .saturating_sub(core::display_width(left_gap))
-        .saturating_sub(core::display_width(right_gap))
-        .saturating_sub(core::display_width(middle_gap) * (columns - 1));
+        .saturating_sub(options.text_width(left_gap))
+        .saturating_sub(options.text_width(right_gap))
+        .saturating_sub(options.text_width(middle_gap) * (columns - 1));
 
     let column_width = std::cmp::max(inner_width / columns, 1);
     options.width = column_width;
     let last_column_padding = " ".repeat(inner_width % column_width);
-    let wrapped_lines = wrap(text, options);
+    let wrapped_lines = wrap_with_breaks(text, &options, &[]);
     let lines_per_column =
         wrapped_lines.len() / columns + usize::from(wrapped_lines.len() % columns > 0);
     let mut lines = Vec::new();
@@ -1232,8 +5310,10 @@ where
         for column_no in 0..columns {
             match wrapped_lines.get(line_no + column_no * lines_per_column) {
                 Some(column_line) => {
-                    line.push_str(&column_line);
-                    line.push_str(&" ".repeat(column_width - core::display_width(&column_line)));
+                    line.push_str(column_line);
+                    line.push_str(
+                        &" ".repeat(column_width.saturating_sub(options.text_width(column_line))),
+                    );
                 }
                 None => {
                     line.push_str(&" ".repeat(column_width));
@@ -1272,19 +5352,50 @@ where
 /// # let width = 80;
 /// Options {
 ///     width: width,
-///     initial_indent: "",
-///     subsequent_indent: "",
+///     initial_indent: "".into(),
+///     subsequent_indent: "".into(),
 ///     break_words: false,
 ///     word_separator: AsciiSpace,
 ///     wrap_algorithm: wrap_algorithms::FirstFit,
 ///     splitter: NoHyphenation,
+///     preserve_breaks: false,
+///     sentence_end_double_space: false,
+///     break_marker: textwrap::BreakMarker::new(),
+///     alignment: textwrap::Alignment::Left,
+///     justification: textwrap::Justification::Auto,
+///     hanging_punctuation: false,
+///     max_lines: None,
+///     placeholder: "…".into(),
+///     overflow: textwrap::Overflow::Wrap,
+///     line_ending: textwrap::LineEnding::Lf,
+///     unicode_line_breaks: false,
+///     whitespace: textwrap::Whitespace::Preserve,
+///     trim: textwrap::Trim::Trailing,
+///     detect_quotes: false,
+///     semantic_line_breaks: false,
+///     hanging_list_indent: false,
+///     line_indent: None,
+///     preserve_indentation: false,
+///     auto_subsequent_indent: false,
+///     paragraphs: textwrap::Paragraphs::Whole,
+///     preformatted: textwrap::Preformatted::Never,
+///     join_hyphens: false,
+///     keep_fitting: false,
+///     line_widths: None,
+///     line_hook: None,
+///     reopen_styles: false,
+///     char_width: None,
+///     ambiguous_width: textwrap::AmbiguousWidth::Narrow,
+///     grapheme_width: textwrap::GraphemeWidth::Characters,
+///     control_chars: textwrap::ControlChars::Keep,
 /// };
 /// ```
 ///
 /// The wrap algorithm is [`wrap_algorithms::FirstFit`] since this
 /// is the fastest algorithm — and the main reason to use
 /// `fill_inplace` is to get the string broken into newlines as fast
-/// as possible.
+/// as possible. Use [`fill_inplace_with`] to fill the text in-place
+/// with another wrapping algorithm or word separator.
 ///
 /// A last difference is that (unlike [`fill`]) `fill_inplace` can
 /// leave trailing whitespace on lines. This is because we wrap by
@@ -1307,12 +5418,49 @@ where
 /// benchmark](https://github.com/mgeisler/textwrap/blob/master/benches/linear.rs)
 /// for details.
 pub fn fill_inplace(text: &mut String, width: usize) {
+    let options = Options::new(width)
+        .word_separator(AsciiSpace)
+        .wrap_algorithm(wrap_algorithms::FirstFit);
+    fill_inplace_with(text, &options);
+}
+
+/// Fill `text` in-place with the given options.
+///
+/// This works like [`fill_inplace`], but uses the
+/// [`Options::width`], [`Options::wrap_algorithm`], and
+/// [`Options::word_separator`] of `options` instead of the greedy
+/// algorithm and [`AsciiSpace`]. The other options are ignored, since
+/// they would need to change the text itself.
+///
+/// Lines can only be broken at `' '` characters. If the word
+/// separator finds a break opportunity without whitespace, such as
+/// after the hyphen in `"well-chosen"`, the words on both sides of
+/// it are kept together.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))] {
+/// use textwrap::{fill_inplace_with, wrap_algorithms, Options};
+///
+/// let mut text = String::from("To be, or not to be: that is the question");
+/// let options = Options::new(10).wrap_algorithm(wrap_algorithms::OptimalFit::new());
+/// fill_inplace_with(&mut text, &options);
+/// assert_eq!(text, "To be,\nor not to\nbe: that\nis the\nquestion");
+/// # }
+/// ```
+pub fn fill_inplace_with<A, R, S>(text: &mut String, options: &Options<'_, A, R, S>)
+where
+    A: wrap_algorithms::WrapAlgorithm,
+    R: WordSeparator,
+{
     let mut indices = Vec::new();
+    let line_widths = [options.width];
 
     let mut offset = 0;
     for line in text.split('\n') {
-        let words = AsciiSpace.find_words(line).collect::<Vec<_>>();
-        let wrapped_words = wrap_algorithms::wrap_first_fit(&words, &[width]);
+        let words = inplace_words(line, &options.word_separator);
+        let wrapped_words = options.wrap_algorithm.wrap(&words, &line_widths);
 
         let mut line_offset = offset;
         for words in &wrapped_words[..wrapped_words.len() - 1] {
@@ -1339,6 +5487,27 @@ pub fn fill_inplace(text: &mut String, width: usize) {
     *text = String::from_utf8(bytes).unwrap();
 }
 
+/// Find the words of `line` for [`fill_inplace_with`]. Words which
+/// are not followed by a `' '` are joined with the next word, since
+/// the line cannot be broken between them.
+fn inplace_words<'a, R: WordSeparator>(line: &'a str, word_separator: &R) -> Vec<core::Word<'a>> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    for word in word_separator.find_words(line) {
+        let end = word.word.as_ptr() as usize - line.as_ptr() as usize
+            + word.word.len()
+            + word.whitespace.len();
+        if word.whitespace.ends_with(' ') || end == line.len() {
+            words.push(core::Word::from(&line[start..end]));
+            start = end;
+        }
+    }
+    if start < line.len() || words.is_empty() {
+        words.push(core::Word::from(&line[start..]));
+    }
+    words
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1510,12 +5679,22 @@ mod tests {
 
     #[test]
     fn initial_indent_break_words() {
-        // This is a corner-case showing how the long word is broken
-        // according to the width of the subsequent lines. The first
-        // fragment of the word no longer fits on the first line,
-        // which ends up being pure indentation.
+        // The long word is broken according to the width of each
+        // line, so the first fragment is shorter than the rest.
         let options = Options::new(5).initial_indent("-->");
-        assert_eq!(wrap("foobarbaz", &options), vec!["-->", "fooba", "rbaz"]);
+        assert_eq!(wrap("foobarbaz", &options), vec!["-->fo", "obarb", "az"]);
+    }
+
+    #[test]
+    fn break_words_prefers_whitespace() {
+        assert_eq!(wrap("foo barbazquux", 6), vec!["foo", "barbaz", "quux"]);
+        assert_eq!(
+            wrap(
+                "foo barbazquux",
+                Options::new(6).wrap_algorithm(wrap_algorithms::FirstFit)
+            ),
+            vec!["foo", "barbaz", "quux"]
+        );
     }
 
     #[test]
@@ -1814,7 +5993,196 @@ mod tests {
     }
 
     #[test]
-    #[cfg(not(feature = "smawk"))]
+    fn wrap_reopen_styles() {
+        let text = "\u{1b}[1mfoo \u{1b}[31mbar baz\u{1b}[0m qux\n\n\u{1b}[32mquux";
+        let options = Options::new(7)
+            .initial_indent("> ")
+            .subsequent_indent("> ")
+            .reopen_styles(true);
+        let lines = wrap(text, &options);
+        assert_eq!(
+            lines,
+            vec![
+                "> \u{1b}[1mfoo\u{1b}[0m",
+                "> \u{1b}[1m\u{1b}[31mbar\u{1b}[0m",
+                "> \u{1b}[1m\u{1b}[31mbaz\u{1b}[0m",
+                "> qux",
+                "> ",
+                "> \u{1b}[32mquux\u{1b}[0m",
+            ]
+        );
+        assert_eq!(wrap_iter(text, &options).collect::<Vec<_>>(), lines);
+        let mut wrapper = Wrapper::new(options.clone());
+        let mut wrapper_lines = Vec::new();
+        wrapper.wrap_into(text, &mut wrapper_lines);
+        assert_eq!(wrapper_lines, lines);
+
+        // The content of a line starts after the reopened styles.
+        let wrapped = WrappedText::new(text, &options);
+        let line = &wrapped.lines[1];
+        assert_eq!(&line.text[line.content_start..], "\u{1b}[31mbar\u{1b}[0m");
+        assert_eq!(&text[line.source.clone()], "\u{1b}[31mbar");
+    }
+
+    #[test]
+    fn wrap_hyperlinks() {
+        let open = "\u{1b}]8;;https://example.org/docs\u{1b}\\";
+        let close = "\u{1b}]8;;\u{1b}\\";
+        let text = format!("See the {}online documentation{} for details.", open, close);
+        let options = Options::new(16).wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(
+            wrap(&text, &options),
+            vec![
+                format!("See the {}online", open),
+                format!("documentation{}", close),
+                String::from("for details."),
+            ]
+        );
+        assert_eq!(
+            wrap(&text, &options.reopen_styles(true)),
+            vec![
+                format!("See the {}online{}", open, close),
+                format!("{}documentation{}", open, close),
+                String::from("for details."),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_ambiguous_width() {
+        let options = Options::new(8)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .initial_indent("§ ")
+            .ambiguous_width(AmbiguousWidth::Wide);
+        assert_eq!(wrap("αβ γδ", &options), vec!["§ αβ", "γδ"]);
+        assert_eq!(measure("αβ γδ", &options), vec![7, 4]);
+        assert_eq!(wrap("αβγδεζ", &options), vec!["§ αβ", "γδεζ"]);
+        // The ellipsis is also ambiguous.
+        assert_eq!(
+            wrap("αβγδ εζ", &options.clone().max_lines(1).placeholder("…")),
+            vec!["§ …"]
+        );
+        assert_eq!(
+            wrap("αβγδ εζ", &options.clone().max_lines(1).placeholder("~")),
+            vec!["§ αβ~"]
+        );
+        assert_eq!(
+            wrap(
+                "αβγδεζ",
+                &options.overflow(Overflow::Truncate).placeholder(".")
+            ),
+            vec!["§ αβ."]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "kashida")]
+    fn justify_kashida() {
+        let options = Options::new(12)
+            .alignment(Alignment::Justify(LastLine::Left))
+            .justification(Justification::Kashida);
+        // Lam followed by alef is a ligature, and the padding goes to
+        // the last join point of each word first.
+        assert_eq!(wrap("لا بيت بيت foo", &options), vec!["لا بيـت بيـت", "foo"]);
+        // Lines without Arabic letters get extra space between words.
+        assert_eq!(
+            wrap("foo bar baz qux", &options),
+            vec!["foo  bar baz", "qux"]
+        );
+    }
+
+    #[test]
+    fn wrap_control_chars() {
+        let text = "a\x00b \x1b]8;;http://x\x07c\x1b]8;;\x07 d\u{90}e\x7f";
+        let options = Options::new(3).wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(core::display_width(text), 7);
+        assert_eq!(
+            wrap(text, &options),
+            vec![
+                "a\x00b",
+                "\x1b]8;;http://x\x07c\x1b]8;;\x07",
+                "d\u{90}e\x7f"
+            ]
+        );
+
+        let options = options.control_chars(ControlChars::Strip);
+        assert_eq!(
+            wrap(text, &options),
+            vec!["ab", "\x1b]8;;http://x\x07c\x1b]8;;\x07", "de"]
+        );
+        let mut wrapper = Wrapper::new(options.clone());
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, wrap(text, &options));
+
+        let options = Options::new(20).control_chars(ControlChars::Replace);
+        assert_eq!(
+            wrap(text, &options),
+            vec!["a\u{2400}b \x1b]8;;http://x\x07c\x1b]8;;\x07 d\u{fffd}e\u{2421}"]
+        );
+        assert_eq!(
+            wrap("\t\x1b[1mx\x1b[0m", &options),
+            vec!["\t\x1b[1mx\x1b[0m"]
+        );
+    }
+
+    #[test]
+    fn wrap_grapheme_width() {
+        let flags = "\u{1f1e9}\u{1f1f0}\u{1f1eb}\u{1f1f7}\u{1f1e9}\u{1f1ea}\u{1f1f8}\u{1f1ea}";
+        let options = Options::new(6)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .word_separator(AsciiSpace)
+            .grapheme_width(GraphemeWidth::Clusters);
+        assert_eq!(wrap(flags, &options), vec![&flags[..24], &flags[24..]]);
+        assert_eq!(
+            wrap(
+                flags,
+                &options.overflow(Overflow::Truncate).placeholder("~")
+            ),
+            vec![format!("{}~", &flags[..16])]
+        );
+    }
+
+    #[test]
+    fn wrap_char_width() {
+        // Every character is two units wide.
+        let options = Options::new(16)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .char_width(FontMetrics::new(|_| 2));
+        assert_eq!(wrap("foo bar baz", &options), vec!["foo bar", "baz"]);
+        assert_eq!(wrap("abcdefghij", &options), vec!["abcdefgh", "ij"]);
+        assert_eq!(measure("foo bar baz", &options), vec![14, 6]);
+        assert_eq!(
+            wrap("foo bar baz", &options.clone().alignment(Alignment::Right)),
+            vec![" foo bar", "     baz"]
+        );
+        assert_eq!(
+            wrap(
+                "foo bar baz",
+                &options.clone().max_lines(1).placeholder("...")
+            ),
+            vec!["foo..."]
+        );
+        assert_eq!(
+            wrap(
+                "foo bar baz",
+                &options
+                    .clone()
+                    .overflow(Overflow::Truncate)
+                    .placeholder("~")
+            ),
+            vec!["foo bar~"]
+        );
+        let mut wrapper = Wrapper::new(options.clone());
+        let mut lines = Vec::new();
+        wrapper.wrap_into("foo bar baz", &mut lines);
+        assert_eq!(lines, vec!["foo bar", "baz"]);
+        let wrapped = WrappedText::new("foo bar baz", &options);
+        assert_eq!(wrapped.lines[0].width, 14);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "smawk", feature = "optimal-fit-dp")))]
     #[cfg(not(feature = "unicode-linebreak"))]
     fn cloning_works() {
         static OPT: Options<wrap_algorithms::FirstFit, AsciiSpace, HyphenSplitter> =
@@ -1845,55 +6213,217 @@ mod tests {
         assert_eq!(text, "Some text to\nwrap over\nmultiple\nlines");
     }
 
-    #[test]
-    fn fill_inplace_long_word() {
-        let mut text = String::from("Internationalization is hard");
-        fill_inplace(&mut text, 10);
-        assert_eq!(text, "Internationalization\nis hard");
+    #[test]
+    fn fill_inplace_long_word() {
+        let mut text = String::from("Internationalization is hard");
+        fill_inplace(&mut text, 10);
+        assert_eq!(text, "Internationalization\nis hard");
+    }
+
+    #[test]
+    fn fill_inplace_no_hyphen_splitting() {
+        let mut text = String::from("A well-chosen example");
+        fill_inplace(&mut text, 10);
+        assert_eq!(text, "A\nwell-chosen\nexample");
+    }
+
+    #[test]
+    fn fill_inplace_newlines() {
+        let mut text = String::from("foo bar\n\nbaz\n\n\n");
+        fill_inplace(&mut text, 10);
+        assert_eq!(text, "foo bar\n\nbaz\n\n\n");
+    }
+
+    #[test]
+    fn fill_inplace_newlines_reset_line_width() {
+        let mut text = String::from("1 3 5\n1 3 5 7 9\n1 3 5 7 9 1 3");
+        fill_inplace(&mut text, 10);
+        assert_eq!(text, "1 3 5\n1 3 5 7 9\n1 3 5 7 9\n1 3");
+    }
+
+    #[test]
+    fn fill_inplace_leading_whitespace() {
+        let mut text = String::from("  foo bar baz");
+        fill_inplace(&mut text, 10);
+        assert_eq!(text, "  foo bar\nbaz");
+    }
+
+    #[test]
+    fn fill_inplace_trailing_whitespace() {
+        let mut text = String::from("foo bar baz  ");
+        fill_inplace(&mut text, 10);
+        assert_eq!(text, "foo bar\nbaz  ");
+    }
+
+    #[test]
+    fn fill_inplace_interior_whitespace() {
+        // To avoid an unwanted indentation of "baz", it is important
+        // to replace the final ' ' with '\n'.
+        let mut text = String::from("foo  bar    baz");
+        fill_inplace(&mut text, 10);
+        assert_eq!(text, "foo  bar   \nbaz");
+    }
+
+    #[test]
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn fill_inplace_with_optimal_fit() {
+        let mut text = String::from("To be, or not to be: that is the question\n\nfoo");
+        let options = Options::new(10).wrap_algorithm(wrap_algorithms::OptimalFit::new());
+        fill_inplace_with(&mut text, &options);
+        assert_eq!(text, "To be,\nor not to\nbe: that\nis the\nquestion\n\nfoo");
+    }
+
+    #[test]
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn optimal_fit_with_broken_word_is_linear() {
+        // A single word broken apart must not make every line
+        // consider every earlier fragment as its start.
+        let long_word = "x".repeat(200);
+        let mut words = vec![core::Word::from("foo "); 1000];
+        words.extend(core::Word::from(long_word.as_str()).break_apart(1));
+        words.extend(vec![core::Word::from("bar "); 1000]);
+
+        let mut penalties = wrap_algorithms::OptimalFit::new();
+        penalties.budget = Some(100 * words.len());
+        let lines = wrap_algorithms::try_wrap_optimal_fit(&words, &[80.0], &penalties).unwrap();
+        assert!(lines.iter().all(|line| line.len() <= 80));
+    }
+
+    /// Fragment with the properties which make the optimal-fit
+    /// algorithm leave the SMAWK algorithm.
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    #[derive(Debug, Default)]
+    struct Item {
+        width: f64,
+        forced: bool,
+        keep: bool,
+        hyphen: bool,
+    }
+
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    impl core::Fragment for Item {
+        fn width(&self) -> f64 {
+            self.width
+        }
+
+        fn whitespace_width(&self) -> f64 {
+            if self.hyphen {
+                0.0
+            } else {
+                1.0
+            }
+        }
+
+        fn penalty_width(&self) -> f64 {
+            if self.hyphen {
+                1.0
+            } else {
+                0.0
+            }
+        }
+
+        fn forced_break(&self) -> bool {
+            self.forced
+        }
+
+        fn keep_with_next(&self) -> bool {
+            self.keep
+        }
+    }
+
+    /// Check that the optimal-fit algorithm finds the same line
+    /// breaks as when all line starts are tried, for pseudo-random
+    /// items made by `item` from random numbers.
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn assert_optimal_fit_is_exact<F>(penalties: wrap_algorithms::OptimalFit, item: F)
+    where
+        F: Fn(u64) -> Item,
+    {
+        let mut seed = 42u64;
+        let mut random = || {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            seed >> 33
+        };
+        for _ in 0..1000 {
+            let count = random() % 30 + 1;
+            let line_width = (random() % 20 + 3) as f64;
+            let items = (0..count).map(|_| item(random())).collect::<Vec<_>>();
+            let mut exact = penalties;
+            exact.lookback = Some(items.len());
+            assert_eq!(
+                wrap_algorithms::wrap_optimal_fit_indices(&items, &[line_width], &penalties),
+                wrap_algorithms::wrap_optimal_fit_indices(&items, &[line_width], &exact),
+                "{:?} with line width {}",
+                items,
+                line_width
+            );
+        }
     }
 
     #[test]
-    fn fill_inplace_no_hyphen_splitting() {
-        let mut text = String::from("A well-chosen example");
-        fill_inplace(&mut text, 10);
-        assert_eq!(text, "A\nwell-chosen\nexample");
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn optimal_fit_with_forced_breaks_is_exact() {
+        assert_optimal_fit_is_exact(wrap_algorithms::OptimalFit::new(), |random| Item {
+            width: (random % 6 + 1) as f64,
+            forced: random % 7 == 0,
+            ..Item::default()
+        });
     }
 
     #[test]
-    fn fill_inplace_newlines() {
-        let mut text = String::from("foo bar\n\nbaz\n\n\n");
-        fill_inplace(&mut text, 10);
-        assert_eq!(text, "foo bar\n\nbaz\n\n\n");
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn optimal_fit_with_kept_fragments_is_exact() {
+        assert_optimal_fit_is_exact(wrap_algorithms::OptimalFit::new(), |random| Item {
+            width: (random % 6 + 1) as f64,
+            keep: random % 5 == 0,
+            ..Item::default()
+        });
     }
 
     #[test]
-    fn fill_inplace_newlines_reset_line_width() {
-        let mut text = String::from("1 3 5\n1 3 5 7 9\n1 3 5 7 9 1 3");
-        fill_inplace(&mut text, 10);
-        assert_eq!(text, "1 3 5\n1 3 5 7 9\n1 3 5 7 9\n1 3");
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn optimal_fit_with_orphans_is_exact() {
+        let mut penalties = wrap_algorithms::OptimalFit::new();
+        penalties.orphan_min_fragments = 5;
+        assert_optimal_fit_is_exact(penalties, |random| Item {
+            width: (random % 3 + 1) as f64,
+            ..Item::default()
+        });
     }
 
     #[test]
-    fn fill_inplace_leading_whitespace() {
-        let mut text = String::from("  foo bar baz");
-        fill_inplace(&mut text, 10);
-        assert_eq!(text, "  foo bar\nbaz");
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn optimal_fit_with_double_hyphens_is_exact() {
+        let mut penalties = wrap_algorithms::OptimalFit::new();
+        penalties.double_hyphen_penalty = 3000;
+        assert_optimal_fit_is_exact(penalties, |random| Item {
+            width: (random % 6 + 1) as f64,
+            hyphen: random / 8 % 3 == 0,
+            ..Item::default()
+        });
     }
 
     #[test]
-    fn fill_inplace_trailing_whitespace() {
-        let mut text = String::from("foo bar baz  ");
-        fill_inplace(&mut text, 10);
-        assert_eq!(text, "foo bar\nbaz  ");
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn optimal_fit_with_adjacent_fitness_is_exact() {
+        let mut penalties = wrap_algorithms::OptimalFit::new();
+        penalties.adjacent_fitness_penalty = 3000;
+        assert_optimal_fit_is_exact(penalties, |random| Item {
+            width: (random % 6 + 1) as f64,
+            ..Item::default()
+        });
     }
 
     #[test]
-    fn fill_inplace_interior_whitespace() {
-        // To avoid an unwanted indentation of "baz", it is important
-        // to replace the final ' ' with '\n'.
-        let mut text = String::from("foo  bar    baz");
-        fill_inplace(&mut text, 10);
-        assert_eq!(text, "foo  bar   \nbaz");
+    #[cfg(feature = "unicode-linebreak")]
+    fn fill_inplace_with_unicode_break_properties() {
+        // The line cannot be broken after the hyphens.
+        let mut text = String::from("A well-chosen long-winded example");
+        let options = Options::new(6).word_separator(UnicodeBreakProperties);
+        fill_inplace_with(&mut text, &options);
+        assert_eq!(text, "A\nwell-chosen\nlong-winded\nexample");
     }
 
     #[test]
@@ -1959,6 +6489,31 @@ mod tests {
         assert_eq!(unfill("foo   bar").0, "foo   bar");
     }
 
+    #[test]
+    fn unfill_hyphenated_words() {
+        assert_eq!(unfill("foo-\nbar").0, "foo- bar");
+        assert_eq!(unfill_hyphenated("foo-\nbar").0, "foobar");
+        assert_eq!(unfill_hyphenated("foo -\nbar").0, "foo - bar");
+        assert_eq!(unfill_hyphenated("2-\nbar").0, "2- bar");
+        assert_eq!(unfill_hyphenated("foo-\nBar").0, "foo- Bar");
+        assert_eq!(unfill_hyphenated("- foo-\n  bar").0, "foobar");
+    }
+
+    #[test]
+    fn refill_join_hyphens_roundtrip() {
+        let text = "Hyphen\u{ad}ation splits long words.";
+        let filled = fill(text, 10);
+        assert_eq!(filled, "Hyphen-\nation\nsplits\nlong\nwords.");
+        assert_eq!(
+            unfill_hyphenated(&filled).0,
+            "Hyphenation splits long words."
+        );
+        assert_eq!(
+            refill(&filled, Options::new(40).join_hyphens(true)),
+            "Hyphenation splits long words."
+        );
+    }
+
     #[test]
     fn trait_object_vec() {
         // Create a vector of Options containing trait-objects.
@@ -2070,4 +6625,688 @@ mod tests {
     fn wrap_columns_panic_with_zero_columns() {
         wrap_columns("", 0, 10, "", "", "");
     }
+
+    #[test]
+    fn measure_matches_wrap() {
+        let text = "foo-bar  baz\n\n\u{1b}[1mbold\u{1b}[0m   words wrapped-in-a-line";
+        for width in 0..20 {
+            let options = Options::new(width)
+                .initial_indent("* ")
+                .subsequent_indent("  ");
+            let wrapped = wrap(text, &options)
+                .iter()
+                .map(|line| core::display_width(line))
+                .collect::<Vec<_>>();
+            assert_eq!(measure(text, &options), wrapped, "width: {}", width);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn wrap_par_matches_wrap() {
+        let text = "foo-bar  baz\n\nsome words which\nare wrapped in parallel\n";
+        for width in 0..20 {
+            let options = Options::new(width)
+                .initial_indent("* ")
+                .subsequent_indent("  ");
+            assert_eq!(wrap_par(text, &options), wrap(text, &options));
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    fn refill_preserve_breaks() {
+        let text = "Memory safety without\ngarbage collection.\n";
+        let options = Options::new(20).preserve_breaks(true);
+        assert_eq!(
+            refill(text, options),
+            "Memory safety\nwithout\ngarbage collection.\n"
+        );
+    }
+
+    #[test]
+    fn refill_keep_fitting() {
+        let text = "# Memory safety\n# without GC.\n\n\
+                    > Concurrency without data races.\r\n\
+                    > Zero-cost.\n";
+        let options = Options::new(20)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .paragraphs(Paragraphs::BlankLines)
+            .keep_fitting(true);
+        assert_eq!(
+            refill(text, &options),
+            "# Memory safety\n# without GC.\n\n\
+             > Concurrency\n> without data\n> races. Zero-cost.\n"
+        );
+        // Every paragraph fits, so nothing changes.
+        assert_eq!(
+            refill("foo\nbar baz\n", Options::new(7).keep_fitting(true)),
+            "foo\nbar baz\n"
+        );
+    }
+
+    #[test]
+    fn wrap_soft_hyphens() {
+        let options = Options::new(12).wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(
+            wrap("Super\u{ad}cali\u{ad}fragilistic", &options),
+            vec!["Supercali-", "fragilistic"]
+        );
+        assert_eq!(wrap("Super\u{ad}cali", &options), vec!["Supercali"]);
+        // Soft hyphens are only used when there is no other break.
+        let options = Options::new(8).wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(wrap("a soft\u{ad}ware", &options), vec!["a", "software"]);
+    }
+
+    #[test]
+    fn wrap_justify() {
+        let options = Options::new(12)
+            .subsequent_indent("  ")
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .alignment(Alignment::Justify(LastLine::Justify));
+        assert_eq!(
+            wrap("To be, or not to be: that is", &options),
+            vec!["To   be,  or", "  not to be:", "  that    is"]
+        );
+        // Lines with a single word are not padded.
+        assert_eq!(
+            wrap("Supercalifragilistic", &options),
+            vec!["Supercalifra", "  gilistic"]
+        );
+        // Soft hyphens are removed from justified lines.
+        assert_eq!(
+            wrap("soft\u{ad}ware is fun", &options.subsequent_indent("")),
+            vec!["software  is", "fun"]
+        );
+    }
+
+    #[test]
+    fn wrap_align_with_indent() {
+        let options = Options::new(10)
+            .initial_indent("> ")
+            .subsequent_indent("> ")
+            .alignment(Alignment::Right);
+        assert_eq!(
+            wrap("foo bar baz", &options),
+            vec![">  foo bar", ">      baz"]
+        );
+        let options = options.alignment(Alignment::Justify(LastLine::Center));
+        assert_eq!(wrap("foo bar baz", &options), vec!["> foo  bar", ">   baz"]);
+        // Lines which are too long are not aligned.
+        let options = options.alignment(Alignment::Center).break_words(false);
+        assert_eq!(
+            wrap("Supercalifragilistic", &options),
+            vec!["> Supercalifragilistic"]
+        );
+    }
+
+    #[test]
+    fn wrap_hanging_punctuation() {
+        let options = Options::new(7).wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(wrap("foo bar-baz", &options), vec!["foo", "bar-baz"]);
+        let options = options.hanging_punctuation(true);
+        assert_eq!(wrap("foo bar-baz", &options), vec!["foo bar-", "baz"]);
+        assert_eq!(wrap("foo barbaz", &options), vec!["foo", "barbaz"]);
+        // The punctuation hangs past the right edge.
+        let options = options.alignment(Alignment::Right);
+        assert_eq!(wrap("foo bar.", &options), vec!["foo bar."]);
+        assert_eq!(wrap("foo.", &options), vec!["    foo."]);
+    }
+
+    #[test]
+    fn wrap_max_lines() {
+        let options = Options::new(10).max_lines(2).placeholder(" [...]");
+        assert_eq!(
+            wrap("foo bar baz qux quux", &options),
+            vec!["foo bar", "baz [...]"]
+        );
+        // The placeholder is put on a line of its own.
+        assert_eq!(
+            wrap("foo bar loooooooong qux", &options),
+            vec!["foo bar", "[...]"]
+        );
+        // The placeholder is added to the previous line.
+        let options = Options::new(14)
+            .max_lines(2)
+            .placeholder("...")
+            .break_words(false);
+        assert_eq!(
+            wrap("foo bar baz supercalifragilistic more", &options),
+            vec!["foo bar baz..."]
+        );
+        let options = Options::new(10).max_lines(0);
+        assert!(wrap("foo bar", &options).is_empty());
+    }
+
+    #[test]
+    fn wrap_max_lines_aligned() {
+        // The placeholder is added before the last line is aligned.
+        let text = "foo bar baz qux quux corge";
+        let options = Options::new(10)
+            .max_lines(2)
+            .placeholder("~")
+            .alignment(Alignment::Right);
+        assert_eq!(wrap(text, &options), vec!["   foo bar", "  baz qux~"]);
+        let options = options.alignment(Alignment::Center);
+        assert_eq!(wrap(text, &options), vec![" foo bar", " baz qux~"]);
+        let options = Options::new(14)
+            .max_lines(2)
+            .placeholder("...")
+            .break_words(false)
+            .alignment(Alignment::Right);
+        assert_eq!(
+            wrap("foo bar baz supercalifragilistic more", &options),
+            vec!["foo bar baz..."]
+        );
+        let options = Options::new(10)
+            .max_lines(2)
+            .placeholder(" [...]")
+            .alignment(Alignment::Right);
+        assert_eq!(
+            wrap("foo bar loooooooong qux", &options),
+            vec!["   foo bar", "     [...]"]
+        );
+    }
+
+    #[test]
+    fn wrap_crlf_line_endings() {
+        assert_eq!(wrap("foo\r\nbar\r\n", 10), vec!["foo", "bar", ""]);
+        let options = Options::new(10).line_ending(LineEnding::FirstLine);
+        assert_eq!(
+            fill("foo bar baz\r\nqux", &options),
+            "foo bar\r\nbaz\r\nqux"
+        );
+        assert_eq!(fill("foo bar baz\nqux", &options), "foo bar\nbaz\nqux");
+        // The first line decides the line ending of all lines.
+        assert_eq!(fill("foo\r\nbar\nbaz", &options), "foo\r\nbar\r\nbaz");
+        assert_eq!(refill("foo\r\nbar baz\r\n", &options), "foo bar\r\nbaz\r\n");
+    }
+
+    #[test]
+    fn wrap_unicode_line_breaks() {
+        let text = "foo\u{85}bar\u{c}baz\u{2029}qux";
+        assert_eq!(wrap(text, 80), vec![text]);
+        let options = Options::new(80).unicode_line_breaks(true);
+        assert_eq!(wrap(text, &options), vec!["foo", "bar", "baz", "qux"]);
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, vec!["foo", "bar", "baz", "qux"]);
+    }
+
+    #[test]
+    fn wrap_whitespace_policy() {
+        let text = "  foo   bar\t baz\n  qux  ";
+        let options = Options::new(7)
+            .whitespace(Whitespace::Normalize)
+            .trim(Trim::Both);
+        assert_eq!(wrap(text, &options), vec!["foo bar", "baz", "qux"]);
+        let options = options.trim(Trim::Neither);
+        assert_eq!(wrap(text, &options), vec!["  foo ", "bar baz", "  qux "]);
+        let options = options.whitespace(Whitespace::Collapse);
+        assert_eq!(wrap(text, &options), vec!["  foo ", "bar baz ", "qux "]);
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, vec!["  foo ", "bar baz ", "qux "]);
+        // A hyphen is not followed by whitespace.
+        let options = Options::new(6).trim(Trim::Neither);
+        assert_eq!(wrap("foo-bar baz", &options), vec!["foo-", "bar ", "baz"]);
+    }
+
+    #[test]
+    fn wrap_detect_quotes() {
+        let options = Options::new(9).detect_quotes(true).subsequent_indent("  ");
+        assert_eq!(
+            wrap("> > foo bar baz\n> >", &options),
+            vec!["> > foo", "> >   bar", "> >   baz", "> >"]
+        );
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into("> foo bar", &mut lines);
+        assert_eq!(lines, vec!["> foo bar"]);
+    }
+
+    #[test]
+    fn refill_detect_quotes() {
+        let text = "> > foo\n> > bar\n>\n> baz\n> qux\nquux\n";
+        let options = Options::new(20).detect_quotes(true);
+        assert_eq!(refill(text, &options), "> > foo bar\n>\n> baz qux\nquux\n");
+        let text = "> foo\r\n> bar\r\n";
+        assert_eq!(refill(text, &options), "> foo bar\r\n");
+    }
+
+    #[test]
+    fn reflow_comment_prefixes() {
+        let options = Options::new(15).line_ending(LineEnding::FirstLine);
+        assert_eq!(
+            reflow_comment(
+                "    # foo\r\n    # bar baz\r\n    #\r\n    # - qux\r\n    #   quux\r\n",
+                options
+            ),
+            "    # foo bar\r\n    # baz\r\n    #\r\n    # - qux\r\n    #   quux\r\n"
+        );
+        assert_eq!(reflow_comment("//foo\n//bar", 10), "//foo bar");
+        // The lines do not share a comment prefix.
+        assert_eq!(reflow_comment("// foo\nbar", 10), "// foo bar");
+    }
+
+    #[test]
+    fn format_commit_message_blocks() {
+        let body = "Note: aaaa bbbb cccc dddd eeee ffff gggg hhhh iiii jjjj kkkk llll mmmm nnnn";
+        let message = format!(
+            "Subject line which is rather long but is never wrapped at all, no matter what\r\n\
+             \r\n\
+             {}\r\n\
+             # Please enter the commit message for your changes. Lines starting with '#'\r\n\
+             - Item\r\n\
+             \x20 continued\r\n\
+             \r\n\
+             Fixes: #123\r\n",
+            body
+        );
+        assert_eq!(
+            format_commit_message(&message),
+            "Subject line which is rather long but is never wrapped at all, no matter what\r\n\
+             \r\n\
+             Note: aaaa bbbb cccc dddd eeee ffff gggg hhhh iiii jjjj kkkk llll mmmm\r\n\
+             nnnn\r\n\
+             # Please enter the commit message for your changes. Lines starting with '#'\r\n\
+             - Item continued\r\n\
+             \r\n\
+             Fixes: #123\r\n"
+        );
+    }
+
+    #[test]
+    fn wrap_semantic_line_breaks() {
+        let options = Options::new(usize::MAX)
+            .semantic_line_breaks(true)
+            .subsequent_indent("  ");
+        let text = "  Foo (\"bar.\")  Baz! qux? Quux.\n\nOne.";
+        assert_eq!(
+            wrap(text, &options),
+            vec!["  Foo (\"bar.\")", "  Baz! qux?", "  Quux.", "", "  One."]
+        );
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, wrap(text, &wrapper.options));
+    }
+
+    #[test]
+    fn wrap_hanging_list_indent() {
+        let options = Options::new(14)
+            .initial_indent("> ")
+            .subsequent_indent(">> ")
+            .hanging_list_indent(true);
+        let text = "- foo bar baz qux\n  10) baz qux quux\nfoo bar baz";
+        assert_eq!(
+            wrap(text, &options),
+            vec![
+                "> - foo bar",
+                "    baz qux",
+                ">>   10) baz",
+                "         qux",
+                "         quux",
+                ">> foo bar baz"
+            ]
+        );
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, wrap(text, &wrapper.options));
+
+        assert_eq!(list_marker_len("• foo"), Some(4));
+        assert_eq!(list_marker_len("ab) foo"), None);
+        assert_eq!(list_marker_len("-foo"), None);
+        assert_eq!(list_marker_len("-  "), None);
+    }
+
+    #[test]
+    fn options_owned_strings() {
+        let prefix = String::from("| ");
+        let options = Options::new(10)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .word_separator(AsciiSpace)
+            .line_indent(move |_| prefix.clone().into())
+            .placeholder(String::from("~"))
+            .max_lines(2);
+        let options: Options<'static, _, _, _> = options;
+        let handle = std::thread::spawn(move || wrap("foo bar baz qux", &options).join("\n"));
+        assert_eq!(handle.join().unwrap(), "| foo bar\n| baz qux");
+    }
+
+    #[test]
+    fn wrap_line_indent() {
+        let growing = |n: usize| " ".repeat(n).into();
+        let options = Options::new(10)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .initial_indent("ignored")
+            .line_indent(growing);
+        let text = "aaa bbb ccc ddd\neee";
+        assert_eq!(wrap(text, &options), vec!["aaa bbb", " ccc ddd", "  eee"]);
+        let mut wrapper = Wrapper::new(options.clone());
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, vec!["aaa bbb", " ccc ddd", "  eee"]);
+        let options = options.max_lines(2).placeholder("~");
+        assert_eq!(wrap(text, &options), vec!["aaa bbb", " ccc ddd~"]);
+    }
+
+    #[test]
+    fn try_wrap_errors() {
+        let options = Options::new(10).subsequent_indent("    ");
+        assert_eq!(try_wrap("foo bar", &options), Ok(wrap("foo bar", &options)));
+        assert_eq!(
+            try_wrap("foo", options.clone().initial_indent("          ")),
+            Err(WrapError::IndentTooWide {
+                indent_width: 10,
+                width: 10
+            })
+        );
+        // The line width and indentation of later lines is checked.
+        let options = options.line_indent(|n| " ".repeat(n).into());
+        assert_eq!(try_wrap("foo", &options), Ok(vec![Cow::from("foo")]));
+        let options = options.line_widths(|n| 1 - n.min(1));
+        assert_eq!(try_wrap("foo", &options), Err(WrapError::ZeroWidth));
+        let options = Options::new(3).overflow(Overflow::Truncate);
+        assert_eq!(try_fill("foo bar", &options), Ok(fill("foo bar", &options)));
+        assert_eq!(
+            try_fill("foo", options.placeholder("[..]")),
+            Err(WrapError::PlaceholderTooWide {
+                placeholder_width: 4,
+                width: 3
+            })
+        );
+    }
+
+    #[test]
+    fn wrap_iter_matches_wrap() {
+        let text = "  Foo bar. Baz quux.\r\n\nfoo\u{2028}- bar baz quux\n";
+        let options = Options::new(8).initial_indent("* ").subsequent_indent("  ");
+        let all_options = vec![
+            options.clone(),
+            options.clone().semantic_line_breaks(true),
+            options.clone().unicode_line_breaks(true),
+            options.clone().hanging_list_indent(true),
+            options.clone().max_lines(3),
+            options.clone().whitespace(Whitespace::Collapse),
+            options.line_indent(|n| "-".repeat(n).into()),
+        ];
+        for options in &all_options {
+            assert_eq!(
+                wrap_iter(text, options).collect::<Vec<_>>(),
+                wrap(text, options),
+                "{:?}",
+                options
+            );
+        }
+        let mut lines = wrap_iter(text, 8);
+        assert_eq!(lines.next(), Some(Cow::from("  Foo")));
+        assert_eq!(lines.next_line_start, Some(22));
+    }
+
+    #[test]
+    fn fill_into_writers() {
+        let options = Options::new(10).line_ending(LineEnding::CrLf);
+        let text = "foo bar baz\nquux";
+        let mut result = String::from(">");
+        fill_into(text, &options, &mut result).unwrap();
+        assert_eq!(result, format!(">{}", fill(text, &options)));
+        let mut output = Vec::new();
+        fill_into_io(text, &options, &mut output).unwrap();
+        assert_eq!(output, b"foo bar\r\nbaz\r\nquux");
+    }
+
+    #[test]
+    fn wrap_line_hook() {
+        let options = Options::new(10)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .max_lines(2)
+            .placeholder("~")
+            .line_hook(|n, last, line| {
+                *line = format!("{}{}:{}", n, if last { "$" } else { "" }, line).into()
+            });
+        let text = "foo bar baz\nquux";
+        assert_eq!(wrap(text, &options), vec!["0:foo bar", "1$:baz~"]);
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, vec!["0:foo bar", "1$:baz~"]);
+        wrapper.wrap_into("", &mut lines);
+        assert_eq!(lines, vec!["0$:"]);
+    }
+
+    #[test]
+    fn wrap_line_widths() {
+        let options = Options::new(100)
+            .wrap_algorithm(wrap_algorithms::FirstFit)
+            .line_widths(|n| 4 + 2 * n)
+            .alignment(Alignment::Right);
+        let text = "aa bb cc dd ee";
+        assert_eq!(wrap(text, &options), vec!["  aa", " bb cc", "   dd ee"]);
+        let mut wrapper = Wrapper::new(options.clone());
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, vec!["  aa", " bb cc", "   dd ee"]);
+        let options = options
+            .alignment(Alignment::Left)
+            .overflow(Overflow::Truncate)
+            .placeholder("~");
+        assert_eq!(wrap("aaaaaaa\nbbbbbbb", &options), vec!["aaa~", "bbbbb~"]);
+    }
+
+    #[test]
+    fn wrap_preserve_indentation() {
+        let options = Options::new(10)
+            .subsequent_indent("> ")
+            .preserve_indentation(true);
+        let text = "foo bar baz\n  foo bar\n   ";
+        assert_eq!(
+            wrap(text, &options),
+            vec!["foo bar", "> baz", ">   foo", ">   bar", ""]
+        );
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, wrap(text, &wrapper.options));
+    }
+
+    #[test]
+    fn wrap_auto_subsequent_indent() {
+        let options = Options::new(12)
+            .initial_indent("> ")
+            .auto_subsequent_indent(true);
+        let text = "a  bb cc dd\n  - foo bar baz\nfoo bar baz";
+        assert_eq!(
+            wrap(text, &options),
+            vec![
+                "> a  bb cc",
+                "     dd",
+                "  - foo bar",
+                "  baz",
+                "foo bar baz"
+            ]
+        );
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, wrap(text, &wrapper.options));
+        assert_eq!(label_len("  foo  "), 2);
+    }
+
+    #[test]
+    fn split_paragraphs_modes() {
+        let text = "    foo\nbar\n  baz\n \n> qux\n>\n> quux\n// a\n// b";
+        assert_eq!(split_paragraphs(text, Paragraphs::Whole), vec![text]);
+        assert_eq!(
+            split_paragraphs(text, Paragraphs::BlankLines),
+            vec!["    foo\nbar\n  baz", " ", "> qux\n>\n> quux\n// a\n// b"]
+        );
+        assert_eq!(
+            split_paragraphs(text, Paragraphs::Indentation),
+            vec!["    foo\nbar", "  baz", " ", "> qux\n>\n> quux\n// a\n// b"]
+        );
+        assert_eq!(
+            split_paragraphs(text, Paragraphs::Prefix),
+            vec![
+                "    foo\nbar",
+                "  baz",
+                " ",
+                "> qux",
+                ">",
+                "> quux",
+                "// a\n// b"
+            ]
+        );
+        let custom = Paragraphs::Custom(|_, line| line.starts_with('/'));
+        assert_eq!(
+            split_paragraphs(text, custom),
+            vec![
+                "    foo\nbar\n  baz",
+                " ",
+                "> qux\n>\n> quux",
+                "// a",
+                "// b"
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_preformatted() {
+        let options = Options::new(10)
+            .subsequent_indent("> ")
+            .preformatted(Preformatted::Detect);
+        let text = "foo bar baz\n    let x = 1;  \n──┐\na == b";
+        assert_eq!(
+            wrap(text, &options),
+            vec![
+                "foo bar",
+                "> baz",
+                ">     let x = 1;  ",
+                "> ──┐",
+                "> a == b"
+            ]
+        );
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into(text, &mut lines);
+        assert_eq!(lines, wrap(text, &wrapper.options));
+
+        let custom = Preformatted::Custom(|line| line.starts_with("$ "));
+        let options = Options::new(5).preformatted(custom);
+        assert_eq!(
+            wrap("$ ls -l\nfoo bar", &options),
+            vec!["$ ls -l", "foo", "bar"]
+        );
+        assert!(looks_preformatted("a ==== b"));
+        assert!(!looks_preformatted("    "));
+    }
+
+    #[test]
+    fn wrap_overflow_truncate() {
+        let options = Options::new(6)
+            .overflow(Overflow::Truncate)
+            .placeholder("~");
+        assert_eq!(wrap("foo   bar\n\nfoo", &options), vec!["foo~", "", "foo"]);
+        let options = options.initial_indent("> ").max_lines(2);
+        assert_eq!(wrap("foo bar\nbaz\nqux", &options), vec!["> foo~", "baz~"]);
+        let mut wrapper = Wrapper::new(options);
+        let mut lines = Vec::new();
+        wrapper.wrap_into("foo bar\nbaz\nqux", &mut lines);
+        assert_eq!(lines, vec!["> foo~", "baz~"]);
+    }
+
+    #[test]
+    fn wrap_overflow_truncate_aligned() {
+        let options = Options::new(10)
+            .overflow(Overflow::Truncate)
+            .placeholder("~")
+            .alignment(Alignment::Right);
+        assert_eq!(
+            wrap("foo bar\nfoo bar baz qux", &options),
+            vec!["   foo bar", "foo bar b~"]
+        );
+        let options = options.alignment(Alignment::Center).initial_indent("> ");
+        assert_eq!(wrap("foo\n\nbar", &options), vec![">   foo", "", "   bar"]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn truncate_middle_graphemes() {
+        assert_eq!(
+            truncate_middle("e\u{301}e\u{301}e\u{301}", 2, "."),
+            "e\u{301}."
+        );
+        assert_eq!(truncate_middle("abc   ", 5, ".."), "ab.. ");
+        assert_eq!(truncate_middle("abcdef", 2, "..."), "...");
+    }
+
+    #[test]
+    fn break_words_prefers_punctuation() {
+        let options = Options::new(10).wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(wrap("key=some.value", &options), vec!["key=some.", "value"]);
+        // A run of punctuation is kept together.
+        assert_eq!(wrap("abc...defghijk", &options), vec!["abc...", "defghijk"]);
+        // Without punctuation, the word is broken anywhere.
+        assert_eq!(wrap("abcdefghijklm", &options), vec!["abcdefghij", "klm"]);
+    }
+
+    #[test]
+    fn wrap_break_marker_width() {
+        let break_marker = BreakMarker {
+            hyphen: "\u{2010}",
+            ..BreakMarker::new()
+        };
+        let options = Options::new(5)
+            .break_marker(break_marker)
+            .wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(
+            wrap("Soft\u{ad}ware", &options),
+            vec!["Soft\u{2010}", "ware"]
+        );
+        // The forced marker counts toward the line width.
+        let options = options.break_marker(BreakMarker {
+            forced: "~",
+            ..break_marker
+        });
+        assert_eq!(wrap("abcdefgh", &options), vec!["abcd~", "efgh"]);
+    }
+
+    #[test]
+    fn wrap_hyphen_limit_keeps_unavoidable_hyphens() {
+        let algorithm = wrap_algorithms::HyphenLimit::new(wrap_algorithms::FirstFit, 0);
+        let options = Options::new(7).wrap_algorithm(algorithm);
+        assert_eq!(
+            wrap("state-of-the-art", &options),
+            vec!["state-", "of-the-", "art"]
+        );
+        assert_eq!(
+            wrap("the state-of-the-art", &options),
+            vec!["the", "state-", "of-the-", "art"]
+        );
+        assert_eq!(wrap("", &options), vec![""]);
+    }
+
+    #[test]
+    fn refill_sentence_end_double_space() {
+        let text = "> \"Hello.\"\n> (World!)\n> Foo. \n> Bar";
+        let options = Options::new(80).sentence_end_double_space(true);
+        assert_eq!(refill(text, options), "> \"Hello.\"  (World!)  Foo.  Bar");
+        assert_eq!(refill(text, 80), "> \"Hello.\" (World!) Foo.  Bar");
+    }
+
+    #[test]
+    fn fit_width_empty_text() {
+        assert_eq!(fit_width("", 1, 80), Some(0));
+        assert_eq!(fit_width("", 0, 80), None);
+    }
+
+    #[test]
+    fn fit_width_without_break_words() {
+        let options = Options::new(80).break_words(false);
+        assert_eq!(fit_width("foo barbaz", 2, &options), Some(6));
+        assert_eq!(fit_width("foo barbaz", 3, &options), Some(6));
+    }
 }
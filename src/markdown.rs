@@ -0,0 +1,444 @@
+//! Functions for filling Markdown text.
+//!
+//! Markdown text consists of blocks such as paragraphs, list items,
+//! headings, tables, and fenced code blocks. Only paragraphs and list
+//! items can be wrapped without changing how the text is rendered:
+//! headings and table rows must stay on a single line and code blocks
+//! must be kept exactly as they are.
+//!
+//! Use [`fill`] to wrap the paragraphs and list items of Markdown
+//! text while keeping the other blocks as they are. Use
+//! [`reflow_doc_comment`] to do the same for Rust documentation
+//! comments.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::{
+    comment_prefix, core, wrap_algorithms, wrap_with_breaks, LineEnding, Options, WordSeparator,
+    WordSplitter,
+};
+
+/// Fill the paragraphs and list items of Markdown `text`.
+///
+/// The text is split into blocks first:
+///
+/// - Fenced code blocks, delimited by `` ``` `` or `~~~`, are kept
+///   as they are. So are table rows (lines starting with `'|'`),
+///   headings (lines starting with `'#'`), link reference
+///   definitions (`[like]: this`), and empty lines.
+/// - List items start with `'-'`, `'*'`, `'+'`, or a number followed
+///   by `'.'` or `')'`. They are wrapped with a hanging indentation,
+///   so that the wrapped lines line up with the text after the list
+///   marker.
+/// - All other lines are paragraphs, which are wrapped with the
+///   indentation of their first line.
+///
+/// Hard line breaks, which are lines ending with two spaces or with
+/// a backslash, are kept. Lines are never broken inside code spans
+/// (`` `like this` ``) or link destinations (`[like](this)`), and
+/// long words are never broken apart since that could break links.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::markdown;
+///
+/// let text = "\
+/// Textwrap is a small library for wrapping text.
+///
+/// - Use `textwrap::fill` to wrap text.
+/// - See the [documentation](https://docs.rs/textwrap) for more.
+///
+/// ~~~
+/// let text = \"not wrapped, not even in a narrow column\";
+/// ~~~
+/// ";
+///
+/// assert_eq!(markdown::fill(text, 28), "\
+/// Textwrap is a small library
+/// for wrapping text.
+///
+/// - Use `textwrap::fill` to
+///   wrap text.
+/// - See the
+///   [documentation](https://docs.rs/textwrap)
+///   for more.
+///
+/// ~~~
+/// let text = \"not wrapped, not even in a narrow column\";
+/// ~~~
+/// ");
+/// ```
+pub fn fill<'a, A, R, S, Opt>(text: &str, width_or_options: Opt) -> String
+where
+    A: wrap_algorithms::WrapAlgorithm + Clone,
+    R: WordSeparator + Clone + 'static,
+    S: WordSplitter + Clone + 'static,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let options = width_or_options.into().break_words(false);
+    let word_separator = options.word_separator.clone().filter_breaks(can_break);
+    let splitter = SpanSplitter(options.splitter.clone());
+    let options = options.word_separator(word_separator).splitter(splitter);
+    let line_ending = options.line_ending.as_str(text);
+
+    let mut lines = Vec::new();
+    let mut paragraph = Vec::new();
+    // The opening fence of the current fenced code block.
+    let mut fence = None;
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(open) = fence {
+            if fence_marker(line).is_some_and(|close| close.starts_with(open)) {
+                fence = None;
+            }
+            lines.push(line.to_owned());
+            continue;
+        }
+
+        let rest = line.trim_start();
+        let is_heading = rest.starts_with('#') && rest.trim_start_matches('#').starts_with(' ');
+        let is_fixed = rest.is_empty()
+            || rest.starts_with('|')
+            || is_heading
+            || is_link_definition(rest)
+            || fence_marker(line).is_some();
+        if is_fixed || list_marker(line).is_some() {
+            fill_paragraph(&paragraph, &options, &mut lines);
+            paragraph.clear();
+        }
+        if is_fixed {
+            fence = fence_marker(line);
+            lines.push(line.to_owned());
+        } else {
+            paragraph.push(line);
+        }
+    }
+    fill_paragraph(&paragraph, &options, &mut lines);
+    lines.join(line_ending)
+}
+
+/// Reflow a block of Rust documentation comments with a new width.
+///
+/// All lines of `comment` must start with the same `"///"` or
+/// `"//!"` comment marker, possibly indented. The marker is removed,
+/// the remaining Markdown text is filled with [`fill`] to the width
+/// minus the width of the marker and a space, and the marker is
+/// added again in front of every line. Code blocks, bullet lists,
+/// and intra-doc links are kept intact as described for [`fill`].
+///
+/// Text without a shared documentation comment marker is filled as
+/// plain Markdown text.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::markdown;
+///
+/// let comment = "\
+/// /// Wrap a line of text at a given width. See [`Options`] for the
+/// /// settings.
+/// ///
+/// /// ```
+/// /// assert_eq!(textwrap::wrap(\"foo bar\", 3), vec![\"foo\", \"bar\"]);
+/// /// ```
+/// ///
+/// /// [`Options`]: crate::Options
+/// ";
+///
+/// assert_eq!(markdown::reflow_doc_comment(comment, 40), "\
+/// /// Wrap a line of text at a given
+/// /// width. See [`Options`] for the
+/// /// settings.
+/// ///
+/// /// ```
+/// /// assert_eq!(textwrap::wrap(\"foo bar\", 3), vec![\"foo\", \"bar\"]);
+/// /// ```
+/// ///
+/// /// [`Options`]: crate::Options
+/// ");
+/// ```
+pub fn reflow_doc_comment<'a, A, R, S, Opt>(comment: &str, new_width_or_options: Opt) -> String
+where
+    A: wrap_algorithms::WrapAlgorithm + Clone,
+    R: WordSeparator + Clone + 'static,
+    S: WordSplitter + Clone + 'static,
+    Opt: Into<Options<'a, A, R, S>>,
+{
+    let trimmed = comment.trim_end_matches(&['\r', '\n'][..]);
+    let lines = trimmed
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+    let marker = comment_prefix(lines[0])
+        .filter(|marker| marker.ends_with("///") || marker.ends_with("//!"))
+        .filter(|marker| lines.iter().all(|line| line.starts_with(marker)))
+        .unwrap_or("");
+    let prefix = if marker.is_empty() {
+        String::new()
+    } else {
+        [marker, " "].concat()
+    };
+    let text = lines
+        .iter()
+        .map(|line| {
+            let content = &line[marker.len()..];
+            content.strip_prefix(' ').unwrap_or(content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut options = new_width_or_options.into();
+    let line_ending = options.line_ending.as_str(comment);
+    options.width = options.width.saturating_sub(options.text_width(&prefix));
+    options.line_ending = LineEnding::Lf;
+    let mut reflowed = fill(&text, options)
+        .split('\n')
+        .map(|line| match line {
+            "" => marker.to_owned(),
+            _ => [prefix.as_str(), line].concat(),
+        })
+        .collect::<Vec<_>>()
+        .join(line_ending);
+    reflowed.push_str(&comment[trimmed.len()..]);
+    reflowed
+}
+
+/// Wrap the `lines` of a paragraph or list item and push the wrapped
+/// lines onto `result`.
+fn fill_paragraph<A, R, S>(lines: &[&str], options: &Options<'_, A, R, S>, result: &mut Vec<String>)
+where
+    A: wrap_algorithms::WrapAlgorithm + Clone,
+    R: WordSeparator + Clone,
+    S: WordSplitter + Clone,
+{
+    let first = match lines.first() {
+        Some(first) => first,
+        None => return,
+    };
+    let indent_len = list_marker(first).unwrap_or(first.len() - first.trim_start().len());
+    let indent = &first[..indent_len];
+    let hanging = match list_marker(first) {
+        Some(_) => " ".repeat(core::display_width(indent)),
+        None => indent.to_owned(),
+    };
+
+    let mut options = options.clone();
+    options.initial_indent = indent.into();
+    options.subsequent_indent = hanging.as_str().into();
+    let mut segment = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let content = if i == 0 {
+            &line[indent_len..]
+        } else {
+            line.trim_start()
+        };
+        if !segment.is_empty() {
+            segment.push(' ');
+        }
+        segment.push_str(content.trim_end());
+
+        // Every hard line break ends a segment of the paragraph.
+        let spaces_break = line.ends_with("  ");
+        if spaces_break || line.ends_with('\\') || i + 1 == lines.len() {
+            let start = result.len();
+            result.extend(
+                wrap_with_breaks(&segment, &options, &[])
+                    .into_iter()
+                    .map(Cow::into_owned),
+            );
+            if spaces_break && result.len() > start {
+                result.last_mut().unwrap().push_str("  ");
+            }
+            segment.clear();
+            options.initial_indent = hanging.as_str().into();
+        }
+    }
+}
+
+/// Find the opening or closing fence of a fenced code block at the
+/// start of `line`.
+fn fence_marker(line: &str) -> Option<&str> {
+    let rest = line.trim_start();
+    ['`', '~'].iter().find_map(|&ch| {
+        let len = rest.len() - rest.trim_start_matches(ch).len();
+        if len >= 3 {
+            Some(&rest[..len])
+        } else {
+            None
+        }
+    })
+}
+
+/// Is `line` a link reference definition such as `[foo]: url`?
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[') && line.contains("]: ")
+}
+
+/// Find the byte length of the indentation, list marker, and the
+/// spaces after the list marker at the start of `line`.
+fn list_marker(line: &str) -> Option<usize> {
+    let rest = line.trim_start();
+    let indent = line.len() - rest.len();
+    let digits = rest.len()
+        - rest
+            .trim_start_matches(|ch: char| ch.is_ascii_digit())
+            .len();
+    let marker = match rest[digits..].chars().next() {
+        Some('-') | Some('*') | Some('+') if digits == 0 => 1,
+        Some('.') | Some(')') if digits > 0 && digits < 10 => digits + 1,
+        _ => return None,
+    };
+    let after = &rest[marker..];
+    let spaces = after.len() - after.trim_start_matches(' ').len();
+    if spaces == 0 {
+        return None;
+    }
+    Some(indent + marker + spaces)
+}
+
+/// Can `line` be broken at the word starting at `offset`? This is
+/// not possible inside code spans and link destinations.
+fn can_break(line: &str, offset: usize) -> bool {
+    !spans(line)
+        .iter()
+        .any(|span| span.start < offset && offset < span.end)
+}
+
+/// Find the code spans and link destinations in `line`.
+fn spans(line: &str) -> Vec<Range<usize>> {
+    let run_len =
+        |idx: usize, ch: char| line[idx..].len() - line[idx..].trim_start_matches(ch).len();
+    let mut spans = Vec::new();
+    let mut idx = 0;
+    while let Some(pos) = line[idx..].find(&['`', ']'][..]) {
+        let start = idx + pos;
+        if line[start..].starts_with('`') {
+            // A code span ends with a run of backticks of the same
+            // length as the run which started it.
+            let run = run_len(start, '`');
+            let mut search = start + run;
+            idx = search;
+            while let Some(pos) = line[search..].find('`') {
+                let close = search + pos;
+                let close_run = run_len(close, '`');
+                if close_run == run {
+                    spans.push(start..close + run);
+                    idx = close + run;
+                    break;
+                }
+                search = close + close_run;
+            }
+        } else if line[start..].starts_with("](") {
+            let mut depth = 0;
+            let end = line[start + 1..].char_indices().find_map(|(i, ch)| {
+                match ch {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => return None,
+                }
+                if depth == 0 {
+                    Some(start + 1 + i + 1)
+                } else {
+                    None
+                }
+            });
+            match end {
+                Some(end) => {
+                    spans.push(start + 1..end);
+                    idx = end;
+                }
+                None => idx = start + 2,
+            }
+        } else {
+            idx = start + 1;
+        }
+    }
+    spans
+}
+
+/// A word splitter which never splits code spans and links.
+#[derive(Debug, Clone)]
+struct SpanSplitter<S>(S);
+
+impl<S: WordSplitter + Clone + 'static> WordSplitter for SpanSplitter<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        if word.contains('`') || word.contains("](") {
+            return Vec::new();
+        }
+        self.0.split_points(word)
+    }
+
+    fn fallback_points(&self, word: &str) -> Vec<usize> {
+        if word.contains('`') || word.contains("](") {
+            return Vec::new();
+        }
+        self.0.fallback_points(word)
+    }
+
+    fn inserts_hyphen(&self) -> bool {
+        self.0.inserts_hyphen()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_hard_breaks() {
+        let text = "foo bar  \nbaz\\\nqux quux\n";
+        assert_eq!(fill(text, 7), "foo bar  \nbaz\\\nqux\nquux\n");
+    }
+
+    #[test]
+    fn fill_keeps_blocks() {
+        let text = "# A long heading\n\
+                    | a | b | c |\n\
+                    ~~~~\n\
+                    a b c d\n\
+                    ```\n\
+                    ~~~~\n\
+                    1. foo bar\n\
+                    10) baz";
+        assert_eq!(
+            fill(text, 6),
+            "# A long heading\n\
+             | a | b | c |\n\
+             ~~~~\n\
+             a b c d\n\
+             ```\n\
+             ~~~~\n\
+             1. foo\n   bar\n\
+             10) baz"
+        );
+    }
+
+    #[test]
+    fn fill_code_spans() {
+        let options = Options::new(8).wrap_algorithm(wrap_algorithms::FirstFit);
+        assert_eq!(fill("a `b c d e` f", &options), "a\n`b c d e`\nf");
+        assert_eq!(fill("a ``b ` c`` d", &options), "a\n``b ` c``\nd");
+        // An unmatched backtick does not start a code span.
+        assert_eq!(fill("a ` b c d", &options), "a ` b c\nd");
+    }
+
+    #[test]
+    fn reflow_doc_comment_inner() {
+        let comment = "    //! - foo\r\n    //!   bar baz\r\n    //!\r\n    //! [foo]: a b c\r\n";
+        let options = Options::new(18).line_ending(LineEnding::FirstLine);
+        assert_eq!(
+            reflow_doc_comment(comment, options),
+            "    //! - foo bar\r\n    //!   baz\r\n    //!\r\n    //! [foo]: a b c\r\n"
+        );
+        // Without doc comment markers, the text is plain Markdown.
+        assert_eq!(reflow_doc_comment("// foo bar", 6), "// foo\nbar");
+    }
+
+    #[test]
+    fn spans_links() {
+        assert_eq!(spans("[a b](c (d) e) f"), vec![5..14]);
+        assert_eq!(spans("[a b](c d"), vec![]);
+    }
+}
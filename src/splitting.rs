@@ -51,6 +51,65 @@ pub trait WordSplitter: WordSplitterClone + std::fmt::Debug {
     /// assert_eq!(HyphenSplitter.split_points("can-be-split"), vec![4, 7]);
     /// ```
     fn split_points(&self, word: &str) -> Vec<usize>;
+
+    /// Should a hyphen be added when a word is split at one of the
+    /// split points?
+    ///
+    /// A hyphen is never added if the first part of the word already
+    /// ends with `-`. The default implementation returns `true`.
+    ///
+    /// ```
+    /// use textwrap::{HyphenSplitter, IdentifierSplitter, WordSplitter};
+    /// assert!(HyphenSplitter.inserts_hyphen());
+    /// assert!(!IdentifierSplitter.inserts_hyphen());
+    /// ```
+    fn inserts_hyphen(&self) -> bool {
+        true
+    }
+
+    /// Return indices where `word` can be split if it cannot be
+    /// wrapped otherwise.
+    ///
+    /// Like the split points at soft hyphens, these split points are
+    /// only used by the wrapping algorithms if no line which fits can
+    /// be made with the [`WordSplitter::split_points`]. The default
+    /// implementation returns no split points. See
+    /// [`WordSplitter::fallback`] for a way to add them.
+    fn fallback_points(&self, word: &str) -> Vec<usize> {
+        let _ = word;
+        Vec::new()
+    }
+
+    /// Use the split points of `fallback` when a word cannot be
+    /// wrapped with the split points of `self`.
+    ///
+    /// The split points of `fallback` become
+    /// [`WordSplitter::fallback_points`]. A hyphen is added according
+    /// to [`WordSplitter::inserts_hyphen`] of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{wrap, FnSplitter, HyphenSplitter, Options, WordSplitter};
+    ///
+    /// // Split between any two characters as a last resort.
+    /// let anywhere = FnSplitter::new(|word: &str| {
+    ///     word.char_indices().skip(1).map(|(idx, _)| idx).collect()
+    /// });
+    /// let options = Options::new(8).splitter(HyphenSplitter.fallback(anywhere));
+    /// assert_eq!(wrap("a self-contained example", &options),
+    ///            vec!["a self-", "cont-", "ained", "example"]);
+    /// ```
+    fn fallback<T>(self, fallback: T) -> Fallback<Self, T>
+    where
+        Self: Sized,
+        T: WordSplitter,
+    {
+        Fallback {
+            splitter: self,
+            fallback,
+        }
+    }
 }
 
 // The internal `WordSplitterClone` trait is allows us to implement
@@ -77,6 +136,14 @@ impl WordSplitter for Box<dyn WordSplitter> {
     fn split_points(&self, word: &str) -> Vec<usize> {
         self.deref().split_points(word)
     }
+
+    fn inserts_hyphen(&self) -> bool {
+        self.deref().inserts_hyphen()
+    }
+
+    fn fallback_points(&self, word: &str) -> Vec<usize> {
+        self.deref().fallback_points(word)
+    }
 }
 
 /// Use this as a [`Options.splitter`] to avoid any kind of
@@ -139,6 +206,390 @@ impl WordSplitter for HyphenSplitter {
     }
 }
 
+/// Use a closure as a word splitter.
+///
+/// The closure is given a word and returns the indices where the
+/// word can be split, just like [`WordSplitter::split_points`]. This
+/// is convenient for one-off splitting rules which do not warrant a
+/// new type.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, FnSplitter, Options};
+///
+/// // Split words after the prefix "anti".
+/// let splitter = FnSplitter::new(|word: &str| match word.len() {
+///     len if len > 4 && word.starts_with("anti") => vec![4],
+///     _ => vec![],
+/// });
+/// let options = Options::new(10).splitter(splitter);
+/// assert_eq!(wrap("take the antibiotics", &options),
+///            vec!["take the", "anti-", "biotics"]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct FnSplitter<F> {
+    split_points: F,
+}
+
+impl<F> FnSplitter<F>
+where
+    F: Fn(&str) -> Vec<usize>,
+{
+    /// Create a new word splitter which uses `split_points` to find
+    /// the split points of each word.
+    pub const fn new(split_points: F) -> Self {
+        FnSplitter { split_points }
+    }
+}
+
+impl<F> std::fmt::Debug for FnSplitter<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnSplitter").finish()
+    }
+}
+
+/// `FnSplitter` implements `WordSplitter` by calling the closure.
+impl<F> WordSplitter for FnSplitter<F>
+where
+    F: Fn(&str) -> Vec<usize> + Clone + 'static,
+{
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        (self.split_points)(word)
+    }
+}
+
+/// A word splitter with a fallback.
+///
+/// This is created by [`WordSplitter::fallback`], please see that
+/// method for details and examples.
+#[derive(Clone, Copy, Debug)]
+pub struct Fallback<S, T> {
+    splitter: S,
+    fallback: T,
+}
+
+/// `Fallback` implements `WordSplitter` by using the split points of
+/// the first splitter as split points and the split points of the
+/// second splitter as fallback points.
+impl<S, T> WordSplitter for Fallback<S, T>
+where
+    S: WordSplitter + Clone + 'static,
+    T: WordSplitter + Clone + 'static,
+{
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        self.splitter.split_points(word)
+    }
+
+    fn inserts_hyphen(&self) -> bool {
+        self.splitter.inserts_hyphen()
+    }
+
+    fn fallback_points(&self, word: &str) -> Vec<usize> {
+        let mut fallback_points = self.splitter.fallback_points(word);
+        fallback_points.extend(self.fallback.split_points(word));
+        fallback_points.extend(self.fallback.fallback_points(word));
+        fallback_points.sort_unstable();
+        fallback_points.dedup();
+        fallback_points
+    }
+}
+
+/// Split identifiers in code at case changes and underscores.
+///
+/// Logs and technical prose often contain long identifiers such as
+/// `VeryLongFunctionName` or `some_long_variable`. This splitter
+/// allows such words to be split between a lowercase letter or digit
+/// and an uppercase letter, before the last uppercase letter of a
+/// run of uppercase letters followed by a lowercase letter (as in
+/// `HTTP|Server`), and after an underscore between two alphanumeric
+/// characters. No hyphen is added when a word is split, since a
+/// hyphen would change the identifier.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, IdentifierSplitter, Options, WordSplitter};
+///
+/// assert_eq!(IdentifierSplitter.split_points("VeryLongFunctionName"), vec![4, 8, 16]);
+/// assert_eq!(IdentifierSplitter.split_points("some_long_variable"), vec![5, 10]);
+/// assert_eq!(IdentifierSplitter.split_points("HTTPServer"), vec![4]);
+///
+/// let options = Options::new(18).splitter(IdentifierSplitter);
+/// assert_eq!(wrap("call VeryLongFunctionName(some_long_variable)", &options),
+///            vec!["call VeryLong", "FunctionName(some_", "long_variable)"]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct IdentifierSplitter;
+
+/// `IdentifierSplitter` implements `WordSplitter` by splitting at
+/// case changes and after underscores.
+impl WordSplitter for IdentifierSplitter {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        let mut splits = Vec::new();
+        let mut chars = word.char_indices().peekable();
+        let mut prev: Option<char> = None;
+        while let Some((idx, ch)) = chars.next() {
+            let next = chars.peek().map(|&(_, next)| next);
+            if let Some(prev) = prev {
+                let case_change = ch.is_uppercase()
+                    && (prev.is_lowercase()
+                        || prev.is_numeric()
+                        || prev.is_uppercase() && next.filter(|c| c.is_lowercase()).is_some());
+                let after_underscore = prev == '_'
+                    && ch.is_alphanumeric()
+                    && word[..idx - 1]
+                        .chars()
+                        .next_back()
+                        .filter(|c| c.is_alphanumeric())
+                        .is_some();
+                if case_change || after_underscore {
+                    splits.push(idx);
+                }
+            }
+            prev = Some(ch);
+        }
+        splits
+    }
+
+    fn inserts_hyphen(&self) -> bool {
+        false
+    }
+}
+
+/// Hyphenation exceptions which take precedence over another
+/// splitter.
+///
+/// Pattern-based hyphenation is never perfect, and publishers often
+/// have a house style for how certain words are hyphenated. A
+/// `HyphenationExceptions` splitter holds a list of such exceptions
+/// and uses the inner splitter for all other words. An exception is
+/// written like in the `\hyphenation{...}` section of TeX files: the
+/// word with a hyphen at every allowed split point. A word without
+/// hyphens is never split.
+///
+/// Exceptions are matched case-insensitively against the word with
+/// leading and trailing punctuation removed, so an exception for
+/// `pro-ject` also applies to `"Project,"`.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{HyphenSplitter, HyphenationExceptions, WordSplitter};
+///
+/// let mut splitter = HyphenationExceptions::new(HyphenSplitter);
+/// splitter.add_exceptions("pro-ject  \\hyphenation{ e-mail table }");
+/// assert_eq!(splitter.split_points("Project,"), vec![3]);
+/// assert_eq!(splitter.split_points("table"), vec![]);
+/// assert_eq!(splitter.split_points("foo-bar"), vec![4]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct HyphenationExceptions<S> {
+    splitter: S,
+    // The split points, counted in characters, keyed by the
+    // lower-cased word without hyphens.
+    exceptions: std::collections::HashMap<String, Vec<usize>>,
+}
+
+impl<S: WordSplitter> HyphenationExceptions<S> {
+    /// Create a new splitter without exceptions. Words are split by
+    /// `splitter` until exceptions are added.
+    pub fn new(splitter: S) -> Self {
+        HyphenationExceptions {
+            splitter,
+            exceptions: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Add a single exception, such as `"pro-ject"`. An exception for
+    /// the same word is replaced.
+    ///
+    /// ```
+    /// use textwrap::{HyphenationExceptions, NoHyphenation, WordSplitter};
+    ///
+    /// let mut splitter = HyphenationExceptions::new(NoHyphenation);
+    /// splitter.add_exception("Pro-ject");
+    /// assert_eq!(splitter.split_points("project"), vec![3]);
+    /// ```
+    pub fn add_exception(&mut self, exception: &str) {
+        let mut word = String::new();
+        let mut split_points = Vec::new();
+        for ch in exception.chars() {
+            if ch == '-' {
+                split_points.push(word.chars().count());
+            } else {
+                word.push(ch.to_lowercase().next().unwrap_or(ch));
+            }
+        }
+        self.exceptions.insert(word, split_points);
+    }
+
+    /// Add exceptions separated by whitespace.
+    ///
+    /// Comments starting with `%` are ignored, and so are TeX
+    /// commands such as `\hyphenation` together with the braces
+    /// around the exceptions. This means that a TeX hyphenation
+    /// exception file can be used directly.
+    pub fn add_exceptions(&mut self, exceptions: &str) {
+        let entries = exceptions
+            .lines()
+            .map(|line| line.split('%').next().unwrap_or(""))
+            .flat_map(|line| line.split(|ch: char| ch.is_whitespace() || ch == '{' || ch == '}'))
+            .filter(|entry| !entry.is_empty() && !entry.starts_with('\\'));
+        for entry in entries {
+            self.add_exception(entry);
+        }
+    }
+
+    /// Load exceptions from a file.
+    ///
+    /// The file is parsed with
+    /// [`HyphenationExceptions::add_exceptions`].
+    pub fn load<P: AsRef<std::path::Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let exceptions = std::fs::read_to_string(path)?;
+        self.add_exceptions(&exceptions);
+        Ok(())
+    }
+}
+
+/// `HyphenationExceptions` implements `WordSplitter` by looking up
+/// the word in the exceptions before falling back to the inner
+/// splitter.
+impl<S: WordSplitter + Clone + 'static> WordSplitter for HyphenationExceptions<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        let (start, key) = exception_key(word);
+        match self.exceptions.get(&key) {
+            Some(split_points) => {
+                let core = &word[start..];
+                let offsets = core.char_indices().map(|(idx, _)| idx).collect::<Vec<_>>();
+                split_points
+                    .iter()
+                    .filter_map(|&n| offsets.get(n))
+                    .map(|&idx| start + idx)
+                    .collect()
+            }
+            None => self.splitter.split_points(word),
+        }
+    }
+
+    fn fallback_points(&self, word: &str) -> Vec<usize> {
+        if self.exceptions.contains_key(&exception_key(word).1) {
+            return Vec::new();
+        }
+        self.splitter.fallback_points(word)
+    }
+
+    fn inserts_hyphen(&self) -> bool {
+        self.splitter.inserts_hyphen()
+    }
+}
+
+/// The byte offset of `word` without leading punctuation and the
+/// lower-cased word without leading and trailing punctuation.
+fn exception_key(word: &str) -> (usize, String) {
+    let is_punctuation = |ch: char| !ch.is_alphanumeric();
+    let core = word.trim_start_matches(is_punctuation);
+    let start = word.len() - core.len();
+    let key = core
+        .trim_end_matches(is_punctuation)
+        .chars()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(ch))
+        .collect();
+    (start, key)
+}
+
+/// Prevent another splitter from leaving short fragments.
+///
+/// Typesetting practice is to never split a word so that only one or
+/// two letters are left on either side of the split: a line ending in
+/// `a-` or a line starting with `ly` is hard to read. A
+/// `MinFragments` splitter removes the split points of the inner
+/// splitter which leave fewer than [`MinFragments::min_prefix`]
+/// characters before or fewer than [`MinFragments::min_suffix`]
+/// characters after the split. Only alphanumeric characters are
+/// counted, so hyphens and punctuation around the word do not make a
+/// fragment long enough.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{HyphenSplitter, MinFragments, WordSplitter};
+///
+/// assert_eq!(HyphenSplitter.split_points("a-maze-ing"), vec![2, 7]);
+///
+/// let splitter = MinFragments::new(HyphenSplitter);
+/// assert_eq!(splitter.split_points("a-maze-ing"), vec![7]);
+///
+/// let splitter = MinFragments::new(HyphenSplitter).min_suffix(4);
+/// assert_eq!(splitter.split_points("a-maze-ing"), vec![]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MinFragments<S> {
+    splitter: S,
+    /// The minimum number of characters before a split point.
+    /// Defaults to 2.
+    pub min_prefix: usize,
+    /// The minimum number of characters after a split point.
+    /// Defaults to 3.
+    pub min_suffix: usize,
+}
+
+impl<S: WordSplitter> MinFragments<S> {
+    /// Limit the split points of `splitter`. The defaults leave at
+    /// least 2 characters before and 3 characters after a split,
+    /// like TeX does for English.
+    pub fn new(splitter: S) -> Self {
+        MinFragments {
+            splitter,
+            min_prefix: 2,
+            min_suffix: 3,
+        }
+    }
+
+    /// Change [`self.min_prefix`]. The minimum number of characters
+    /// before a split point.
+    ///
+    /// [`self.min_prefix`]: Self::min_prefix
+    pub fn min_prefix(self, min_prefix: usize) -> Self {
+        MinFragments { min_prefix, ..self }
+    }
+
+    /// Change [`self.min_suffix`]. The minimum number of characters
+    /// after a split point.
+    ///
+    /// [`self.min_suffix`]: Self::min_suffix
+    pub fn min_suffix(self, min_suffix: usize) -> Self {
+        MinFragments { min_suffix, ..self }
+    }
+}
+
+impl<S> MinFragments<S> {
+    /// Keep the split points which leave long enough fragments.
+    fn retain(&self, word: &str, mut split_points: Vec<usize>) -> Vec<usize> {
+        let count = |text: &str| text.chars().filter(|ch| ch.is_alphanumeric()).count();
+        split_points.retain(|&idx| {
+            count(&word[..idx]) >= self.min_prefix && count(&word[idx..]) >= self.min_suffix
+        });
+        split_points
+    }
+}
+
+/// `MinFragments` implements `WordSplitter` by filtering the split
+/// points of the inner splitter.
+impl<S: WordSplitter + Clone + 'static> WordSplitter for MinFragments<S> {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        self.retain(word, self.splitter.split_points(word))
+    }
+
+    fn fallback_points(&self, word: &str) -> Vec<usize> {
+        self.retain(word, self.splitter.fallback_points(word))
+    }
+
+    fn inserts_hyphen(&self) -> bool {
+        self.splitter.inserts_hyphen()
+    }
+}
+
 /// A hyphenation dictionary can be used to do language-specific
 /// hyphenation using patterns from the [hyphenation] crate.
 ///
@@ -153,3 +604,172 @@ impl WordSplitter for hyphenation::Standard {
         self.hyphenate(word).breaks
     }
 }
+
+/// Hyphenation with TeX hyphenation patterns loaded at runtime.
+///
+/// The patterns use the format of the `\patterns{...}` section of
+/// TeX hyphenation files, such as the `hyph-*.tex` files from the
+/// [hyph-utf8] project: each pattern is a run of letters with digits
+/// between them, and an odd digit allows a break at its position.
+/// The patterns are applied with Liang's algorithm, the algorithm
+/// used by TeX itself. Like in TeX, a word is never split before its
+/// second letter or after its third-to-last letter. Letters are
+/// lower-cased before they are matched against the patterns, and
+/// each run of letters in a word is hyphenated separately, so
+/// punctuation such as a trailing comma is left alone.
+///
+/// Use this instead of the `hyphenation` feature to hyphenate with
+/// patterns which are not built into the [hyphenation] crate, or to
+/// load them from a file chosen at runtime with
+/// [`TexPatterns::from_path`].
+///
+/// **Note:** Only available when the `tex-patterns` Cargo feature is
+/// enabled.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, Options, TexPatterns, WordSplitter};
+///
+/// let patterns = TexPatterns::parse(
+///     "% A few of the patterns from Knuth's hyphen.tex.
+///      \\patterns{ hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n }",
+/// );
+/// assert_eq!(patterns.split_points("hyphenation"), vec![2, 6]);
+///
+/// let options = Options::new(8).splitter(patterns);
+/// assert_eq!(wrap("Use hyphenation", &options),
+///            vec!["Use", "hyphen-", "ation"]);
+/// ```
+///
+/// [hyph-utf8]: https://www.hyphenation.org/
+/// [hyphenation]: https://docs.rs/hyphenation/
+#[cfg(feature = "tex-patterns")]
+#[derive(Clone, Debug, Default)]
+pub struct TexPatterns {
+    // The digits of each pattern keyed by its letters. There is one
+    // more digit than there are letters.
+    patterns: std::collections::HashMap<String, Vec<u8>>,
+    // The number of letters in the longest pattern.
+    max_len: usize,
+}
+
+#[cfg(feature = "tex-patterns")]
+impl TexPatterns {
+    /// Parse TeX hyphenation patterns.
+    ///
+    /// The patterns are separated by whitespace. Comments starting
+    /// with `%` are ignored, and so are TeX commands such as
+    /// `\patterns` together with the braces around the patterns.
+    /// This means that the content of a TeX hyphenation file can be
+    /// parsed directly.
+    ///
+    /// ```
+    /// use textwrap::{TexPatterns, WordSplitter};
+    ///
+    /// let patterns = TexPatterns::parse("1na 1tio 2io o2n");
+    /// assert_eq!(patterns.split_points("nation"), vec![2]);
+    /// ```
+    pub fn parse(patterns: &str) -> Self {
+        let mut result = TexPatterns::default();
+        let tokens = patterns
+            .lines()
+            .map(|line| line.split('%').next().unwrap_or(""))
+            .flat_map(|line| line.split(|ch: char| ch.is_whitespace() || ch == '{' || ch == '}'))
+            .filter(|token| !token.is_empty() && !token.starts_with('\\'));
+        for token in tokens {
+            let mut letters = String::new();
+            let mut digits = vec![0];
+            for ch in token.chars() {
+                match ch.to_digit(10) {
+                    Some(digit) => *digits.last_mut().unwrap() = digit as u8,
+                    None => {
+                        letters.extend(ch.to_lowercase());
+                        digits.push(0);
+                    }
+                }
+            }
+            result.max_len = result.max_len.max(digits.len() - 1);
+            result.patterns.insert(letters, digits);
+        }
+        result
+    }
+
+    /// Load TeX hyphenation patterns from a file.
+    ///
+    /// The file is parsed with [`TexPatterns::parse`].
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        std::fs::read_to_string(path).map(|patterns| TexPatterns::parse(&patterns))
+    }
+
+    /// Find the split points of a single run of letters. The `start`
+    /// is the byte offset of the run in the word.
+    fn hyphenate(&self, letters: &[(usize, char)], start: usize, splits: &mut Vec<usize>) {
+        // Minimum number of letters before and after a split, using
+        // the defaults of TeX for English.
+        const LEFT_MIN: usize = 2;
+        const RIGHT_MIN: usize = 3;
+        if letters.len() < LEFT_MIN + RIGHT_MIN {
+            return;
+        }
+
+        // The letters with the word boundary markers used by the
+        // patterns. Letters are lower-cased one by one so that
+        // positions stay the same.
+        let mut chars = vec!['.'];
+        chars.extend(
+            letters
+                .iter()
+                .map(|&(_, ch)| ch.to_lowercase().next().unwrap_or(ch)),
+        );
+        chars.push('.');
+
+        let mut points = vec![0u8; chars.len() + 1];
+        let mut key = String::new();
+        for i in 0..chars.len() {
+            key.clear();
+            for &ch in &chars[i..chars.len().min(i + self.max_len)] {
+                key.push(ch);
+                if let Some(digits) = self.patterns.get(&key) {
+                    for (k, &digit) in digits.iter().enumerate() {
+                        points[i + k] = points[i + k].max(digit);
+                    }
+                }
+            }
+        }
+
+        // Position i in `letters` corresponds to position i + 1 in
+        // `chars` due to the leading marker.
+        for i in LEFT_MIN..=letters.len() - RIGHT_MIN {
+            if points[i + 1] % 2 == 1 {
+                splits.push(start + letters[i].0);
+            }
+        }
+    }
+}
+
+/// `TexPatterns` implements `WordSplitter` by hyphenating each run of
+/// letters in the word with the patterns.
+#[cfg(feature = "tex-patterns")]
+impl WordSplitter for TexPatterns {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        let mut splits = Vec::new();
+        let mut letters = Vec::new();
+        for (idx, ch) in word
+            .char_indices()
+            .chain(std::iter::once((word.len(), ' ')))
+        {
+            if ch.is_alphabetic() {
+                letters.push((idx, ch));
+            } else if !letters.is_empty() {
+                let start = letters[0].0;
+                for letter in letters.iter_mut() {
+                    letter.0 -= start;
+                }
+                self.hyphenate(&letters, start, &mut splits);
+                letters.clear();
+            }
+        }
+        splits
+    }
+}
@@ -0,0 +1,1026 @@
+//! Measuring the displayed width of text.
+
+use crate::core::{
+    self, is_grapheme_extend, is_regional_indicator, skip_ansi_escape_sequence, SOFT_HYPHEN,
+    ZERO_WIDTH_JOINER,
+};
+use crate::{replace_control_chars, ControlChars, Options};
+
+/// Measures the displayed width of characters and strings.
+///
+/// The wrapping algorithms only see the widths of words, so they
+/// work with any unit: columns in a terminal or pixels in a
+/// proportional font. The [`Options::width`](crate::Options::width)
+/// must then be given in the same unit. Use
+/// [`Options::char_width`](crate::Options::char_width) to change
+/// how text is measured.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{AsciiWidth, CharWidth, UnicodeWidth, WcWidth};
+///
+/// assert_eq!(UnicodeWidth.str_width("Café 你好"), 9);
+/// assert_eq!(AsciiWidth.str_width("Café 你好"), 7);
+/// assert_eq!(WcWidth.str_width("Café 你好"), 9);
+/// ```
+pub trait CharWidth: std::fmt::Debug + Send + Sync {
+    /// The displayed width of `ch`.
+    ///
+    /// Control characters should have no width, see
+    /// [`Options::control_chars`](crate::Options::control_chars).
+    fn char_width(&self, ch: char) -> usize;
+
+    /// The displayed width of `text`.
+    ///
+    /// The default implementation adds up the width of every
+    /// character. ANSI escape sequences and U+00AD (Soft Hyphen),
+    /// which is invisible unless a line is broken at it, are skipped.
+    fn str_width(&self, text: &str) -> usize {
+        let mut chars = text.chars();
+        let mut width = 0;
+        while let Some(ch) = chars.next() {
+            if skip_ansi_escape_sequence(ch, &mut chars) || ch == SOFT_HYPHEN {
+                continue;
+            }
+            width += self.char_width(ch);
+        }
+        width
+    }
+}
+
+/// Measure text like [`core::display_width`].
+///
+/// This uses the `unicode-width` crate when the Cargo feature of the
+/// same name is enabled, and a crude approximation otherwise. This is
+/// how text is measured unless
+/// [`Options::char_width`](crate::Options::char_width) is set.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnicodeWidth;
+
+impl CharWidth for UnicodeWidth {
+    #[inline]
+    fn char_width(&self, ch: char) -> usize {
+        core::ch_width(ch)
+    }
+
+    #[inline]
+    fn str_width(&self, text: &str) -> usize {
+        core::display_width(text)
+    }
+}
+
+/// Measure text like the classic `wcwidth` function by Markus Kuhn.
+///
+/// Many terminals and C libraries still use these tables from
+/// Unicode 5.0. Combining marks and control characters have no
+/// width, CJK characters are two columns wide, and everything else,
+/// including emojis, is one column wide. Use this to match the
+/// output of such a terminal.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{CharWidth, UnicodeWidth, WcWidth};
+///
+/// assert_eq!(WcWidth.str_width("Cafe\u{301}"), 4);
+/// assert_eq!(WcWidth.str_width("😊"), 1);
+/// assert_eq!(UnicodeWidth.str_width("😊"), 2);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WcWidth;
+
+impl CharWidth for WcWidth {
+    fn char_width(&self, ch: char) -> usize {
+        if ch < ' ' || ('\u{7f}'..'\u{a0}').contains(&ch) {
+            return 0;
+        }
+        if core::is_grapheme_extend(ch)
+            || ('\u{1160}'..='\u{11ff}').contains(&ch) // Hangul Jamo medial vowels
+            || ch == '\u{200b}'
+        {
+            return 0;
+        }
+        let wide = matches!(ch,
+            '\u{1100}'..='\u{115f}'       // Hangul Jamo initial consonants
+            | '\u{2329}' | '\u{232a}'
+            | '\u{2e80}'..='\u{303e}'     // CJK Radicals .. CJK Symbols
+            | '\u{3040}'..='\u{a4cf}'     // Hiragana .. Yi
+            | '\u{ac00}'..='\u{d7a3}'     // Hangul Syllables
+            | '\u{f900}'..='\u{faff}'     // CJK Compatibility Ideographs
+            | '\u{fe10}'..='\u{fe19}'     // Vertical forms
+            | '\u{fe30}'..='\u{fe6f}'     // CJK Compatibility Forms
+            | '\u{ff00}'..='\u{ff60}'     // Fullwidth Forms
+            | '\u{ffe0}'..='\u{ffe6}'
+            | '\u{20000}'..='\u{2fffd}'
+            | '\u{30000}'..='\u{3fffd}'
+        );
+        if wide {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// Measure every character as one column wide.
+///
+/// This is exact and fast for ASCII text, and it is what a simple
+/// character count gives for other text. Control characters have no
+/// width.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsciiWidth;
+
+impl CharWidth for AsciiWidth {
+    #[inline]
+    fn char_width(&self, ch: char) -> usize {
+        usize::from(!ch.is_control())
+    }
+
+    fn str_width(&self, text: &str) -> usize {
+        if text.bytes().all(|b| (b' '..b'\x7f').contains(&b)) {
+            return text.len();
+        }
+        let mut chars = text.chars();
+        let mut width = 0;
+        while let Some(ch) = chars.next() {
+            if skip_ansi_escape_sequence(ch, &mut chars) || ch == SOFT_HYPHEN {
+                continue;
+            }
+            width += self.char_width(ch);
+        }
+        width
+    }
+}
+
+/// Use a closure to measure characters, for example with the metrics
+/// of a font.
+///
+/// The closure returns the width of a character in any unit, such as
+/// pixels. The width of the lines must be given in the same unit.
+/// Lines are still built from the characters of the text, so an
+/// alignment other than [`Alignment::Left`](crate::Alignment::Left)
+/// pads the lines with as many spaces as fit in the extra width.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{wrap, FontMetrics, Options};
+///
+/// // A proportional font where narrow letters are 3 pixels wide,
+/// // wide letters are 9 pixels wide, and other characters are 6
+/// // pixels wide.
+/// let font = FontMetrics::new(|ch| match ch {
+///     'i' | 'l' | 't' | ' ' => 3,
+///     'm' | 'w' | 'M' => 9,
+///     _ => 6,
+/// });
+/// let options = Options::new(90).char_width(font);
+/// assert_eq!(wrap("Memory safety without garbage collection.", &options),
+///            vec!["Memory safety", "without garbage", "collection."]);
+/// ```
+pub struct FontMetrics<F> {
+    char_width: F,
+}
+
+impl<F> FontMetrics<F>
+where
+    F: Fn(char) -> usize + Send + Sync,
+{
+    /// Create new font metrics which use `char_width` to measure
+    /// every character.
+    pub const fn new(char_width: F) -> Self {
+        FontMetrics { char_width }
+    }
+}
+
+impl<F> std::fmt::Debug for FontMetrics<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FontMetrics").finish()
+    }
+}
+
+/// `FontMetrics` implements `CharWidth` by calling the closure.
+impl<F> CharWidth for FontMetrics<F>
+where
+    F: Fn(char) -> usize + Send + Sync,
+{
+    fn char_width(&self, ch: char) -> usize {
+        (self.char_width)(ch)
+    }
+}
+
+/// How East Asian ambiguous characters are measured. See
+/// [`Options::ambiguous_width`](crate::Options::ambiguous_width).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmbiguousWidth {
+    /// Ambiguous characters are one column wide, as in most Western
+    /// terminals. This is the default.
+    #[default]
+    Narrow,
+    /// Ambiguous characters are two columns wide, as in terminals
+    /// configured for Chinese, Japanese, or Korean text.
+    Wide,
+}
+
+/// How grapheme clusters are measured. See
+/// [`Options::grapheme_width`](crate::Options::grapheme_width).
+///
+/// A grapheme cluster is what a reader sees as a single character,
+/// such as an emoji made from several emojis joined by U+200D (Zero
+/// Width Joiner), a flag made from two regional indicators, or an
+/// emoji with a skin tone modifier. The emoji presentation of a
+/// character is its colorful form, which is usually two columns
+/// wide. Some characters, such as “❤” (U+2764), are shown in text
+/// presentation unless they are followed by U+FE0F (Variation
+/// Selector-16).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraphemeWidth {
+    /// Every character is measured on its own and the widths are
+    /// added up. This is the default.
+    #[default]
+    Characters,
+    /// Every grapheme cluster is measured once, by its first
+    /// character. A flag is two columns wide, and so is a character
+    /// followed by U+FE0F (Variation Selector-16), which selects the
+    /// emoji presentation.
+    Clusters,
+    /// Like [`GraphemeWidth::Clusters`], but emojis are shown in
+    /// emoji presentation unless they are followed by U+FE0E
+    /// (Variation Selector-15). Use this for terminals which show
+    /// “❤” as a colorful heart.
+    EmojiPresentation,
+    /// Like [`GraphemeWidth::Clusters`], but variation selectors are
+    /// ignored. Use this for terminals which do not support emoji
+    /// presentation.
+    TextPresentation,
+}
+
+/// How [`display_width`] measures text.
+///
+/// The settings are the same as the ones in [`Options`] which change
+/// how text is measured, so converting the [`Options`] gives a
+/// `WidthOptions` which measures text exactly like the wrapper.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{display_width, AmbiguousWidth, Options, WidthOptions};
+///
+/// let options = Options::new(80).ambiguous_width(AmbiguousWidth::Wide);
+/// assert_eq!(display_width("α β", &WidthOptions::from(&options)), 5);
+/// assert_eq!(display_width("α β", &WidthOptions::new()), 3);
+/// ```
+///
+/// [`Options`]: crate::Options
+#[derive(Debug, Clone, Default)]
+pub struct WidthOptions {
+    /// How characters are measured, if not by [`UnicodeWidth`]. See
+    /// the [`WidthOptions::char_width`] method.
+    pub char_width: Option<std::sync::Arc<dyn CharWidth>>,
+    /// How East Asian ambiguous characters are measured. See the
+    /// [`WidthOptions::ambiguous_width`] method.
+    pub ambiguous_width: AmbiguousWidth,
+    /// How grapheme clusters are measured. See the
+    /// [`WidthOptions::grapheme_width`] method.
+    pub grapheme_width: GraphemeWidth,
+    /// How control characters are measured. See the
+    /// [`WidthOptions::control_chars`] method.
+    pub control_chars: ControlChars,
+    /// Expand tabs to this many columns, if set. See the
+    /// [`WidthOptions::expand_tabs`] method.
+    pub tab_width: Option<usize>,
+}
+
+impl WidthOptions {
+    /// Measure text like [`core::display_width`].
+    pub const fn new() -> Self {
+        WidthOptions {
+            char_width: None,
+            ambiguous_width: AmbiguousWidth::Narrow,
+            grapheme_width: GraphemeWidth::Characters,
+            control_chars: ControlChars::Keep,
+            tab_width: None,
+        }
+    }
+
+    /// Change [`self.char_width`]. This works like
+    /// [`Options::char_width`](crate::Options::char_width).
+    ///
+    /// [`self.char_width`]: #structfield.char_width
+    pub fn char_width<W: CharWidth + 'static>(self, char_width: W) -> Self {
+        WidthOptions {
+            char_width: Some(std::sync::Arc::new(char_width)),
+            ..self
+        }
+    }
+
+    /// Change [`self.ambiguous_width`]. This works like
+    /// [`Options::ambiguous_width`](crate::Options::ambiguous_width).
+    ///
+    /// [`self.ambiguous_width`]: #structfield.ambiguous_width
+    pub fn ambiguous_width(self, ambiguous_width: AmbiguousWidth) -> Self {
+        WidthOptions {
+            ambiguous_width,
+            ..self
+        }
+    }
+
+    /// Change [`self.grapheme_width`]. This works like
+    /// [`Options::grapheme_width`](crate::Options::grapheme_width).
+    ///
+    /// [`self.grapheme_width`]: #structfield.grapheme_width
+    pub fn grapheme_width(self, grapheme_width: GraphemeWidth) -> Self {
+        WidthOptions {
+            grapheme_width,
+            ..self
+        }
+    }
+
+    /// Change [`self.control_chars`]. This works like
+    /// [`Options::control_chars`](crate::Options::control_chars):
+    /// with [`ControlChars::Replace`], control characters are as
+    /// wide as the symbols which replace them.
+    ///
+    /// [`self.control_chars`]: #structfield.control_chars
+    pub fn control_chars(self, control_chars: ControlChars) -> Self {
+        WidthOptions {
+            control_chars,
+            ..self
+        }
+    }
+
+    /// Change [`self.tab_width`]. A tab then advances the width to
+    /// the next multiple of `tab_width`, like a terminal with tab
+    /// stops every `tab_width` columns. Without this, tabs are
+    /// measured like other control characters, which is how the
+    /// wrapper measures them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{display_width, WidthOptions};
+    ///
+    /// assert_eq!(display_width("ab\tc", &WidthOptions::new()), 3);
+    /// assert_eq!(display_width("ab\tc", &WidthOptions::new().expand_tabs(8)), 9);
+    /// ```
+    ///
+    /// [`self.tab_width`]: #structfield.tab_width
+    pub fn expand_tabs(self, tab_width: usize) -> Self {
+        WidthOptions {
+            tab_width: Some(tab_width),
+            ..self
+        }
+    }
+
+    fn measure(&self) -> Measure<'_> {
+        Measure::new(
+            self.char_width.as_deref(),
+            self.ambiguous_width,
+            self.grapheme_width,
+        )
+    }
+}
+
+impl<'a, A, R, S> From<&'a Options<'a, A, R, S>> for WidthOptions {
+    fn from(options: &'a Options<'a, A, R, S>) -> Self {
+        WidthOptions {
+            char_width: options.char_width.clone(),
+            ambiguous_width: options.ambiguous_width,
+            grapheme_width: options.grapheme_width,
+            control_chars: options.control_chars,
+            tab_width: None,
+        }
+    }
+}
+
+/// Compute the displayed width of a line of `text`.
+///
+/// Unlike [`core::display_width`], this honors the settings in
+/// `options`, so applications can measure text, such as a table
+/// column or a status line, the exact same way as the wrapper does.
+/// ANSI escape sequences and soft hyphens are not counted.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{display_width, GraphemeWidth, WidthOptions};
+///
+/// let text = "\x1b[1mLove:\x1b[0m ❤\u{fe0f}";
+/// assert_eq!(display_width(text, &WidthOptions::new()), 7);
+/// let options = WidthOptions::new().grapheme_width(GraphemeWidth::Clusters);
+/// assert_eq!(display_width(text, &options), 8);
+/// ```
+pub fn display_width(text: &str, options: &WidthOptions) -> usize {
+    let replaced = replace_control_chars(text, options.control_chars);
+    let text = replaced.as_deref().unwrap_or(text);
+    let measure = options.measure();
+    let tab_width = match options.tab_width {
+        Some(tab_width) => std::cmp::max(tab_width, 1),
+        None => return measure.str_width(text),
+    };
+    let mut segments = text.split('\t');
+    let mut width = segments
+        .next()
+        .map_or(0, |segment| measure.str_width(segment));
+    for segment in segments {
+        width += tab_width - width % tab_width;
+        width += measure.str_width(segment);
+    }
+    width
+}
+
+/// U+FE0E (Variation Selector-15) selects the text presentation.
+const TEXT_PRESENTATION_SELECTOR: char = '\u{fe0e}';
+
+/// U+FE0F (Variation Selector-16) selects the emoji presentation.
+const EMOJI_PRESENTATION_SELECTOR: char = '\u{fe0f}';
+
+/// Emojis which are shown in text presentation by default. Emojis
+/// with emoji presentation by default are already two columns wide.
+const TEXT_DEFAULT_EMOJIS: &[(char, char)] = &[
+    ('\u{a9}', '\u{a9}'),
+    ('\u{ae}', '\u{ae}'),
+    ('\u{203c}', '\u{203c}'),
+    ('\u{2049}', '\u{2049}'),
+    ('\u{2122}', '\u{2122}'),
+    ('\u{2139}', '\u{2139}'),
+    ('\u{2194}', '\u{2199}'),
+    ('\u{21a9}', '\u{21aa}'),
+    ('\u{2328}', '\u{2328}'),
+    ('\u{23cf}', '\u{23cf}'),
+    ('\u{23ed}', '\u{23ef}'),
+    ('\u{23f1}', '\u{23f2}'),
+    ('\u{23f8}', '\u{23fa}'),
+    ('\u{24c2}', '\u{24c2}'),
+    ('\u{25aa}', '\u{25ab}'),
+    ('\u{25b6}', '\u{25b6}'),
+    ('\u{25c0}', '\u{25c0}'),
+    ('\u{25fb}', '\u{25fc}'),
+    ('\u{2600}', '\u{2604}'),
+    ('\u{260e}', '\u{260e}'),
+    ('\u{2611}', '\u{2611}'),
+    ('\u{2618}', '\u{2618}'),
+    ('\u{261d}', '\u{261d}'),
+    ('\u{2620}', '\u{2620}'),
+    ('\u{2622}', '\u{2623}'),
+    ('\u{2626}', '\u{2626}'),
+    ('\u{262a}', '\u{262a}'),
+    ('\u{262e}', '\u{262f}'),
+    ('\u{2638}', '\u{263a}'),
+    ('\u{2640}', '\u{2640}'),
+    ('\u{2642}', '\u{2642}'),
+    ('\u{265f}', '\u{2660}'),
+    ('\u{2663}', '\u{2663}'),
+    ('\u{2665}', '\u{2666}'),
+    ('\u{2668}', '\u{2668}'),
+    ('\u{267b}', '\u{267b}'),
+    ('\u{267e}', '\u{267e}'),
+    ('\u{2692}', '\u{2692}'),
+    ('\u{2694}', '\u{2697}'),
+    ('\u{2699}', '\u{2699}'),
+    ('\u{269b}', '\u{269c}'),
+    ('\u{26a0}', '\u{26a0}'),
+    ('\u{26a7}', '\u{26a7}'),
+    ('\u{26b0}', '\u{26b1}'),
+    ('\u{26c8}', '\u{26c8}'),
+    ('\u{26cf}', '\u{26cf}'),
+    ('\u{26d1}', '\u{26d1}'),
+    ('\u{26d3}', '\u{26d3}'),
+    ('\u{26e9}', '\u{26e9}'),
+    ('\u{26f0}', '\u{26f1}'),
+    ('\u{26f4}', '\u{26f4}'),
+    ('\u{26f7}', '\u{26f9}'),
+    ('\u{2702}', '\u{2702}'),
+    ('\u{2708}', '\u{2709}'),
+    ('\u{270c}', '\u{270d}'),
+    ('\u{270f}', '\u{270f}'),
+    ('\u{2712}', '\u{2712}'),
+    ('\u{2714}', '\u{2714}'),
+    ('\u{2716}', '\u{2716}'),
+    ('\u{271d}', '\u{271d}'),
+    ('\u{2721}', '\u{2721}'),
+    ('\u{2733}', '\u{2734}'),
+    ('\u{2744}', '\u{2744}'),
+    ('\u{2747}', '\u{2747}'),
+    ('\u{2763}', '\u{2764}'),
+    ('\u{27a1}', '\u{27a1}'),
+    ('\u{2934}', '\u{2935}'),
+    ('\u{2b05}', '\u{2b07}'),
+    ('\u{3030}', '\u{3030}'),
+    ('\u{303d}', '\u{303d}'),
+    ('\u{3297}', '\u{3297}'),
+    ('\u{3299}', '\u{3299}'),
+    ('\u{1f170}', '\u{1f171}'),
+    ('\u{1f17e}', '\u{1f17f}'),
+    ('\u{1f321}', '\u{1f321}'),
+    ('\u{1f324}', '\u{1f32c}'),
+    ('\u{1f336}', '\u{1f336}'),
+    ('\u{1f37d}', '\u{1f37d}'),
+    ('\u{1f396}', '\u{1f397}'),
+    ('\u{1f399}', '\u{1f39b}'),
+    ('\u{1f39e}', '\u{1f39f}'),
+    ('\u{1f3cb}', '\u{1f3ce}'),
+    ('\u{1f3d4}', '\u{1f3df}'),
+    ('\u{1f3f3}', '\u{1f3f3}'),
+    ('\u{1f3f5}', '\u{1f3f5}'),
+    ('\u{1f3f7}', '\u{1f3f7}'),
+    ('\u{1f43f}', '\u{1f43f}'),
+    ('\u{1f441}', '\u{1f441}'),
+    ('\u{1f4fd}', '\u{1f4fd}'),
+    ('\u{1f549}', '\u{1f54a}'),
+    ('\u{1f56f}', '\u{1f570}'),
+    ('\u{1f573}', '\u{1f579}'),
+    ('\u{1f587}', '\u{1f587}'),
+    ('\u{1f58a}', '\u{1f58d}'),
+    ('\u{1f590}', '\u{1f590}'),
+    ('\u{1f5a5}', '\u{1f5a5}'),
+    ('\u{1f5a8}', '\u{1f5a8}'),
+    ('\u{1f5b1}', '\u{1f5b2}'),
+    ('\u{1f5bc}', '\u{1f5bc}'),
+    ('\u{1f5c2}', '\u{1f5c4}'),
+    ('\u{1f5d1}', '\u{1f5d3}'),
+    ('\u{1f5dc}', '\u{1f5de}'),
+    ('\u{1f5e1}', '\u{1f5e1}'),
+    ('\u{1f5e3}', '\u{1f5e3}'),
+    ('\u{1f5e8}', '\u{1f5e8}'),
+    ('\u{1f5ef}', '\u{1f5ef}'),
+    ('\u{1f5f3}', '\u{1f5f3}'),
+    ('\u{1f5fa}', '\u{1f5fa}'),
+    ('\u{1f6cb}', '\u{1f6cb}'),
+    ('\u{1f6cd}', '\u{1f6cf}'),
+    ('\u{1f6e0}', '\u{1f6e5}'),
+    ('\u{1f6e9}', '\u{1f6e9}'),
+    ('\u{1f6f0}', '\u{1f6f0}'),
+    ('\u{1f6f3}', '\u{1f6f3}'),
+];
+
+/// Is `ch` in one of the sorted, non-overlapping `ranges`?
+fn in_ranges(ranges: &[(char, char)], ch: char) -> bool {
+    ranges
+        .binary_search_by(|&(start, end)| {
+            if end < ch {
+                std::cmp::Ordering::Less
+            } else if start > ch {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Characters with the East Asian Width property "A" (ambiguous),
+/// see [Unicode Standard Annex #11][uax11]. Zero-width characters and
+/// private use characters are left out since they are never widened.
+///
+/// [uax11]: https://www.unicode.org/reports/tr11/
+const AMBIGUOUS: &[(char, char)] = &[
+    ('\u{a1}', '\u{a1}'),
+    ('\u{a4}', '\u{a4}'),
+    ('\u{a7}', '\u{a8}'),
+    ('\u{aa}', '\u{aa}'),
+    ('\u{ae}', '\u{ae}'),
+    ('\u{b0}', '\u{b4}'),
+    ('\u{b6}', '\u{ba}'),
+    ('\u{bc}', '\u{bf}'),
+    ('\u{c6}', '\u{c6}'),
+    ('\u{d0}', '\u{d0}'),
+    ('\u{d7}', '\u{d8}'),
+    ('\u{de}', '\u{e1}'),
+    ('\u{e6}', '\u{e6}'),
+    ('\u{e8}', '\u{ea}'),
+    ('\u{ec}', '\u{ed}'),
+    ('\u{f0}', '\u{f0}'),
+    ('\u{f2}', '\u{f3}'),
+    ('\u{f7}', '\u{fa}'),
+    ('\u{fc}', '\u{fc}'),
+    ('\u{fe}', '\u{fe}'),
+    ('\u{101}', '\u{101}'),
+    ('\u{111}', '\u{111}'),
+    ('\u{113}', '\u{113}'),
+    ('\u{11b}', '\u{11b}'),
+    ('\u{126}', '\u{127}'),
+    ('\u{12b}', '\u{12b}'),
+    ('\u{131}', '\u{133}'),
+    ('\u{138}', '\u{138}'),
+    ('\u{13f}', '\u{142}'),
+    ('\u{144}', '\u{144}'),
+    ('\u{148}', '\u{14b}'),
+    ('\u{14d}', '\u{14d}'),
+    ('\u{152}', '\u{153}'),
+    ('\u{166}', '\u{167}'),
+    ('\u{16b}', '\u{16b}'),
+    ('\u{1ce}', '\u{1ce}'),
+    ('\u{1d0}', '\u{1d0}'),
+    ('\u{1d2}', '\u{1d2}'),
+    ('\u{1d4}', '\u{1d4}'),
+    ('\u{1d6}', '\u{1d6}'),
+    ('\u{1d8}', '\u{1d8}'),
+    ('\u{1da}', '\u{1da}'),
+    ('\u{1dc}', '\u{1dc}'),
+    ('\u{251}', '\u{251}'),
+    ('\u{261}', '\u{261}'),
+    ('\u{2c4}', '\u{2c4}'),
+    ('\u{2c7}', '\u{2c7}'),
+    ('\u{2c9}', '\u{2cb}'),
+    ('\u{2cd}', '\u{2cd}'),
+    ('\u{2d0}', '\u{2d0}'),
+    ('\u{2d8}', '\u{2db}'),
+    ('\u{2dd}', '\u{2dd}'),
+    ('\u{2df}', '\u{2df}'),
+    ('\u{391}', '\u{3a1}'),
+    ('\u{3a3}', '\u{3a9}'),
+    ('\u{3b1}', '\u{3c1}'),
+    ('\u{3c3}', '\u{3c9}'),
+    ('\u{401}', '\u{401}'),
+    ('\u{410}', '\u{44f}'),
+    ('\u{451}', '\u{451}'),
+    ('\u{2010}', '\u{2010}'),
+    ('\u{2013}', '\u{2016}'),
+    ('\u{2018}', '\u{2019}'),
+    ('\u{201c}', '\u{201d}'),
+    ('\u{2020}', '\u{2022}'),
+    ('\u{2024}', '\u{2027}'),
+    ('\u{2030}', '\u{2030}'),
+    ('\u{2032}', '\u{2033}'),
+    ('\u{2035}', '\u{2035}'),
+    ('\u{203b}', '\u{203b}'),
+    ('\u{203e}', '\u{203e}'),
+    ('\u{2074}', '\u{2074}'),
+    ('\u{207f}', '\u{207f}'),
+    ('\u{2081}', '\u{2084}'),
+    ('\u{20ac}', '\u{20ac}'),
+    ('\u{2103}', '\u{2103}'),
+    ('\u{2105}', '\u{2105}'),
+    ('\u{2109}', '\u{2109}'),
+    ('\u{2113}', '\u{2113}'),
+    ('\u{2116}', '\u{2116}'),
+    ('\u{2121}', '\u{2122}'),
+    ('\u{2126}', '\u{2126}'),
+    ('\u{212b}', '\u{212b}'),
+    ('\u{2153}', '\u{2154}'),
+    ('\u{215b}', '\u{215e}'),
+    ('\u{2160}', '\u{216b}'),
+    ('\u{2170}', '\u{2179}'),
+    ('\u{2189}', '\u{2189}'),
+    ('\u{2190}', '\u{2199}'),
+    ('\u{21b8}', '\u{21b9}'),
+    ('\u{21d2}', '\u{21d2}'),
+    ('\u{21d4}', '\u{21d4}'),
+    ('\u{21e7}', '\u{21e7}'),
+    ('\u{2200}', '\u{2200}'),
+    ('\u{2202}', '\u{2203}'),
+    ('\u{2207}', '\u{2208}'),
+    ('\u{220b}', '\u{220b}'),
+    ('\u{220f}', '\u{220f}'),
+    ('\u{2211}', '\u{2211}'),
+    ('\u{2215}', '\u{2215}'),
+    ('\u{221a}', '\u{221a}'),
+    ('\u{221d}', '\u{2220}'),
+    ('\u{2223}', '\u{2223}'),
+    ('\u{2225}', '\u{2225}'),
+    ('\u{2227}', '\u{222c}'),
+    ('\u{222e}', '\u{222e}'),
+    ('\u{2234}', '\u{2237}'),
+    ('\u{223c}', '\u{223d}'),
+    ('\u{2248}', '\u{2248}'),
+    ('\u{224c}', '\u{224c}'),
+    ('\u{2252}', '\u{2252}'),
+    ('\u{2260}', '\u{2261}'),
+    ('\u{2264}', '\u{2267}'),
+    ('\u{226a}', '\u{226b}'),
+    ('\u{226e}', '\u{226f}'),
+    ('\u{2282}', '\u{2283}'),
+    ('\u{2286}', '\u{2287}'),
+    ('\u{2295}', '\u{2295}'),
+    ('\u{2299}', '\u{2299}'),
+    ('\u{22a5}', '\u{22a5}'),
+    ('\u{22bf}', '\u{22bf}'),
+    ('\u{2312}', '\u{2312}'),
+    ('\u{2460}', '\u{24e9}'),
+    ('\u{24eb}', '\u{254b}'),
+    ('\u{2550}', '\u{2573}'),
+    ('\u{2580}', '\u{258f}'),
+    ('\u{2592}', '\u{2595}'),
+    ('\u{25a0}', '\u{25a1}'),
+    ('\u{25a3}', '\u{25a9}'),
+    ('\u{25b2}', '\u{25b3}'),
+    ('\u{25b6}', '\u{25b7}'),
+    ('\u{25bc}', '\u{25bd}'),
+    ('\u{25c0}', '\u{25c1}'),
+    ('\u{25c6}', '\u{25c8}'),
+    ('\u{25cb}', '\u{25cb}'),
+    ('\u{25ce}', '\u{25d1}'),
+    ('\u{25e2}', '\u{25e5}'),
+    ('\u{25ef}', '\u{25ef}'),
+    ('\u{2605}', '\u{2606}'),
+    ('\u{2609}', '\u{2609}'),
+    ('\u{260e}', '\u{260f}'),
+    ('\u{261c}', '\u{261c}'),
+    ('\u{261e}', '\u{261e}'),
+    ('\u{2640}', '\u{2640}'),
+    ('\u{2642}', '\u{2642}'),
+    ('\u{2660}', '\u{2661}'),
+    ('\u{2663}', '\u{2665}'),
+    ('\u{2667}', '\u{266a}'),
+    ('\u{266c}', '\u{266d}'),
+    ('\u{266f}', '\u{266f}'),
+    ('\u{269e}', '\u{269f}'),
+    ('\u{26bf}', '\u{26bf}'),
+    ('\u{26c6}', '\u{26cd}'),
+    ('\u{26cf}', '\u{26d3}'),
+    ('\u{26d5}', '\u{26e1}'),
+    ('\u{26e3}', '\u{26e3}'),
+    ('\u{26e8}', '\u{26e9}'),
+    ('\u{26eb}', '\u{26f1}'),
+    ('\u{26f4}', '\u{26f4}'),
+    ('\u{26f6}', '\u{26f9}'),
+    ('\u{26fb}', '\u{26fc}'),
+    ('\u{26fe}', '\u{26ff}'),
+    ('\u{273d}', '\u{273d}'),
+    ('\u{2776}', '\u{277f}'),
+    ('\u{2b56}', '\u{2b59}'),
+    ('\u{3248}', '\u{324f}'),
+    ('\u{fffd}', '\u{fffd}'),
+    ('\u{1f100}', '\u{1f10a}'),
+    ('\u{1f110}', '\u{1f12d}'),
+    ('\u{1f130}', '\u{1f169}'),
+    ('\u{1f170}', '\u{1f18d}'),
+    ('\u{1f18f}', '\u{1f190}'),
+    ('\u{1f19b}', '\u{1f1ac}'),
+];
+
+/// Is `ch` an East Asian ambiguous character?
+pub(crate) fn is_ambiguous(ch: char) -> bool {
+    in_ranges(AMBIGUOUS, ch)
+}
+
+/// The first character of a grapheme cluster and what follows it.
+/// See [`Measure::cluster_width`].
+#[derive(Debug, Clone, Copy)]
+struct Cluster {
+    first: char,
+    last: char,
+    regional_indicators: usize,
+    text_presentation: bool,
+    emoji_presentation: bool,
+}
+
+impl Cluster {
+    fn new(first: char) -> Self {
+        Cluster {
+            first,
+            last: first,
+            regional_indicators: usize::from(is_regional_indicator(first)),
+            text_presentation: false,
+            emoji_presentation: false,
+        }
+    }
+
+    /// Add `ch` to the cluster if it belongs to it. This uses the
+    /// same rules as [`core::Word::break_apart`].
+    fn push(&mut self, ch: char) -> bool {
+        let joined = self.last == ZERO_WIDTH_JOINER
+            || is_grapheme_extend(ch)
+            || is_regional_indicator(ch) && self.regional_indicators == 1;
+        if !joined {
+            return false;
+        }
+        if is_regional_indicator(ch) {
+            self.regional_indicators += 1;
+        }
+        self.text_presentation |= ch == TEXT_PRESENTATION_SELECTOR;
+        self.emoji_presentation |= ch == EMOJI_PRESENTATION_SELECTOR;
+        self.last = ch;
+        true
+    }
+}
+
+/// How [`Options`](crate::Options) measure text: the
+/// [`Options::char_width`](crate::Options::char_width) together with
+/// the settings which adjust it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Measure<'a> {
+    char_width: Option<&'a dyn CharWidth>,
+    ambiguous_width: AmbiguousWidth,
+    grapheme_width: GraphemeWidth,
+}
+
+impl<'a> Measure<'a> {
+    pub(crate) fn new(
+        char_width: Option<&'a dyn CharWidth>,
+        ambiguous_width: AmbiguousWidth,
+        grapheme_width: GraphemeWidth,
+    ) -> Self {
+        Measure {
+            char_width,
+            ambiguous_width,
+            grapheme_width,
+        }
+    }
+
+    /// Is this how text is measured by [`core::display_width`]? The
+    /// words found by a [`WordSeparator`](crate::WordSeparator) are
+    /// already measured like this.
+    pub(crate) fn is_default(&self) -> bool {
+        self.char_width.is_none()
+            && self.ambiguous_width == AmbiguousWidth::Narrow
+            && self.grapheme_width == GraphemeWidth::Characters
+    }
+
+    /// The [`CharWidth`] which is adjusted by the other settings.
+    fn base(&self) -> &'a dyn CharWidth {
+        self.char_width.unwrap_or(&UnicodeWidth)
+    }
+
+    /// The width of a grapheme cluster. This is the width of the
+    /// first character, unless the presentation makes it wider or
+    /// narrower.
+    fn cluster_width(&self, cluster: &Cluster) -> usize {
+        let width = self.char_width(cluster.first);
+        if width == 0 {
+            return 0;
+        }
+        let text_default = width == 1 && in_ranges(TEXT_DEFAULT_EMOJIS, cluster.first);
+        match self.grapheme_width {
+            _ if cluster.regional_indicators == 2 => std::cmp::max(width, 2),
+            GraphemeWidth::TextPresentation => width,
+            _ if cluster.emoji_presentation => std::cmp::max(width, 2),
+            GraphemeWidth::EmojiPresentation if text_default && !cluster.text_presentation => 2,
+            _ => width,
+        }
+    }
+}
+
+impl CharWidth for Measure<'_> {
+    fn char_width(&self, ch: char) -> usize {
+        let width = self.base().char_width(ch);
+        match self.ambiguous_width {
+            AmbiguousWidth::Wide if width == 1 && is_ambiguous(ch) => 2,
+            _ => width,
+        }
+    }
+
+    fn str_width(&self, text: &str) -> usize {
+        let mut chars = text.chars();
+        let mut width = 0;
+        match self.grapheme_width {
+            GraphemeWidth::Characters if self.ambiguous_width == AmbiguousWidth::Narrow => {
+                width = self.base().str_width(text);
+            }
+            GraphemeWidth::Characters => {
+                while let Some(ch) = chars.next() {
+                    if skip_ansi_escape_sequence(ch, &mut chars) || ch == SOFT_HYPHEN {
+                        continue;
+                    }
+                    width += self.char_width(ch);
+                }
+            }
+            _ => {
+                let mut cluster: Option<Cluster> = None;
+                while let Some(ch) = chars.next() {
+                    if skip_ansi_escape_sequence(ch, &mut chars) || ch == SOFT_HYPHEN {
+                        continue;
+                    }
+                    if cluster.as_mut().is_some_and(|cluster| cluster.push(ch)) {
+                        continue;
+                    }
+                    if let Some(cluster) = &cluster {
+                        width += self.cluster_width(cluster);
+                    }
+                    cluster = Some(Cluster::new(ch));
+                }
+                if let Some(cluster) = &cluster {
+                    width += self.cluster_width(cluster);
+                }
+            }
+        }
+        width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_width_with_options() {
+        let options = WidthOptions::new();
+        assert_eq!(display_width("", &options), 0);
+        assert_eq!(display_width("foo\x00bar", &options), 6);
+        assert_eq!(display_width("\t", &options.clone().expand_tabs(4)), 4);
+        assert_eq!(display_width("abcd\te", &options.clone().expand_tabs(4)), 9);
+        assert_eq!(display_width("a\t\tb", &options.clone().expand_tabs(0)), 4);
+        let options = options
+            .char_width(AsciiWidth)
+            .control_chars(ControlChars::Replace);
+        assert_eq!(display_width("foo\x00bar", &options), 7);
+
+        let options = Options::new(10)
+            .char_width(AsciiWidth)
+            .control_chars(ControlChars::Strip);
+        let width_options = WidthOptions::from(&options);
+        assert_eq!(display_width("你好\x07", &width_options), 2);
+        assert_eq!(
+            display_width("你好", &width_options),
+            options.text_width("你好")
+        );
+    }
+
+    #[test]
+    fn str_width_skips_escapes_and_soft_hyphens() {
+        let text = "\x1b[31msoft\u{ad}ware\x1b[0m";
+        assert_eq!(UnicodeWidth.str_width(text), 8);
+        assert_eq!(AsciiWidth.str_width(text), 8);
+        assert_eq!(WcWidth.str_width(text), 8);
+        assert_eq!(FontMetrics::new(|_| 2).str_width(text), 16);
+    }
+
+    #[test]
+    fn ambiguous_width() {
+        assert!(is_ambiguous('\u{a1}'));
+        assert!(is_ambiguous('α'));
+        assert!(is_ambiguous('─'));
+        assert!(is_ambiguous('\u{1f1ac}'));
+        assert!(!is_ambiguous('a'));
+        assert!(!is_ambiguous('\u{a0}'));
+        assert!(!is_ambiguous('你'));
+
+        let wide = Measure::new(None, AmbiguousWidth::Wide, GraphemeWidth::Characters);
+        assert_eq!(wide.str_width("±1°"), 5);
+        assert_eq!(wide.str_width("\x1b[1m§\x1b[0m"), 2);
+        let narrow = Measure::new(None, AmbiguousWidth::Narrow, GraphemeWidth::Characters);
+        assert_eq!(narrow.str_width("±1°"), 3);
+        let wcwidth = Measure::new(
+            Some(&WcWidth),
+            AmbiguousWidth::Wide,
+            GraphemeWidth::Characters,
+        );
+        assert_eq!(wcwidth.str_width("×😊"), 3);
+        let pixels = FontMetrics::new(|_| 6);
+        let pixels = Measure::new(
+            Some(&pixels),
+            AmbiguousWidth::Wide,
+            GraphemeWidth::Characters,
+        );
+        assert_eq!(pixels.str_width("±"), 6);
+    }
+
+    #[test]
+    fn grapheme_width() {
+        let measure = |grapheme_width| {
+            let measure = Measure::new(None, AmbiguousWidth::Narrow, grapheme_width);
+            move |text: &str| measure.str_width(text)
+        };
+        let characters = measure(GraphemeWidth::Characters);
+        let clusters = measure(GraphemeWidth::Clusters);
+        let emoji = measure(GraphemeWidth::EmojiPresentation);
+        let text = measure(GraphemeWidth::TextPresentation);
+
+        // Man with red hair, a family, and thumbs up with a skin tone.
+        for sequence in &[
+            "\u{1f468}\u{200d}\u{1f9b0}",
+            "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}",
+            "\u{1f44d}\u{1f3fd}",
+        ] {
+            assert!(characters(sequence) > 2);
+            assert_eq!(clusters(sequence), 2);
+            assert_eq!(emoji(sequence), 2);
+            assert_eq!(text(sequence), 2);
+        }
+
+        // Two flags, and a regional indicator on its own.
+        let flags = "\u{1f1e9}\u{1f1f0}\u{1f1eb}\u{1f1f7}\u{1f1e9}";
+        assert_eq!(clusters(flags), 4 + clusters("\u{1f1e9}"));
+
+        // Heart, heart with text presentation, and heart with emoji
+        // presentation. Without the `unicode-width` feature, the
+        // heart is always two columns wide.
+        #[cfg(feature = "unicode-width")]
+        {
+            assert_eq!(clusters("\u{2764}"), 1);
+            assert_eq!(clusters("\u{2764}\u{fe0e}"), 1);
+            assert_eq!(clusters("\u{2764}\u{fe0f}"), 2);
+            assert_eq!(emoji("\u{2764}"), 2);
+            assert_eq!(emoji("\u{2764}\u{fe0e}"), 1);
+            assert_eq!(text("\u{2764}\u{fe0f}"), 1);
+        }
+
+        // A keycap, and combining marks.
+        assert_eq!(clusters("1\u{fe0f}\u{20e3}"), 2);
+        assert_eq!(clusters("\x1b[1me\u{301}\x1b[0m"), 1);
+    }
+
+    #[test]
+    fn wcwidth_tables() {
+        assert_eq!(WcWidth.char_width('a'), 1);
+        assert_eq!(WcWidth.char_width('\t'), 0);
+        assert_eq!(WcWidth.char_width('\u{301}'), 0);
+        assert_eq!(WcWidth.char_width('\u{303f}'), 1);
+        assert_eq!(WcWidth.char_width('あ'), 2);
+        assert_eq!(WcWidth.char_width('\u{ff01}'), 2);
+        assert_eq!(WcWidth.char_width('\u{1f600}'), 1);
+        assert_eq!(WcWidth.char_width('\u{20000}'), 2);
+    }
+}
@@ -1,8 +1,10 @@
 //! Line breaking functionality.
 
 #[cfg(feature = "unicode-linebreak")]
-use crate::core::skip_ansi_escape_sequence;
-use crate::core::Word;
+use crate::core::SOFT_HYPHEN;
+use crate::core::{
+    ansi_escape_sequences, inside_escape_sequence, skip_ansi_escape_sequence, BreakPriority, Word,
+};
 
 /// Describes where words occur in a line of text.
 ///
@@ -29,6 +31,83 @@ pub trait WordSeparator: WordSeparatorClone + std::fmt::Debug {
     // https://github.com/rust-lang/rfcs/blob/master/text/1522-conservative-impl-trait.md
     /// Find all words in `line`.
     fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a>;
+
+    /// Find all words in `line` and push them onto `words`.
+    ///
+    /// The default implementation extends `words` with the words
+    /// returned by [`WordSeparator::find_words`]. Implementations can
+    /// override this to avoid allocating the boxed iterator. This is
+    /// used by [`Wrapper`](crate::Wrapper).
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// use textwrap::{AsciiSpace, WordSeparator};
+    ///
+    /// let mut words = vec![Word::from("Hello ")];
+    /// AsciiSpace.find_words_into("World!", &mut words);
+    /// assert_eq!(words, vec![Word::from("Hello "), Word::from("World!")]);
+    /// ```
+    fn find_words_into<'a>(&self, line: &'a str, words: &mut Vec<Word<'a>>) {
+        words.extend(self.find_words(line));
+    }
+
+    /// Split the words found by this word separator with another
+    /// word separator.
+    ///
+    /// The second word separator sees each word without its trailing
+    /// whitespace. The last piece of a word keeps the whitespace.
+    ///
+    /// ```
+    /// #[cfg(feature = "regex")] {
+    /// use textwrap::core::Word;
+    /// use textwrap::{AsciiSpace, RegexSeparator, WordSeparator};
+    ///
+    /// // Break at spaces and after slashes, but never inside backticks.
+    /// let separator = AsciiSpace
+    ///     .and_then(RegexSeparator::new("/").unwrap())
+    ///     .filter_breaks(|line: &str, idx: usize| line[..idx].matches('`').count() % 2 == 0);
+    /// assert_eq!(separator.find_words("see `a/b` or c/d").collect::<Vec<_>>(),
+    ///            vec![Word::from("see "),
+    ///                 Word::from("`a/b` "),
+    ///                 Word::from("or "),
+    ///                 Word::from("c/"),
+    ///                 Word::from("d")]);
+    /// }
+    /// ```
+    fn and_then<T: WordSeparator>(self, then: T) -> AndThen<Self, T>
+    where
+        Self: Sized,
+    {
+        AndThen { first: self, then }
+    }
+
+    /// Keep only the line breaks accepted by `predicate`.
+    ///
+    /// The predicate is called with the line and the byte offset of
+    /// each possible line break. Words are joined where the predicate
+    /// returns `false`.
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// use textwrap::{AsciiSpace, WordSeparator};
+    ///
+    /// // Never break inside backticks.
+    /// let separator = AsciiSpace.filter_breaks(|line: &str, idx: usize| {
+    ///     line[..idx].matches('`').count() % 2 == 0
+    /// });
+    /// assert_eq!(separator.find_words("see `foo bar` now").collect::<Vec<_>>(),
+    ///            vec![Word::from("see "), Word::from("`foo bar` "), Word::from("now")]);
+    /// ```
+    fn filter_breaks<F>(self, predicate: F) -> FilterBreaks<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&str, usize) -> bool,
+    {
+        FilterBreaks {
+            word_separator: self,
+            predicate,
+        }
+    }
 }
 
 // The internal `WordSeparatorClone` trait is allows us to implement
@@ -57,9 +136,20 @@ impl WordSeparator for Box<dyn WordSeparator> {
         use std::ops::Deref;
         self.deref().find_words(line)
     }
+
+    fn find_words_into<'a>(&self, line: &'a str, words: &mut Vec<Word<'a>>) {
+        use std::ops::Deref;
+        self.deref().find_words_into(line, words)
+    }
 }
 
 /// Find words by splitting on regions of `' '` characters.
+///
+/// A U+200B (Zero Width Space) also ends a word. This is an invisible
+/// break opportunity, which can be used to allow breaks in long
+/// identifiers or URLs. Other whitespace characters are part of the
+/// words. In particular, no-break spaces such as U+00A0 never
+/// separate words.
 #[derive(Clone, Copy, Debug, Default)]
 pub struct AsciiSpace;
 
@@ -74,42 +164,61 @@ pub struct AsciiSpace;
 /// let words = AsciiSpace.find_words("Hello   World!").collect::<Vec<_>>();
 /// assert_eq!(words, vec![Word::from("Hello   "),
 ///                        Word::from("World!")]);
+///
+/// let words = AsciiSpace.find_words("snake\u{200b}_case").collect::<Vec<_>>();
+/// assert_eq!(words, vec![Word::from("snake\u{200b}"),
+///                        Word::from("_case")]);
 /// ```
 impl WordSeparator for AsciiSpace {
     fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
-        let mut start = 0;
-        let mut in_whitespace = false;
-        let mut char_indices = line.char_indices();
+        Box::new(find_ascii_words(line))
+    }
 
-        Box::new(std::iter::from_fn(move || {
-            // for (idx, ch) in char_indices does not work, gives this
-            // error:
-            //
-            // > cannot move out of `char_indices`, a captured variable in
-            // > an `FnMut` closure
-            #[allow(clippy::while_let_on_iterator)]
-            while let Some((idx, ch)) = char_indices.next() {
-                if in_whitespace && ch != ' ' {
-                    let word = Word::from(&line[start..idx]);
-                    start = idx;
-                    in_whitespace = ch == ' ';
-                    return Some(word);
-                }
+    fn find_words_into<'a>(&self, line: &'a str, words: &mut Vec<Word<'a>>) {
+        words.extend(find_ascii_words(line));
+    }
+}
 
-                in_whitespace = ch == ' ';
-            }
+fn find_ascii_words(line: &str) -> impl Iterator<Item = Word<'_>> {
+    let mut start = 0;
+    let mut in_whitespace = false;
+    let mut char_indices = line.char_indices();
 
-            if start < line.len() {
-                let word = Word::from(&line[start..]);
-                start = line.len();
+    std::iter::from_fn(move || {
+        // for (idx, ch) in char_indices does not work, gives this
+        // error:
+        //
+        // > cannot move out of `char_indices`, a captured variable in
+        // > an `FnMut` closure
+        #[allow(clippy::while_let_on_iterator)]
+        while let Some((idx, ch)) = char_indices.next() {
+            // ANSI escape sequences can contain spaces, but they are
+            // never split.
+            let escape =
+                skip_ansi_escape_sequence(ch, &mut char_indices.by_ref().map(|(_, ch)| ch));
+            if in_whitespace && ch != ' ' && ch != ZERO_WIDTH_SPACE {
+                let word = Word::from(&line[start..idx]);
+                start = idx;
+                in_whitespace = false;
                 return Some(word);
             }
 
-            None
-        }))
-    }
+            in_whitespace = !escape && (ch == ' ' || ch == ZERO_WIDTH_SPACE);
+        }
+
+        if start < line.len() {
+            let word = Word::from(&line[start..]);
+            start = line.len();
+            return Some(word);
+        }
+
+        None
+    })
 }
 
+/// U+200B (Zero Width Space) marks an invisible break opportunity.
+const ZERO_WIDTH_SPACE: char = '\u{200b}';
+
 /// Find words using the Unicode line breaking algorithm.
 #[cfg(feature = "unicode-linebreak")]
 #[derive(Clone, Copy, Debug, Default)]
@@ -123,8 +232,8 @@ pub struct UnicodeBreakProperties;
 /// to break lines. There is a small difference in that the U+002D
 /// (Hyphen-Minus) and U+00AD (Soft Hyphen) don’t create a line break:
 /// to allow a line break at a hyphen, use the
-/// [`HyphenSplitter`](super::HyphenSplitter). Soft hyphens are not
-/// currently supported.
+/// [`HyphenSplitter`](super::HyphenSplitter). Words are split at soft
+/// hyphens by [`split_words`](crate::core::split_words).
 ///
 /// # Examples
 ///
@@ -163,6 +272,26 @@ pub struct UnicodeBreakProperties;
 /// }
 /// ```
 ///
+/// The same holds for U+00A0 (No-Break Space), U+202F (Narrow
+/// No-Break Space), and U+2007 (Figure Space). This is useful for
+/// keeping a number together with its unit:
+///
+/// ```
+/// #[cfg(feature = "unicode-linebreak")] {
+/// use textwrap::{UnicodeBreakProperties, WordSeparator};
+/// use textwrap::core::Word;
+///
+/// assert_eq!(UnicodeBreakProperties.find_words("only 10\u{a0}km away").collect::<Vec<_>>(),
+///            vec![Word::from("only "),
+///                 Word::from("10\u{a0}km "),
+///                 Word::from("away")]);
+/// }
+/// ```
+///
+/// Other break opportunities come straight from the line breaking
+/// algorithm: a line can be broken between CJK ideographs, before and
+/// after U+2014 (Em Dash), and at U+200B (Zero Width Space).
+///
 /// The Unicode line breaking algorithm will also automatically
 /// suppress break breaks around certain punctuation characters::
 ///
@@ -196,7 +325,7 @@ impl WordSeparator for UnicodeBreakProperties {
             None => None,
         });
 
-        let stripped = strip_ansi_escape_sequences(&line);
+        let stripped = strip_ansi_escape_sequences(line);
         let mut opportunities = unicode_linebreak::linebreaks(&stripped)
             .filter(|(idx, _)| {
                 #[allow(clippy::match_like_matches_macro)]
@@ -204,10 +333,9 @@ impl WordSeparator for UnicodeBreakProperties {
                     // We suppress breaks at ‘-’ since we want to control
                     // this via the WordSplitter.
                     Some('-') => false,
-                    // Soft hyphens are currently not supported since we
-                    // require all `Word` fragments to be continuous in
-                    // the input string.
-                    Some(SHY) => false,
+                    // Soft hyphens are handled when splitting words
+                    // since the break must show a hyphen.
+                    Some(SOFT_HYPHEN) => false,
                     // Other breaks should be fine!
                     _ => true,
                 }
@@ -243,15 +371,7 @@ impl WordSeparator for UnicodeBreakProperties {
     }
 }
 
-/// Soft hyphen, also knows as a “shy hyphen”. Should show up as ‘-’
-/// if a line is broken at this point, and otherwise be invisible.
-/// Textwrap does not currently support breaking words at soft
-/// hyphens.
-#[cfg(feature = "unicode-linebreak")]
-const SHY: char = '\u{00ad}';
-
 // Strip all ANSI escape sequences from `text`.
-#[cfg(feature = "unicode-linebreak")]
 fn strip_ansi_escape_sequences(text: &str) -> String {
     let mut result = String::with_capacity(text.len());
 
@@ -266,6 +386,624 @@ fn strip_ansi_escape_sequences(text: &str) -> String {
     result
 }
 
+/// Split each word found by one word separator with another.
+///
+/// Created by [`WordSeparator::and_then`], please see that method
+/// for details and examples.
+#[derive(Clone, Copy, Debug)]
+pub struct AndThen<R, T> {
+    first: R,
+    then: T,
+}
+
+impl<R, T> WordSeparator for AndThen<R, T>
+where
+    R: WordSeparator + Clone + 'static,
+    T: WordSeparator + Clone + 'static,
+{
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let mut words = Vec::new();
+        for word in self.first.find_words(line) {
+            let mut pieces = self.then.find_words(word.word).peekable();
+            while let Some(piece) = pieces.next() {
+                if pieces.peek().is_some() {
+                    words.push(piece);
+                } else {
+                    words.push(end_like(piece, &word));
+                }
+            }
+        }
+        Box::new(words.into_iter())
+    }
+}
+
+/// Keep only some of the line breaks found by a word separator.
+///
+/// Created by [`WordSeparator::filter_breaks`], please see that method
+/// for details and examples.
+#[derive(Clone, Copy)]
+pub struct FilterBreaks<R, F> {
+    word_separator: R,
+    predicate: F,
+}
+
+impl<R: std::fmt::Debug, F> std::fmt::Debug for FilterBreaks<R, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilterBreaks")
+            .field("word_separator", &self.word_separator)
+            .finish()
+    }
+}
+
+impl<R, F> WordSeparator for FilterBreaks<R, F>
+where
+    R: WordSeparator + Clone + 'static,
+    F: Fn(&str, usize) -> bool + Clone + 'static,
+{
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let words = self.word_separator.find_words(line);
+        let joined = join_words(line, words, |_, _, offset| (self.predicate)(line, offset));
+        Box::new(joined.into_iter())
+    }
+}
+
+/// Join the words of `line` between which `can_break` does not allow
+/// a line break. The closure is called with two consecutive words and
+/// the byte offset in `line` where the second word starts.
+fn join_words<'a, I, F>(line: &'a str, words: I, mut can_break: F) -> Vec<Word<'a>>
+where
+    I: IntoIterator<Item = Word<'a>>,
+    F: FnMut(&Word<'a>, &Word<'a>, usize) -> bool,
+{
+    let mut joined_words = Vec::new();
+    let mut group_start = 0;
+    let mut group_len = 0;
+    let mut offset = 0;
+    let mut words = words.into_iter().peekable();
+    while let Some(word) = words.next() {
+        offset += word.len() + word.whitespace.len();
+        group_len += 1;
+        if let Some(next) = words.peek() {
+            if !can_break(&word, next, offset) {
+                continue;
+            }
+        }
+
+        // The words are contiguous in `line`, so the group can be
+        // joined into a single word.
+        if group_len == 1 {
+            joined_words.push(word);
+        } else {
+            let joined = Word::from(&line[group_start..offset - word.whitespace.len()]);
+            joined_words.push(end_like(joined, &word));
+        }
+        group_len = 0;
+        group_start = offset;
+    }
+    joined_words
+}
+
+/// Give `piece` the whitespace, penalty, break priority, and existing
+/// break of `word`. Used for the last piece of a word which has been
+/// split or for a word which has been joined from several words.
+fn end_like<'a>(mut piece: Word<'a>, word: &Word<'a>) -> Word<'a> {
+    piece.whitespace = word.whitespace;
+    piece.penalty = word.penalty;
+    piece.break_priority = word.break_priority;
+    piece.existing_break = word.existing_break;
+    piece
+}
+
+/// Find words by breaking after the matches of a regular expression.
+///
+/// Every match of the regular expression ends a word, which makes it
+/// easy to allow line breaks after commas in long CSV-like lines or
+/// after `::` in Rust paths. Trailing `' '` characters become the
+/// whitespace of a word as usual, so include `' '` in the regular
+/// expression to break at spaces too.
+///
+/// Only available when the `regex` Cargo feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// #[cfg(feature = "regex")] {
+/// use textwrap::core::Word;
+/// use textwrap::{RegexSeparator, WordSeparator};
+///
+/// let separator = RegexSeparator::new("::| ").unwrap();
+/// assert_eq!(separator.find_words("use std::collections::HashMap;").collect::<Vec<_>>(),
+///            vec![Word::from("use "),
+///                 Word::from("std::"),
+///                 Word::from("collections::"),
+///                 Word::from("HashMap;")]);
+/// }
+/// ```
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct RegexSeparator {
+    regex: regex::Regex,
+}
+
+#[cfg(feature = "regex")]
+impl RegexSeparator {
+    /// Create a new word separator which breaks after the matches of
+    /// `pattern`. Returns an error if `pattern` is not a valid
+    /// regular expression.
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        regex::Regex::new(pattern).map(RegexSeparator::from)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl From<regex::Regex> for RegexSeparator {
+    fn from(regex: regex::Regex) -> Self {
+        RegexSeparator { regex }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl WordSeparator for RegexSeparator {
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let mut words = Vec::new();
+        let mut start = 0;
+        for m in self.regex.find_iter(line) {
+            // Empty matches at the start of a word cannot end it.
+            if m.end() > start && m.end() < line.len() {
+                words.push(Word::from(&line[start..m.end()]));
+                start = m.end();
+            }
+        }
+        if start < line.len() {
+            words.push(Word::from(&line[start..]));
+        }
+        Box::new(words.into_iter())
+    }
+}
+
+/// Find words and allow line breaks inside URLs and file paths.
+///
+/// Long URLs and paths contain no whitespace, so they overflow the
+/// line or are broken at arbitrary places. This word separator first
+/// finds words with another word separator, and then allows a line
+/// break after `'/'`, `'?'`, `'&'`, `'.'`, and `'-'` in words which
+/// look like a URL or a path. A run of these characters, such as the
+/// `"//"` in `"https://"`, is kept together. No hyphens are inserted
+/// at these breaks.
+///
+/// A word looks like a URL if it contains `"://"` or starts with
+/// `"www."`, and it looks like a path if it starts with `'/'`,
+/// `"./"`, `"../"`, or `"~/"`.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::{AsciiSpace, UrlBreaks, WordSeparator};
+///
+/// let separator = UrlBreaks::new(AsciiSpace);
+/// assert_eq!(separator.find_words("see https://example.com/a-b?q=1").collect::<Vec<_>>(),
+///            vec![Word::from("see "),
+///                 Word::from("https://"),
+///                 Word::from("example."),
+///                 Word::from("com/"),
+///                 Word::from("a-"),
+///                 Word::from("b?"),
+///                 Word::from("q=1")]);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UrlBreaks<R> {
+    word_separator: R,
+}
+
+impl<R: WordSeparator> UrlBreaks<R> {
+    /// Create a new word separator which breaks URLs and paths found
+    /// by `word_separator`.
+    pub const fn new(word_separator: R) -> Self {
+        UrlBreaks { word_separator }
+    }
+}
+
+impl<R: WordSeparator + Clone + 'static> WordSeparator for UrlBreaks<R> {
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let mut words = Vec::new();
+        for word in self.word_separator.find_words(line) {
+            // ANSI escape sequences, such as the URL of a terminal
+            // hyperlink, are never split and do not count as text.
+            let sequences = ansi_escape_sequences(word.word);
+            let url_like = if sequences.is_empty() {
+                is_url_like(word.word)
+            } else {
+                is_url_like(&strip_ansi_escape_sequences(word.word))
+            };
+            if !url_like {
+                words.push(word);
+                continue;
+            }
+
+            let mut start = 0;
+            let mut chars = word.word.char_indices().peekable();
+            while let Some((_, ch)) = chars.next() {
+                let next = match chars.peek() {
+                    Some(&(idx, next)) => (idx, next),
+                    None => break,
+                };
+                // Sequences such as "//" in "https://" or "../" are
+                // kept together.
+                if is_url_break(ch)
+                    && !is_url_break(next.1)
+                    && !inside_escape_sequence(&sequences, next.0)
+                {
+                    words.push(Word::from(&word.word[start..next.0]));
+                    start = next.0;
+                }
+            }
+            words.push(end_like(Word::from(&word.word[start..]), &word));
+        }
+        Box::new(words.into_iter())
+    }
+}
+
+/// Can a URL or a path be broken after `ch`?
+fn is_url_break(ch: char) -> bool {
+    matches!(ch, '/' | '?' | '&' | '.' | '-')
+}
+
+/// Does `word` look like a URL or a file path?
+fn is_url_like(word: &str) -> bool {
+    word.contains("://")
+        || ["www.", "/", "./", "../", "~/"]
+            .iter()
+            .any(|prefix| word.starts_with(prefix))
+}
+
+/// Find words and allow line breaks after dashes and closing
+/// punctuation.
+///
+/// This word separator first finds words with another word separator,
+/// and then allows a line break after U+2014 (Em Dash), U+2013 (En
+/// Dash), `')'`, `']'`, and `'}'` when they are followed by more text.
+/// These breaks have [`BreakPriority::Punctuation`], which is lower
+/// than the priority of a break at whitespace, so they are only used
+/// when a line cannot be broken at whitespace. Breaks found by the
+/// other word separator after these characters get the lower
+/// priority too.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::FirstFit;
+/// use textwrap::{wrap, AsciiSpace, Options, PunctuationBreaks};
+///
+/// let options = Options::new(7)
+///     .word_separator(PunctuationBreaks::new(AsciiSpace))
+///     .wrap_algorithm(FirstFit);
+/// // The break at the space is preferred:
+/// assert_eq!(wrap("ab cd—ef gh", &options), vec!["ab", "cd—ef", "gh"]);
+/// // The break after the em dash is used when needed:
+/// assert_eq!(wrap("abcd—efgh", &options), vec!["abcd—", "efgh"]);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PunctuationBreaks<R> {
+    word_separator: R,
+}
+
+impl<R: WordSeparator> PunctuationBreaks<R> {
+    /// Create a new word separator which also breaks the words found
+    /// by `word_separator` after dashes and closing punctuation.
+    pub const fn new(word_separator: R) -> Self {
+        PunctuationBreaks { word_separator }
+    }
+}
+
+impl<R: WordSeparator + Clone + 'static> WordSeparator for PunctuationBreaks<R> {
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let mut words = Vec::new();
+        for word in self.word_separator.find_words(line) {
+            // ANSI escape sequences are never split.
+            let sequences = ansi_escape_sequences(word.word);
+            let mut start = 0;
+            let mut chars = word.word.char_indices().peekable();
+            while let Some((_, ch)) = chars.next() {
+                match chars.peek() {
+                    Some(&(idx, next))
+                        if is_punctuation_break(ch)
+                            && !is_punctuation_break(next)
+                            && !inside_escape_sequence(&sequences, idx) =>
+                    {
+                        let mut piece = Word::from(&word.word[start..idx]);
+                        piece.break_priority = BreakPriority::Punctuation;
+                        words.push(piece);
+                        start = idx;
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut last = end_like(Word::from(&word.word[start..]), &word);
+            let ends_with_punctuation = last
+                .word
+                .chars()
+                .next_back()
+                .is_some_and(is_punctuation_break);
+            if last.whitespace.is_empty() && ends_with_punctuation {
+                last.break_priority =
+                    std::cmp::min(last.break_priority, BreakPriority::Punctuation);
+            }
+            words.push(last);
+        }
+        Box::new(words.into_iter())
+    }
+}
+
+/// Is a break after `ch` a punctuation break?
+fn is_punctuation_break(ch: char) -> bool {
+    matches!(ch, '\u{2014}' | '\u{2013}' | ')' | ']' | '}')
+}
+
+/// Find words while respecting Japanese line breaking rules.
+///
+/// Kinsoku shori (禁則処理) forbids some characters at the start of a
+/// line, such as closing brackets, the ideographic full stop `。`,
+/// and small kana, and other characters at the end of a line, such as
+/// opening brackets. This word separator first finds words with
+/// another word separator, and then joins the words which would
+/// otherwise let a line start or end with a forbidden character.
+///
+/// The forbidden characters can be changed with
+/// [`Kinsoku::forbidden_start`] and [`Kinsoku::forbidden_end`].
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::{AsciiSpace, Kinsoku, WordSeparator};
+///
+/// let kinsoku = Kinsoku::new(AsciiSpace);
+/// assert_eq!(kinsoku.find_words("（ちょっと ）待って 。").collect::<Vec<_>>(),
+///            vec![Word::from("（ちょっと ）待って 。")]);
+///
+/// let kinsoku = kinsoku.forbidden_start("!?").forbidden_end("");
+/// assert_eq!(kinsoku.find_words("Quoi ? Non !").collect::<Vec<_>>(),
+///            vec![Word::from("Quoi ? "), Word::from("Non !")]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Kinsoku<R> {
+    word_separator: R,
+    forbidden_start: String,
+    forbidden_end: String,
+}
+
+impl<R: WordSeparator> Kinsoku<R> {
+    /// Characters which cannot start a line by default.
+    pub const FORBIDDEN_START: &'static str = "!%),.:;?]}¢°’”‰′″℃、。々〉》」』】〕〗〙〟ぁぃぅぇぉっゃゅょゎゕゖゝゞ゠ァィゥェォッャュョヮヵヶ・ーヽヾㇰㇱㇲㇳㇴㇵㇶㇷㇸㇹㇺㇻㇼㇽㇾㇿ！％），．：；？］｝｡｣､･ｧｨｩｪｫｬｭｮｯｰ";
+
+    /// Characters which cannot end a line by default.
+    pub const FORBIDDEN_END: &'static str = "$([{£¥‘“〈《「『【〔〖〘〝＄（［｛｢￡￥";
+
+    /// Create a new kinsoku word separator with the default forbidden
+    /// characters. The words are first found with `word_separator`.
+    pub fn new(word_separator: R) -> Self {
+        Kinsoku {
+            word_separator,
+            forbidden_start: String::from(Self::FORBIDDEN_START),
+            forbidden_end: String::from(Self::FORBIDDEN_END),
+        }
+    }
+
+    /// Change the characters which cannot start a line.
+    pub fn forbidden_start(self, chars: &str) -> Self {
+        Kinsoku {
+            forbidden_start: String::from(chars),
+            ..self
+        }
+    }
+
+    /// Change the characters which cannot end a line.
+    pub fn forbidden_end(self, chars: &str) -> Self {
+        Kinsoku {
+            forbidden_end: String::from(chars),
+            ..self
+        }
+    }
+
+    /// Can the line be broken between `word` and `next`?
+    fn can_break(&self, word: &Word<'_>, next: &Word<'_>) -> bool {
+        let ends_forbidden = word.whitespace.is_empty()
+            && word
+                .word
+                .chars()
+                .next_back()
+                .is_some_and(|ch| self.forbidden_end.contains(ch));
+        let starts_forbidden = next
+            .word
+            .chars()
+            .next()
+            .is_some_and(|ch| self.forbidden_start.contains(ch));
+        !ends_forbidden && !starts_forbidden
+    }
+}
+
+impl<R: WordSeparator + Clone + 'static> WordSeparator for Kinsoku<R> {
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let words = self.word_separator.find_words(line);
+        let joined = join_words(line, words, |word, next, _| self.can_break(word, next));
+        Box::new(joined.into_iter())
+    }
+}
+
+/// Find words in scripts without spaces using a dictionary.
+///
+/// Thai, Lao, and Khmer are written without spaces between words, so
+/// a paragraph in these scripts is one giant word for other word
+/// separators. This word separator first finds words with another
+/// word separator, and then splits each run of Thai, Lao, or Khmer
+/// characters into the words of a dictionary. Text in other scripts
+/// is left alone.
+///
+/// A run is split into as few unknown characters as possible, and
+/// then into as few words as possible. Consecutive characters which
+/// are not part of a dictionary word are kept together.
+///
+/// Only available when the `dictionary-segmentation` Cargo feature is
+/// enabled.
+///
+/// # Examples
+///
+/// ```
+/// #[cfg(feature = "dictionary-segmentation")] {
+/// use textwrap::core::Word;
+/// use textwrap::{AsciiSpace, DictionarySegmenter, WordSeparator};
+///
+/// let segmenter = DictionarySegmenter::new(AsciiSpace, vec!["ภาษา", "ไทย", "ง่าย"]);
+/// assert_eq!(segmenter.find_words("ภาษาไทยง่ายมาก ok").collect::<Vec<_>>(),
+///            vec![Word::from("ภาษา"),
+///                 Word::from("ไทย"),
+///                 Word::from("ง่าย"),
+///                 Word::from("มาก "),
+///                 Word::from("ok")]);
+/// }
+/// ```
+#[cfg(feature = "dictionary-segmentation")]
+#[derive(Clone, Debug)]
+pub struct DictionarySegmenter<R> {
+    word_separator: R,
+    words: std::collections::HashSet<String>,
+    // The number of characters in the longest dictionary word.
+    max_chars: usize,
+}
+
+#[cfg(feature = "dictionary-segmentation")]
+impl<R: WordSeparator> DictionarySegmenter<R> {
+    /// Create a new dictionary segmenter. The words are first found
+    /// with `word_separator` and then split into the dictionary
+    /// `words`.
+    pub fn new<I, W>(word_separator: R, words: I) -> Self
+    where
+        I: IntoIterator<Item = W>,
+        W: Into<String>,
+    {
+        let words = words
+            .into_iter()
+            .map(Into::into)
+            .collect::<std::collections::HashSet<String>>();
+        let max_chars = words
+            .iter()
+            .map(|word| word.chars().count())
+            .max()
+            .unwrap_or(0);
+        DictionarySegmenter {
+            word_separator,
+            words,
+            max_chars,
+        }
+    }
+
+    /// Push the byte offsets in `run` where words start onto
+    /// `offsets`. The offset zero is not included.
+    fn segment(&self, run: &str, offsets: &mut Vec<usize>) {
+        let boundaries = run
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .chain(std::iter::once(run.len()))
+            .collect::<Vec<_>>();
+        let n = boundaries.len() - 1;
+
+        // best[j] is the (unknown characters, words) cost of
+        // segmenting the first j characters, the start of the last
+        // segment, and whether that segment is a dictionary word.
+        let mut best = vec![((usize::MAX, usize::MAX), 0, false); n + 1];
+        best[0].0 = (0, 0);
+        for i in 0..n {
+            let (unknown, words) = best[i].0;
+            if unknown == usize::MAX {
+                continue;
+            }
+            let mut relax = |j: usize, cost: (usize, usize), known: bool| {
+                if cost < best[j].0 {
+                    best[j] = (cost, i, known);
+                }
+            };
+            relax(i + 1, (unknown + 1, words + 1), false);
+            for j in i + 1..=std::cmp::min(n, i + self.max_chars) {
+                if self.words.contains(&run[boundaries[i]..boundaries[j]]) {
+                    relax(j, (unknown, words + 1), true);
+                }
+            }
+        }
+
+        // Collect the segments from the end and keep consecutive
+        // unknown characters together.
+        let mut segments = Vec::new();
+        let mut j = n;
+        while j > 0 {
+            let (_, i, known) = best[j];
+            segments.push((boundaries[i], known));
+            j = i;
+        }
+        segments.reverse();
+        for pair in segments.windows(2) {
+            let ((_, prev_known), (start, known)) = (pair[0], pair[1]);
+            if prev_known || known {
+                offsets.push(start);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "dictionary-segmentation")]
+impl<R: WordSeparator + Clone + 'static> WordSeparator for DictionarySegmenter<R> {
+    fn find_words<'a>(&self, line: &'a str) -> Box<dyn Iterator<Item = Word<'a>> + 'a> {
+        let mut words = Vec::new();
+        let mut offsets = Vec::new();
+        for word in self.word_separator.find_words(line) {
+            // Find the runs of Thai, Lao, and Khmer characters. The
+            // final space ends the last run.
+            offsets.clear();
+            let mut run_start = None;
+            let char_indices = word.word.char_indices();
+            for (idx, ch) in char_indices.chain(std::iter::once((word.word.len(), ' '))) {
+                match (run_start, is_dictionary_script(ch)) {
+                    (None, true) => run_start = Some(idx),
+                    (Some(start), false) => {
+                        let count = offsets.len();
+                        self.segment(&word.word[start..idx], &mut offsets);
+                        offsets[count..]
+                            .iter_mut()
+                            .for_each(|offset| *offset += start);
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+
+            if offsets.is_empty() {
+                words.push(word);
+                continue;
+            }
+            let mut start = 0;
+            for &offset in &offsets {
+                words.push(Word::from(&word.word[start..offset]));
+                start = offset;
+            }
+            words.push(end_like(Word::from(&word.word[start..]), &word));
+        }
+        Box::new(words.into_iter())
+    }
+}
+
+/// Is `ch` in a script which is written without spaces between
+/// words?
+#[cfg(feature = "dictionary-segmentation")]
+fn is_dictionary_script(ch: char) -> bool {
+    matches!(ch,
+        '\u{0e00}'..='\u{0e7f}' // Thai
+        | '\u{0e80}'..='\u{0eff}' // Lao
+        | '\u{1780}'..='\u{17ff}' // Khmer
+        | '\u{19e0}'..='\u{19ff}' // Khmer Symbols
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -357,6 +1095,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ascii_space_zero_width_space() {
+        assert_iter_eq!(
+            AsciiSpace.find_words("foo\u{200b} bar\u{200b}"),
+            vec![Word::from("foo\u{200b} "), Word::from("bar\u{200b}")]
+        );
+        assert_iter_eq!(
+            AsciiSpace.find_words("foo\u{2060}bar"),
+            vec![Word::from("foo\u{2060}bar")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-linebreak")]
+    fn unicode_break_properties_zero_width_space() {
+        assert_iter_eq!(
+            UnicodeBreakProperties.find_words("foo\u{200b}bar"),
+            vec![Word::from("foo\u{200b}"), Word::from("bar")]
+        );
+    }
+
+    #[test]
+    fn ascii_space_no_break_spaces() {
+        assert_iter_eq!(
+            AsciiSpace.find_words("10\u{a0}kg Bonjour\u{202f}! 1\u{2007}000"),
+            vec![
+                Word::from("10\u{a0}kg "),
+                Word::from("Bonjour\u{202f}! "),
+                Word::from("1\u{2007}000")
+            ]
+        );
+    }
+
     #[test]
     #[cfg(unix)]
     fn ascii_space_colored_text() {
@@ -387,4 +1158,140 @@ mod tests {
             vec![Word::from(text)]
         );
     }
+
+    #[test]
+    fn ascii_space_escape_sequence_with_spaces() {
+        let title = "\u{1b}]0;My title\u{7}";
+        let text = format!("foo {}bar baz", title);
+        assert_iter_eq!(
+            AsciiSpace.find_words(&text),
+            vec![
+                Word::from("foo "),
+                Word::from(&text[4..4 + title.len() + 4]),
+                Word::from("baz")
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-linebreak")]
+    fn unicode_break_properties_no_break_space() {
+        assert_iter_eq!(
+            UnicodeBreakProperties.find_words("foo\u{a0}bar baz"),
+            vec![Word::from("foo\u{a0}bar "), Word::from("baz")]
+        );
+        assert_iter_eq!(
+            UnicodeBreakProperties.find_words("Bonjour\u{202f}! 1\u{2007}000"),
+            vec![Word::from("Bonjour\u{202f}! "), Word::from("1\u{2007}000")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-linebreak")]
+    fn unicode_break_properties_word_joiner() {
+        assert_iter_eq!(
+            UnicodeBreakProperties.find_words("你\u{2060}好"),
+            vec![Word::from("你\u{2060}好")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "dictionary-segmentation")]
+    fn dictionary_segmenter_unknown_characters() {
+        let segmenter = DictionarySegmenter::new(AsciiSpace, vec!["ສະບາຍ"]);
+        assert_iter_eq!(
+            segmenter.find_words("ສະບາຍດີ abc"),
+            vec![Word::from("ສະບາຍ"), Word::from("ດີ "), Word::from("abc")]
+        );
+        assert_iter_eq!(segmenter.find_words("ດີດີ"), vec![Word::from("ດີດີ")]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-linebreak")]
+    fn kinsoku_forbidden_end() {
+        let kinsoku = Kinsoku::new(UnicodeBreakProperties).forbidden_end("你");
+        assert_iter_eq!(
+            kinsoku.find_words("你好吗"),
+            vec![Word::from("你好"), Word::from("吗")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_separator_csv() {
+        let separator = RegexSeparator::new(",").unwrap();
+        assert_iter_eq!(separator.find_words(""), vec![]);
+        assert_iter_eq!(
+            separator.find_words("a,b,,c,"),
+            vec![
+                Word::from("a,"),
+                Word::from("b,"),
+                Word::from(","),
+                Word::from("c,")
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_breaks_none() {
+        let separator = AsciiSpace.filter_breaks(|_: &str, _: usize| false);
+        assert_iter_eq!(
+            separator.find_words("foo bar  "),
+            vec![Word::from("foo bar  ")]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-linebreak")]
+    fn and_then_keeps_whitespace() {
+        let separator = AsciiSpace.and_then(UnicodeBreakProperties);
+        assert_iter_eq!(
+            separator.find_words("你好 x"),
+            vec![Word::from("你"), Word::from("好 "), Word::from("x")]
+        );
+    }
+
+    #[test]
+    fn url_breaks_paths() {
+        let separator = UrlBreaks::new(AsciiSpace);
+        assert_iter_eq!(
+            separator.find_words("../src/lib.rs. and foo/bar"),
+            vec![
+                Word::from("../"),
+                Word::from("src/"),
+                Word::from("lib."),
+                Word::from("rs. "),
+                Word::from("and "),
+                Word::from("foo/bar")
+            ]
+        );
+    }
+
+    #[test]
+    fn url_breaks_hyperlinks() {
+        let separator = UrlBreaks::new(AsciiSpace);
+        let open = "\u{1b}]8;;https://example.org/a-b\u{7}";
+        let text = format!("{}online\u{1b}]8;;\u{7}", open);
+        assert_iter_eq!(separator.find_words(&text), vec![Word::from(&text)]);
+
+        let text = format!("{}https://example.org/a-b\u{1b}]8;;\u{7}", open);
+        let https = open.len() + "https://".len();
+        assert_iter_eq!(
+            separator.find_words(&text),
+            vec![
+                Word::from(&text[..https]),
+                Word::from("example."),
+                Word::from("org/"),
+                Word::from("a-"),
+                Word::from(&text[text.len() - 7..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn punctuation_breaks_hyperlinks() {
+        let separator = PunctuationBreaks::new(AsciiSpace);
+        let text = "\u{1b}]8;;https://example.org/Rust_(language)\u{7}Rust\u{1b}]8;;\u{7}";
+        assert_iter_eq!(separator.find_words(text), vec![Word::from(text)]);
+    }
 }
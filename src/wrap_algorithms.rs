@@ -14,19 +14,59 @@
 //!
 //! While both algorithms run in linear time, the first-fit algorithm
 //! is about 4 times faster than the optimal-fit algorithm.
+//!
+//! For short texts such as headlines, [`wrap_balanced`] makes all
+//! lines, including the last line, roughly equally wide.
+//! In between the two, [`wrap_pretty`] is a cheap way to avoid a lone
+//! word on the last line of a paragraph.
+//!
+//! When text is re-wrapped while it is being edited, [`Rewrapper`]
+//! only recomputes the first-fit lines affected by each edit.
+//!
+//! Any of the algorithms can be combined with [`HyphenLimit`] to
+//! avoid many consecutive lines ending with a hyphen.
+//!
+//! Use [`FnWrapAlgorithm`] to plug in an algorithm of your own, and
+//! [`score`] to compare the lines made by different algorithms.
+
+mod line_widths;
+pub use line_widths::{Cutout, DropCap, HangingIndent, LineWidths};
+
+mod balanced;
+pub use balanced::{wrap_balanced, wrap_balanced_indices, Balanced};
+
+mod pretty;
+pub use pretty::{wrap_pretty, wrap_pretty_indices, Pretty};
+
+mod rewrapper;
+pub use rewrapper::Rewrapper;
+
+mod hyphen_limit;
+pub use hyphen_limit::HyphenLimit;
 
-#[cfg(feature = "smawk")]
+mod score;
+pub use score::{score, Score};
+
+#[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
 mod optimal_fit;
-#[cfg(feature = "smawk")]
-pub use optimal_fit::{wrap_optimal_fit, OptimalFit};
+#[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+pub use optimal_fit::{
+    explain_optimal_fit, try_wrap_optimal_fit, wrap_optimal_fit, wrap_optimal_fit_for_each,
+    wrap_optimal_fit_indices, wrap_optimal_fit_with, AlternativeLine, ExplainedLine, LineCost,
+    OptimalFit, OptimalFitError, TieBreak,
+};
+
+use std::collections::VecDeque;
+use std::iter::Peekable;
 
-use crate::core::{Fragment, Word};
+use crate::core::{BreakPriority, Fragment, Word};
 
 /// Describes how to wrap words into lines.
 ///
 /// The simplest approach is to wrap words one word at a time. This is
-/// implemented by [`FirstFit`]. If the `smawk` Cargo feature is
-/// enabled, a more complex algorithm is available, implemented by
+/// implemented by [`FirstFit`]. If the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled, a more complex
+/// algorithm is available, implemented by
 /// [`OptimalFit`], which will look at an entire paragraph at a time
 /// in order to find optimal line breaks.
 pub trait WrapAlgorithm: WrapAlgorithmClone + std::fmt::Debug {
@@ -38,6 +78,23 @@ pub trait WrapAlgorithm: WrapAlgorithmClone + std::fmt::Debug {
     ///
     /// Please see the implementors of the trait for examples.
     fn wrap<'a, 'b>(&self, words: &'b [Word<'a>], line_widths: &'b [usize]) -> Vec<&'b [Word<'a>]>;
+
+    /// Wrap words according to line widths and store the index after
+    /// the last word of each line in `line_ends`.
+    ///
+    /// The `line_ends` are cleared first. The default implementation
+    /// calls [`WrapAlgorithm::wrap`], implementations can override
+    /// this to reuse the memory of `line_ends` instead of allocating
+    /// a new vector for every call. This is used by
+    /// [`Wrapper`](crate::Wrapper).
+    fn wrap_into(&self, words: &[Word<'_>], line_widths: &[usize], line_ends: &mut Vec<usize>) {
+        line_ends.clear();
+        let mut end = 0;
+        for line in self.wrap(words, line_widths) {
+            end += line.len();
+            line_ends.push(end);
+        }
+    }
 }
 
 // The internal `WrapAlgorithmClone` trait is allows us to implement
@@ -67,6 +124,11 @@ impl WrapAlgorithm for Box<dyn WrapAlgorithm> {
         use std::ops::Deref;
         self.deref().wrap(words, line_widths)
     }
+
+    fn wrap_into(&self, words: &[Word<'_>], line_widths: &[usize], line_ends: &mut Vec<usize>) {
+        use std::ops::Deref;
+        self.deref().wrap_into(words, line_widths, line_ends)
+    }
 }
 
 /// Wrap words using a fast and simple algorithm.
@@ -77,23 +139,138 @@ impl WrapAlgorithm for Box<dyn WrapAlgorithm> {
 #[derive(Clone, Copy, Debug, Default)]
 pub struct FirstFit;
 
+impl FirstFit {
+    /// Create a new first-fit wrapping algorithm.
+    pub const fn new() -> Self {
+        FirstFit
+    }
+}
+
 impl WrapAlgorithm for FirstFit {
     #[inline]
     fn wrap<'a, 'b>(&self, words: &'b [Word<'a>], line_widths: &'b [usize]) -> Vec<&'b [Word<'a>]> {
-        wrap_first_fit(words, line_widths)
+        wrap_first_fit(words, &to_f64_line_widths(line_widths))
+    }
+
+    fn wrap_into(&self, words: &[Word<'_>], line_widths: &[usize], line_ends: &mut Vec<usize>) {
+        line_ends.clear();
+        first_fit_lines(words, &ColumnWidths(line_widths), |end, _| {
+            line_ends.push(end);
+            true
+        });
+    }
+}
+
+/// Use a closure as a wrapping algorithm.
+///
+/// The closure is given the words of a paragraph and the target line
+/// widths, like [`WrapAlgorithm::wrap`], and returns the index after
+/// the last word of each line. The indices must be increasing and the
+/// last index must be the number of words. The words are found and
+/// split, and the lines are indented and assembled as usual. This is
+/// convenient for custom algorithms in other crates which do not
+/// warrant a new type.
+///
+/// Since [`Options`](crate::Options) uses a `Box<dyn WrapAlgorithm>`
+/// by default, the algorithm can also be chosen at runtime.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::FnWrapAlgorithm;
+/// use textwrap::{wrap, Options};
+///
+/// // Put two words on every line, no matter the width.
+/// let pairs = FnWrapAlgorithm::new(|words: &[Word<'_>], _: &[usize]| {
+///     (1..=words.len())
+///         .filter(|&end| end % 2 == 0 || end == words.len())
+///         .collect()
+/// });
+/// let options = Options::new(80).wrap_algorithm(pairs);
+/// assert_eq!(wrap("Memory safety without garbage collection.", &options),
+///            vec!["Memory safety", "without garbage", "collection."]);
+/// ```
+#[derive(Clone, Copy)]
+pub struct FnWrapAlgorithm<F> {
+    line_ends: F,
+}
+
+impl<F> FnWrapAlgorithm<F>
+where
+    F: Fn(&[Word<'_>], &[usize]) -> Vec<usize>,
+{
+    /// Create a new wrapping algorithm which uses `line_ends` to find
+    /// the index after the last word of each line.
+    pub const fn new(line_ends: F) -> Self {
+        FnWrapAlgorithm { line_ends }
+    }
+}
+
+impl<F> std::fmt::Debug for FnWrapAlgorithm<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnWrapAlgorithm").finish()
+    }
+}
+
+/// `FnWrapAlgorithm` implements `WrapAlgorithm` by calling the
+/// closure.
+impl<F> WrapAlgorithm for FnWrapAlgorithm<F>
+where
+    F: Fn(&[Word<'_>], &[usize]) -> Vec<usize> + Clone + 'static,
+{
+    fn wrap<'a, 'b>(&self, words: &'b [Word<'a>], line_widths: &'b [usize]) -> Vec<&'b [Word<'a>]> {
+        lines_from_indices(words, &(self.line_ends)(words, line_widths))
+    }
+
+    fn wrap_into(&self, words: &[Word<'_>], line_widths: &[usize], line_ends: &mut Vec<usize>) {
+        line_ends.clear();
+        line_ends.extend((self.line_ends)(words, line_widths));
+    }
+}
+
+/// Convert line widths given in columns to the floating point widths
+/// used by [`wrap_first_fit`] and [`wrap_optimal_fit`].
+pub(crate) fn to_f64_line_widths(line_widths: &[usize]) -> Vec<f64> {
+    line_widths.iter().map(|&width| width as f64).collect()
+}
+
+/// Line widths given in columns. Unlike [`to_f64_line_widths`], this
+/// converts the widths without allocating.
+struct ColumnWidths<'a>(&'a [usize]);
+
+impl LineWidths for ColumnWidths<'_> {
+    fn line_width(&self, line_number: usize) -> f64 {
+        // The final line width is used for all remaining lines.
+        match self.0.get(line_number) {
+            Some(&width) => width as f64,
+            None => self.0.last().map_or(0.0, |&width| width as f64),
+        }
     }
 }
 
 /// Wrap abstract fragments into lines with a first-fit algorithm.
 ///
-/// The `line_widths` slice gives the target line width for each line
-/// (the last slice element is repeated as necessary). This can be
-/// used to implement hanging indentation.
+/// The `line_widths` give the target width of each line, see
+/// [`LineWidths`]. For a slice, the last element is repeated as
+/// necessary. This can be used to implement hanging indentation and
+/// other shapes.
 ///
 /// The fragments must already have been split into the desired
 /// widths, this function will not (and cannot) attempt to split them
 /// further when arranging them into lines.
 ///
+/// A line always ends after a fragment with a
+/// [`Fragment::forced_break`] and never after a fragment which must
+/// be kept with the next, see [`Fragment::keep_with_next`]. When a
+/// group of such fragments no longer fits on a line, the entire group
+/// is moved to the next line.
+///
+/// When a fragment no longer fits, the line ends at the latest break
+/// with the highest [`Fragment::break_priority`]. This means that a
+/// word which has been broken into [`BreakPriority::Anywhere`] pieces
+/// is moved to the next line as a whole if possible.
+///
 /// # First-Fit Algorithm
 ///
 /// This implements a simple “greedy” algorithm: accumulate fragments
@@ -119,7 +296,7 @@ impl WrapAlgorithm for FirstFit {
 ///
 /// let text = "These few words will unfortunately not wrap nicely.";
 /// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
-/// assert_eq!(lines_to_strings(wrap_algorithms::wrap_first_fit(&words, &[15])),
+/// assert_eq!(lines_to_strings(wrap_algorithms::wrap_first_fit(&words, &[15.0])),
 ///            vec!["These few words",
 ///                 "will",  // <-- short line
 ///                 "unfortunately",
@@ -127,8 +304,8 @@ impl WrapAlgorithm for FirstFit {
 ///                 "nicely."]);
 ///
 /// // We can avoid the short line if we look ahead:
-/// #[cfg(feature = "smawk")]
-/// assert_eq!(lines_to_strings(wrap_algorithms::wrap_optimal_fit(&words, &[15])),
+/// #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+/// assert_eq!(lines_to_strings(wrap_algorithms::wrap_optimal_fit(&words, &[15.0], &Default::default())),
 ///            vec!["These few",
 ///                 "words will",
 ///                 "unfortunately",
@@ -163,41 +340,41 @@ impl WrapAlgorithm for FirstFit {
 /// #[derive(Debug)]
 /// struct Task<'a> {
 ///     name: &'a str,
-///     hours: usize,   // Time needed to complete task.
-///     sweep: usize,   // Time needed for a quick sweep after task during the day.
-///     cleanup: usize, // Time needed for full cleanup if day ends with this task.
+///     hours: f64,   // Time needed to complete task.
+///     sweep: f64,   // Time needed for a quick sweep after task during the day.
+///     cleanup: f64, // Time needed for full cleanup if day ends with this task.
 /// }
 ///
 /// impl Fragment for Task<'_> {
-///     fn width(&self) -> usize { self.hours }
-///     fn whitespace_width(&self) -> usize { self.sweep }
-///     fn penalty_width(&self) -> usize { self.cleanup }
+///     fn width(&self) -> f64 { self.hours }
+///     fn whitespace_width(&self) -> f64 { self.sweep }
+///     fn penalty_width(&self) -> f64 { self.cleanup }
 /// }
 ///
 /// // The morning tasks
 /// let tasks = vec![
-///     Task { name: "Foundation",  hours: 4, sweep: 2, cleanup: 3 },
-///     Task { name: "Framing",     hours: 3, sweep: 1, cleanup: 2 },
-///     Task { name: "Plumbing",    hours: 2, sweep: 2, cleanup: 2 },
-///     Task { name: "Electrical",  hours: 2, sweep: 1, cleanup: 2 },
-///     Task { name: "Insulation",  hours: 2, sweep: 1, cleanup: 2 },
-///     Task { name: "Drywall",     hours: 3, sweep: 1, cleanup: 2 },
-///     Task { name: "Floors",      hours: 3, sweep: 1, cleanup: 2 },
-///     Task { name: "Countertops", hours: 1, sweep: 1, cleanup: 2 },
-///     Task { name: "Bathrooms",   hours: 2, sweep: 1, cleanup: 2 },
+///     Task { name: "Foundation",  hours: 4.0, sweep: 2.0, cleanup: 3.0 },
+///     Task { name: "Framing",     hours: 3.0, sweep: 1.0, cleanup: 2.0 },
+///     Task { name: "Plumbing",    hours: 2.0, sweep: 2.0, cleanup: 2.0 },
+///     Task { name: "Electrical",  hours: 2.0, sweep: 1.0, cleanup: 2.0 },
+///     Task { name: "Insulation",  hours: 2.0, sweep: 1.0, cleanup: 2.0 },
+///     Task { name: "Drywall",     hours: 3.0, sweep: 1.0, cleanup: 2.0 },
+///     Task { name: "Floors",      hours: 3.0, sweep: 1.0, cleanup: 2.0 },
+///     Task { name: "Countertops", hours: 1.0, sweep: 1.0, cleanup: 2.0 },
+///     Task { name: "Bathrooms",   hours: 2.0, sweep: 1.0, cleanup: 2.0 },
 /// ];
 ///
 /// // Fill tasks into days, taking `day_length` into account. The
 /// // output shows the hours worked per day along with the names of
 /// // the tasks for that day.
-/// fn assign_days<'a>(tasks: &[Task<'a>], day_length: usize) -> Vec<(usize, Vec<&'a str>)> {
+/// fn assign_days<'a>(tasks: &[Task<'a>], day_length: f64) -> Vec<(f64, Vec<&'a str>)> {
 ///     let mut days = Vec::new();
 ///     // Assign tasks to days. The assignment is a vector of slices,
 ///     // with a slice per day.
 ///     let assigned_days: Vec<&[Task<'a>]> = wrap_first_fit(&tasks, &[day_length]);
 ///     for day in assigned_days.iter() {
 ///         let last = day.last().unwrap();
-///         let work_hours: usize = day.iter().map(|t| t.hours + t.sweep).sum();
+///         let work_hours: f64 = day.iter().map(|t| t.hours + t.sweep).sum();
 ///         let names = day.iter().map(|t| t.name).collect::<Vec<_>>();
 ///         days.push((work_hours - last.sweep + last.cleanup, names));
 ///     }
@@ -206,52 +383,335 @@ impl WrapAlgorithm for FirstFit {
 ///
 /// // With a single crew working 8 hours a day:
 /// assert_eq!(
-///     assign_days(&tasks, 8),
+///     assign_days(&tasks, 8.0),
 ///     [
-///         (7, vec!["Foundation"]),
-///         (8, vec!["Framing", "Plumbing"]),
-///         (7, vec!["Electrical", "Insulation"]),
-///         (5, vec!["Drywall"]),
-///         (7, vec!["Floors", "Countertops"]),
-///         (4, vec!["Bathrooms"]),
+///         (7.0, vec!["Foundation"]),
+///         (8.0, vec!["Framing", "Plumbing"]),
+///         (7.0, vec!["Electrical", "Insulation"]),
+///         (5.0, vec!["Drywall"]),
+///         (7.0, vec!["Floors", "Countertops"]),
+///         (4.0, vec!["Bathrooms"]),
 ///     ]
 /// );
 ///
 /// // With two crews working in shifts, 16 hours a day:
 /// assert_eq!(
-///     assign_days(&tasks, 16),
+///     assign_days(&tasks, 16.0),
 ///     [
-///         (14, vec!["Foundation", "Framing", "Plumbing"]),
-///         (15, vec!["Electrical", "Insulation", "Drywall", "Floors"]),
-///         (6, vec!["Countertops", "Bathrooms"]),
+///         (14.0, vec!["Foundation", "Framing", "Plumbing"]),
+///         (15.0, vec!["Electrical", "Insulation", "Drywall", "Floors"]),
+///         (6.0, vec!["Countertops", "Bathrooms"]),
 ///     ]
 /// );
 /// ```
 ///
 /// Apologies to anyone who actually knows how to build a house and
 /// knows how long each step takes :-)
-pub fn wrap_first_fit<'a, 'b, T: Fragment>(
+pub fn wrap_first_fit<'a, T: Fragment, W: LineWidths + ?Sized>(
     fragments: &'a [T],
-    line_widths: &'b [usize],
+    line_widths: &W,
 ) -> Vec<&'a [T]> {
-    // The final line width is used for all remaining lines.
-    let default_line_width = line_widths.last().copied().unwrap_or(0);
+    let indices = wrap_first_fit_indices(fragments, line_widths);
+    lines_from_indices(fragments, &indices)
+}
+
+/// Find line breaks with a first-fit algorithm.
+///
+/// This works like [`wrap_first_fit`], but instead of returning the
+/// lines as slices, the index after the last fragment of each line
+/// is returned. This is useful if you need to know the position of
+/// the fragments when rendering the lines. The last index is always
+/// `fragments.len()`.
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::wrap_first_fit_indices;
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "These few words will unfortunately not wrap nicely.";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(wrap_first_fit_indices(&words, &[15.0]),
+///            vec![3, 4, 5, 7, 8]);
+/// ```
+pub fn wrap_first_fit_indices<T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &[T],
+    line_widths: &W,
+) -> Vec<usize> {
     let mut lines = Vec::new();
+    let mut end = 0;
+    wrap_first_fit_with(fragments, line_widths, |line| {
+        end += line.len();
+        lines.push(end);
+    });
+    lines
+}
+
+/// Wrap abstract fragments into lines with a first-fit algorithm and
+/// call a function for each line.
+///
+/// This works like [`wrap_first_fit`], but instead of collecting the
+/// lines into a `Vec`, `f` is called with each line as soon as it has
+/// been found. This function does not allocate, which makes it
+/// suitable for hot paths such as a terminal application which
+/// re-wraps its text on every frame.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::wrap_first_fit_with;
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "These few words will unfortunately not wrap nicely.";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// let mut longest = 0;
+/// wrap_first_fit_with(&words, &[15.0], |line| {
+///     longest = std::cmp::max(longest, line.len());
+/// });
+/// assert_eq!(longest, 3);
+/// ```
+pub fn wrap_first_fit_with<'a, T, W, F>(fragments: &'a [T], line_widths: &W, mut f: F)
+where
+    T: Fragment,
+    W: LineWidths + ?Sized,
+    F: FnMut(&'a [T]),
+{
     let mut start = 0;
-    let mut width = 0;
+    first_fit_lines(fragments, line_widths, |end, _| {
+        f(&fragments[start..end]);
+        start = end;
+        true
+    });
+}
 
-    for (idx, fragment) in fragments.iter().enumerate() {
-        let line_width = line_widths
-            .get(lines.len())
-            .copied()
-            .unwrap_or(default_line_width);
-        if width + fragment.width() + fragment.penalty_width() > line_width && idx > start {
-            lines.push(&fragments[start..idx]);
-            start = idx;
-            width = 0;
+/// Find first-fit line breaks.
+///
+/// The closure is called with the end of each line and the index of
+/// the last fragment which was looked at to end the line. Line
+/// breaking stops when the closure returns `false`.
+///
+/// Every line is computed from scratch: fragments which are moved to
+/// a new line are checked against the width of that line. This means
+/// that the lines following a line break only depend on the fragments
+/// after the break and on the line number.
+pub(crate) fn first_fit_lines<T, W, F>(fragments: &[T], line_widths: &W, mut f: F)
+where
+    T: Fragment,
+    W: LineWidths + ?Sized,
+    F: FnMut(usize, usize) -> bool,
+{
+    let mut line_number = 0;
+    let mut start = 0;
+    let mut width = 0.0;
+    let mut idx = 0;
+
+    while idx < fragments.len() {
+        let fragment = &fragments[idx];
+        let line_width = line_widths.line_width(line_number);
+        if width + fragment.width() + fragment.penalty_width() - fragment.hanging_width()
+            > line_width
+        {
+            if let Some(count) = first_fit_break(&fragments[start..idx]) {
+                if !f(start + count, idx) {
+                    return;
+                }
+                line_number += 1;
+                start += count;
+                width = 0.0;
+                idx = start;
+                continue;
+            }
         }
+
         width += fragment.width() + fragment.whitespace_width();
+        idx += 1;
+        if fragment.forced_break() && idx < fragments.len() {
+            if !f(idx, idx) {
+                return;
+            }
+            line_number += 1;
+            start = idx;
+            width = 0.0;
+        }
+    }
+    f(fragments.len(), fragments.len());
+}
+
+/// Find where to end a line when the next fragment does not fit.
+///
+/// The line ends at the latest break with the highest priority. A
+/// line cannot end inside a group of fragments which must be kept
+/// together. Returns the number of fragments from `line` to keep on
+/// the line, or `None` if the line cannot be broken.
+fn first_fit_break<T: Fragment>(line: &[T]) -> Option<usize> {
+    let mut best: Option<(usize, BreakPriority)> = None;
+    for (idx, fragment) in line.iter().enumerate().rev() {
+        if fragment.keep_with_next() {
+            continue;
+        }
+        let priority = fragment.break_priority();
+        match best {
+            Some((_, best_priority)) if best_priority >= priority => {}
+            _ => best = Some((idx + 1, priority)),
+        }
+        if priority == BreakPriority::Normal {
+            break;
+        }
+    }
+    best.map(|(count, _)| count)
+}
+
+/// Find the latest break with a higher priority before each break.
+///
+/// The break at index `j` is the break after `fragments[j - 1]`. The
+/// result at index `j` is the latest break `k < j` which has a higher
+/// [`Fragment::break_priority`] than the break at `j`, or `0` if there
+/// is no such break. A line starting at `i` can thus only end at `j`
+/// if the result at `j` is at most `i`.
+pub(crate) fn higher_priority_breaks<T: Fragment>(fragments: &[T]) -> Vec<usize> {
+    let mut higher_breaks = Vec::with_capacity(fragments.len() + 1);
+    let mut last_breaks = [0; 4];
+    higher_breaks.push(0);
+    for (idx, fragment) in fragments.iter().enumerate() {
+        let priority = fragment.break_priority() as usize;
+        let higher_break = last_breaks[priority + 1..].iter().copied().max();
+        higher_breaks.push(higher_break.unwrap_or(0));
+        if !fragment.keep_with_next() || fragment.forced_break() {
+            last_breaks[priority] = idx + 1;
+        }
+    }
+    higher_breaks
+}
+
+/// Wrap a stream of fragments into lines with a first-fit algorithm.
+///
+/// This works like [`wrap_first_fit`], but the fragments are taken
+/// from an iterator and the lines are returned by an iterator. Each
+/// line is returned as soon as it is complete, so only the fragments
+/// of the current line are kept in memory. This lets you wrap huge
+/// inputs, such as log files, with constant memory use.
+///
+/// Since the fragments are consumed, each line is returned as a
+/// `Vec` of the fragments on the line. Like [`wrap_first_fit`], at
+/// least one line is always returned, even if there are no fragments.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::{wrap_first_fit, wrap_first_fit_iter};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "These few words will unfortunately not wrap nicely.";
+/// let lines = wrap_first_fit_iter(AsciiSpace.find_words(text), [15.0])
+///     .map(|line| line.iter().map(|word| word.word).collect::<Vec<_>>().join(" "))
+///     .collect::<Vec<_>>();
+/// assert_eq!(lines, vec!["These few words",
+///                        "will",
+///                        "unfortunately",
+///                        "not wrap",
+///                        "nicely."]);
+///
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(wrap_first_fit_iter(words.iter().copied(), [15.0]).collect::<Vec<_>>(),
+///            wrap_first_fit(&words, &[15.0]));
+/// ```
+pub fn wrap_first_fit_iter<T, I, W>(fragments: I, line_widths: W) -> FirstFitIter<I::IntoIter, W>
+where
+    T: Fragment,
+    I: IntoIterator<Item = T>,
+    W: LineWidths,
+{
+    FirstFitIter {
+        fragments: fragments.into_iter().peekable(),
+        line_widths,
+        pending: VecDeque::new(),
+        line: Vec::new(),
+        width: 0.0,
+        line_number: 0,
+        complete_lines: VecDeque::new(),
+        finished: false,
+    }
+}
+
+/// Iterator over lines returned by [`wrap_first_fit_iter`].
+#[derive(Debug)]
+pub struct FirstFitIter<I: Iterator, W> {
+    fragments: Peekable<I>,
+    line_widths: W,
+    // Fragments which must be pushed before the next fragment from
+    // the iterator.
+    pending: VecDeque<I::Item>,
+    // Fragments on the current line and their width.
+    line: Vec<I::Item>,
+    width: f64,
+    line_number: usize,
+    complete_lines: VecDeque<Vec<I::Item>>,
+    finished: bool,
+}
+
+impl<T: Fragment, I: Iterator<Item = T>, W: LineWidths> FirstFitIter<I, W> {
+    fn push(&mut self, fragment: T) {
+        let line_width = self.line_widths.line_width(self.line_number);
+        if self.width + fragment.width() + fragment.penalty_width() - fragment.hanging_width()
+            > line_width
+        {
+            if let Some(count) = first_fit_break(&self.line) {
+                // The remaining fragments are pushed again since they
+                // must fit on the new line.
+                let rest = self.line.split_off(count);
+                self.end_line();
+                self.pending.push_front(fragment);
+                for fragment in rest.into_iter().rev() {
+                    self.pending.push_front(fragment);
+                }
+                return;
+            }
+        }
+
+        self.width += fragment.width() + fragment.whitespace_width();
+        let forced_break = fragment.forced_break();
+        self.line.push(fragment);
+        if forced_break && (!self.pending.is_empty() || self.fragments.peek().is_some()) {
+            self.end_line();
+        }
+    }
+
+    fn end_line(&mut self) {
+        let line = std::mem::take(&mut self.line);
+        self.complete_lines.push_back(line);
+        self.line_number += 1;
+        self.width = 0.0;
+    }
+}
+
+impl<T: Fragment, I: Iterator<Item = T>, W: LineWidths> Iterator for FirstFitIter<I, W> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.complete_lines.pop_front() {
+                return Some(line);
+            }
+            if self.finished {
+                return None;
+            }
+            match self.pending.pop_front().or_else(|| self.fragments.next()) {
+                Some(fragment) => self.push(fragment),
+                None => {
+                    self.finished = true;
+                    return Some(std::mem::take(&mut self.line));
+                }
+            }
+        }
+    }
+}
+
+/// Split `fragments` into lines ending at the given indices.
+pub(crate) fn lines_from_indices<'a, T>(fragments: &'a [T], indices: &[usize]) -> Vec<&'a [T]> {
+    let mut start = 0;
+    let mut lines = Vec::with_capacity(indices.len());
+    for &end in indices {
+        lines.push(&fragments[start..end]);
+        start = end;
     }
-    lines.push(&fragments[start..]);
     lines
 }
@@ -0,0 +1,232 @@
+use crate::core::{Fragment, Word};
+use crate::wrap_algorithms::{
+    higher_priority_breaks, lines_from_indices, to_f64_line_widths, wrap_first_fit_indices,
+};
+use crate::wrap_algorithms::{LineWidths, WrapAlgorithm};
+
+/// Wrap words into lines of roughly equal width.
+///
+/// This is similar to `text-wrap: balance` in CSS and works well for
+/// short texts such as headlines. Implemented by [`wrap_balanced`],
+/// please see that function for details and examples.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Balanced;
+
+impl Balanced {
+    /// Create a new balanced wrapping algorithm.
+    pub const fn new() -> Self {
+        Balanced
+    }
+}
+
+impl WrapAlgorithm for Balanced {
+    #[inline]
+    fn wrap<'a, 'b>(&self, words: &'b [Word<'a>], line_widths: &'b [usize]) -> Vec<&'b [Word<'a>]> {
+        wrap_balanced(words, &to_f64_line_widths(line_widths))
+    }
+}
+
+/// Wrap abstract fragments into lines of roughly equal width.
+///
+/// The fragments are wrapped into the same number of lines as
+/// [`wrap_first_fit`](super::wrap_first_fit) would use, but the line
+/// breaks are chosen to minimize the difference between the longest
+/// and the shortest line. Unlike
+/// [`wrap_optimal_fit`](super::wrap_optimal_fit), the last line is
+/// included. This avoids a headline or a button label with one long
+/// line and one short line.
+///
+/// The `line_widths` give the maximum width of each line, see
+/// [`LineWidths`]. Like the other wrapping algorithms, this function
+/// respects [`Fragment::forced_break`], [`Fragment::keep_with_next`],
+/// and [`Fragment::break_priority`].
+///
+/// The running time grows quickly with the number of fragments, so
+/// use this for short texts only.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::{wrap_balanced, wrap_first_fit};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// // Helper to convert wrapped lines to a Vec<String>.
+/// fn lines_to_strings(lines: Vec<&[Word<'_>]>) -> Vec<String> {
+///     lines.iter().map(|line| {
+///         line.iter().map(|word| &**word).collect::<Vec<_>>().join(" ")
+///     }).collect::<Vec<_>>()
+/// }
+///
+/// let text = "Textwrap releases a new version today";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(lines_to_strings(wrap_first_fit(&words, &[30.0])),
+///            vec!["Textwrap releases a new", "version today"]);
+/// assert_eq!(lines_to_strings(wrap_balanced(&words, &[30.0])),
+///            vec!["Textwrap releases a", "new version today"]);
+/// ```
+pub fn wrap_balanced<'a, T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &'a [T],
+    line_widths: &W,
+) -> Vec<&'a [T]> {
+    let indices = wrap_balanced_indices(fragments, line_widths);
+    lines_from_indices(fragments, &indices)
+}
+
+/// Find line breaks which make the lines roughly equal in width.
+///
+/// This works like [`wrap_balanced`], but instead of returning the
+/// lines as slices, the index after the last fragment of each line
+/// is returned. The last index is always `fragments.len()`.
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::wrap_balanced_indices;
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "Textwrap releases a new version today";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(wrap_balanced_indices(&words, &[30.0]), vec![3, 6]);
+/// ```
+pub fn wrap_balanced_indices<T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &[T],
+    line_widths: &W,
+) -> Vec<usize> {
+    let greedy = wrap_first_fit_indices(fragments, line_widths);
+    let line_count = greedy.len();
+    if line_count <= 1 {
+        return greedy;
+    }
+
+    let len = fragments.len();
+    let mut widths = Vec::with_capacity(len + 1);
+    let mut width = 0.0;
+    widths.push(width);
+    // Number of forced breaks before each fragment.
+    let mut forced_breaks = Vec::with_capacity(len + 1);
+    let mut forced = 0;
+    forced_breaks.push(forced);
+    // Number of possible breaks before each fragment. A line without
+    // possible breaks is a single group of fragments.
+    let mut possible_breaks = Vec::with_capacity(len + 1);
+    let mut possible = 0;
+    possible_breaks.push(possible);
+    for fragment in fragments {
+        width += fragment.width() + fragment.whitespace_width();
+        widths.push(width);
+        if fragment.forced_break() {
+            forced += 1;
+        }
+        forced_breaks.push(forced);
+        if !fragment.keep_with_next() || fragment.forced_break() {
+            possible += 1;
+        }
+        possible_breaks.push(possible);
+    }
+    let higher_breaks = higher_priority_breaks(fragments);
+
+    // Width of a line containing fragments[i..j].
+    let span_width = |i: usize, j: usize| {
+        widths[j] - widths[i] - fragments[j - 1].whitespace_width()
+            + fragments[j - 1].penalty_width()
+            - fragments[j - 1].hanging_width()
+    };
+    // Can fragments[i..j] form a line? Lines which are too wide are
+    // only allowed if they cannot be broken.
+    let is_valid = |i: usize, j: usize, line_number: usize| {
+        if forced_breaks[j - 1] > forced_breaks[i] {
+            return false;
+        }
+        let last = &fragments[j - 1];
+        if j < len && last.keep_with_next() && !last.forced_break() {
+            return false;
+        }
+        if j < len && higher_breaks[j] > i {
+            return false;
+        }
+        span_width(i, j) <= line_widths.line_width(line_number)
+            || possible_breaks[j - 1] == possible_breaks[i]
+    };
+
+    // Every valid line width is a candidate for the shortest line.
+    let max_line_width = (0..line_count)
+        .map(|line_number| line_widths.line_width(line_number))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let mut shortest_widths = Vec::new();
+    for i in 0..len {
+        for j in i + 1..=len {
+            if span_width(i, j) > max_line_width && possible_breaks[j - 1] > possible_breaks[i] {
+                break;
+            }
+            shortest_widths.push(span_width(i, j));
+        }
+    }
+    shortest_widths.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    shortest_widths.dedup();
+
+    // For each candidate, we find the line breaks which minimize the
+    // longest line when no line is shorter than the candidate.
+    // longest[l * (len + 1) + j] is the longest line when wrapping
+    // fragments[..j] into l lines.
+    let stride = len + 1;
+    let mut longest = vec![f64::INFINITY; (line_count + 1) * stride];
+    let mut breaks = vec![0; (line_count + 1) * stride];
+    let mut best: Option<(f64, Vec<usize>)> = None;
+    for &shortest in &shortest_widths {
+        if let Some((difference, _)) = best {
+            if difference == 0.0 {
+                break;
+            }
+        }
+
+        longest.iter_mut().for_each(|cost| *cost = f64::INFINITY);
+        longest[0] = f64::NEG_INFINITY;
+        for line_number in 0..line_count {
+            for j in line_number + 1..=len {
+                for i in (line_number..j).rev() {
+                    let width = span_width(i, j);
+                    if width > line_widths.line_width(line_number)
+                        && possible_breaks[j - 1] > possible_breaks[i]
+                    {
+                        // Starting the line earlier will only make it wider.
+                        break;
+                    }
+                    let prev = longest[line_number * stride + i];
+                    if width < shortest || prev == f64::INFINITY {
+                        continue;
+                    }
+                    if !is_valid(i, j, line_number) {
+                        continue;
+                    }
+                    let cost = f64::max(prev, width);
+                    let idx = (line_number + 1) * stride + j;
+                    if cost < longest[idx] {
+                        longest[idx] = cost;
+                        breaks[idx] = i;
+                    }
+                }
+            }
+        }
+
+        let difference = longest[line_count * stride + len] - shortest;
+        let improved = match best {
+            Some((best_difference, _)) => difference < best_difference,
+            None => difference.is_finite(),
+        };
+        if improved {
+            let mut indices = Vec::with_capacity(line_count);
+            let mut j = len;
+            for line_number in (1..=line_count).rev() {
+                indices.push(j);
+                j = breaks[line_number * stride + j];
+            }
+            indices.reverse();
+            best = Some((difference, indices));
+        }
+    }
+
+    match best {
+        Some((_, indices)) => indices,
+        None => greedy,
+    }
+}
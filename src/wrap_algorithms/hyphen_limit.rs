@@ -0,0 +1,122 @@
+use crate::core::{Fragment, Word};
+use crate::wrap_algorithms::{lines_from_indices, WrapAlgorithm};
+
+/// Limit the number of consecutive lines which end with a hyphen.
+///
+/// Narrow columns with automatic hyphenation sometimes end many
+/// lines in a row with a hyphen. Such a “hyphen ladder” is considered
+/// poor typography. A `HyphenLimit` wraps words with another wrapping
+/// algorithm and enforces that at most
+/// [`HyphenLimit::max_hyphenated_lines`] consecutive lines end with a
+/// hyphen.
+///
+/// When a line would exceed the limit, it is ended at the latest
+/// earlier break which does not need a hyphen, and the remaining
+/// words are wrapped again by the inner algorithm, starting with the
+/// following line. If the line has no such break, the hyphen is kept
+/// since the line would otherwise be too long. The inner algorithm
+/// can thus run several times for a single paragraph.
+///
+/// A line ends with a hyphen if its last word was split and either
+/// gets a hyphen as its penalty or already ends with `-`.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::{FirstFit, HyphenLimit};
+/// use textwrap::{wrap, Options};
+///
+/// let text = "Textwrap is a well-known, cross-platform, state-of-the-art text wrapper";
+/// let options = Options::new(19).wrap_algorithm(FirstFit);
+/// assert_eq!(wrap(text, &options),
+///            vec!["Textwrap is a well-",
+///                 "known, cross-",
+///                 "platform, state-of-",
+///                 "the-art text",
+///                 "wrapper"]);
+///
+/// let options = options.wrap_algorithm(HyphenLimit::new(FirstFit, 1));
+/// assert_eq!(wrap(text, &options),
+///            vec!["Textwrap is a well-",
+///                 "known,",
+///                 "cross-platform,",
+///                 "state-of-the-art",
+///                 "text wrapper"]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct HyphenLimit<A> {
+    algorithm: A,
+    /// The maximum number of consecutive lines which end with a
+    /// hyphen.
+    pub max_hyphenated_lines: usize,
+}
+
+impl<A: WrapAlgorithm> HyphenLimit<A> {
+    /// Wrap with `algorithm`, but end at most `max_hyphenated_lines`
+    /// consecutive lines with a hyphen.
+    pub const fn new(algorithm: A, max_hyphenated_lines: usize) -> Self {
+        HyphenLimit {
+            algorithm,
+            max_hyphenated_lines,
+        }
+    }
+}
+
+impl<A: WrapAlgorithm + Clone + 'static> WrapAlgorithm for HyphenLimit<A> {
+    fn wrap<'a, 'b>(&self, words: &'b [Word<'a>], line_widths: &'b [usize]) -> Vec<&'b [Word<'a>]> {
+        let mut line_ends = Vec::new();
+        self.wrap_into(words, line_widths, &mut line_ends);
+        lines_from_indices(words, &line_ends)
+    }
+
+    fn wrap_into(&self, words: &[Word<'_>], line_widths: &[usize], line_ends: &mut Vec<usize>) {
+        if words.is_empty() {
+            return self.algorithm.wrap_into(words, line_widths, line_ends);
+        }
+
+        line_ends.clear();
+        let mut start = 0;
+        let mut hyphenated_lines = 0;
+        let mut ends = Vec::new();
+        'wrap: while start < words.len() {
+            // Wrap the remaining words, starting with the next line.
+            let skip = line_ends.len().min(line_widths.len().saturating_sub(1));
+            self.algorithm
+                .wrap_into(&words[start..], &line_widths[skip..], &mut ends);
+
+            let mut line_start = start;
+            for &end in ends.iter() {
+                let line_end = start + end;
+                if line_end == words.len() || !ends_with_hyphen(&words[line_end - 1]) {
+                    hyphenated_lines = 0;
+                } else if hyphenated_lines < self.max_hyphenated_lines {
+                    hyphenated_lines += 1;
+                } else if let Some(k) = earlier_break(words, line_start, line_end) {
+                    line_ends.push(k);
+                    hyphenated_lines = 0;
+                    start = k;
+                    continue 'wrap;
+                }
+                line_ends.push(line_end);
+                line_start = line_end;
+            }
+            break;
+        }
+    }
+}
+
+/// Does a line ending with `word` end with a hyphen?
+fn ends_with_hyphen(word: &Word<'_>) -> bool {
+    word.whitespace.is_empty() && (!word.penalty.is_empty() || word.word.ends_with('-'))
+}
+
+/// Find the latest break inside `words[start..end]` which does not
+/// need a hyphen and which has at least the priority of the break at
+/// `end`.
+fn earlier_break(words: &[Word<'_>], start: usize, end: usize) -> Option<usize> {
+    let priority = words[end - 1].break_priority();
+    (start + 1..end).rev().find(|&k| {
+        let word = &words[k - 1];
+        !ends_with_hyphen(word) && !word.keep_with_next() && word.break_priority() >= priority
+    })
+}
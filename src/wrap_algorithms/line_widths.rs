@@ -0,0 +1,264 @@
+//! Line widths and shapes for the wrapping algorithms.
+
+use std::ops::Range;
+
+/// Describes the width of every line in a paragraph.
+///
+/// The wrapping algorithms ask for the width of each line by line
+/// number. Besides the width, a line can also have a left offset,
+/// which tells you where to place the line when rendering it. The
+/// wrapping algorithms only use the line widths, but together with
+/// the offsets, this lets text flow around floated images and other
+/// rectangular exclusions.
+///
+/// The trait is implemented for slices, arrays, and vectors of `f64`
+/// values: the final element is used for all remaining lines and an
+/// empty slice means that all lines have a width of zero. The
+/// [`HangingIndent`], [`DropCap`], and [`Cutout`] structs describe
+/// common shapes.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::LineWidths;
+///
+/// let line_widths = [10.0, 20.0];
+/// assert_eq!(line_widths.line_width(0), 10.0);
+/// assert_eq!(line_widths.line_width(5), 20.0);
+/// assert_eq!(line_widths.left_offset(5), 0.0);
+/// ```
+pub trait LineWidths {
+    /// Width of the line with the given line number. The first line
+    /// has line number zero.
+    fn line_width(&self, line_number: usize) -> f64;
+
+    /// Distance from the left margin to the start of the line with
+    /// the given line number.
+    ///
+    /// The default implementation returns `0.0`.
+    fn left_offset(&self, line_number: usize) -> f64 {
+        let _ = line_number;
+        0.0
+    }
+}
+
+impl LineWidths for [f64] {
+    fn line_width(&self, line_number: usize) -> f64 {
+        // The final line width is used for all remaining lines.
+        match self.get(line_number) {
+            Some(&width) => width,
+            None => self.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+impl<const N: usize> LineWidths for [f64; N] {
+    fn line_width(&self, line_number: usize) -> f64 {
+        self[..].line_width(line_number)
+    }
+}
+
+impl LineWidths for Vec<f64> {
+    fn line_width(&self, line_number: usize) -> f64 {
+        self[..].line_width(line_number)
+    }
+}
+
+impl<T: LineWidths + ?Sized> LineWidths for &T {
+    fn line_width(&self, line_number: usize) -> f64 {
+        (**self).line_width(line_number)
+    }
+
+    fn left_offset(&self, line_number: usize) -> f64 {
+        (**self).left_offset(line_number)
+    }
+}
+
+/// A paragraph with a hanging indent: the first line uses the full
+/// width and the following lines are indented.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::{wrap_first_fit, HangingIndent, LineWidths};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "Term: the definition of the term.";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// let shape = HangingIndent::new(16.0, 4.0);
+/// let lines = wrap_first_fit(&words, &shape);
+/// let rendered = lines.iter().enumerate().map(|(line_number, line)| {
+///     let offset = " ".repeat(shape.left_offset(line_number) as usize);
+///     let words = line.iter().map(|word| word.word).collect::<Vec<_>>();
+///     format!("{}{}", offset, words.join(" "))
+/// }).collect::<Vec<_>>();
+/// assert_eq!(rendered, vec!["Term: the",
+///                           "    definition",
+///                           "    of the term."]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HangingIndent {
+    /// Width of the first line.
+    pub width: f64,
+    /// Indentation of the following lines.
+    pub indent: f64,
+}
+
+impl HangingIndent {
+    /// Create a hanging indent shape. The lines after the first line
+    /// are `indent` narrower than `width`.
+    pub const fn new(width: f64, indent: f64) -> Self {
+        HangingIndent { width, indent }
+    }
+}
+
+impl LineWidths for HangingIndent {
+    fn line_width(&self, line_number: usize) -> f64 {
+        self.width - self.left_offset(line_number)
+    }
+
+    fn left_offset(&self, line_number: usize) -> f64 {
+        if line_number == 0 {
+            0.0
+        } else {
+            self.indent
+        }
+    }
+}
+
+/// A paragraph starting with a drop cap: a large initial letter
+/// which spans several lines at the left margin.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::{DropCap, LineWidths};
+///
+/// let shape = DropCap::new(40.0, 5.0, 3);
+/// assert_eq!(shape.line_width(2), 35.0);
+/// assert_eq!(shape.left_offset(2), 5.0);
+/// assert_eq!(shape.line_width(3), 40.0);
+/// assert_eq!(shape.left_offset(3), 0.0);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DropCap {
+    /// Width of the paragraph.
+    pub width: f64,
+    /// Width of the drop cap, including any space after it.
+    pub cap_width: f64,
+    /// Number of lines spanned by the drop cap.
+    pub lines: usize,
+}
+
+impl DropCap {
+    /// Create a drop cap shape. The first `lines` lines are
+    /// `cap_width` narrower than `width`.
+    pub const fn new(width: f64, cap_width: f64, lines: usize) -> Self {
+        DropCap {
+            width,
+            cap_width,
+            lines,
+        }
+    }
+}
+
+impl LineWidths for DropCap {
+    fn line_width(&self, line_number: usize) -> f64 {
+        self.width - self.left_offset(line_number)
+    }
+
+    fn left_offset(&self, line_number: usize) -> f64 {
+        if line_number < self.lines {
+            self.cap_width
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A paragraph with a rectangle cut out at the left or right margin,
+/// such as a floated image.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::{wrap_first_fit, Cutout, LineWidths};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// // An image 8 columns wide is floated right on lines 1 and 2.
+/// let shape = Cutout::new(20.0, 1..3).right(8.0);
+/// assert_eq!(shape.line_width(0), 20.0);
+/// assert_eq!(shape.line_width(1), 12.0);
+/// assert_eq!(shape.left_offset(1), 0.0);
+///
+/// let text = "The image floats to the right of this text.";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// let lines = wrap_first_fit(&words, &shape)
+///     .iter()
+///     .map(|line| line.iter().map(|word| word.word).collect::<Vec<_>>().join(" "))
+///     .collect::<Vec<_>>();
+/// assert_eq!(lines, vec!["The image floats to",
+///                        "the right of",
+///                        "this text."]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cutout {
+    /// Width of the paragraph.
+    pub width: f64,
+    /// The line numbers next to the cut-out.
+    pub lines: Range<usize>,
+    /// Width cut out at the left margin.
+    pub left: f64,
+    /// Width cut out at the right margin.
+    pub right: f64,
+}
+
+impl Cutout {
+    /// Create a shape with a cut-out next to the given lines. Use
+    /// [`Cutout::left`] and [`Cutout::right`] to set the width of the
+    /// cut-out.
+    pub const fn new(width: f64, lines: Range<usize>) -> Self {
+        Cutout {
+            width,
+            lines,
+            left: 0.0,
+            right: 0.0,
+        }
+    }
+
+    /// Change [`self.left`]. This is the width cut out at the left
+    /// margin.
+    ///
+    /// [`self.left`]: #structfield.left
+    pub fn left(self, left: f64) -> Self {
+        Cutout { left, ..self }
+    }
+
+    /// Change [`self.right`]. This is the width cut out at the right
+    /// margin.
+    ///
+    /// [`self.right`]: #structfield.right
+    pub fn right(self, right: f64) -> Self {
+        Cutout { right, ..self }
+    }
+}
+
+impl LineWidths for Cutout {
+    fn line_width(&self, line_number: usize) -> f64 {
+        if self.lines.contains(&line_number) {
+            self.width - self.left - self.right
+        } else {
+            self.width
+        }
+    }
+
+    fn left_offset(&self, line_number: usize) -> f64 {
+        if self.lines.contains(&line_number) {
+            self.left
+        } else {
+            0.0
+        }
+    }
+}
@@ -1,7 +1,11 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
 
 use crate::core::{Fragment, Word};
-use crate::wrap_algorithms::WrapAlgorithm;
+use crate::wrap_algorithms::{
+    higher_priority_breaks, lines_from_indices, to_f64_line_widths, wrap_first_fit_indices,
+};
+use crate::wrap_algorithms::{LineWidths, WrapAlgorithm};
 
 /// Wrap words using an advanced algorithm with look-ahead.
 ///
@@ -9,18 +13,392 @@ use crate::wrap_algorithms::WrapAlgorithm;
 /// optimal line breaks. Implemented by [`wrap_optimal_fit`], please
 /// see that function for details and examples.
 ///
-/// **Note:** Only available when the `smawk` Cargo feature is
-/// enabled.
-#[derive(Clone, Copy, Debug, Default)]
-pub struct OptimalFit;
+/// The fields of the struct are mostly the penalties used by the
+/// algorithm. You can adjust them to change how the line breaks are
+/// chosen:
+///
+/// ```
+/// use textwrap::wrap_algorithms::OptimalFit;
+///
+/// let mut penalties = OptimalFit::new();
+/// // Never hyphenate two lines in a row if it can be avoided.
+/// penalties.double_hyphen_penalty = 10_000;
+/// ```
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+#[derive(Clone, Copy, Debug)]
+pub struct OptimalFit {
+    /// Per-line penalty. This is added for every line, which makes it
+    /// expensive to output more lines than the minimum required.
+    pub nline_penalty: i32,
+
+    /// Per-character cost for lines that overflow the target line
+    /// width.
+    ///
+    /// With a default value of 50², every single character costs as
+    /// much as leaving a gap of 50 characters behind. This is because
+    /// we assign as cost of `gap * gap` to a short line. When
+    /// wrapping text, this means that we can overflow the line by 1
+    /// character in extreme cases:
+    ///
+    /// ```
+    /// use textwrap::wrap_algorithms::{wrap_optimal_fit, OptimalFit};
+    /// use textwrap::core::Word;
+    ///
+    /// let short = "foo ";
+    /// let long = "x".repeat(50);
+    /// let fragments = vec![Word::from(short), Word::from(&long)];
+    /// let penalties = OptimalFit::new();
+    ///
+    /// // Perfect fit, both words are on a single line with no overflow.
+    /// let wrapped = wrap_optimal_fit(&fragments, &[(short.len() + long.len()) as f64], &penalties);
+    /// assert_eq!(wrapped, vec![&[Word::from(short), Word::from(&long)]]);
+    ///
+    /// // The words no longer fit, yet we get a single line back. While
+    /// // the cost of overflow (`1 * 2500`) is the same as the cost of the
+    /// // gap (`50 * 50 = 2500`), the tie is broken by `nline_penalty`
+    /// // which makes it cheaper to overflow than to use two lines.
+    /// let wrapped = wrap_optimal_fit(&fragments, &[(short.len() + long.len() - 1) as f64], &penalties);
+    /// assert_eq!(wrapped, vec![&[Word::from(short), Word::from(&long)]]);
+    ///
+    /// // The cost of overflow would be 2 * 2500, whereas the cost of
+    /// // the gap is only `49 * 49 + nline_penalty = 2401 + 1000 =
+    /// // 3401`. We therefore get two lines.
+    /// let wrapped = wrap_optimal_fit(&fragments, &[(short.len() + long.len() - 2) as f64], &penalties);
+    /// assert_eq!(wrapped, vec![&[Word::from(short)],
+    ///                          &[Word::from(&long)]]);
+    /// ```
+    ///
+    /// This only happens if the overflowing word is 50 characters
+    /// long _and_ if the word overflows the line by exactly one
+    /// character. If it overflows by more than one character, the
+    /// overflow penalty will quickly outgrow the cost of the gap, as
+    /// seen above.
+    pub overflow_penalty: i32,
+
+    /// When should the a single word on the last line be considered
+    /// "too short"?
+    ///
+    /// If the last line of the text consist of a single word and if
+    /// this word is shorter than `1 / short_last_line_fraction` of
+    /// the line width, then the final line will be considered "short"
+    /// and `short_last_line_penalty` is added as an extra penalty.
+    pub short_last_line_fraction: usize,
+
+    /// Penalty for a last line with a single short word.
+    ///
+    /// Set this to zero if you do not want to penalize short last
+    /// lines.
+    pub short_last_line_penalty: i32,
+
+    /// Minimum number of fragments on the last line of a paragraph.
+    ///
+    /// A last line with fewer fragments is an _orphan_ and gets the
+    /// `orphan_penalty`. The default value of 1 disables this check.
+    /// Lines ending with a [`Fragment::forced_break`] also count as
+    /// last lines, but a paragraph with a single line is never
+    /// penalized.
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// use textwrap::wrap_algorithms::{wrap_optimal_fit, OptimalFit};
+    /// use textwrap::{AsciiSpace, WordSeparator};
+    ///
+    /// // Helper to convert wrapped lines to a Vec<String>.
+    /// fn lines_to_strings(lines: Vec<&[Word<'_>]>) -> Vec<String> {
+    ///     lines.iter().map(|line| {
+    ///         line.iter().map(|word| &**word).collect::<Vec<_>>().join(" ")
+    ///     }).collect::<Vec<_>>()
+    /// }
+    ///
+    /// let text = "To be, or not to be: that is the question";
+    /// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+    /// let mut penalties = OptimalFit::new();
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[16.0], &penalties)),
+    ///            vec!["To be, or not to", "be: that is the", "question"]);
+    ///
+    /// penalties.orphan_min_fragments = 2;
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[16.0], &penalties)),
+    ///            vec!["To be, or not", "to be: that is", "the question"]);
+    /// ```
+    pub orphan_min_fragments: usize,
+
+    /// Penalty for a last line with fewer than
+    /// `orphan_min_fragments` fragments.
+    ///
+    /// Set this to `i32::MAX` to forbid such lines entirely. When
+    /// orphans are penalized, the line breaks cannot be found with
+    /// the SMAWK algorithm and a slower search is used instead.
+    pub orphan_penalty: i32,
+
+    /// Penalty for lines ending with a hyphen.
+    pub hyphen_penalty: i32,
+
+    /// Extra penalty for two consecutive lines ending with a hyphen.
+    ///
+    /// This is added on top of `hyphen_penalty` and corresponds to
+    /// `\doublehyphendemerits` in TeX. Increase this to avoid
+    /// “ladders” of hyphens in narrow columns.
+    ///
+    /// Since the penalty depends on the previous line, the line
+    /// breaks cannot be found with the SMAWK algorithm when this is
+    /// non-zero. A slower search is used instead.
+    pub double_hyphen_penalty: i32,
+
+    /// Penalty for adjacent lines which are visually incompatible.
+    ///
+    /// Lines are classified as _tight_, _decent_, _loose_, or _very
+    /// loose_ depending on how much their whitespace must be adjusted
+    /// to fill the line, see [`wrap_optimal_fit`]. This penalty is
+    /// added when two adjacent lines are more than one class apart,
+    /// such as a tight line followed by a loose line. This
+    /// corresponds to `\adjdemerits` in TeX.
+    ///
+    /// Like with `double_hyphen_penalty`, a slower search is used
+    /// instead of the SMAWK algorithm when this is non-zero.
+    pub adjacent_fitness_penalty: i32,
+
+    /// Penalty for a line which ends where the text had no line
+    /// break before.
+    ///
+    /// This is only used if some fragment has an existing line break,
+    /// see [`Fragment::existing_break`]. The penalty makes the
+    /// algorithm keep the existing line breaks when text is
+    /// re-wrapped, unless this makes the lines much worse. Lines
+    /// ending with a forced break and the last line are never
+    /// penalized.
+    pub new_break_penalty: i32,
+
+    /// Number of lines to add to or remove from the optimal solution.
+    ///
+    /// With a positive looseness, the paragraph is made this many
+    /// lines longer than optimal and with a negative looseness, it is
+    /// made shorter. If this is impossible, the number of lines
+    /// closest to the requested number is used. This corresponds to
+    /// `\looseness` in TeX and can be used to fix widows and orphans
+    /// when laying out pages.
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// use textwrap::wrap_algorithms::{wrap_optimal_fit, OptimalFit};
+    /// use textwrap::{AsciiSpace, WordSeparator};
+    ///
+    /// // Helper to convert wrapped lines to a Vec<String>.
+    /// fn lines_to_strings(lines: Vec<&[Word<'_>]>) -> Vec<String> {
+    ///     lines.iter().map(|line| {
+    ///         line.iter().map(|word| &**word).collect::<Vec<_>>().join(" ")
+    ///     }).collect::<Vec<_>>()
+    /// }
+    ///
+    /// let text = "To be, or not to be: that is the question";
+    /// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+    /// let mut penalties = OptimalFit::new();
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[14.0], &penalties)),
+    ///            vec!["To be, or not", "to be: that is", "the question"]);
+    ///
+    /// penalties.looseness = 1;
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[14.0], &penalties)),
+    ///            vec!["To be, or", "not to be:", "that is the", "question"]);
+    ///
+    /// // The text cannot fit on two lines, so we get the optimal
+    /// // three lines back:
+    /// penalties.looseness = -1;
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[14.0], &penalties)),
+    ///            vec!["To be, or not", "to be: that is", "the question"]);
+    /// ```
+    ///
+    /// A non-zero looseness makes the algorithm consider every
+    /// possible break for every line count, which takes quadratic
+    /// time.
+    pub looseness: i32,
+
+    /// Which line breaks to use when several ways of wrapping the
+    /// fragments have the same cost.
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// use textwrap::wrap_algorithms::{wrap_optimal_fit, OptimalFit, TieBreak};
+    /// use textwrap::{AsciiSpace, WordSeparator};
+    ///
+    /// // Helper to convert wrapped lines to a Vec<String>.
+    /// fn lines_to_strings(lines: Vec<&[Word<'_>]>) -> Vec<String> {
+    ///     lines.iter().map(|line| {
+    ///         line.iter().map(|word| &**word).collect::<Vec<_>>().join(" ")
+    ///     }).collect::<Vec<_>>()
+    /// }
+    ///
+    /// // The first two lines leave gaps of 4 and 1 columns or 1 and 4
+    /// // columns, both of which cost 4² + 1² = 17.
+    /// let text = "To be, or not to be: that is the question";
+    /// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+    /// let mut penalties = OptimalFit::new();
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[10.0], &penalties)),
+    ///            vec!["To be,", "or not to", "be: that", "is the", "question"]);
+    ///
+    /// penalties.tie_break = TieBreak::Later;
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[10.0], &penalties)),
+    ///            vec!["To be, or", "not to", "be: that", "is the", "question"]);
+    /// ```
+    pub tie_break: TieBreak,
+
+    /// Maximum number of fragments on a line.
+    ///
+    /// When set, only line breaks within the last `lookback`
+    /// fragments are considered when finding the best line break
+    /// before a fragment. This uses a simpler algorithm which runs in
+    /// `O(n * lookback)` time instead of the SMAWK algorithm. With a
+    /// small `lookback`, this is faster for very long paragraphs and
+    /// the line breaks are still optimal as long as no line needs
+    /// more than `lookback` fragments.
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// use textwrap::wrap_algorithms::{wrap_optimal_fit, OptimalFit};
+    /// use textwrap::{AsciiSpace, WordSeparator};
+    ///
+    /// // Helper to convert wrapped lines to a Vec<String>.
+    /// fn lines_to_strings(lines: Vec<&[Word<'_>]>) -> Vec<String> {
+    ///     lines.iter().map(|line| {
+    ///         line.iter().map(|word| &**word).collect::<Vec<_>>().join(" ")
+    ///     }).collect::<Vec<_>>()
+    /// }
+    ///
+    /// let text = "To be, or not to be: that is the question";
+    /// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+    /// let mut penalties = OptimalFit::new();
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[16.0], &penalties)),
+    ///            vec!["To be, or not to", "be: that is the", "question"]);
+    ///
+    /// penalties.lookback = Some(4);
+    /// assert_eq!(lines_to_strings(wrap_optimal_fit(&words, &[16.0], &penalties)),
+    ///            vec!["To be, or not", "to be: that is", "the question"]);
+    /// ```
+    ///
+    /// The default value of `None` means that all line breaks are
+    /// considered.
+    pub lookback: Option<usize>,
+
+    /// Maximum number of line costs to compute.
+    ///
+    /// The optimal-fit algorithm computes the cost of several
+    /// candidate lines for each fragment. When the budget is exceeded,
+    /// [`wrap_optimal_fit`] gives up and falls back to
+    /// [`wrap_first_fit`](super::wrap_first_fit), just like when the
+    /// cost overflows. This ensures that interactive applications
+    /// stay responsive, even with very long or adversarial input. The
+    /// budget is counted in cost computations instead of time so that
+    /// the output is deterministic.
+    ///
+    /// [`try_wrap_optimal_fit`] returns
+    /// [`OptimalFitError::BudgetExceeded`] when the budget is
+    /// exceeded.
+    ///
+    /// ```
+    /// use textwrap::core::Word;
+    /// use textwrap::wrap_algorithms::{try_wrap_optimal_fit, wrap_first_fit, wrap_optimal_fit};
+    /// use textwrap::wrap_algorithms::{OptimalFit, OptimalFitError};
+    /// use textwrap::{AsciiSpace, WordSeparator};
+    ///
+    /// let text = "These few words will unfortunately not wrap nicely.";
+    /// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+    /// let mut penalties = OptimalFit::new();
+    /// penalties.budget = Some(10);
+    /// assert_eq!(wrap_optimal_fit(&words, &[15.0], &penalties),
+    ///            wrap_first_fit(&words, &[15.0]));
+    ///
+    /// penalties.budget = Some(100);
+    /// assert_ne!(wrap_optimal_fit(&words, &[15.0], &penalties),
+    ///            wrap_first_fit(&words, &[15.0]));
+    ///
+    /// penalties.budget = Some(10);
+    /// assert_eq!(try_wrap_optimal_fit(&words, &[15.0], &penalties),
+    ///            Err(OptimalFitError::BudgetExceeded));
+    /// ```
+    ///
+    /// The default value of `None` means no limit.
+    pub budget: Option<usize>,
+}
+
+/// How to choose between line breaks with the same cost in
+/// [`wrap_optimal_fit`].
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Prefer breaking lines early. This puts fewer fragments on the
+    /// first lines of a paragraph.
+    Earlier,
+    /// Prefer breaking lines late. This puts more fragments on the
+    /// first lines of a paragraph.
+    Later,
+}
+
+impl OptimalFit {
+    /// Default penalties for the optimal-fit algorithm.
+    ///
+    /// The penalties here have been hand-tuned to produce "nice"
+    /// line breaks when wrapping text in a terminal. The penalties
+    /// for consecutive hyphens and for incompatible adjacent lines
+    /// are disabled by default.
+    pub const fn new() -> Self {
+        OptimalFit {
+            nline_penalty: 1000,
+            overflow_penalty: 50 * 50,
+            short_last_line_fraction: 4,
+            short_last_line_penalty: 25,
+            orphan_min_fragments: 1,
+            orphan_penalty: 1000,
+            hyphen_penalty: 25,
+            double_hyphen_penalty: 0,
+            adjacent_fitness_penalty: 0,
+            new_break_penalty: 1000,
+            looseness: 0,
+            tie_break: TieBreak::Earlier,
+            lookback: None,
+            budget: None,
+        }
+    }
+}
+
+impl Default for OptimalFit {
+    fn default() -> Self {
+        OptimalFit::new()
+    }
+}
 
 impl WrapAlgorithm for OptimalFit {
     #[inline]
     fn wrap<'a, 'b>(&self, words: &'b [Word<'a>], line_widths: &'b [usize]) -> Vec<&'b [Word<'a>]> {
-        wrap_optimal_fit(words, line_widths)
+        wrap_optimal_fit(words, &to_f64_line_widths(line_widths), self)
+    }
+}
+
+/// Error during the [`try_wrap_optimal_fit`] computation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OptimalFitError {
+    /// The cost of a line became infinite or is not a number, which
+    /// can happen with very large fragment or line widths.
+    Overflow,
+    /// More line costs were needed than allowed by
+    /// [`OptimalFit::budget`].
+    BudgetExceeded,
+}
+
+impl std::fmt::Display for OptimalFitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptimalFitError::Overflow => write!(f, "wrap_optimal_fit cost computation overflowed"),
+            OptimalFitError::BudgetExceeded => {
+                write!(f, "wrap_optimal_fit exceeded its cost computation budget")
+            }
+        }
     }
 }
 
+impl std::error::Error for OptimalFitError {}
+
 /// Cache for line numbers. This is necessary to avoid a O(n**2)
 /// behavior when computing line numbers in [`wrap_optimal_fit`].
 struct LineNumbers {
@@ -39,7 +417,7 @@ impl LineNumbers {
     fn get<T>(&self, i: usize, minima: &[(usize, T)]) -> usize {
         while self.line_numbers.borrow_mut().len() < i + 1 {
             let pos = self.line_numbers.borrow().len();
-            let line_number = 1 + self.get(minima[pos].0, &minima);
+            let line_number = 1 + self.get(minima[pos].0, minima);
             self.line_numbers.borrow_mut().push(line_number);
         }
 
@@ -47,69 +425,81 @@ impl LineNumbers {
     }
 }
 
-/// Per-line penalty. This is added for every line, which makes it
-/// expensive to output more lines than the minimum required.
-const NLINE_PENALTY: i32 = 1000;
+/// Largest badness of a line with stretchable or shrinkable
+/// whitespace. This is the value of `inf_bad` in TeX.
+const INF_BAD: f64 = 10_000.0;
 
-/// Per-character cost for lines that overflow the target line width.
-///
-/// With a value of 50², every single character costs as much as
-/// leaving a gap of 50 characters behind. This is becuase we assign
-/// as cost of `gap * gap` to a short line. This means that we can
-/// overflow the line by 1 character in extreme cases:
-///
-/// ```
-/// use textwrap::wrap_algorithms::wrap_optimal_fit;
-/// use textwrap::core::Word;
-///
-/// let short = "foo ";
-/// let long = "x".repeat(50);
-/// let fragments = vec![Word::from(short), Word::from(&long)];
-///
-/// // Perfect fit, both words are on a single line with no overflow.
-/// let wrapped = wrap_optimal_fit(&fragments, &[short.len() + long.len()]);
-/// assert_eq!(wrapped, vec![&[Word::from(short), Word::from(&long)]]);
-///
-/// // The words no longer fit, yet we get a single line back. While
-/// // the cost of overflow (`1 * 2500`) is the same as the cost of the
-/// // gap (`50 * 50 = 2500`), the tie is broken by `NLINE_PENALTY`
-/// // which makes it cheaper to overflow than to use two lines.
-/// let wrapped = wrap_optimal_fit(&fragments, &[short.len() + long.len() - 1]);
-/// assert_eq!(wrapped, vec![&[Word::from(short), Word::from(&long)]]);
-///
-/// // The cost of overflow would be 2 * 2500, whereas the cost of the
-/// // gap is only `49 * 49 + NLINE_PENALTY = 2401 + 1000 = 3401`. We
-/// // therefore get two lines.
-/// let wrapped = wrap_optimal_fit(&fragments, &[short.len() + long.len() - 2]);
-/// assert_eq!(wrapped, vec![&[Word::from(short)],
-///                          &[Word::from(&long)]]);
-/// ```
-///
-/// This only happens if the overflowing word is 50 characters long
-/// _and_ if it happens to overflow the line by exactly one character.
-/// If it overflows by more than one character, the overflow penalty
-/// will quickly outgrow the cost of the gap, as seen above.
-const OVERFLOW_PENALTY: i32 = 50 * 50;
-
-/// The last line is short if it is less than 1/4 of the target width.
-const SHORT_LINE_FRACTION: usize = 4;
+/// Fitness class of a line with the given width, as in TeX: 0 is
+/// tight, 1 is decent, 2 is loose, and 3 is very loose. The
+/// `stretch` and `shrink` is the flexibility of the whitespace on
+/// the line.
+fn fitness_class(line_width: f64, target_width: f64, stretch: f64, shrink: f64) -> usize {
+    // The adjustment ratio is infinite if there is no flexibility.
+    let ratio = if line_width > target_width {
+        -((line_width - target_width) / shrink)
+    } else if line_width < target_width {
+        (target_width - line_width) / stretch
+    } else {
+        0.0
+    };
 
-/// Penalize a short last line.
-const SHORT_LAST_LINE_PENALTY: i32 = 25;
+    if ratio < -0.5 {
+        0
+    } else if ratio <= 0.5 {
+        1
+    } else if ratio <= 1.0 {
+        2
+    } else {
+        3
+    }
+}
 
-/// Penalty for lines ending with a hyphen.
-const HYPHEN_PENALTY: i32 = 25;
+/// Badness of a line where the whitespace must be stretched or shrunk
+/// by `delta` columns when the total stretchability or shrinkability
+/// of the whitespace is `flexibility`. This is `100 * r³` where `r`
+/// is the adjustment ratio, as in TeX.
+fn badness(delta: f64, flexibility: f64) -> f64 {
+    if delta <= 0.0 {
+        return 0.0;
+    }
+    // The ratio is infinite if there is no flexibility.
+    let ratio = delta / flexibility;
+    (100.0 * ratio.powi(3)).min(INF_BAD).floor()
+}
 
 /// Wrap abstract fragments into lines with an optimal-fit algorithm.
 ///
-/// The `line_widths` slice gives the target line width for each line
-/// (the last slice element is repeated as necessary). This can be
-/// used to implement hanging indentation.
+/// The `line_widths` give the target width of each line, see
+/// [`LineWidths`]. For a slice, the last element is repeated as
+/// necessary. This can be used to implement hanging indentation and
+/// other shapes. The `penalties` determine the cost of different
+/// kinds of line breaks, see [`OptimalFit`].
 ///
 /// The fragments must already have been split into the desired
 /// widths, this function will not (and cannot) attempt to split them
 /// further when arranging them into lines.
 ///
+/// If the cost of the line breaks overflows, the fragments are
+/// wrapped with [`wrap_first_fit`](super::wrap_first_fit) instead.
+/// Use [`try_wrap_optimal_fit`] if you want to detect this.
+///
+/// A line always ends after a fragment with a
+/// [`Fragment::forced_break`]. Such a line is treated like the last
+/// line of a paragraph, meaning that it can be short without a
+/// penalty. A line never ends after a fragment which must be kept
+/// with the next, see [`Fragment::keep_with_next`]. A line only ends
+/// at a break with a low [`Fragment::break_priority`] if the line
+/// contains no break with a higher priority. Fragments with forced
+/// breaks, fragments kept with the next, and fragments with
+/// low-priority breaks are wrapped by searching backwards from each
+/// break until no earlier line start can be better. This takes
+/// `O(n * w)` time, where `w` is the number of fragments on a line,
+/// unless a negative penalty or a custom line penalty makes every
+/// line start a candidate.
+///
+/// When some fragments have a [`Fragment::existing_break`], lines
+/// ending elsewhere get the [`OptimalFit::new_break_penalty`].
+///
 /// # Optimal-Fit Algorithm
 ///
 /// The algorithm considers all possible break points and picks the
@@ -165,6 +555,12 @@ const HYPHEN_PENALTY: i32 = 25;
 /// [`wrap_first_fit`](super::wrap_first_fit), this function is about
 /// 4 times slower.
 ///
+/// Without the `smawk` Cargo feature, a simpler dynamic programming
+/// algorithm is used instead. For each fragment, it considers the
+/// lines ending with the fragment from the shortest to the longest,
+/// stopping when no longer line can be cheaper. This usually takes
+/// O(_n_ × _w_) time where _w_ is the number of words on a line.
+///
 /// The optimization of per-line costs over the entire paragraph is
 /// inspired by the line breaking algorithm used in TeX, as described
 /// in the 1981 article [_Breaking Paragraphs into
@@ -173,84 +569,1113 @@ const HYPHEN_PENALTY: i32 = 25;
 /// code by David
 /// Eppstein](https://github.com/jfinkels/PADS/blob/master/pads/wrap.py).
 ///
-/// **Note:** Only available when the `smawk` Cargo feature is
-/// enabled.
-pub fn wrap_optimal_fit<'a, 'b, T: Fragment>(
+/// # Penalties
+///
+/// Besides the gaps, the cost of a line includes a penalty for
+/// ending the line with a hyphen and an extra penalty if the previous
+/// line also ended with a hyphen. Lines can also be penalized if they
+/// look very different from the previous line. For this, each line is
+/// put in a _fitness class_ based on the ratio between the gap and
+/// the total width of the whitespace on the line: lines are _tight_
+/// if they overflow, _decent_ if the ratio is at most ½, _loose_ if
+/// it is at most 1, and _very loose_ otherwise. Two adjacent lines
+/// which are more than one class apart, e.g., a decent line followed
+/// by a very loose line, are given the
+/// [`OptimalFit::adjacent_fitness_penalty`].
+///
+/// ```
+/// use textwrap::core::Fragment;
+/// use textwrap::wrap_algorithms::{wrap_optimal_fit, OptimalFit};
+///
+/// // A syllable with a flag indicating if it is followed by a
+/// // hyphenation point. A hyphen is inserted if we break there.
+/// #[derive(Debug)]
+/// struct Syllable<'a>(&'a str, bool);
+///
+/// impl Fragment for Syllable<'_> {
+///     fn width(&self) -> f64 { self.0.len() as f64 }
+///     fn whitespace_width(&self) -> f64 { if self.1 { 0.0 } else { 1.0 } }
+///     fn penalty_width(&self) -> f64 { if self.1 { 1.0 } else { 0.0 } }
+/// }
+///
+/// // Helper to convert wrapped lines to a Vec<String>.
+/// fn lines_to_strings(lines: Vec<&[Syllable<'_>]>) -> Vec<String> {
+///     lines.iter().map(|line| {
+///         let mut result = String::new();
+///         for (idx, syllable) in line.iter().enumerate() {
+///             result.push_str(syllable.0);
+///             match (idx + 1 == line.len(), syllable.1) {
+///                 (false, false) => result.push(' '),
+///                 (true, true) => result.push('-'),
+///                 _ => {}
+///             }
+///         }
+///         result
+///     }).collect()
+/// }
+///
+/// let text = "Hy-phen-ation is use-ful when wrap-ping nar-row col-umns of text";
+/// let mut syllables = Vec::new();
+/// for word in text.split(' ') {
+///     let parts = word.split('-').collect::<Vec<_>>();
+///     for (idx, part) in parts.iter().enumerate() {
+///         syllables.push(Syllable(part, idx + 1 < parts.len()));
+///     }
+/// }
+///
+/// let mut penalties = OptimalFit::new();
+/// assert_eq!(lines_to_strings(wrap_optimal_fit(&syllables, &[18.0], &penalties)),
+///            vec!["Hyphenation is",
+///                 "useful when wrap-",
+///                 "ping narrow col-",
+///                 "umns of text"]);
+///
+/// // Avoid two hyphens in a row:
+/// penalties.double_hyphen_penalty = 100;
+/// assert_eq!(lines_to_strings(wrap_optimal_fit(&syllables, &[18.0], &penalties)),
+///            vec!["Hyphenation",
+///                 "is useful when",
+///                 "wrapping narrow",
+///                 "columns of text"]);
+/// ```
+///
+/// # Stretchable Whitespace
+///
+/// If the fragments have stretchable or shrinkable whitespace (see
+/// [`Fragment::whitespace_stretch`] and
+/// [`Fragment::whitespace_shrink`]), lines are measured by their
+/// _adjustment ratio_ instead of by the raw gap. The ratio `r` is the
+/// gap divided by the total stretchability of the line (or the
+/// overflow divided by the total shrinkability) and the cost of the
+/// line is `100 * r³`, capped at 10,000. A line with a gap, but no
+/// stretchable whitespace, gets the maximum cost. A line which is too
+/// long can be shrunk without overflow penalty as long as `r` is at
+/// most 1.
+///
+/// This means that a gap is cheaper on a line with many stretchable
+/// spaces than on a line with few of them:
+///
+/// ```
+/// use textwrap::core::Fragment;
+/// use textwrap::wrap_algorithms::{wrap_optimal_fit, OptimalFit};
+///
+/// #[derive(Debug)]
+/// struct Glue(usize, usize);
+///
+/// impl Fragment for Glue {
+///     fn width(&self) -> f64 { self.0 as f64 }
+///     fn whitespace_width(&self) -> f64 { 1.0 }
+///     fn penalty_width(&self) -> f64 { 0.0 }
+///     fn whitespace_stretch(&self) -> f64 { self.1 as f64 }
+/// }
+///
+/// // Helper to show the fragment widths on each line.
+/// fn widths(lines: Vec<&[Glue]>) -> Vec<Vec<usize>> {
+///     lines.iter().map(|line| line.iter().map(|g| g.0).collect()).collect()
+/// }
+///
+/// let sizes = [1, 1, 1, 1, 4, 4, 2, 2, 5];
+/// let rigid = sizes.iter().map(|&w| Glue(w, 0)).collect::<Vec<_>>();
+/// assert_eq!(widths(wrap_optimal_fit(&rigid, &[9.0], &OptimalFit::new())),
+///            vec![vec![1, 1, 1, 1], vec![4, 4], vec![2, 2], vec![5]]);
+///
+/// // The third line above would need to stretch its single space by
+/// // four columns. With flexible whitespace, it is better to spread
+/// // the gaps out over more lines:
+/// let flexible = sizes.iter().map(|&w| Glue(w, 1)).collect::<Vec<_>>();
+/// assert_eq!(widths(wrap_optimal_fit(&flexible, &[9.0], &OptimalFit::new())),
+///            vec![vec![1, 1, 1], vec![1, 4], vec![4, 2], vec![2, 5]]);
+/// ```
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+pub fn wrap_optimal_fit<'a, 'b, T: Fragment, W: LineWidths + ?Sized>(
     fragments: &'a [T],
-    line_widths: &'b [usize],
+    line_widths: &'b W,
+    penalties: &'b OptimalFit,
 ) -> Vec<&'a [T]> {
-    // The final line width is used for all remaining lines.
-    let default_line_width = line_widths.last().copied().unwrap_or(0);
+    let indices = wrap_optimal_fit_indices(fragments, line_widths, penalties);
+    lines_from_indices(fragments, &indices)
+}
+
+/// Find line breaks with an optimal-fit algorithm.
+///
+/// This works like [`wrap_optimal_fit`], but instead of returning
+/// the lines as slices, the index after the last fragment of each
+/// line is returned. This is useful if you need to know the position
+/// of the fragments when rendering the lines. The last index is
+/// always `fragments.len()`.
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::{wrap_optimal_fit_indices, OptimalFit};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "To be, or not to be: that is the question";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(wrap_optimal_fit_indices(&words, &[10.0], &OptimalFit::new()),
+///            vec![2, 5, 7, 9, 10]);
+/// ```
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+pub fn wrap_optimal_fit_indices<T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &[T],
+    line_widths: &W,
+    penalties: &OptimalFit,
+) -> Vec<usize> {
+    wrap_optimal_fit_impl(fragments, line_widths, penalties, None, None)
+        .unwrap_or_else(|_| wrap_first_fit_indices(fragments, line_widths))
+}
+
+/// Wrap abstract fragments into lines with an optimal-fit algorithm,
+/// returning an error if the cost computation overflows.
+///
+/// This works like [`wrap_optimal_fit`], but instead of falling back
+/// to [`wrap_first_fit`](super::wrap_first_fit) on overflow, an
+/// [`OptimalFitError`] is returned. This lets you handle pathological
+/// inputs yourself:
+///
+/// ```
+/// use textwrap::core::Fragment;
+/// use textwrap::wrap_algorithms::{try_wrap_optimal_fit, wrap_optimal_fit};
+/// use textwrap::wrap_algorithms::{OptimalFit, OptimalFitError};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Huge(f64);
+///
+/// impl Fragment for Huge {
+///     fn width(&self) -> f64 { self.0 }
+///     fn whitespace_width(&self) -> f64 { 1.0 }
+///     fn penalty_width(&self) -> f64 { 0.0 }
+/// }
+///
+/// let fragments = [Huge(f64::MAX / 2.0), Huge(f64::MAX / 2.0)];
+/// let penalties = OptimalFit::new();
+/// assert_eq!(try_wrap_optimal_fit(&fragments, &[80.0], &penalties),
+///            Err(OptimalFitError::Overflow));
+///
+/// // The result of the first-fit algorithm is used instead:
+/// assert_eq!(wrap_optimal_fit(&fragments, &[80.0], &penalties),
+///            vec![&fragments[..1], &fragments[1..]]);
+/// ```
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+pub fn try_wrap_optimal_fit<'a, 'b, T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &'a [T],
+    line_widths: &'b W,
+    penalties: &'b OptimalFit,
+) -> Result<Vec<&'a [T]>, OptimalFitError> {
+    let indices = wrap_optimal_fit_impl(fragments, line_widths, penalties, None, None)?;
+    Ok(lines_from_indices(fragments, &indices))
+}
+
+/// Wrap abstract fragments into lines with an optimal-fit algorithm
+/// and call a function for each line.
+///
+/// This works like [`wrap_optimal_fit`], but instead of collecting
+/// the lines into a `Vec`, `f` is called with each line in order.
+/// The algorithm itself still needs memory proportional to the
+/// number of fragments. See
+/// [`wrap_first_fit_with`](super::wrap_first_fit_with) for a
+/// first-fit version which does not allocate at all.
+///
+/// This is not to be confused with [`wrap_optimal_fit_with`], which
+/// uses a custom cost function.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::{wrap_optimal_fit_for_each, OptimalFit};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "These few words will unfortunately not wrap nicely.";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// let mut line_lengths = Vec::new();
+/// wrap_optimal_fit_for_each(&words, &[15.0], &OptimalFit::new(), |line| {
+///     line_lengths.push(line.len());
+/// });
+/// assert_eq!(line_lengths, vec![2, 2, 1, 2, 1]);
+/// ```
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+pub fn wrap_optimal_fit_for_each<'a, T, W, F>(
+    fragments: &'a [T],
+    line_widths: &W,
+    penalties: &OptimalFit,
+    mut f: F,
+) where
+    T: Fragment,
+    W: LineWidths + ?Sized,
+    F: FnMut(&'a [T]),
+{
+    let mut start = 0;
+    for end in wrap_optimal_fit_indices(fragments, line_widths, penalties) {
+        f(&fragments[start..end]);
+        start = end;
+    }
+}
+
+/// Wrap abstract fragments into lines with an optimal-fit algorithm
+/// using a custom cost function.
+///
+/// The `line_penalty` function is called with the range of fragments
+/// on a line, the width of the line, the target width of the line,
+/// and the accumulated cost of the lines before it. It must return
+/// the accumulated cost including the line. Return `f64::INFINITY`
+/// for lines which must not be used. The algorithm will find the
+/// line breaks which minimize the accumulated cost of the final line.
+///
+/// [`Fragment::forced_break`], [`Fragment::keep_with_next`], and
+/// [`Fragment::break_priority`] are still taken into account, but
+/// the penalties of [`OptimalFit`] are not used.
+/// [`OptimalFitError::Overflow`] is returned if `line_penalty`
+/// returns NaN.
+///
+/// The line breaks are found with the same SMAWK algorithm as for
+/// [`wrap_optimal_fit`]. This requires that the cost of a line grows
+/// with the gap in a convex way: sums of a convex function of the
+/// line width, such as `gap * gap`, work well. For other cost
+/// functions, the line breaks found might not be optimal.
+///
+/// # Examples
+///
+/// Unlike [`wrap_optimal_fit`], we can also penalize the gap on the
+/// last line. This gives paragraphs where all lines have roughly the
+/// same width:
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::wrap_optimal_fit_with;
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// // Helper to convert wrapped lines to a Vec<String>.
+/// fn lines_to_strings(lines: Vec<&[Word<'_>]>) -> Vec<String> {
+///     lines.iter().map(|line| {
+///         line.iter().map(|word| &**word).collect::<Vec<_>>().join(" ")
+///     }).collect::<Vec<_>>()
+/// }
+///
+/// let text = "To be, or not to be: that is the question";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// let lines = wrap_optimal_fit_with(&words, &[16.0], |_, width, target, cost| {
+///     if width > target {
+///         cost + 1000.0 * (width - target)
+///     } else {
+///         cost + (target - width).powi(2)
+///     }
+/// });
+/// assert_eq!(lines_to_strings(lines.unwrap()),
+///            vec!["To be, or not", "to be: that is", "the question"]);
+/// ```
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+pub fn wrap_optimal_fit_with<'a, T, W, F>(
+    fragments: &'a [T],
+    line_widths: &W,
+    line_penalty: F,
+) -> Result<Vec<&'a [T]>, OptimalFitError>
+where
+    T: Fragment,
+    W: LineWidths + ?Sized,
+    F: Fn(Range<usize>, f64, f64, f64) -> f64,
+{
+    let penalties = OptimalFit::new();
+    let indices = wrap_optimal_fit_impl(
+        fragments,
+        line_widths,
+        &penalties,
+        Some(&line_penalty),
+        None,
+    )?;
+    Ok(lines_from_indices(fragments, &indices))
+}
+
+/// The cost of a line found by [`wrap_optimal_fit`], broken down
+/// into the penalties of [`OptimalFit`]. See [`explain_optimal_fit`].
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LineCost {
+    /// The [`OptimalFit::nline_penalty`] paid by every line.
+    pub line: f64,
+    /// The [`OptimalFit::overflow_penalty`] for every column the line
+    /// is too wide.
+    pub overflow: f64,
+    /// The cost of the gap at the end of the line. This is `gap *
+    /// gap` or, with flexible whitespace, the badness of stretching
+    /// or shrinking the whitespace. The last line of a paragraph has
+    /// no gap cost.
+    pub gap: f64,
+    /// The [`OptimalFit::short_last_line_penalty`].
+    pub short_last_line: f64,
+    /// The [`OptimalFit::orphan_penalty`].
+    pub orphan: f64,
+    /// The [`OptimalFit::hyphen_penalty`] for a line ending with a
+    /// penalty, typically a hyphen.
+    pub hyphen: f64,
+    /// The [`OptimalFit::double_hyphen_penalty`].
+    pub double_hyphen: f64,
+    /// The [`OptimalFit::new_break_penalty`].
+    pub new_break: f64,
+    /// The [`OptimalFit::adjacent_fitness_penalty`].
+    pub fitness: f64,
+}
+
+impl LineCost {
+    /// The total cost of the line.
+    pub fn total(&self) -> f64 {
+        self.add_to(0.0)
+    }
+
+    /// Add the cost of the line to `cost`. The components are added
+    /// one by one, in the same order as the algorithm does.
+    fn add_to(&self, cost: f64) -> f64 {
+        cost + self.line
+            + self.overflow
+            + self.gap
+            + self.short_last_line
+            + self.orphan
+            + self.hyphen
+            + self.double_hyphen
+            + self.new_break
+            + self.fitness
+    }
+}
+
+/// A line chosen by [`wrap_optimal_fit`], explained by
+/// [`explain_optimal_fit`].
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExplainedLine {
+    /// The fragments on the line.
+    pub fragments: Range<usize>,
+    /// The cost of the line.
+    pub cost: LineCost,
+    /// The cost of the line and all lines before it.
+    pub total_cost: f64,
+    /// The cheapest other lines ending at the same break, cheapest
+    /// first. At most three alternatives are kept.
+    pub alternatives: Vec<AlternativeLine>,
+}
+
+/// A line which [`wrap_optimal_fit`] rejected. See
+/// [`ExplainedLine::alternatives`].
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlternativeLine {
+    /// The fragments on the line.
+    pub fragments: Range<usize>,
+    /// The cost of the line.
+    pub cost: LineCost,
+    /// The cost of the line and the best lines before it. This is
+    /// what the algorithm compared with
+    /// [`ExplainedLine::total_cost`].
+    pub total_cost: f64,
+}
+
+/// Number of alternatives kept in [`ExplainedLine::alternatives`].
+const MAX_ALTERNATIVES: usize = 3;
+
+/// Explain the line breaks found by [`wrap_optimal_fit`].
+///
+/// For every line, this returns the cost of the line broken down
+/// into the penalties of [`OptimalFit`], and the cheapest lines
+/// which could have ended at the same break instead. A rejected line
+/// is compared with the best line breaks before it, just as the
+/// algorithm compares lines. This takes `O(n²)` time and is meant for
+/// tuning the penalties, not for wrapping text.
+///
+/// Like with [`try_wrap_optimal_fit`], an [`OptimalFitError`] is
+/// returned if the cost computation overflows.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::{explain_optimal_fit, OptimalFit};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "To be, or not to be: that is the question";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// let lines = explain_optimal_fit(&words, &[10.0], &OptimalFit::new()).unwrap();
+///
+/// // The first line is "To be," with a gap of 4 columns.
+/// assert_eq!(lines[0].fragments, 0..2);
+/// assert_eq!(lines[0].cost.gap, 16.0);
+/// assert_eq!(lines[0].cost.total(), 1016.0);
+///
+/// // The third line, "be: that", was chosen over "that", which
+/// // leaves a larger gap, and over "to be: that", which overflows.
+/// let rejected = &lines[2].alternatives;
+/// assert_eq!(lines[2].fragments, 5..7);
+/// assert_eq!(lines[2].total_cost, 3021.0);
+/// assert_eq!(rejected[0].fragments, 6..7);
+/// assert_eq!(rejected[0].cost.gap, 36.0);
+/// assert_eq!(rejected[0].total_cost, 3037.0);
+/// assert_eq!(rejected[1].fragments, 4..7);
+/// assert_eq!(rejected[1].cost.overflow, 2500.0);
+/// ```
+///
+/// **Note:** Only available when the `smawk` or the
+/// `optimal-fit-dp` Cargo feature is enabled.
+pub fn explain_optimal_fit<T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &[T],
+    line_widths: &W,
+    penalties: &OptimalFit,
+) -> Result<Vec<ExplainedLine>, OptimalFitError> {
+    let mut explanation = Vec::new();
+    wrap_optimal_fit_impl(
+        fragments,
+        line_widths,
+        penalties,
+        None,
+        Some(&mut explanation),
+    )?;
+    Ok(explanation)
+}
+
+/// Custom cost function used by [`wrap_optimal_fit_with`].
+type LinePenalty<'a> = dyn Fn(Range<usize>, f64, f64, f64) -> f64 + 'a;
+
+/// Implementation of [`try_wrap_optimal_fit`] and
+/// [`wrap_optimal_fit_with`]. The custom `line_penalty` replaces the
+/// built-in cost function if present. Returns the line breaks as
+/// described in [`wrap_optimal_fit_indices`]. The cost of every line
+/// is pushed to `explanation` if present, see
+/// [`explain_optimal_fit`].
+fn wrap_optimal_fit_impl<T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &[T],
+    line_widths: &W,
+    penalties: &OptimalFit,
+    line_penalty: Option<&LinePenalty>,
+    explanation: Option<&mut Vec<ExplainedLine>>,
+) -> Result<Vec<usize>, OptimalFitError> {
+    let target_width_for = |line_number: usize| f64::max(1.0, line_widths.line_width(line_number));
+
     let mut widths = Vec::with_capacity(fragments.len() + 1);
-    let mut width = 0;
+    let mut width = 0.0;
     widths.push(width);
+    // Number of forced breaks before each fragment. This lets us
+    // check in constant time if a line spans a forced break.
+    let mut forced_breaks = Vec::with_capacity(fragments.len() + 1);
+    let mut forced = 0;
+    forced_breaks.push(forced);
+    // Position after the last forced break before each position. A
+    // line ending at position `j` starts at `segment_starts[j - 1]`
+    // or later.
+    let mut segment_starts = Vec::with_capacity(fragments.len() + 1);
+    let mut segment_start = 0;
+    segment_starts.push(segment_start);
+    // Accumulated width, stretchability, and shrinkability of the
+    // whitespace.
+    let mut whitespaces = Vec::with_capacity(fragments.len() + 1);
+    let mut stretches = Vec::with_capacity(fragments.len() + 1);
+    let mut shrinks = Vec::with_capacity(fragments.len() + 1);
+    let (mut whitespace, mut stretch, mut shrink) = (0.0, 0.0, 0.0);
+    whitespaces.push(whitespace);
+    stretches.push(stretch);
+    shrinks.push(shrink);
+    // A line cannot end at a break if it contains a break with a
+    // higher priority.
+    let higher_breaks = higher_priority_breaks(fragments);
     for fragment in fragments {
         width += fragment.width() + fragment.whitespace_width();
+        if !width.is_finite() {
+            return Err(OptimalFitError::Overflow);
+        }
         widths.push(width);
+        if fragment.forced_break() {
+            forced += 1;
+            segment_start = widths.len() - 1;
+        }
+        forced_breaks.push(forced);
+        segment_starts.push(segment_start);
+        whitespace += fragment.whitespace_width();
+        whitespaces.push(whitespace);
+        stretch += fragment.whitespace_stretch();
+        stretches.push(stretch);
+        shrink += fragment.whitespace_shrink();
+        shrinks.push(shrink);
     }
+    // We use the Knuth–Plass glue model if any whitespace is flexible.
+    let flexible = stretch > 0.0 || shrink > 0.0;
+    // New line breaks are only penalized when re-wrapping text.
+    let rewrapping = fragments.iter().any(|fragment| fragment.existing_break());
+
+    // Compute the width of a line spanning fragments[i..j] in
+    // constant time. We need to adjust widths[j] by subtracting the
+    // whitespace of fragment[j-i] and then add the penalty, minus
+    // what may hang past the end of the line.
+    let span_width = |i: usize, j: usize| {
+        widths[j] - widths[i] - fragments[j - 1].whitespace_width()
+            + fragments[j - 1].penalty_width()
+            - fragments[j - 1].hanging_width()
+    };
+
+    // The whitespace between fragments[i..j] can be adjusted by this
+    // much. The whitespace after fragments[j-1] is dropped. Rigid
+    // whitespace is seen as stretchable by its own width when we
+    // classify lines as tight or loose.
+    let flexibility = |i: usize, j: usize| {
+        if flexible {
+            (stretches[j - 1] - stretches[i], shrinks[j - 1] - shrinks[i])
+        } else {
+            (whitespaces[j - 1] - whitespaces[i], 0.0)
+        }
+    };
 
     let line_numbers = LineNumbers::new(fragments.len());
 
-    let minima = smawk::online_column_minima(0, widths.len(), |minima, i, j| {
-        // Line number for fragment `i`.
-        let line_number = line_numbers.get(i, &minima);
-        let line_width = line_widths
-            .get(line_number)
-            .copied()
-            .unwrap_or(default_line_width);
-        let target_width = std::cmp::max(1, line_width);
-
-        // Compute the width of a line spanning fragments[i..j] in
-        // constant time. We need to adjust widths[j] by subtracting
-        // the whitespace of fragment[j-i] and then add the penalty.
-        let line_width = widths[j] - widths[i] - fragments[j - 1].whitespace_width()
-            + fragments[j - 1].penalty_width();
-
-        // We compute cost of the line containing fragments[i..j]. We
-        // start with values[i].1, which is the optimal cost for
-        // breaking before fragments[i].
-        //
+    // A line containing fragments[i..j] is impossible if it spans a
+    // forced break or if it ends at a break which must not be used.
+    let possible = |i: usize, j: usize| {
+        // A line cannot continue past a forced break.
+        if forced_breaks[j - 1] > forced_breaks[i] {
+            return false;
+        }
+
+        // A line cannot end inside a group of fragments which must be
+        // kept together. This also makes it impossible to start the
+        // next line inside the group.
+        let last = &fragments[j - 1];
+        if j < fragments.len() && last.keep_with_next() && !last.forced_break() {
+            return false;
+        }
+        // Lower-priority breaks are only used as a last resort.
+        !(j < fragments.len() && higher_breaks[j] > i)
+    };
+
+    // Compute the cost of a possible line containing fragments[i..j]
+    // when the line has the given line number. The previous line
+    // contains fragments[prev..i].
+    //
+    // We return `None` if the line must not be used.
+    let line_costs = |i: usize, j: usize, line_number: usize, prev: usize| {
+        let target_width = target_width_for(line_number);
+        let line_width = span_width(i, j);
+
         // First, every extra line cost NLINE_PENALTY.
-        let mut cost = minima[i].1 + NLINE_PENALTY;
+        let mut cost = LineCost {
+            line: f64::from(penalties.nline_penalty),
+            ..LineCost::default()
+        };
+
+        // Lines ending with a forced break are treated like the last
+        // line of the paragraph.
+        let is_last_line = j == fragments.len() || fragments[j - 1].forced_break();
+
+        let (stretch, shrink) = flexibility(i, j);
 
         // Next, we add a penalty depending on the line length.
         if line_width > target_width {
-            // Lines that overflow get a hefty penalty.
-            let overflow = (line_width - target_width) as i32;
-            cost += overflow * OVERFLOW_PENALTY;
-        } else if j < fragments.len() {
+            let overflow = line_width - target_width;
+            if overflow <= shrink {
+                // The line fits if we shrink the whitespace.
+                cost.gap = badness(overflow, shrink);
+            } else {
+                // Lines that overflow get a hefty penalty. With
+                // flexible whitespace, they must also be worse than
+                // any line which can be stretched.
+                cost.overflow = (overflow - shrink) * f64::from(penalties.overflow_penalty);
+                if flexible {
+                    cost.gap = INF_BAD;
+                }
+            }
+        } else if !is_last_line {
             // Other lines (except for the last line) get a milder
             // penalty which depend on the size of the gap.
-            let gap = (target_width - line_width) as i32;
-            cost += gap * gap;
-        } else if i + 1 == j && line_width < target_width / SHORT_LINE_FRACTION {
+            let gap = target_width - line_width;
+            if flexible {
+                cost.gap = badness(gap, stretch);
+            } else {
+                cost.gap = gap * gap;
+            }
+        } else if i + 1 == j
+            && line_width < target_width / penalties.short_last_line_fraction as f64
+        {
             // The last line can have any size gap, but we do add a
             // penalty if the line is very short (typically because it
             // contains just a single word).
-            cost += SHORT_LAST_LINE_PENALTY;
+            cost.short_last_line = f64::from(penalties.short_last_line_penalty);
         }
 
-        // Finally, we discourage hyphens.
-        if fragments[j - 1].penalty_width() > 0 {
+        // We also discourage orphans: last lines with too few
+        // fragments. This only applies if the paragraph has more than
+        // one line.
+        if is_last_line
+            && j - i < penalties.orphan_min_fragments
+            && i > 0
+            && !fragments[i - 1].forced_break()
+        {
+            if penalties.orphan_penalty == i32::MAX {
+                return None;
+            }
+            cost.orphan = f64::from(penalties.orphan_penalty);
+        }
+
+        // Next, we discourage hyphens.
+        if fragments[j - 1].penalty_width() > 0.0 {
             // TODO: this should use a penalty value from the fragment
             // instead.
-            cost += HYPHEN_PENALTY;
+            cost.hyphen = f64::from(penalties.hyphen_penalty);
+
+            // The previous line ends with fragments[i-1].
+            if i > 0 && fragments[i - 1].penalty_width() > 0.0 {
+                cost.double_hyphen = f64::from(penalties.double_hyphen_penalty);
+            }
+        }
+
+        // When re-wrapping text, we discourage changing the line
+        // breaks.
+        if rewrapping && !is_last_line && !fragments[j - 1].existing_break() {
+            cost.new_break = f64::from(penalties.new_break_penalty);
+        }
+
+        // Finally, we discourage a line which looks very different
+        // from the previous line. We only know the best way to break
+        // the text before fragments[i], so we compare with the
+        // previous line from that solution.
+        if penalties.adjacent_fitness_penalty != 0
+            && i > 0
+            && !is_last_line
+            && !fragments[i - 1].forced_break()
+        {
+            let prev_target_width = target_width_for(line_number - 1);
+            let (prev_stretch, prev_shrink) = flexibility(prev, i);
+            let prev_class = fitness_class(
+                span_width(prev, i),
+                prev_target_width,
+                prev_stretch,
+                prev_shrink,
+            );
+            let class = fitness_class(line_width, target_width, stretch, shrink);
+            if std::cmp::max(prev_class, class) - std::cmp::min(prev_class, class) > 1 {
+                cost.fitness = f64::from(penalties.adjacent_fitness_penalty);
+            }
         }
 
-        cost
-    });
+        Some(cost)
+    };
 
-    let mut lines = Vec::with_capacity(line_numbers.get(fragments.len(), &minima));
+    // Compute the cost of a line containing fragments[i..j] when the
+    // line has the given line number. The previous line contains
+    // fragments[prev..i] and `base_cost` is the cost of the text
+    // before fragments[i].
+    //
+    // We return `None` if the cost overflows. Impossible lines have an
+    // infinite cost.
+    let line_cost = |i: usize, j: usize, line_number: usize, prev: usize, base_cost: f64| {
+        if !possible(i, j) || base_cost.is_infinite() {
+            return Some(f64::INFINITY);
+        }
+
+        if let Some(line_penalty) = line_penalty {
+            let line_width = span_width(i, j);
+            let target_width = target_width_for(line_number);
+            let cost = line_penalty(i..j, line_width, target_width, base_cost);
+            return Some(cost).filter(|cost| !cost.is_nan());
+        }
+
+        // We add the cost of the line containing fragments[i..j] to
+        // the optimal cost for breaking before fragments[i]. An
+        // infinite cost would mean that the line is impossible.
+        match line_costs(i, j, line_number, prev) {
+            Some(cost) => Some(cost.add_to(base_cost)).filter(|cost| cost.is_finite()),
+            None => Some(f64::INFINITY),
+        }
+    };
+
+    // Set to the first error, such as when the cost of a line
+    // overflows.
+    let error = Cell::new(None);
+    let fail = |err: OptimalFitError| {
+        if error.get().is_none() {
+            error.set(Some(err));
+        }
+        f64::INFINITY
+    };
+    // Number of line costs computed so far.
+    let evaluations = Cell::new(0);
+    let checked_line_cost = |i: usize, j: usize, line_number: usize, prev: usize, base: f64| {
+        evaluations.set(evaluations.get() + 1);
+        if matches!(penalties.budget, Some(budget) if evaluations.get() > budget) {
+            // We give up, no need to compute more costs.
+            return fail(OptimalFitError::BudgetExceeded);
+        }
+        line_cost(i, j, line_number, prev, base).unwrap_or_else(|| fail(OptimalFitError::Overflow))
+    };
+
+    // Ties are broken by comparing the start of the line.
+    #[cfg(feature = "smawk")]
+    let tie_key = |i: usize| match penalties.tie_break {
+        TieBreak::Earlier => i,
+        TieBreak::Later => usize::MAX - i,
+    };
+
+    // The cost of a line is at least the overflow penalty for its
+    // width, unless a penalty can lower the cost. We split this bound
+    // into a part for the start and a part for the end of the line.
+    // This lets us stop searching for line starts once no earlier
+    // start can be better.
+    let overflow_penalty = f64::from(penalties.overflow_penalty);
+    let bounded = line_penalty.is_none()
+        && [
+            penalties.nline_penalty,
+            penalties.overflow_penalty,
+            penalties.short_last_line_penalty,
+            penalties.orphan_penalty,
+            penalties.hyphen_penalty,
+            penalties.double_hyphen_penalty,
+            penalties.adjacent_fitness_penalty,
+            penalties.new_break_penalty,
+        ]
+        .iter()
+        .all(|&penalty| penalty >= 0);
+    let start_bound = |i: usize, minima: &[(usize, f64)]| {
+        let shrink = if flexible { shrinks[i] } else { 0.0 };
+        let target_width = target_width_for(line_numbers.get(i, minima));
+        let bound = minima[i].1 - overflow_penalty * (widths[i] - shrink + target_width);
+        if bounded && !bound.is_nan() {
+            bound
+        } else {
+            f64::NEG_INFINITY
+        }
+    };
+    let end_bound = |j: usize| {
+        let shrink = if flexible { shrinks[j - 1] } else { 0.0 };
+        overflow_penalty * (span_width(0, j) - shrink)
+    };
+
+    // The SMAWK algorithm gives wrong results when some lines are
+    // impossible, such as lines spanning a forced break or ending
+    // inside a group of fragments kept together, or when a line can
+    // only end at some breaks if it starts late enough. We then
+    // search for the best line start backwards from each break
+    // instead. This is also done for penalized orphans since the
+    // penalty only applies to last lines with few fragments, and for
+    // penalties which depend on the previous line.
+    let orphans = penalties.orphan_min_fragments > 1 && penalties.orphan_penalty != 0;
+    let previous_line =
+        penalties.double_hyphen_penalty != 0 || penalties.adjacent_fitness_penalty != 0;
+    let constrained = higher_breaks.iter().any(|&k| k > 0)
+        || fragments
+            .iter()
+            .any(|fragment| fragment.forced_break() || fragment.keep_with_next())
+        || orphans
+        || previous_line;
+    let minima = match penalties.lookback {
+        None if constrained => pruned_minima(
+            fragments.len(),
+            penalties.tie_break,
+            &line_numbers,
+            |j| {
+                if j < fragments.len() {
+                    std::cmp::max(segment_starts[j - 1], higher_breaks[j])
+                } else {
+                    segment_starts[j - 1]
+                }
+            },
+            start_bound,
+            end_bound,
+            checked_line_cost,
+        ),
+        Some(lookback) => bounded_minima(
+            fragments.len(),
+            lookback,
+            penalties.tie_break,
+            &line_numbers,
+            checked_line_cost,
+        ),
+        #[cfg(feature = "smawk")]
+        None => smawk::online_column_minima((0.0, 0), widths.len(), |minima, i, j| {
+            // Line number for fragment `i`.
+            let line_number = line_numbers.get(i, minima);
+            let cost = checked_line_cost(i, j, line_number, minima[i].0, (minima[i].1).0);
+            (cost, tie_key(i))
+        })
+        .into_iter()
+        .map(|(prev, (cost, _))| (prev, cost))
+        .collect(),
+        #[cfg(not(feature = "smawk"))]
+        None => pruned_minima(
+            fragments.len(),
+            penalties.tie_break,
+            &line_numbers,
+            |_| 0,
+            start_bound,
+            end_bound,
+            checked_line_cost,
+        ),
+    };
+    if let Some(err) = error.get() {
+        return Err(err);
+    }
+
+    let line_count = line_numbers.get(fragments.len(), &minima);
+    let mut breaks = minima.iter().map(|&(prev, _)| prev).collect::<Vec<_>>();
+    if penalties.looseness != 0 && !fragments.is_empty() {
+        let desired = line_count as isize + penalties.looseness as isize;
+        let desired = desired.max(1).min(fragments.len() as isize) as usize;
+        // Like in TeX, we only consider layouts where the lines fit.
+        // A line with a single fragment can always be used.
+        let feasible_cost = |i: usize, j: usize, line_number: usize, prev: usize, base: f64| {
+            let overflow = span_width(i, j) - target_width_for(line_number);
+            if j - i > 1 && overflow > flexibility(i, j).1 {
+                return f64::INFINITY;
+            }
+            checked_line_cost(i, j, line_number, prev, base)
+        };
+        if let Some(loose_breaks) = wrap_with_line_count(
+            fragments.len(),
+            desired,
+            line_count,
+            penalties.tie_break,
+            feasible_cost,
+        ) {
+            breaks = loose_breaks;
+        }
+        if let Some(err) = error.get() {
+            return Err(err);
+        }
+    }
+
+    let mut indices = Vec::with_capacity(line_count);
     let mut pos = fragments.len();
     loop {
-        let prev = minima[pos].0;
-        lines.push(&fragments[prev..pos]);
-        pos = prev;
+        indices.push(pos);
+        pos = breaks[pos];
         if pos == 0 {
             break;
         }
     }
 
-    lines.reverse();
-    lines
+    indices.reverse();
+
+    if let Some(explanation) = explanation {
+        let mut start = 0;
+        let mut prev = 0;
+        let mut total_cost = 0.0;
+        for (line_number, &end) in indices.iter().enumerate() {
+            let cost = line_costs(start, end, line_number, prev).unwrap_or(LineCost {
+                line: f64::INFINITY,
+                ..LineCost::default()
+            });
+            total_cost = cost.add_to(total_cost);
+
+            // The other lines ending at the same break are compared
+            // with the best line breaks before them.
+            let first = penalties
+                .lookback
+                .map_or(0, |lookback| end.saturating_sub(std::cmp::max(1, lookback)));
+            let mut alternatives = (first..end)
+                .filter(|&i| i != start && possible(i, end) && minima[i].1.is_finite())
+                .filter_map(|i| {
+                    let line_number = line_numbers.get(i, &minima);
+                    let cost = line_costs(i, end, line_number, minima[i].0)?;
+                    let total_cost = cost.add_to(minima[i].1);
+                    Some(AlternativeLine {
+                        fragments: i..end,
+                        cost,
+                        total_cost,
+                    })
+                })
+                .filter(|alternative| alternative.total_cost.is_finite())
+                .collect::<Vec<_>>();
+            alternatives.sort_by(|a, b| a.total_cost.partial_cmp(&b.total_cost).unwrap());
+            alternatives.truncate(MAX_ALTERNATIVES);
+
+            explanation.push(ExplainedLine {
+                fragments: start..end,
+                cost,
+                total_cost,
+                alternatives,
+            });
+            prev = start;
+            start = end;
+        }
+    }
+
+    Ok(indices)
+}
+
+/// Find the best way to break `len` fragments into lines with at most
+/// `lookback` fragments each. This is a simple dynamic programming
+/// algorithm which runs in `O(n * lookback)` time.
+///
+/// Returns the start and the cost of the line ending before each
+/// position, in the same format as the minima computed by the SMAWK
+/// algorithm.
+fn bounded_minima<F>(
+    len: usize,
+    lookback: usize,
+    tie_break: TieBreak,
+    line_numbers: &LineNumbers,
+    line_cost: F,
+) -> Vec<(usize, f64)>
+where
+    F: Fn(usize, usize, usize, usize, f64) -> f64,
+{
+    let mut minima = Vec::with_capacity(len + 1);
+    minima.push((0, 0.0));
+    for j in 1..=len {
+        let first = j.saturating_sub(std::cmp::max(1, lookback));
+        let mut best = (first, f64::INFINITY);
+        for i in first..j {
+            let line_number = line_numbers.get(i, &minima);
+            let cost = line_cost(i, j, line_number, minima[i].0, minima[i].1);
+            let better = match tie_break {
+                TieBreak::Earlier => cost < best.1,
+                TieBreak::Later => cost <= best.1,
+            };
+            if better {
+                best = (i, cost);
+            }
+        }
+        minima.push(best);
+    }
+    minima
+}
+
+/// Find the best way to break `len` fragments into lines by trying
+/// the line starts before each position from the closest to the
+/// furthest. A line ending before position `j` starts at
+/// `first_start(j)` or later.
+///
+/// The cost of a line from `i` to `j` plus the cost before `i` must
+/// be at least `start_bound(i) + end_bound(j)`. The search for the
+/// start of a line stops when no earlier start can beat the best line
+/// found so far, which keeps the search close to `j` for the usual
+/// costs. The result is the same as when all starts are tried.
+///
+/// Returns the start and the cost of the line ending before each
+/// position, in the same format as the minima computed by the SMAWK
+/// algorithm.
+fn pruned_minima<S, B, E, F>(
+    len: usize,
+    tie_break: TieBreak,
+    line_numbers: &LineNumbers,
+    first_start: S,
+    start_bound: B,
+    end_bound: E,
+    line_cost: F,
+) -> Vec<(usize, f64)>
+where
+    S: Fn(usize) -> usize,
+    B: Fn(usize, &[(usize, f64)]) -> f64,
+    E: Fn(usize) -> f64,
+    F: Fn(usize, usize, usize, usize, f64) -> f64,
+{
+    let mut minima = Vec::with_capacity(len + 1);
+    minima.push((0, 0.0));
+    // The lowest start bound before each position.
+    let mut lowest_bounds = Vec::with_capacity(len + 1);
+    lowest_bounds.push(f64::INFINITY);
+    for j in 1..=len {
+        let lowest_bound = f64::min(lowest_bounds[j - 1], start_bound(j - 1, &minima));
+        lowest_bounds.push(lowest_bound);
+        let end_bound = end_bound(j);
+        let mut best = (j - 1, f64::INFINITY);
+        for i in (first_start(j)..j).rev() {
+            let line_number = line_numbers.get(i, &minima);
+            let cost = line_cost(i, j, line_number, minima[i].0, minima[i].1);
+            // We try the line starts in decreasing order.
+            let better = match tie_break {
+                TieBreak::Earlier => cost <= best.1,
+                TieBreak::Later => cost < best.1,
+            };
+            if better {
+                best = (i, cost);
+            }
+            if lowest_bounds[i] + end_bound > best.1 {
+                break;
+            }
+        }
+        minima.push(best);
+    }
+    minima
+}
+
+/// Find the best way to break `len` fragments into exactly `desired`
+/// lines. If this is impossible, we use the number of lines closest
+/// to `desired` in the direction of `optimal`, which is the number
+/// of lines in the optimal solution. Impossible lines have an
+/// infinite cost.
+///
+/// The `line_cost` function gives the cost of a line as described in
+/// [`wrap_optimal_fit`] and ties are broken according to `tie_break`.
+/// Unlike the SMAWK algorithm, this looks at
+/// all line breaks for every line count and it thus runs in `O(n² *
+/// desired)` time.
+///
+/// Returns the start of the line ending before each position, in the
+/// same format as the minima computed by the SMAWK algorithm.
+fn wrap_with_line_count<F>(
+    len: usize,
+    desired: usize,
+    optimal: usize,
+    tie_break: TieBreak,
+    line_cost: F,
+) -> Option<Vec<usize>>
+where
+    F: Fn(usize, usize, usize, usize, f64) -> f64,
+{
+    // The costs and starts of lines after placing `n` lines. The
+    // first row represents placing zero lines.
+    let mut costs = vec![vec![f64::INFINITY; len + 1]];
+    let mut starts = vec![vec![0; len + 1]];
+    costs[0][0] = 0.0;
+
+    for line_number in 0..std::cmp::max(desired, optimal) {
+        let mut row_costs = vec![f64::INFINITY; len + 1];
+        let mut row_starts = vec![0; len + 1];
+        for j in 1..=len {
+            for i in 0..j {
+                let base_cost = costs[line_number][i];
+                if base_cost.is_infinite() {
+                    continue;
+                }
+                let prev = starts[line_number][i];
+                let cost = line_cost(i, j, line_number, prev, base_cost);
+                // We try the line starts in increasing order.
+                let better = match tie_break {
+                    TieBreak::Earlier => cost < row_costs[j],
+                    TieBreak::Later => cost <= row_costs[j],
+                };
+                if better && cost.is_finite() {
+                    row_costs[j] = cost;
+                    row_starts[j] = i;
+                }
+            }
+        }
+        costs.push(row_costs);
+        starts.push(row_starts);
+    }
+
+    // Pick the possible number of lines closest to `desired`.
+    let mut n = if desired > optimal {
+        (optimal..=desired)
+            .rev()
+            .find(|&n| costs[n][len].is_finite())?
+    } else {
+        (desired..=optimal).find(|&n| costs[n][len].is_finite())?
+    };
+
+    // Reconstruct the starts of the lines, walking backwards.
+    let mut breaks = vec![0; len + 1];
+    let mut pos = len;
+    while n > 0 {
+        let prev = starts[n][pos];
+        breaks[pos] = prev;
+        pos = prev;
+        n -= 1;
+    }
+    Some(breaks)
 }
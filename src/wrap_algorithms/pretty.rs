@@ -0,0 +1,127 @@
+use crate::core::{Fragment, Word};
+use crate::wrap_algorithms::{
+    higher_priority_breaks, lines_from_indices, to_f64_line_widths, wrap_first_fit_indices,
+};
+use crate::wrap_algorithms::{LineWidths, WrapAlgorithm};
+
+/// Wrap words with a fast algorithm which avoids a lone word on the
+/// last line.
+///
+/// This is similar to `text-wrap: pretty` in CSS. Implemented by
+/// [`wrap_pretty`], please see that function for details and
+/// examples.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Pretty;
+
+impl Pretty {
+    /// Create a new pretty wrapping algorithm.
+    pub const fn new() -> Self {
+        Pretty
+    }
+}
+
+impl WrapAlgorithm for Pretty {
+    #[inline]
+    fn wrap<'a, 'b>(&self, words: &'b [Word<'a>], line_widths: &'b [usize]) -> Vec<&'b [Word<'a>]> {
+        wrap_pretty(words, &to_f64_line_widths(line_widths))
+    }
+}
+
+/// Wrap abstract fragments into lines without a lone fragment on the
+/// last line.
+///
+/// The fragments are first wrapped with
+/// [`wrap_first_fit`](super::wrap_first_fit). If the last line ends
+/// up with a single fragment, the last fragment of the line before it
+/// is moved down to the last line, provided that it fits there. This
+/// is much cheaper than
+/// [`wrap_optimal_fit`](super::wrap_optimal_fit), while it still
+/// avoids the most glaring problem of the first-fit algorithm.
+///
+/// A last line which follows a [`Fragment::forced_break`] is left
+/// alone.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::{wrap_first_fit, wrap_pretty};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// // Helper to convert wrapped lines to a Vec<String>.
+/// fn lines_to_strings(lines: Vec<&[Word<'_>]>) -> Vec<String> {
+///     lines.iter().map(|line| {
+///         line.iter().map(|word| &**word).collect::<Vec<_>>().join(" ")
+///     }).collect::<Vec<_>>()
+/// }
+///
+/// let text = "To be, or not to be: that is the question";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(lines_to_strings(wrap_first_fit(&words, &[12.0])),
+///            vec!["To be, or", "not to be:", "that is the", "question"]);
+/// assert_eq!(lines_to_strings(wrap_pretty(&words, &[12.0])),
+///            vec!["To be, or", "not to be:", "that is", "the question"]);
+/// ```
+pub fn wrap_pretty<'a, T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &'a [T],
+    line_widths: &W,
+) -> Vec<&'a [T]> {
+    let indices = wrap_pretty_indices(fragments, line_widths);
+    lines_from_indices(fragments, &indices)
+}
+
+/// Find line breaks which avoid a lone fragment on the last line.
+///
+/// This works like [`wrap_pretty`], but instead of returning the
+/// lines as slices, the index after the last fragment of each line
+/// is returned. The last index is always `fragments.len()`.
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::wrap_pretty_indices;
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "To be, or not to be: that is the question";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(wrap_pretty_indices(&words, &[12.0]), vec![3, 6, 8, 10]);
+/// ```
+pub fn wrap_pretty_indices<T: Fragment, W: LineWidths + ?Sized>(
+    fragments: &[T],
+    line_widths: &W,
+) -> Vec<usize> {
+    let mut indices = wrap_first_fit_indices(fragments, line_widths);
+    let line_count = indices.len();
+    if line_count < 2 {
+        return indices;
+    }
+
+    let end = fragments.len();
+    let last_start = indices[line_count - 2];
+    let prev_start = if line_count > 2 {
+        indices[line_count - 3]
+    } else {
+        0
+    };
+    if end - last_start != 1 || fragments[last_start - 1].forced_break() {
+        return indices;
+    }
+
+    // Find the latest break in the line before the last line.
+    let higher_breaks = higher_priority_breaks(fragments);
+    let new_start = (prev_start + 1..last_start)
+        .rev()
+        .find(|&k| !fragments[k - 1].keep_with_next() && higher_breaks[k] <= prev_start);
+    if let Some(new_start) = new_start {
+        let width = fragments[new_start..end]
+            .iter()
+            .map(|f| f.width() + f.whitespace_width())
+            .sum::<f64>()
+            - fragments[end - 1].whitespace_width()
+            + fragments[end - 1].penalty_width()
+            - fragments[end - 1].hanging_width();
+        if width <= line_widths.line_width(line_count - 1) {
+            indices[line_count - 2] = new_start;
+        }
+    }
+    indices
+}
@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+use crate::core::Fragment;
+use crate::wrap_algorithms::{first_fit_lines, lines_from_indices, LineWidths};
+
+/// Incremental first-fit wrapping for text which is being edited.
+///
+/// A `Rewrapper` remembers the line breaks found by
+/// [`wrap_first_fit`](super::wrap_first_fit). When some fragments
+/// are changed, [`Rewrapper::rewrap`] only recomputes the lines
+/// affected by the edit: it starts at the first line whose line break
+/// depends on the edited fragments and stops as soon as a line starts
+/// at the same place as before the edit. This makes re-wrapping a
+/// long paragraph on every keystroke cheap.
+///
+/// The line breaks are always the same as those found by
+/// [`wrap_first_fit_indices`](super::wrap_first_fit_indices).
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::core::Word;
+/// use textwrap::wrap_algorithms::Rewrapper;
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "Memory safety without garbage collection. \
+///             Concurrency without data races.";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// let mut rewrapper = Rewrapper::new(&words, [20.0]);
+/// assert_eq!(rewrapper.line_breaks(), &[2, 4, 5, 7, 9]);
+///
+/// // Insert "any" before "data".
+/// let text = "Memory safety without garbage collection. \
+///             Concurrency without any data races.";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+/// assert_eq!(rewrapper.rewrap(&words, 7..7), 3..5);
+/// let lines = rewrapper
+///     .lines(&words)
+///     .iter()
+///     .map(|line| line.iter().map(|word| word.word).collect::<Vec<_>>().join(" "))
+///     .collect::<Vec<_>>();
+/// assert_eq!(lines, vec!["Memory safety",
+///                        "without garbage",
+///                        "collection.",
+///                        "Concurrency without",
+///                        "any data races."]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rewrapper<W> {
+    line_widths: W,
+    // The end of each line.
+    line_breaks: Vec<usize>,
+    // The last fragment looked at when ending each line.
+    lookaheads: Vec<usize>,
+}
+
+impl<W: LineWidths> Rewrapper<W> {
+    /// Wrap `fragments` into lines with the given line widths.
+    pub fn new<T: Fragment>(fragments: &[T], line_widths: W) -> Self {
+        let mut line_breaks = Vec::new();
+        let mut lookaheads = Vec::new();
+        first_fit_lines(fragments, &line_widths, |end, lookahead| {
+            line_breaks.push(end);
+            lookaheads.push(lookahead);
+            true
+        });
+        Rewrapper {
+            line_widths,
+            line_breaks,
+            lookaheads,
+        }
+    }
+
+    /// The index after the last fragment of each line. The last index
+    /// is always the number of fragments.
+    pub fn line_breaks(&self) -> &[usize] {
+        &self.line_breaks
+    }
+
+    /// Split `fragments` into the current lines.
+    ///
+    /// The fragments must be the ones given to [`Rewrapper::new`] or
+    /// to the latest call to [`Rewrapper::rewrap`].
+    pub fn lines<'a, T>(&self, fragments: &'a [T]) -> Vec<&'a [T]> {
+        lines_from_indices(fragments, &self.line_breaks)
+    }
+
+    /// Update the line breaks after an edit.
+    ///
+    /// The `edit` is the range of the old fragments which were
+    /// replaced, and `fragments` are all fragments after the edit. An
+    /// insertion is an empty range and a deletion is a range which
+    /// was replaced by nothing.
+    ///
+    /// Returns the range of the new lines which were recomputed. The
+    /// lines before the range are unchanged. The lines after the range
+    /// are unchanged too, but they are renumbered if the number of
+    /// lines changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `edit` is not a range of the old fragments or if it
+    /// does not match the number of new fragments.
+    pub fn rewrap<T: Fragment>(&mut self, fragments: &[T], edit: Range<usize>) -> Range<usize> {
+        let old_len = self.line_breaks[self.line_breaks.len() - 1];
+        assert!(
+            edit.start <= edit.end && edit.end <= old_len,
+            "edit {:?} is outside the {} old fragments",
+            edit,
+            old_len
+        );
+        let new_edit_end = (edit.end + fragments.len())
+            .checked_sub(old_len)
+            .filter(|&end| end >= edit.start)
+            .expect("edit does not match the number of fragments");
+
+        // The first line whose line break depends on the edit. The
+        // last line looks at all fragments, so there is always one.
+        let first_line = self
+            .lookaheads
+            .iter()
+            .position(|&lookahead| lookahead >= edit.start)
+            .unwrap_or(self.lookaheads.len() - 1);
+        let start = match first_line {
+            0 => 0,
+            _ => self.line_breaks[first_line - 1],
+        };
+
+        let mut line_breaks = Vec::new();
+        let mut lookaheads = Vec::new();
+        let mut unchanged_line = None;
+        let line_widths = SkipLines {
+            line_widths: &self.line_widths,
+            skip: first_line,
+        };
+        let old_line_breaks = &self.line_breaks;
+        first_fit_lines(&fragments[start..], &line_widths, |end, lookahead| {
+            let end = start + end;
+            line_breaks.push(end);
+            lookaheads.push(start + lookahead);
+
+            // Once a line after the edit starts where a line started
+            // before the edit, the remaining lines are unchanged.
+            let line_number = first_line + line_breaks.len();
+            if end >= new_edit_end
+                && end < fragments.len()
+                && line_number < old_line_breaks.len()
+                && old_line_breaks[line_number - 1] == end + old_len - fragments.len()
+            {
+                unchanged_line = Some(line_number);
+                return false;
+            }
+            true
+        });
+
+        let recomputed = first_line..first_line + line_breaks.len();
+        if let Some(line_number) = unchanged_line {
+            let shift = |idx: usize| idx + fragments.len() - old_len;
+            line_breaks.extend(
+                self.line_breaks[line_number..]
+                    .iter()
+                    .map(|&end| shift(end)),
+            );
+            lookaheads.extend(self.lookaheads[line_number..].iter().map(|&idx| shift(idx)));
+        }
+        self.line_breaks.truncate(first_line);
+        self.line_breaks.extend(line_breaks);
+        self.lookaheads.truncate(first_line);
+        self.lookaheads.extend(lookaheads);
+        recomputed
+    }
+}
+
+/// Line widths which start at a later line number.
+struct SkipLines<'a, W> {
+    line_widths: &'a W,
+    skip: usize,
+}
+
+impl<W: LineWidths> LineWidths for SkipLines<'_, W> {
+    fn line_width(&self, line_number: usize) -> f64 {
+        self.line_widths.line_width(self.skip + line_number)
+    }
+}
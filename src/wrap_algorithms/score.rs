@@ -0,0 +1,109 @@
+use crate::core::Fragment;
+use crate::wrap_algorithms::LineWidths;
+#[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+use crate::wrap_algorithms::OptimalFit;
+
+/// Quality metrics of wrapped lines, computed by [`score`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Score {
+    /// The number of lines.
+    pub lines: usize,
+    /// The sum of the squared gaps at the end of the lines. This
+    /// measures how ragged the right margin is. The last line and
+    /// lines ending with a [`Fragment::forced_break`] can have any
+    /// gap, so they are left out, as are lines which overflow.
+    pub raggedness: f64,
+    /// The number of lines which end with a penalty, typically a
+    /// hyphen.
+    pub hyphens: usize,
+    /// The number of lines which are wider than the line width.
+    pub overflows: usize,
+    /// The total width by which lines are wider than the line width.
+    pub overflow_width: f64,
+}
+
+impl Score {
+    /// Combine the metrics into a single cost using the penalties of
+    /// the optimal-fit algorithm. Lower is better.
+    ///
+    /// This is the sum of the [`OptimalFit::nline_penalty`] for every
+    /// line, the raggedness, the [`OptimalFit::overflow_penalty`] for
+    /// every column of overflow, and the
+    /// [`OptimalFit::hyphen_penalty`] for every hyphen. The other
+    /// penalties of [`OptimalFit`] depend on the exact shape of the
+    /// lines and are not included, so this is a lower bound on the
+    /// cost which [`wrap_optimal_fit`](super::wrap_optimal_fit)
+    /// minimizes.
+    ///
+    /// **Note:** Only available when the `smawk` Cargo feature is
+    /// enabled.
+    #[cfg(any(feature = "smawk", feature = "optimal-fit-dp"))]
+    pub fn cost(&self, penalties: &OptimalFit) -> f64 {
+        self.lines as f64 * f64::from(penalties.nline_penalty)
+            + self.raggedness
+            + self.overflow_width * f64::from(penalties.overflow_penalty)
+            + self.hyphens as f64 * f64::from(penalties.hyphen_penalty)
+    }
+}
+
+/// Score lines of wrapped fragments.
+///
+/// The `lines` can come from any wrapping algorithm. The line widths
+/// are computed like the wrapping algorithms compute them: the
+/// whitespace after the last fragment of a line is left out and its
+/// penalty is added. Use this to compare wrapping algorithms or to
+/// check the effect of custom penalties.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::{score, wrap_first_fit, wrap_pretty};
+/// use textwrap::{AsciiSpace, WordSeparator};
+///
+/// let text = "To be, or not to be: that is the question";
+/// let words = AsciiSpace.find_words(text).collect::<Vec<_>>();
+///
+/// // The lines are "To be, or", "not to be:", "that is the", and
+/// // "question", with gaps of 3, 2, and 1 columns.
+/// let first_fit = score(&wrap_first_fit(&words, &[12.0]), &[12.0]);
+/// assert_eq!(first_fit.lines, 4);
+/// assert_eq!(first_fit.raggedness, 14.0);
+///
+/// // The pretty algorithm moves "the" down, which leaves a larger
+/// // gap on the third line.
+/// let pretty = score(&wrap_pretty(&words, &[12.0]), &[12.0]);
+/// assert_eq!(pretty.raggedness, 38.0);
+/// assert_eq!(pretty.overflows, 0);
+/// ```
+pub fn score<T: Fragment, W: LineWidths + ?Sized>(lines: &[&[T]], line_widths: &W) -> Score {
+    let mut score = Score {
+        lines: lines.len(),
+        ..Score::default()
+    };
+    for (line_number, line) in lines.iter().enumerate() {
+        let last = match line.last() {
+            Some(last) => last,
+            None => continue,
+        };
+        let width = line
+            .iter()
+            .map(|fragment| fragment.width() + fragment.whitespace_width())
+            .sum::<f64>()
+            - last.whitespace_width()
+            + last.penalty_width()
+            - last.hanging_width();
+        let target_width = line_widths.line_width(line_number);
+        let is_last_line = line_number + 1 == lines.len() || last.forced_break();
+        if width > target_width {
+            score.overflows += 1;
+            score.overflow_width += width - target_width;
+        } else if !is_last_line {
+            let gap = target_width - width;
+            score.raggedness += gap * gap;
+        }
+        if !is_last_line && last.penalty_width() > 0.0 {
+            score.hyphens += 1;
+        }
+    }
+    score
+}
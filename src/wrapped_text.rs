@@ -0,0 +1,340 @@
+//! Wrapped text together with the geometry of every line.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::core;
+use crate::wrap_algorithms::WrapAlgorithm;
+use crate::{wrap_with_spans, Options, WordSeparator, WordSplitter};
+
+/// How a wrapped line ends. See [`WrappedLine::line_break`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineBreak {
+    /// The line ends at the whitespace between two words.
+    Space,
+    /// The line ends inside a word at a break opportunity, such as
+    /// after a hyphen or at a hyphenation point found by the
+    /// [`WordSplitter`].
+    Hyphenation,
+    /// The line ends inside a word which is too wide for a line. See
+    /// [`Options::break_words`].
+    Forced,
+    /// The line ends at a line break in the text or at the end of the
+    /// text.
+    Newline,
+}
+
+/// A wrapped line together with its geometry. See [`WrappedText`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedLine<'a> {
+    /// The line, exactly as [`wrap`](crate::wrap) returns it.
+    pub text: Cow<'a, str>,
+    /// The displayed width of [`self.text`] in columns, or in the
+    /// unit of the [`Options::char_width`] if it is set.
+    ///
+    /// [`self.text`]: #structfield.text
+    pub width: usize,
+    /// The byte range of the source text shown on the line. This
+    /// leaves out indentation and other text added to the line, as
+    /// well as the whitespace at the line break. The range is empty
+    /// for an empty line.
+    ///
+    /// If the line is made from text which was changed before
+    /// wrapping, such as by [`Options::whitespace`], the range covers
+    /// all the source text which the line could come from.
+    pub source: Range<usize>,
+    /// The byte offset in [`self.text`] where the source text starts.
+    /// The text before it is indentation, quote marks, or padding
+    /// added by the [`Options::alignment`].
+    ///
+    /// [`self.text`]: #structfield.text
+    pub content_start: usize,
+    /// How the line ends.
+    pub line_break: LineBreak,
+}
+
+impl WrappedLine<'_> {
+    /// The displayed width of the text before [`self.content_start`].
+    ///
+    /// [`self.content_start`]: #structfield.content_start
+    fn content_column(&self) -> usize {
+        core::display_width(&self.text[..self.content_start])
+    }
+}
+
+/// A position in wrapped text. See [`WrappedText::position`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line number. The first line has line number zero.
+    pub line: usize,
+    /// The column on the line, counted in displayed columns. The
+    /// first column is zero.
+    pub column: usize,
+}
+
+/// Wrapped text with the geometry of every line.
+///
+/// The lines are the same as the lines returned by
+/// [`wrap`](crate::wrap), but every line also knows its width, where
+/// it came from in the source text, and how it was broken. This saves
+/// terminal user interfaces and editors from measuring the lines
+/// again. Use [`WrappedText::position`] and
+/// [`WrappedText::source_offset`] to translate cursor positions
+/// between the source text and the wrapped text.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::{LineBreak, WrappedText};
+///
+/// let text = "Memory safety without garbage-collection.";
+/// let wrapped = WrappedText::new(text, 15);
+/// let lines = &wrapped.lines;
+/// assert_eq!(lines[0].text, "Memory safety");
+/// assert_eq!(lines[0].width, 13);
+/// assert_eq!(lines[0].line_break, LineBreak::Space);
+/// assert_eq!(lines[1].text, "without");
+/// assert_eq!(lines[2].text, "garbage-");
+/// assert_eq!(&text[lines[2].source.clone()], "garbage-");
+/// assert_eq!(lines[2].line_break, LineBreak::Hyphenation);
+/// assert_eq!(lines[3].line_break, LineBreak::Newline);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedText<'a> {
+    /// The wrapped lines.
+    pub lines: Vec<WrappedLine<'a>>,
+}
+
+impl<'a> WrappedText<'a> {
+    /// Wrap `text` like [`wrap`](crate::wrap) and record the
+    /// geometry of the lines.
+    pub fn new<'o, A, R, S, Opt>(text: &'a str, width_or_options: Opt) -> Self
+    where
+        A: WrapAlgorithm,
+        R: WordSeparator,
+        S: WordSplitter,
+        Opt: Into<Options<'o, A, R, S>>,
+    {
+        let options = width_or_options.into();
+        let mut spans = Vec::new();
+        let lines = wrap_with_spans(text, &options, &[], Some(&mut spans));
+        debug_assert_eq!(lines.len(), spans.len());
+        let lines = lines
+            .into_iter()
+            .zip(spans)
+            .map(|(text, span)| WrappedLine {
+                width: options.text_width(&text),
+                text,
+                source: span.source,
+                content_start: span.content_start,
+                line_break: span.line_break,
+            })
+            .collect();
+        WrappedText { lines }
+    }
+
+    /// Find the position in the wrapped text of the source text at
+    /// byte offset `offset`.
+    ///
+    /// This translates a cursor position in the source text to the
+    /// wrapped text. Offsets inside the whitespace at a line break
+    /// are mapped to the start of the next line, and offsets after
+    /// the end of the last line are mapped to the end of the last
+    /// line.
+    /// `None` is returned if there are no lines.
+    ///
+    /// Use [`WrappedText::source_offset`] to go the other way. Map
+    /// the start and the end of a selection to translate the
+    /// selection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textwrap::{Position, WrappedText};
+    ///
+    /// let text = "Memory safety without garbage collection.";
+    /// let wrapped = WrappedText::new(text, 15);
+    /// let offset = text.find("garbage").unwrap();
+    /// assert_eq!(wrapped.position(offset), Some(Position { line: 1, column: 8 }));
+    /// assert_eq!(wrapped.source_offset(Position { line: 1, column: 8 }), Some(offset));
+    ///
+    /// // Offsets are bytes. Convert them to characters if needed.
+    /// let text = "Grüße aus Köln";
+    /// let wrapped = WrappedText::new(text, 9);
+    /// let offset = wrapped.source_offset(Position { line: 1, column: 2 }).unwrap();
+    /// assert_eq!(&text[offset..], "ln");
+    /// assert_eq!(text[..offset].chars().count(), 12);
+    /// ```
+    pub fn position(&self, offset: usize) -> Option<Position> {
+        let line_number = self
+            .lines
+            .iter()
+            .position(|line| offset <= line.source.end)
+            .unwrap_or_else(|| self.lines.len().saturating_sub(1));
+        let line = self.lines.get(line_number)?;
+        let source = &line.source;
+        let content = &line.text[line.content_start..];
+        let offset = std::cmp::max(offset, source.start);
+        let mut len = std::cmp::min(offset, source.end) - source.start;
+        len = std::cmp::min(len, content.len());
+        while !content.is_char_boundary(len) {
+            len -= 1;
+        }
+        Some(Position {
+            line: line_number,
+            column: line.content_column() + core::display_width(&content[..len]),
+        })
+    }
+
+    /// Find the byte offset of the source text shown at `position`.
+    ///
+    /// This translates a cursor position in the wrapped text back to
+    /// the source text. Positions in the indentation are mapped to
+    /// the start of the source text on the line, and positions after
+    /// the end of the line are mapped to the end of the source text
+    /// on the line. `None` is returned if the line does not exist.
+    ///
+    /// See [`WrappedText::position`] for an example.
+    pub fn source_offset(&self, position: Position) -> Option<usize> {
+        let line = self.lines.get(position.line)?;
+        let source = &line.source;
+        let content = &line.text[line.content_start..];
+        let mut column = line.content_column();
+        let mut len = content.len();
+        for (idx, ch) in content.char_indices() {
+            column += core::ch_width(ch);
+            if column > position.column {
+                len = idx;
+                break;
+            }
+        }
+        Some(std::cmp::min(source.start + len, source.end))
+    }
+}
+
+/// The geometry of a wrapped line, recorded while wrapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LineSpan {
+    pub(crate) source: Range<usize>,
+    pub(crate) content_start: usize,
+    pub(crate) line_break: LineBreak,
+}
+
+impl LineSpan {
+    pub(crate) fn new(source: Range<usize>, content_start: usize, line_break: LineBreak) -> Self {
+        LineSpan {
+            source,
+            content_start,
+            line_break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wrap_algorithms::FirstFit;
+    use crate::{wrap, AsciiSpace, HyphenSplitter, NoHyphenation, Overflow, Whitespace};
+
+    fn geometry<'a, S>(
+        text: &'a str,
+        options: &Options<'_, FirstFit, AsciiSpace, S>,
+    ) -> Vec<(&'a str, LineBreak)>
+    where
+        S: WordSplitter + Clone,
+    {
+        let wrapped = WrappedText::new(text, options);
+        assert_eq!(
+            wrapped
+                .lines
+                .iter()
+                .map(|line| &line.text)
+                .collect::<Vec<_>>(),
+            wrap(text, options).iter().collect::<Vec<_>>()
+        );
+        for line in &wrapped.lines {
+            assert_eq!(line.width, core::display_width(&line.text));
+        }
+        wrapped
+            .lines
+            .into_iter()
+            .map(|line| (&text[line.source], line.line_break))
+            .collect()
+    }
+
+    #[test]
+    fn line_breaks() {
+        use LineBreak::*;
+        let options = Options::new(8)
+            .wrap_algorithm(FirstFit)
+            .word_separator(AsciiSpace)
+            .splitter(HyphenSplitter)
+            .initial_indent("> ");
+        assert_eq!(
+            geometry("foo well-known\r\n\nabcdefghij x", &options),
+            vec![
+                ("foo", Space),
+                ("well-", Hyphenation),
+                ("known", Newline),
+                ("", Newline),
+                ("abcdefgh", Forced),
+                ("ij x", Newline),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_lines() {
+        use LineBreak::*;
+        let options = Options::new(7)
+            .wrap_algorithm(FirstFit)
+            .word_separator(AsciiSpace)
+            .splitter(NoHyphenation)
+            .placeholder("~");
+        let text = "foo bar baz quux";
+        let truncated = options.clone().max_lines(2);
+        assert_eq!(
+            geometry(text, &truncated),
+            vec![("foo bar", Space), ("baz", Newline)]
+        );
+        let truncated = options.clone().max_lines(1);
+        assert_eq!(geometry(text, &truncated), vec![("foo", Newline)]);
+        let truncated = options.clone().overflow(Overflow::Truncate);
+        assert_eq!(geometry(text, &truncated), vec![("foo ba", Newline)]);
+        let collapsed = options.whitespace(Whitespace::Collapse);
+        assert_eq!(
+            geometry("foo  bar\nbaz", &collapsed),
+            vec![("foo  bar\nbaz", Space), ("foo  bar\nbaz", Newline)]
+        );
+    }
+
+    #[test]
+    fn position_roundtrip() {
+        let text = "foo bar baz\n\nquux";
+        let options = Options::new(9)
+            .wrap_algorithm(FirstFit)
+            .word_separator(AsciiSpace)
+            .splitter(NoHyphenation)
+            .initial_indent("* ")
+            .subsequent_indent("  ");
+        let wrapped = WrappedText::new(text, &options);
+        assert_eq!(
+            wrap(text, &options),
+            vec!["* foo bar", "  baz", "  ", "  quux"]
+        );
+        let position = |line, column| Position { line, column };
+        assert_eq!(wrapped.position(0), Some(position(0, 2)));
+        assert_eq!(wrapped.position(1), Some(position(0, 3)));
+        assert_eq!(wrapped.position(7), Some(position(0, 9)));
+        assert_eq!(wrapped.position(11), Some(position(1, 5)));
+        assert_eq!(wrapped.position(12), Some(position(2, 2)));
+        assert_eq!(wrapped.position(99), Some(position(3, 6)));
+        for offset in 0..text.len() {
+            let position = wrapped.position(offset).unwrap();
+            assert_eq!(wrapped.source_offset(position), Some(offset));
+        }
+        assert_eq!(wrapped.source_offset(position(1, 0)), Some(8));
+        assert_eq!(wrapped.source_offset(position(1, 99)), Some(11));
+        assert_eq!(wrapped.source_offset(position(4, 0)), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Reusable wrapping with scratch space.
+
+use std::borrow::Cow;
+
+use crate::core::Word;
+use crate::wrap_algorithms::WrapAlgorithm;
+use crate::{
+    call_line_hook, find_line_words, list_marker_len, push_wrapped_lines, split_lines,
+    truncate_lines, wrap_single_line, wrap_with_breaks, ControlChars, Options, Overflow, Trim,
+    Whitespace, WordSeparator, WordSplitter,
+};
+
+/// Wrap many texts with the same options without allocating.
+///
+/// A `Wrapper` owns its [`Options`] together with the scratch space
+/// needed for wrapping: the words of the current line and the line
+/// breaks found by the wrapping algorithm. The scratch space is kept
+/// between calls to [`Wrapper::wrap_into`], so once it has grown
+/// large enough for the texts at hand, wrapping allocates nothing.
+/// This makes a `Wrapper` suitable for render loops which wrap the
+/// same kind of text many times per second.
+///
+/// The wrapped lines borrow from the text, except for lines which
+/// need to be indented or which end with a hyphen: these lines are
+/// owned and are allocated for every call. Apart from that, the
+/// [`AsciiSpace`] word separator, the [`NoHyphenation`] splitter
+/// and the [`FirstFit`] algorithm never allocate. Other word
+/// separators, splitters and wrapping algorithms may allocate
+/// internally, see [`WordSeparator::find_words_into`] and
+/// [`WrapAlgorithm::wrap_into`].
+///
+/// [`AsciiSpace`]: crate::AsciiSpace
+/// [`NoHyphenation`]: crate::NoHyphenation
+/// [`FirstFit`]: crate::wrap_algorithms::FirstFit
+///
+/// # Examples
+///
+/// ```
+/// use textwrap::wrap_algorithms::FirstFit;
+/// use textwrap::{wrap, AsciiSpace, NoHyphenation, Options, Wrapper};
+///
+/// let options = Options::new(15)
+///     .word_separator(AsciiSpace)
+///     .splitter(NoHyphenation)
+///     .wrap_algorithm(FirstFit);
+/// let mut wrapper = Wrapper::new(options);
+/// let mut lines = Vec::new();
+///
+/// for text in &["Memory safety without garbage collection.",
+///               "Concurrency without data races."] {
+///     wrapper.wrap_into(text, &mut lines);
+///     assert_eq!(lines, wrap(text, &wrapper.options));
+/// }
+/// assert_eq!(lines, vec!["Concurrency", "without data", "races."]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Wrapper<
+    'a,
+    A = Box<dyn WrapAlgorithm>,
+    R = Box<dyn WordSeparator>,
+    S = Box<dyn WordSplitter>,
+> {
+    /// The options used for wrapping.
+    pub options: Options<'a, A, R, S>,
+    // The words of the current line. The vector is always empty
+    // between calls, so the lifetime of the words does not matter.
+    words: Vec<Word<'static>>,
+    // The index after the last word of each wrapped line.
+    line_ends: Vec<usize>,
+}
+
+impl<'a, A, R, S> Wrapper<'a, A, R, S>
+where
+    A: WrapAlgorithm,
+    R: WordSeparator,
+    S: WordSplitter,
+{
+    /// Create a new `Wrapper` from a width or from [`Options`].
+    ///
+    /// ```
+    /// use textwrap::{Options, Wrapper};
+    ///
+    /// let wrapper = Wrapper::new(Options::new(80).break_words(false));
+    /// assert_eq!(wrapper.options.width, 80);
+    /// ```
+    pub fn new<Opt: Into<Options<'a, A, R, S>>>(width_or_options: Opt) -> Self {
+        Wrapper {
+            options: width_or_options.into(),
+            words: Vec::new(),
+            line_ends: Vec::new(),
+        }
+    }
+
+    /// Wrap `text` into `lines`.
+    ///
+    /// The `lines` are cleared first and then filled with the same
+    /// lines as [`wrap`](crate::wrap) returns. Reuse the same vector
+    /// for every call to avoid allocating it again.
+    ///
+    /// ```
+    /// use textwrap::{Options, Wrapper};
+    ///
+    /// let mut wrapper = Wrapper::new(Options::new(10).subsequent_indent("  "));
+    /// let mut lines = Vec::new();
+    /// wrapper.wrap_into("Memory safety without garbage collection.", &mut lines);
+    /// assert_eq!(lines, vec!["Memory", "  safety", "  without", "  garbage", "  collecti", "  on."]);
+    /// ```
+    pub fn wrap_into<'t>(&mut self, text: &'t str, lines: &mut Vec<Cow<'t, str>>) {
+        lines.clear();
+        let options = &self.options;
+        if options.whitespace == Whitespace::Collapse
+            || options.semantic_line_breaks
+            || options.reopen_styles
+        {
+            lines.extend(wrap_with_breaks(text, options, &[]));
+            return;
+        }
+        let initial_width = options
+            .width
+            .saturating_sub(options.text_width(&options.initial_indent));
+        let subsequent_width = options
+            .width
+            .saturating_sub(options.text_width(&options.subsequent_indent));
+
+        let mut words = recycle(std::mem::take(&mut self.words));
+        for (_, line) in split_lines(text, options.unicode_line_breaks) {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let first_line = lines.is_empty();
+            // Truncated lines, quoted lines, list items, lines with
+            // computed widths or with computed or preserved
+            // indentation, lines with changed whitespace or control
+            // characters, and preformatted lines are handled by the
+            // simpler code path.
+            if options.overflow == Overflow::Truncate
+                || options.whitespace != Whitespace::Preserve
+                || options.control_chars != ControlChars::Keep
+                || options.trim == Trim::Both
+                || options.detect_quotes
+                || options.line_indent.is_some()
+                || options.line_widths.is_some()
+                || options.preserve_indentation && line.starts_with(char::is_whitespace)
+                || options.auto_subsequent_indent
+                || options.preformatted.is_preformatted(line)
+                || options.hanging_list_indent && list_marker_len(line).is_some()
+            {
+                wrap_single_line(line, 0, options, &[], first_line, lines, None);
+                continue;
+            }
+            let (first_indent, first_width) = if first_line {
+                (&*options.initial_indent, initial_width)
+            } else {
+                (&*options.subsequent_indent, subsequent_width)
+            };
+            let line_widths = [first_width, subsequent_width];
+            find_line_words(line, options, first_width, subsequent_width, &mut words);
+            options
+                .wrap_algorithm
+                .wrap_into(&words, &line_widths, &mut self.line_ends);
+
+            let mut start = 0;
+            let wrapped_words = self.line_ends.iter().map(|&end| {
+                let words = &words[start..end];
+                start = end;
+                words
+            });
+            let indents = [first_indent, &options.subsequent_indent];
+            push_wrapped_lines(line, 0, wrapped_words, options, "", indents, lines, None);
+        }
+        self.words = recycle(words);
+        truncate_lines(lines, options, None);
+        call_line_hook(lines, options);
+    }
+}
+
+/// Empty `words` and reuse its memory for words with another
+/// lifetime. Collecting an empty vector into a vector of a type with
+/// the same size and alignment reuses the allocation.
+fn recycle<'x, 'y>(mut words: Vec<Word<'x>>) -> Vec<Word<'y>> {
+    words.clear();
+    words.into_iter().map(|_| unreachable!()).collect()
+}
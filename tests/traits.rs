@@ -12,7 +12,7 @@ fn type_name<T: ?Sized>(_val: &T) -> String {
 }
 
 #[test]
-#[cfg(not(feature = "smawk"))]
+#[cfg(not(any(feature = "smawk", feature = "optimal-fit-dp")))]
 #[cfg(not(feature = "unicode-linebreak"))]
 fn static_hyphensplitter() {
     // Inferring the full type.
@@ -0,0 +1,200 @@
+//! Procedural macros which run [textwrap] functions at compile time.
+//!
+//! The macros take string literals and expand to string literals, so
+//! the dedented or wrapped text is baked into the binary. This is
+//! useful for help texts in command line programs and for embedded
+//! systems, where the text is known when the program is compiled.
+//!
+//! The features of this crate select the textwrap features used by
+//! the macros, in the same way as the features of textwrap itself.
+//!
+//! [textwrap]: https://docs.rs/textwrap/
+
+use proc_macro::{Delimiter, Literal, TokenStream, TokenTree};
+
+/// Remove common leading whitespace from a string literal.
+///
+/// This runs [`textwrap::dedent`] at compile time.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap_macros::dedent;
+///
+/// const USAGE: &str = dedent!("
+///     Usage:
+///         hello [--name NAME]
+/// ");
+/// assert_eq!(USAGE, "\nUsage:\n    hello [--name NAME]\n");
+/// ```
+#[proc_macro]
+pub fn dedent(input: TokenStream) -> TokenStream {
+    expand(input, |args| match args {
+        [TokenTree::Literal(text)] => Ok(textwrap::dedent(&parse_string(text)?)),
+        _ => Err(String::from("expected a single string literal")),
+    })
+}
+
+/// Fill a string literal to the given width.
+///
+/// This runs [`textwrap::fill`] at compile time. The first argument
+/// is the width, which must be an integer literal.
+///
+/// # Examples
+///
+/// ```
+/// use textwrap_macros::fill;
+///
+/// const ABOUT: &str = fill!(20, "Memory safety without garbage collection.");
+/// assert_eq!(ABOUT, "Memory safety\nwithout garbage\ncollection.");
+/// ```
+#[proc_macro]
+pub fn fill(input: TokenStream) -> TokenStream {
+    expand(input, |args| match args {
+        [TokenTree::Literal(width), TokenTree::Literal(text)] => {
+            Ok(textwrap::fill(&parse_string(text)?, parse_width(width)?))
+        }
+        _ => Err(String::from(
+            "expected a width and a string literal separated by a comma",
+        )),
+    })
+}
+
+/// Split `input` into comma separated arguments, call `f` with them,
+/// and turn the result into a string literal. Errors become calls to
+/// `compile_error!`.
+fn expand<F>(input: TokenStream, f: F) -> TokenStream
+where
+    F: FnOnce(&[TokenTree]) -> Result<String, String>,
+{
+    let mut args = Vec::new();
+    let mut expect_comma = false;
+    let mut result = Ok(());
+    for token in flatten(input) {
+        match token {
+            TokenTree::Punct(ref punct) if punct.as_char() == ',' && expect_comma => {
+                expect_comma = false;
+            }
+            _ if expect_comma => {
+                result = Err(format!("expected a comma, found `{}`", token));
+                break;
+            }
+            _ => {
+                args.push(token);
+                expect_comma = true;
+            }
+        }
+    }
+    match result.and_then(|()| f(&args)) {
+        Ok(text) => TokenTree::Literal(Literal::string(&text)).into(),
+        Err(msg) => format!("compile_error!({:?})", msg).parse().unwrap(),
+    }
+}
+
+/// Remove invisible groups from `input`. These are added around
+/// arguments passed on by `macro_rules!` macros.
+fn flatten(input: TokenStream) -> Vec<TokenTree> {
+    let mut tokens = Vec::new();
+    for token in input {
+        match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+                tokens.extend(flatten(group.stream()))
+            }
+            _ => tokens.push(token),
+        }
+    }
+    tokens
+}
+
+/// Parse an integer literal, such as `80` or `80usize`.
+fn parse_width(literal: &Literal) -> Result<usize, String> {
+    let repr = literal.to_string();
+    repr.trim_end_matches("usize")
+        .replace('_', "")
+        .parse()
+        .map_err(|_| format!("expected a width, found `{}`", repr))
+}
+
+/// Parse a string literal.
+fn parse_string(literal: &Literal) -> Result<String, String> {
+    unescape(&literal.to_string())
+}
+
+/// Find the value of the string literal written as `repr` in the
+/// source code.
+fn unescape(repr: &str) -> Result<String, String> {
+    if let Some(raw) = repr.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        return Ok(raw[hashes + 1..raw.len() - hashes - 1].to_owned());
+    }
+    let body = repr
+        .strip_prefix('"')
+        .and_then(|body| body.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a string literal, found `{}`", repr))?;
+
+    let mut result = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('0') => result.push('\0'),
+            Some(ch @ '\\') | Some(ch @ '\'') | Some(ch @ '"') => result.push(ch),
+            Some('x') => {
+                let digits = chars.by_ref().take(2).collect::<String>();
+                result.push(parse_char(&digits)?);
+            }
+            Some('u') => {
+                // Skip the '{' and consume the digits up to the '}'.
+                let digits = chars
+                    .by_ref()
+                    .skip(1)
+                    .take_while(|&ch| ch != '}')
+                    .filter(|&ch| ch != '_')
+                    .collect::<String>();
+                result.push(parse_char(&digits)?);
+            }
+            // A line ending with a backslash continues on the next
+            // line without the leading whitespace.
+            Some('\n') | Some('\r') => chars = chars.as_str().trim_start().chars(),
+            _ => return Err(format!("invalid escape in `{}`", repr)),
+        }
+    }
+    Ok(result)
+}
+
+/// Parse the hexadecimal code of a character.
+fn parse_char(digits: &str) -> Result<char, String> {
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .and_then(std::char::from_u32)
+        .ok_or_else(|| format!("invalid character code `{}`", digits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_escapes() {
+        assert_eq!(unescape(r#""a\tb\\\"\n""#).unwrap(), "a\tb\\\"\n");
+        assert_eq!(
+            unescape(r#""\x41\u{1F980}\u{e9}""#).unwrap(),
+            "A\u{1F980}\u{e9}"
+        );
+        assert_eq!(unescape("\"foo \\\n    bar\"").unwrap(), "foo bar");
+        assert!(unescape(r#""\q""#).is_err());
+        assert!(unescape("b\"foo\"").is_err());
+    }
+
+    #[test]
+    fn unescape_raw() {
+        assert_eq!(unescape(r#"r"a\n""#).unwrap(), "a\\n");
+        assert_eq!(unescape(r###"r##"a"#b"##"###).unwrap(), "a\"#b");
+    }
+}